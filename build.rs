@@ -0,0 +1,4 @@
+fn main() {
+    // Generates src/parser/grammar.rs into OUT_DIR
+    lalrpop::process_root().unwrap();
+}
@@ -1,15 +1,247 @@
 //! Diagnostic formatting and suggestions
 
 use crate::lexer::Span;
-use crate::error_management::error_types::LexicalError;
+use crate::error_management::error_types::{LexicalError, LexicalWarning, ParseError, SemanticError};
+use crate::error_management::fixes::Suggestion;
+use std::fmt;
+
+/// How serious a diagnostic is (ordered from least to most severe)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Help,
+    Note,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Help => "help",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    /// SARIF `level` for this severity
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            Severity::Help | Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Help => "\x1b[32m",    // Green
+            Severity::Note => "\x1b[36m",    // Cyan
+            Severity::Warning => "\x1b[33m", // Yellow
+            Severity::Error => "\x1b[31m",   // Red
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// A single reportable message, shared by every compiler phase
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable code such as `E0002`, see `error_codes`
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub span: Span,
+    pub help: Option<String>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            span,
+            help: None,
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    pub fn note(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Note, message, span)
+    }
+
+    pub fn help(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Help, message, span)
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<Suggestion>) -> Self {
+        self.suggestions.extend(suggestions);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// e.g. `error[E0002]:` or `note:`
+    pub fn header(&self) -> String {
+        match self.code {
+            Some(code) => format!("{}[{}]:", self.severity, code),
+            None => format!("{}:", self.severity),
+        }
+    }
+}
+
+impl From<&LexicalError> for Diagnostic {
+    fn from(error: &LexicalError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: Some(error.code()),
+            message: error.message(),
+            span: error.span(),
+            help: error.suggestion(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: Some(error.code()),
+            message: error.message(),
+            span: error.span(),
+            help: error.suggestion(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl From<&SemanticError> for Diagnostic {
+    fn from(error: &SemanticError) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: Some(error.code()),
+            message: error.message(),
+            span: error.span(),
+            help: error.suggestion(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl From<&LexicalWarning> for Diagnostic {
+    fn from(warning: &LexicalWarning) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            code: Some(warning.code()),
+            message: warning.message(),
+            span: warning.span(),
+            help: warning.suggestion(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl miette::Diagnostic for Diagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.code.map(|code| Box::new(code) as Box<dyn fmt::Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Note | Severity::Help => miette::Severity::Advice,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        // miette has no notes, so they trail the help text
+        let mut lines: Vec<String> = self.help.iter().cloned().collect();
+        lines.extend(self.notes.iter().map(|note| format!("note: {}", note)));
+        if lines.is_empty() {
+            None
+        } else {
+            Some(Box::new(lines.join("\n")))
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span;
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(span.start..span.end, "here"))))
+    }
+}
 
 pub struct DiagnosticFormatter;
 
 impl DiagnosticFormatter {
     pub fn format_lexical_error(error: &LexicalError, source: &str) -> String {
-        let span = error.span();
-        let message = error.message();
-        let suggestion = error.suggestion();
+        Self::format_diagnostic(&Diagnostic::from(error), source)
+    }
+
+    pub fn format_diagnostic(diagnostic: &Diagnostic, source: &str) -> String {
+        Self::format_diagnostic_with(diagnostic, source, true)
+    }
+
+    /// Render as rustc-style text, with or without ANSI colours
+    pub fn format_diagnostic_with(diagnostic: &Diagnostic, source: &str, colored: bool) -> String {
+        let span = diagnostic.span;
+        let paint = |code: &'static str| if colored { code } else { "" };
+        let color = paint(diagnostic.severity.color());
+        let reset = paint("\x1b[0m");
+        let cyan = paint("\x1b[36m");
+        let yellow = paint("\x1b[33m");
 
         let mut output = String::new();
 
@@ -21,20 +253,29 @@ impl DiagnosticFormatter {
             ""
         };
 
-        // Format error
-        output.push_str(&format!("\x1b[31merror:\x1b[0m {}\n", message));
-        output.push_str(&format!("  \x1b[36m--> {}:{}\x1b[0m\n", span.line, span.column));
+        // Format diagnostic
+        output.push_str(&format!("{}{}{} {}\n", color, diagnostic.header(), reset, diagnostic.message));
+        output.push_str(&format!("  {}--> {}:{}{}\n", cyan, span.line, span.column, reset));
         output.push_str("   |\n");
         output.push_str(&format!("{:3} | {}\n", span.line, line_text));
-        output.push_str(&format!("   | {}{}\n",
+        output.push_str(&format!("   | {}{}^{}\n",
                                  " ".repeat(span.column.saturating_sub(1)),
-                                 "\x1b[31m^\x1b[0m"
+                                 color,
+                                 reset
         ));
 
-        if let Some(suggest) = suggestion {
-            output.push_str(&format!("   \x1b[33m= help:\x1b[0m {}\n", suggest));
+        for note in &diagnostic.notes {
+            output.push_str(&format!("   {}= note:{} {}\n", cyan, reset, note));
+        }
+
+        if let Some(suggest) = &diagnostic.help {
+            output.push_str(&format!("   {}= help:{} {}\n", yellow, reset, suggest));
+        }
+
+        for fix in &diagnostic.suggestions {
+            output.push_str(&format!("   {}= fix:{} {}: `{}` ({})\n", yellow, reset, fix.message, fix.replacement, fix.applicability));
         }
 
         output
     }
-}
\ No newline at end of file
+}
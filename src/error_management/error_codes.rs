@@ -0,0 +1,292 @@
+//! Stable diagnostic codes and their long-form explanations
+//!
+//! Codes are never reused: retire an entry by keeping it here rather than
+//! deleting it. `E` codes are errors, `W` codes are warnings.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    pub bad_example: &'static str,
+    pub good_example: &'static str,
+}
+
+// Lexical errors
+pub const E0001: &str = "E0001";
+pub const E0002: &str = "E0002";
+pub const E0003: &str = "E0003";
+pub const E0004: &str = "E0004";
+pub const E0005: &str = "E0005";
+pub const E0006: &str = "E0006";
+pub const E0007: &str = "E0007";
+
+// Parse errors
+pub const E0100: &str = "E0100";
+pub const E0101: &str = "E0101";
+pub const E0102: &str = "E0102";
+pub const E0103: &str = "E0103";
+
+// Semantic errors
+pub const E0200: &str = "E0200";
+pub const E0201: &str = "E0201";
+pub const E0202: &str = "E0202";
+pub const E0203: &str = "E0203";
+pub const E0204: &str = "E0204";
+pub const E0205: &str = "E0205";
+pub const E0206: &str = "E0206";
+pub const E0207: &str = "E0207";
+pub const E0208: &str = "E0208";
+pub const E0209: &str = "E0209";
+
+// Lexical warnings
+pub const W0001: &str = "W0001";
+pub const W0002: &str = "W0002";
+pub const W0003: &str = "W0003";
+pub const W0004: &str = "W0004";
+
+pub static ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: E0001,
+        title: "unexpected character",
+        explanation: "The lexer found a character that cannot start any token. \
+Only ASCII identifiers, numbers, string and character literals, operators and \
+delimiters are valid outside of strings and comments.",
+        bad_example: "let total = price ` 2",
+        good_example: "let total = price * 2",
+    },
+    ErrorCode {
+        code: E0002,
+        title: "unterminated string literal",
+        explanation: "A string literal was opened with `\"` but never closed. Normal \
+and interpolated strings must be closed on the same logical string; verbatim \
+strings (`@\"...\"`) may span lines but still need a closing quote.",
+        bad_example: "let name = \"Alice",
+        good_example: "let name = \"Alice\"",
+    },
+    ErrorCode {
+        code: E0003,
+        title: "unterminated block comment",
+        explanation: "A block comment opened with `/*` was never closed with `*/`. \
+Block comments nest, so every inner `/*` needs its own `*/` as well.",
+        bad_example: "/* outer /* inner */\nlet x = 1",
+        good_example: "/* outer /* inner */ */\nlet x = 1",
+    },
+    ErrorCode {
+        code: E0004,
+        title: "invalid number literal",
+        explanation: "A number literal is malformed or does not fit in 64 bits. Hex \
+literals need at least one digit after `0x`, binary literals after `0b`, and a \
+float may contain only one decimal point.",
+        bad_example: "let mask = 0x",
+        good_example: "let mask = 0xFF",
+    },
+    ErrorCode {
+        code: E0005,
+        title: "invalid escape sequence",
+        explanation: "Only `\\n`, `\\t`, `\\r`, `\\\\` and `\\\"` are recognised inside \
+strings, plus `\\{` and `\\}` inside interpolated strings. Use a verbatim string \
+(`@\"...\"`) when backslashes should be kept as-is.",
+        bad_example: "let path = $\"C:\\data\\{file}\"",
+        good_example: "let path = $@\"C:\\data\\{file}\"",
+    },
+    ErrorCode {
+        code: E0006,
+        title: "invalid string interpolation",
+        explanation: "An expression hole `{` inside an interpolated string was not \
+closed with a matching `}`. Write `\\{` to include a literal brace.",
+        bad_example: "println($\"Hello, {name\")",
+        good_example: "println($\"Hello, {name}\")",
+    },
+    ErrorCode {
+        code: E0007,
+        title: "invalid character literal",
+        explanation: "Character literals hold exactly one character or one escape \
+sequence. Use a string literal for longer text.",
+        bad_example: "let c = 'ab'",
+        good_example: "let c = 'a'",
+    },
+    ErrorCode {
+        code: E0100,
+        title: "unexpected token",
+        explanation: "The parser found a token that cannot continue the construct it \
+was reading. The message lists every token that would have been accepted at that \
+point. Parsing resumes at the next statement or declaration, so later errors in the \
+same file are still reported.",
+        bad_example: "fn add(a: int b: int) int { return a + b }",
+        good_example: "fn add(a: int, b: int) int { return a + b }",
+    },
+    ErrorCode {
+        code: E0101,
+        title: "unexpected end of file",
+        explanation: "The file ended while a declaration, block or expression was still \
+open. This is usually a missing closing `}`, `)` or `]`.",
+        bad_example: "fn main() {\n    println(\"hi\")",
+        good_example: "fn main() {\n    println(\"hi\")\n}",
+    },
+    ErrorCode {
+        code: E0102,
+        title: "invalid syntax",
+        explanation: "The tokens are individually valid but do not form an allowed \
+construct here, such as a lambda parameter list without `=>` after it.",
+        bad_example: "fn main() {\n    let f = (x: int)\n}",
+        good_example: "fn main() {\n    let f = (x: int) => x * 2\n}",
+    },
+    ErrorCode {
+        code: E0103,
+        title: "chained comparison",
+        explanation: "Comparison operators are non-associative, so `a < b < c` is \
+rejected rather than read as `(a < b) < c`, which would compare a boolean with a \
+number. Spell out both comparisons and join them with `and`.",
+        bad_example: "fn main() {\n    let ok = 0 < x < 10\n}",
+        good_example: "fn main() {\n    let ok = 0 < x and x < 10\n}",
+    },
+    ErrorCode {
+        code: E0200,
+        title: "ambiguous extension method",
+        explanation: "Two summoned modules both extend the receiver's type with a \
+method of this name, and the type has no method of its own by that name. Name the \
+one you mean with `from module summon [method]`: a method summoned by name wins \
+over every method of a summoned module.",
+        bad_example: "summon text.ascii\nsummon text.unicode\n\nfn main() {\n    \"Hi\".shout()\n}",
+        good_example: "summon text.ascii\nfrom text.unicode summon [shout]\n\nfn main() {\n    \"Hi\".shout()\n}",
+    },
+    ErrorCode {
+        code: E0201,
+        title: "extension method not summoned",
+        explanation: "The method exists as an extension in another module, but that \
+module is not summoned here. Extension methods are only visible where their module \
+is summoned, or where the method itself is summoned by name.",
+        bad_example: "fn main() {\n    42.is_even()\n}",
+        good_example: "summon math.parity\n\nfn main() {\n    42.is_even()\n}",
+    },
+    ErrorCode {
+        code: E0202,
+        title: "undeclared lifetime",
+        explanation: "A reference type or an `outlives` constraint names a lifetime \
+that the enclosing function does not declare. Every lifetime a signature or body \
+uses must appear in the `[lifetime ...]` list after the function name.",
+        bad_example: "fn first(x: &L str) &L str {\n    return x\n}",
+        good_example: "fn first[lifetime L](x: &L str) &L str {\n    return x\n}",
+    },
+    ErrorCode {
+        code: E0203,
+        title: "cyclic lifetime constraints",
+        explanation: "The `outlives` constraints of a function loop back on themselves, \
+as in `L outlives M, M outlives L`. Outliving is an ordering, so lifetimes on a cycle \
+could only ever be equal; declare a single lifetime instead.",
+        bad_example: "fn pick[lifetime L, lifetime M where L outlives M, M outlives L](x: &L str, y: &M str) &L str {\n    return x\n}",
+        good_example: "fn pick[lifetime L, lifetime M where L outlives M](x: &L str, y: &M str) &M str {\n    return y\n}",
+    },
+    ErrorCode {
+        code: E0204,
+        title: "bound on an undeclared generic",
+        explanation: "A `where` clause bounds a name that is not a generic parameter \
+of the item. A function may bound its own generics, and a method also those of the \
+struct, `impl` or `extend` block around it. Usually the parameter is missing from \
+the `<...>` list or its name is misspelled.",
+        bad_example: "fn show<T>(value: T) string where U: Display {\n    return value.to_string()\n}",
+        good_example: "fn show<T>(value: T) string where T: Display {\n    return value.to_string()\n}",
+    },
+    ErrorCode {
+        code: E0205,
+        title: "unknown attribute",
+        explanation: "The attribute is not one the compiler knows. The known attributes \
+are `@tier`, `@inline`, `@test`, `@bench`, `@deprecated`, `@allow` and `@export`; \
+anything else is most likely a misspelling of one of them.",
+        bad_example: "@teir(high)\nfn handle() {\n}",
+        good_example: "@tier(high)\nfn handle() {\n}",
+    },
+    ErrorCode {
+        code: E0206,
+        title: "attribute on the wrong kind of item",
+        explanation: "Each attribute decorates only some kinds of item. `@test` and \
+`@bench` mark free functions, `@inline` marks functions and methods, and `@export` \
+cannot decorate a method or an `impl` or `extend` block. The diagnostic lists the \
+kinds the attribute does apply to.",
+        bad_example: "@test\nstruct Fixture {\n    x: int\n}",
+        good_example: "@test\nfn fixture_works() {\n}",
+    },
+    ErrorCode {
+        code: E0207,
+        title: "invalid attribute arguments",
+        explanation: "The arguments do not fit the attribute. `@tier` takes one of \
+`high`, `mid` or `low`; `@deprecated` takes an optional string note; `@allow` takes \
+one or more lint names; the other attributes take no arguments.",
+        bad_example: "@tier(fast)\nfn handle() {\n}",
+        good_example: "@tier(high)\nfn handle() {\n}",
+    },
+    ErrorCode {
+        code: E0208,
+        title: "duplicate attribute",
+        explanation: "The same attribute appears twice on one item, or an `@allow` \
+names a lint an earlier `@allow` already names. The repeat has no effect.",
+        bad_example: "@inline\n@inline\nfn add(a: int, b: int) int {\n    return a + b\n}",
+        good_example: "@inline\nfn add(a: int, b: int) int {\n    return a + b\n}",
+    },
+    ErrorCode {
+        code: E0209,
+        title: "conflicting attributes",
+        explanation: "Two attributes on one item contradict each other: two different \
+tiers, or `@test` together with `@bench`. An item runs in exactly one tier and is \
+either a test or a benchmark, so keep one of them.",
+        bad_example: "@tier(high)\n@tier(low)\nfn handle() {\n}",
+        good_example: "@tier(low)\nfn handle() {\n}",
+    },
+    ErrorCode {
+        code: W0001,
+        title: "mixed indentation",
+        explanation: "A line is indented with both tabs and spaces. Tab width differs \
+between editors, so mixed indentation renders inconsistently.",
+        bad_example: "fn main() {\n \tlet x = 1\n}",
+        good_example: "fn main() {\n    let x = 1\n}",
+    },
+    ErrorCode {
+        code: W0002,
+        title: "trailing whitespace in verbatim string",
+        explanation: "A line inside a verbatim string ends in spaces or tabs. Verbatim \
+strings keep every character, so the whitespace becomes part of the value even \
+though it is invisible in most editors.",
+        bad_example: "let text = @\"first line   \nsecond line\"",
+        good_example: "let text = @\"first line\nsecond line\"",
+    },
+    ErrorCode {
+        code: W0003,
+        title: "float literal without fractional digits",
+        explanation: "A float literal ends in a decimal point, such as `1.`. This reads \
+like the start of a method call or range; write the fractional zero explicitly.",
+        bad_example: "let ratio = 1.",
+        good_example: "let ratio = 1.0",
+    },
+    ErrorCode {
+        code: W0004,
+        title: "unterminated doc comment",
+        explanation: "A doc comment (`/** ... */` or `/*! ... */`) is not closed, so it \
+swallows the rest of the file as documentation.",
+        bad_example: "/** Adds two numbers\nfn add(a: int, b: int) int { return a + b }",
+        good_example: "/** Adds two numbers */\nfn add(a: int, b: int) int { return a + b }",
+    },
+];
+
+/// Find a code, ignoring case (`e0002` finds `E0002`)
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES.iter().find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+impl ErrorCode {
+    /// Long-form text shown by `stratc explain`
+    pub fn render(&self) -> String {
+        let indent = |text: &str| {
+            text.lines().map(|line| format!("    {}\n", line)).collect::<String>()
+        };
+
+        format!(
+            "{}: {}\n\n{}\n\nErroneous example:\n\n{}\nCorrected:\n\n{}",
+            self.code,
+            self.title,
+            self.explanation,
+            indent(self.bad_example),
+            indent(self.good_example),
+        )
+    }
+}
@@ -1,60 +1,161 @@
-//! Central error manager - collects all errors
+//! Central error manager - collects all errors, warnings and notes
 
-use crate::error_management::error_types::LexicalError;
+use crate::error_management::error_types::{LexicalError, LexicalWarning, ParseError, SemanticError};
+use crate::error_management::diagnostics::{Diagnostic, Severity};
 use crate::error_management::logger::Logger;
-use crate::lexer::Span;
+use crate::error_management::renderer::DiagnosticRenderer;
+use crate::error_management::fixes::Suggestion;
 
-#[derive(Debug)]  // ← ADDED THIS - Now ErrorManager implements Debug!
+#[derive(Debug)]
 pub struct ErrorManager {
     lexical_errors: Vec<LexicalError>,
+    parse_errors: Vec<ParseError>,
+    diagnostics: Vec<Diagnostic>,
     source: String,
     max_errors: usize,
+    deny_warnings: bool,
 }
 
 impl ErrorManager {
     pub fn new(source: String) -> Self {
         ErrorManager {
             lexical_errors: Vec::new(),
+            parse_errors: Vec::new(),
+            diagnostics: Vec::new(),
             source,
             max_errors: 100, // Stop after 100 errors
+            deny_warnings: false,
+        }
+    }
+
+    /// Promote every warning (past and future) to an error
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.deny_warnings = deny;
+        if deny {
+            for diagnostic in &mut self.diagnostics {
+                if diagnostic.severity == Severity::Warning {
+                    diagnostic.severity = Severity::Error;
+                }
+            }
         }
     }
 
     pub fn add_lexical_error(&mut self, error: LexicalError) {
-        if self.lexical_errors.len() < self.max_errors {
+        if self.error_count() < self.max_errors {
+            let fixes = error.fixes(&self.source);
+            self.diagnostics.push(Diagnostic::from(&error).with_suggestions(fixes));
             self.lexical_errors.push(error);
         }
     }
 
+    pub fn add_parse_error(&mut self, error: ParseError) {
+        if self.error_count() < self.max_errors {
+            let fixes = error.fixes(&self.source);
+            self.diagnostics.push(Diagnostic::from(&error).with_suggestions(fixes));
+            self.parse_errors.push(error);
+        }
+    }
+
+    pub fn add_semantic_error(&mut self, error: SemanticError) {
+        self.add_diagnostic(Diagnostic::from(&error));
+    }
+
+    pub fn add_lexical_warning(&mut self, warning: LexicalWarning) {
+        let fixes = warning.fixes(&self.source);
+        self.add_diagnostic(Diagnostic::from(&warning).with_suggestions(fixes));
+    }
+
+    pub fn add_diagnostic(&mut self, mut diagnostic: Diagnostic) {
+        if self.deny_warnings && diagnostic.severity == Severity::Warning {
+            diagnostic.severity = Severity::Error;
+        }
+        if diagnostic.is_error() && self.error_count() >= self.max_errors {
+            return;
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
     pub fn has_errors(&self) -> bool {
-        !self.lexical_errors.is_empty()
+        self.error_count() > 0
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        self.warning_count() > 0
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == severity).count()
     }
 
     pub fn error_count(&self) -> usize {
-        self.lexical_errors.len()
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    pub fn note_count(&self) -> usize {
+        self.count(Severity::Note)
+    }
+
+    pub fn help_count(&self) -> usize {
+        self.count(Severity::Help)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Every suggestion attached to any diagnostic
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        self.diagnostics.iter().flat_map(|d| d.suggestions.iter().cloned()).collect()
+    }
+
+    pub fn parse_errors(&self) -> &[ParseError] {
+        &self.parse_errors
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
     }
 
     pub fn report_all(&self) {
-        if self.lexical_errors.is_empty() {
+        if self.diagnostics.is_empty() {
             return;
         }
 
-        Logger::error(&format!("\n{} lexical error(s) found:", self.lexical_errors.len()));
-
-        for (idx, error) in self.lexical_errors.iter().enumerate() {
-            Logger::formatted_error(error, &error.span(), &self.source);
+        for (idx, diagnostic) in self.diagnostics.iter().enumerate() {
+            Logger::formatted_diagnostic(diagnostic, &self.source);
 
-            if let Some(suggestion) = error.suggestion() {
-                eprintln!("   \x1b[33mSuggestion:\x1b[0m {}", suggestion);
+            if idx < self.diagnostics.len() - 1 {
+                eprintln!();
             }
+        }
 
-            if idx < self.lexical_errors.len() - 1 {
-                eprintln!();
+        Logger::info(&self.summary());
+    }
+
+    /// Render every diagnostic with the given backend
+    pub fn render(&self, renderer: &dyn DiagnosticRenderer, path: &str) -> String {
+        renderer.render(&self.diagnostics, &self.source, path)
+    }
+
+    /// e.g. "2 error(s), 1 warning(s)"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        for severity in [Severity::Error, Severity::Warning, Severity::Note, Severity::Help] {
+            let count = self.count(severity);
+            if count > 0 {
+                parts.push(format!("{} {}(s)", count, severity));
             }
         }
+        parts.join(", ")
     }
 
     pub fn take_errors(&mut self) -> Vec<LexicalError> {
+        self.diagnostics.retain(|d| !d.is_error());
+        self.parse_errors.clear();
         std::mem::take(&mut self.lexical_errors)
     }
 }
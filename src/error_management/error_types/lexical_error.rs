@@ -1,9 +1,11 @@
 //! Lexical errors with fix suggestions
 
 use crate::lexer::Span;
+use crate::error_management::error_codes;
+use crate::error_management::fixes::{Applicability, Suggestion};
 use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LexicalError {
     UnexpectedChar {
         ch: char,
@@ -49,6 +51,19 @@ pub enum StringType {
 }
 
 impl LexicalError {
+    /// Stable diagnostic code, see `stratc explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexicalError::UnexpectedChar { .. } => error_codes::E0001,
+            LexicalError::UnterminatedString { .. } => error_codes::E0002,
+            LexicalError::UnterminatedBlockComment { .. } => error_codes::E0003,
+            LexicalError::InvalidNumber { .. } => error_codes::E0004,
+            LexicalError::InvalidEscape { .. } => error_codes::E0005,
+            LexicalError::InvalidInterpolation { .. } => error_codes::E0006,
+            LexicalError::InvalidCharLiteral { .. } => error_codes::E0007,
+        }
+    }
+
     pub fn span(&self) -> Span {
         match self {
             LexicalError::UnexpectedChar { span, .. } => *span,
@@ -131,10 +146,67 @@ impl LexicalError {
     }
 }
 
+/// ASCII replacement for typographic quotes pasted from documents
+pub fn ascii_quote_for(ch: char) -> Option<char> {
+    match ch {
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{00AB}' | '\u{00BB}' => Some('"'),
+        '\u{2018}' | '\u{2019}' | '\u{201A}' => Some('\''),
+        _ => None,
+    }
+}
+
+impl LexicalError {
+    /// Structured edits that resolve this error
+    pub fn fixes(&self, source: &str) -> Vec<Suggestion> {
+        match self {
+            LexicalError::UnexpectedChar { ch, span, .. } => {
+                ascii_quote_for(*ch)
+                    .map(|quote| Suggestion::replace(
+                        format!("Replace '{}' with '{}'", ch, quote),
+                        *span,
+                        quote.to_string(),
+                        Applicability::MachineApplicable,
+                    ))
+                    .into_iter()
+                    .collect()
+            }
+            LexicalError::UnterminatedString { span, string_type } => {
+                // Normal strings end at the line break; the others may span lines
+                let applicability = if *string_type == StringType::Normal {
+                    Applicability::MachineApplicable
+                } else {
+                    Applicability::MaybeIncorrect
+                };
+                vec![Suggestion::insert("Insert closing quote", Span::point(source, span.end), "\"", applicability)]
+            }
+            LexicalError::UnterminatedBlockComment { span, nesting_level } => {
+                let closing = " */".repeat(*nesting_level);
+                vec![Suggestion::insert("Insert closing */", Span::point(source, span.end), closing, Applicability::MachineApplicable)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl fmt::Display for LexicalError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.message())
     }
 }
 
-impl std::error::Error for LexicalError {}
\ No newline at end of file
+impl std::error::Error for LexicalError {}
+
+impl miette::Diagnostic for LexicalError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.suggestion().map(|s| Box::new(s) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span();
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(span.start..span.end, "here"))))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,127 @@
+//! Lexical warnings - suspicious but valid input
+
+use crate::lexer::Span;
+use crate::error_management::error_codes;
+use crate::error_management::fixes::{Applicability, Suggestion};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum LexicalWarning {
+    MixedIndentation {
+        span: Span,
+    },
+    TrailingWhitespaceInVerbatim {
+        span: Span,
+    },
+    TrailingDotFloat {
+        text: String,
+        span: Span,
+    },
+    UnterminatedDocComment {
+        span: Span,
+    },
+}
+
+impl LexicalWarning {
+    /// Stable diagnostic code, see `stratc explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexicalWarning::MixedIndentation { .. } => error_codes::W0001,
+            LexicalWarning::TrailingWhitespaceInVerbatim { .. } => error_codes::W0002,
+            LexicalWarning::TrailingDotFloat { .. } => error_codes::W0003,
+            LexicalWarning::UnterminatedDocComment { .. } => error_codes::W0004,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            LexicalWarning::MixedIndentation { span } => *span,
+            LexicalWarning::TrailingWhitespaceInVerbatim { span } => *span,
+            LexicalWarning::TrailingDotFloat { span, .. } => *span,
+            LexicalWarning::UnterminatedDocComment { span } => *span,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            LexicalWarning::MixedIndentation { .. } => {
+                "Indentation mixes tabs and spaces".to_string()
+            }
+            LexicalWarning::TrailingWhitespaceInVerbatim { .. } => {
+                "Trailing whitespace inside verbatim string is part of its value".to_string()
+            }
+            LexicalWarning::TrailingDotFloat { text, .. } => {
+                format!("Float literal '{}' has no digits after the decimal point", text)
+            }
+            LexicalWarning::UnterminatedDocComment { .. } => {
+                "Unterminated doc comment runs to end of file".to_string()
+            }
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            LexicalWarning::MixedIndentation { .. } => {
+                Some("Indent with either tabs or spaces, not both".to_string())
+            }
+            LexicalWarning::TrailingWhitespaceInVerbatim { .. } => {
+                Some("Remove the trailing whitespace if it is not intended".to_string())
+            }
+            LexicalWarning::TrailingDotFloat { text, .. } => {
+                let digits = text.trim_end_matches(['f', 'F']);
+                let suffix = &text[digits.len()..];
+                Some(format!("Write '{}0{}' instead", digits, suffix))
+            }
+            LexicalWarning::UnterminatedDocComment { .. } => {
+                Some("Add closing */".to_string())
+            }
+        }
+    }
+}
+
+impl LexicalWarning {
+    /// Structured edits that silence this warning
+    pub fn fixes(&self, source: &str) -> Vec<Suggestion> {
+        match self {
+            LexicalWarning::TrailingDotFloat { text, span } => {
+                let digits = text.trim_end_matches(['f', 'F']);
+                let at = Span::point(source, span.start + digits.len());
+                vec![Suggestion::insert("Add a fractional zero", at, "0", Applicability::MachineApplicable)]
+            }
+            LexicalWarning::UnterminatedDocComment { span } => {
+                vec![Suggestion::insert("Insert closing */", Span::point(source, span.end), " */", Applicability::MachineApplicable)]
+            }
+            LexicalWarning::TrailingWhitespaceInVerbatim { span } => {
+                vec![Suggestion::replace("Remove trailing whitespace", *span, "", Applicability::MaybeIncorrect)]
+            }
+            LexicalWarning::MixedIndentation { .. } => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for LexicalWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for LexicalWarning {}
+
+impl miette::Diagnostic for LexicalWarning {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(miette::Severity::Warning)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.suggestion().map(|s| Box::new(s) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span();
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(span.start..span.end, "here"))))
+    }
+}
@@ -1,9 +1,13 @@
 //! Error type definitions
 
 pub mod lexical_error;
+pub mod lexical_warning;
+pub mod parse_error;
+pub mod semantic_error;
 // TODO: Future error types
-// pub mod parse_error;
-// pub mod semantic_error;
 // pub mod runtime_error;
 
-pub use lexical_error::{LexicalError, StringType};
\ No newline at end of file
+pub use lexical_error::{LexicalError, StringType, ascii_quote_for};
+pub use lexical_warning::LexicalWarning;
+pub use parse_error::ParseError;
+pub use semantic_error::SemanticError;
@@ -0,0 +1,151 @@
+//! Syntax errors with the set of tokens the parser would have accepted
+
+use crate::lexer::Span;
+use crate::error_management::error_types::LexicalError;
+use crate::error_management::error_codes;
+use crate::error_management::fixes::{Applicability, Suggestion};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// `found` is already in message form: "`let`" or "a newline"
+    UnexpectedToken {
+        expected: Vec<String>,
+        found: String,
+        span: Span,
+    },
+    UnexpectedEof {
+        expected: Vec<String>,
+        span: Span,
+    },
+    /// A construct that is well-tokenised but not allowed here
+    InvalidSyntax {
+        message: String,
+        span: Span,
+        suggestion: Option<String>,
+    },
+    /// `a < b < c`; `replacement` is the `a < b and b < c` spelling
+    ChainedComparison {
+        span: Span,
+        replacement: String,
+    },
+    /// Found by the lexer inside an interpolation hole, which is only
+    /// tokenised when the parser reaches it; keeps the lexer's code
+    Lexical(LexicalError),
+}
+
+impl ParseError {
+    /// Stable diagnostic code, see `stratc explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedToken { .. } => error_codes::E0100,
+            ParseError::UnexpectedEof { .. } => error_codes::E0101,
+            ParseError::InvalidSyntax { .. } => error_codes::E0102,
+            ParseError::ChainedComparison { .. } => error_codes::E0103,
+            ParseError::Lexical(error) => error.code(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. } => *span,
+            ParseError::UnexpectedEof { span, .. } => *span,
+            ParseError::InvalidSyntax { span, .. } => *span,
+            ParseError::ChainedComparison { span, .. } => *span,
+            ParseError::Lexical(error) => error.span(),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken { expected, found, .. } => {
+                format!("expected {}, found {}", expected_list(expected), found)
+            }
+            ParseError::UnexpectedEof { expected, .. } => {
+                format!("expected {}, found end of file", expected_list(expected))
+            }
+            ParseError::InvalidSyntax { message, .. } => message.clone(),
+            ParseError::ChainedComparison { .. } => "comparison operators cannot be chained".to_string(),
+            ParseError::Lexical(error) => error.message(),
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            ParseError::UnexpectedEof { expected, .. } => {
+                closing_delimiter(expected).map(|token| format!("Add the missing {}", token))
+            }
+            ParseError::UnexpectedToken { .. } => None,
+            ParseError::InvalidSyntax { suggestion, .. } => suggestion.clone(),
+            ParseError::ChainedComparison { replacement, .. } => {
+                Some(format!("Split the comparison: `{}`", replacement))
+            }
+            ParseError::Lexical(error) => error.suggestion(),
+        }
+    }
+
+    /// Structured edits that resolve this error
+    pub fn fixes(&self, source: &str) -> Vec<Suggestion> {
+        match self {
+            ParseError::UnexpectedEof { expected, span } => {
+                closing_delimiter(expected)
+                    .map(|token| Suggestion::insert(
+                        format!("Insert {}", token),
+                        Span::point(source, span.end),
+                        token.trim_matches('`'),
+                        Applicability::MaybeIncorrect,
+                    ))
+                    .into_iter()
+                    .collect()
+            }
+            ParseError::ChainedComparison { span, replacement } => vec![Suggestion::replace(
+                "Split the comparison",
+                *span,
+                replacement.clone(),
+                Applicability::MaybeIncorrect,
+            )],
+            ParseError::Lexical(error) => error.fixes(source),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// "`)`", "one of `,` or `)`", "one of `fn`, `struct` or `enum`"
+pub fn expected_list(expected: &[String]) -> String {
+    match expected {
+        [] => "something else".to_string(),
+        [only] => only.clone(),
+        [init @ .., last] => format!("one of {} or {}", init.join(", "), last),
+    }
+}
+
+/// The single closing delimiter the parser was waiting for, if that is all it wanted
+fn closing_delimiter(expected: &[String]) -> Option<&str> {
+    match expected {
+        [only] if matches!(only.as_str(), "`)`" | "`]`" | "`}`" | "`>`") => Some(only),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl miette::Diagnostic for ParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.suggestion().map(|s| Box::new(s) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span();
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(span.start..span.end, "here"))))
+    }
+}
@@ -0,0 +1,191 @@
+//! Errors in programs that parse but do not make sense, such as a method
+//! call that resolves to more than one extension or a reference to a
+//! lifetime nobody declared
+
+use crate::lexer::Span;
+use crate::error_management::error_codes;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticError {
+    /// More than one summoned module extends `ty` with `method`; `modules`
+    /// are the candidates in summon order
+    AmbiguousMethod {
+        method: String,
+        ty: String,
+        modules: Vec<String>,
+        span: Span,
+    },
+    /// `module` extends `ty` with `method`, but is not summoned
+    MethodNotSummoned {
+        method: String,
+        ty: String,
+        module: String,
+        span: Span,
+    },
+    /// A reference type or `outlives` constraint names a lifetime missing
+    /// from the function's `[lifetime ...]` list
+    UndeclaredLifetime {
+        name: String,
+        span: Span,
+    },
+    /// A where-clause bounds `name`, which is not a generic parameter in scope
+    UndeclaredGeneric {
+        name: String,
+        span: Span,
+    },
+    /// `@name` is not in the attribute registry; `known` lists the ones
+    /// that are, as `@tier`
+    UnknownAttribute {
+        name: String,
+        known: Vec<String>,
+        span: Span,
+    },
+    /// A known attribute on a kind of item it does not apply to; `target`
+    /// and `allowed` are plural kinds such as "structs"
+    MisplacedAttribute {
+        name: String,
+        target: String,
+        allowed: Vec<String>,
+        span: Span,
+    },
+    /// Arguments that do not fit the attribute's schema; `usage` shows the
+    /// expected form, e.g. `@tier(high|mid|low)`
+    InvalidAttributeArgs {
+        name: String,
+        usage: String,
+        span: Span,
+    },
+    /// The same attribute twice on one item
+    DuplicateAttribute {
+        name: String,
+        span: Span,
+    },
+    /// Two attributes on one item that contradict each other, as written
+    ConflictingAttributes {
+        first: String,
+        second: String,
+        span: Span,
+    },
+    /// `outlives` constraints that loop back to their start; `cycle` lists
+    /// the lifetimes in order, ending where it began
+    OutlivesCycle {
+        cycle: Vec<String>,
+        span: Span,
+    },
+}
+
+impl SemanticError {
+    /// Stable diagnostic code, see `stratc explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::AmbiguousMethod { .. } => error_codes::E0200,
+            SemanticError::MethodNotSummoned { .. } => error_codes::E0201,
+            SemanticError::UndeclaredLifetime { .. } => error_codes::E0202,
+            SemanticError::OutlivesCycle { .. } => error_codes::E0203,
+            SemanticError::UndeclaredGeneric { .. } => error_codes::E0204,
+            SemanticError::UnknownAttribute { .. } => error_codes::E0205,
+            SemanticError::MisplacedAttribute { .. } => error_codes::E0206,
+            SemanticError::InvalidAttributeArgs { .. } => error_codes::E0207,
+            SemanticError::DuplicateAttribute { .. } => error_codes::E0208,
+            SemanticError::ConflictingAttributes { .. } => error_codes::E0209,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            SemanticError::AmbiguousMethod { span, .. } => *span,
+            SemanticError::MethodNotSummoned { span, .. } => *span,
+            SemanticError::UndeclaredLifetime { span, .. } => *span,
+            SemanticError::OutlivesCycle { span, .. } => *span,
+            SemanticError::UndeclaredGeneric { span, .. } => *span,
+            SemanticError::UnknownAttribute { span, .. } => *span,
+            SemanticError::MisplacedAttribute { span, .. } => *span,
+            SemanticError::InvalidAttributeArgs { span, .. } => *span,
+            SemanticError::DuplicateAttribute { span, .. } => *span,
+            SemanticError::ConflictingAttributes { span, .. } => *span,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            SemanticError::AmbiguousMethod { method, ty, modules, .. } => {
+                format!("`{}` on `{}` is ambiguous: it is an extension in {}", method, ty, module_list(modules))
+            }
+            SemanticError::MethodNotSummoned { method, ty, .. } => {
+                format!("no method `{}` on `{}` is in scope", method, ty)
+            }
+            SemanticError::UndeclaredLifetime { name, .. } => format!("use of undeclared lifetime `{}`", name),
+            SemanticError::OutlivesCycle { cycle, .. } => {
+                format!("lifetime constraints form a cycle: `{}`", cycle.join(" outlives "))
+            }
+            SemanticError::UndeclaredGeneric { name, .. } => {
+                format!("cannot bound `{}`: it is not a generic parameter here", name)
+            }
+            SemanticError::UnknownAttribute { name, .. } => format!("unknown attribute `@{}`", name),
+            SemanticError::MisplacedAttribute { name, target, .. } => {
+                format!("`@{}` cannot be applied to {}", name, target)
+            }
+            SemanticError::InvalidAttributeArgs { name, .. } => format!("invalid arguments to `@{}`", name),
+            SemanticError::DuplicateAttribute { name, .. } => format!("duplicate attribute `@{}`", name),
+            SemanticError::ConflictingAttributes { first, second, .. } => {
+                format!("`{}` conflicts with `{}`", second, first)
+            }
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            SemanticError::AmbiguousMethod { method, modules, .. } => {
+                Some(format!("Pick one with `from {} summon [{}]`", modules[0], method))
+            }
+            SemanticError::MethodNotSummoned { module, .. } => Some(format!("Add `summon {}`", module)),
+            SemanticError::UndeclaredLifetime { name, .. } => {
+                Some(format!("Declare it after the function name: `[lifetime {}]`", name))
+            }
+            SemanticError::OutlivesCycle { .. } => {
+                Some("Lifetimes that outlive each other are the same lifetime; use one name".to_string())
+            }
+            SemanticError::UndeclaredGeneric { name, .. } => Some(format!("Declare it first: `<{}>`", name)),
+            SemanticError::UnknownAttribute { known, .. } => Some(format!("Known attributes are {}", module_list(known))),
+            SemanticError::MisplacedAttribute { name, allowed, .. } => {
+                Some(format!("`@{}` applies to: {}", name, allowed.join(", ")))
+            }
+            SemanticError::InvalidAttributeArgs { usage, .. } => Some(format!("Write it as `{}`", usage)),
+            SemanticError::DuplicateAttribute { .. } => Some("Remove the repeated attribute".to_string()),
+            SemanticError::ConflictingAttributes { .. } => Some("Keep only one of them".to_string()),
+        }
+    }
+}
+
+/// "`a`", "`a` and `b`", "`a`, `b` and `c`"
+fn module_list(modules: &[String]) -> String {
+    let quoted: Vec<String> = modules.iter().map(|m| format!("`{}`", m)).collect();
+    match quoted.as_slice() {
+        [init @ .., last] if !init.is_empty() => format!("{} and {}", init.join(", "), last),
+        _ => quoted.concat(),
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl miette::Diagnostic for SemanticError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.suggestion().map(|s| Box::new(s) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span();
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(span.start..span.end, "here"))))
+    }
+}
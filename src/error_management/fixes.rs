@@ -0,0 +1,129 @@
+//! Structured fix-it suggestions and applying them to source files
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::lexer::Span;
+
+/// How confident we are that a suggestion can be applied without review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    /// Definitely what the user meant; `--fix` applies it
+    MachineApplicable,
+    /// Probably right, but the user should look first
+    MaybeIncorrect,
+    /// Contains placeholders the user must fill in
+    HasPlaceholders,
+    Unspecified,
+}
+
+impl Applicability {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+            Applicability::HasPlaceholders => "has-placeholders",
+            Applicability::Unspecified => "unspecified",
+        }
+    }
+}
+
+impl fmt::Display for Applicability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Replace the text covered by `span` with `replacement` (empty span = insertion)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn replace(message: impl Into<String>, span: Span, replacement: impl Into<String>, applicability: Applicability) -> Self {
+        Suggestion {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+            applicability,
+        }
+    }
+
+    pub fn insert(message: impl Into<String>, at: Span, text: impl Into<String>, applicability: Applicability) -> Self {
+        Self::replace(message, Span::new(at.start, at.start, at.line, at.column), text, applicability)
+    }
+
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixResult {
+    pub output: String,
+    pub applied: usize,
+    /// Suggestions dropped because they overlap an earlier one
+    pub conflicting: usize,
+}
+
+/// Apply machine-applicable suggestions; overlapping edits keep the first one
+pub fn apply_suggestions(source: &str, suggestions: &[Suggestion]) -> FixResult {
+    let mut edits: Vec<&Suggestion> = suggestions.iter().filter(|s| s.is_machine_applicable()).collect();
+    edits.sort_by_key(|s| (s.span.start, s.span.end));
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut last_start = None;
+    let mut applied = 0;
+    let mut conflicting = 0;
+
+    for edit in edits {
+        // Edits starting at the same offset would interleave unpredictably
+        let overlaps = edit.span.start < cursor || last_start == Some(edit.span.start);
+        if overlaps || edit.span.end > source.len() {
+            conflicting += 1;
+            continue;
+        }
+
+        output.push_str(&source[cursor..edit.span.start]);
+        output.push_str(&edit.replacement);
+        cursor = edit.span.end;
+        last_start = Some(edit.span.start);
+        applied += 1;
+    }
+
+    output.push_str(&source[cursor..]);
+    FixResult { output, applied, conflicting }
+}
+
+/// Unified diff between the original and fixed text
+pub fn diff(path: &str, original: &str, fixed: &str) -> String {
+    let path = path.trim_start_matches('/');
+    let mut options = diffy::DiffOptions::new();
+    options
+        .set_original_filename(format!("a/{}", path))
+        .set_modified_filename(format!("b/{}", path));
+    options.create_patch(original, fixed).to_string()
+}
+
+/// Replace `path` in one step so a crash never leaves a half-written file
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{}.fix.tmp", file_name));
+
+    fs::write(&temp, contents)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(&temp, metadata.permissions())?;
+    }
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
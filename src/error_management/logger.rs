@@ -2,8 +2,10 @@
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::fmt;
+use crate::error_management::diagnostics::{Diagnostic, DiagnosticFormatter};
 
 static LOGGER_ENABLED: AtomicBool = AtomicBool::new(true);
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogLevel {
@@ -28,6 +30,14 @@ impl Logger {
         LOGGER_ENABLED.load(Ordering::SeqCst)
     }
 
+    pub fn set_color(enabled: bool) {
+        COLOR_ENABLED.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn color_enabled() -> bool {
+        COLOR_ENABLED.load(Ordering::SeqCst)
+    }
+
     pub fn debug(message: &str) {
         Self::log(LogLevel::Debug, message);
     }
@@ -56,29 +66,22 @@ impl Logger {
             LogLevel::Error => ("ERROR", "\x1b[31m"),    // Red
         };
 
-        eprintln!("{}[{}]\x1b[0m {}", color, prefix, message);
+        if Self::color_enabled() {
+            eprintln!("{}[{}]\x1b[0m {}", color, prefix, message);
+        } else {
+            eprintln!("[{}] {}", prefix, message);
+        }
     }
 
     pub fn formatted_error(error: &impl fmt::Display, span: &crate::lexer::Span, source: &str) {
+        Self::formatted_diagnostic(&Diagnostic::error(error.to_string(), *span), source);
+    }
+
+    pub fn formatted_diagnostic(diagnostic: &Diagnostic, source: &str) {
         if !Self::is_enabled() {
             return;
         }
 
-        // Extract source line
-        let lines: Vec<&str> = source.lines().collect();
-        let line_text = if span.line > 0 && span.line <= lines.len() {
-            lines[span.line - 1]
-        } else {
-            ""
-        };
-
-        eprintln!("\x1b[31m[ERROR]\x1b[0m {}", error);
-        eprintln!("  \x1b[36m--> {}:{}\x1b[0m", span.line, span.column);
-        eprintln!("   |");
-        eprintln!("{:3} | {}", span.line, line_text);
-        eprintln!("   | {}{}",
-                  " ".repeat(span.column.saturating_sub(1)),
-                  "\x1b[31m^\x1b[0m"
-        );
+        eprint!("{}", DiagnosticFormatter::format_diagnostic_with(diagnostic, source, Self::color_enabled()));
     }
-}
\ No newline at end of file
+}
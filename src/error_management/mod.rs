@@ -4,7 +4,12 @@ pub mod error_manager;
 pub mod logger;
 pub mod error_types;
 pub mod diagnostics;
+pub mod error_codes;
+pub mod renderer;
+pub mod fixes;
 
 pub use error_manager::ErrorManager;
 pub use logger::Logger;
-pub use diagnostics::DiagnosticFormatter;
\ No newline at end of file
+pub use diagnostics::{Diagnostic, DiagnosticFormatter, Severity};
+pub use renderer::{DiagnosticRenderer, MessageFormat};
+pub use fixes::{Applicability, Suggestion};
@@ -0,0 +1,262 @@
+//! Diagnostic renderer backends: fancy (miette), plain text, JSON lines and SARIF
+
+use std::fmt;
+use std::str::FromStr;
+use serde_json::{json, Value};
+use crate::error_management::diagnostics::{Diagnostic, DiagnosticFormatter};
+use crate::error_management::error_codes;
+use crate::lexer::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Graphical miette output
+    Fancy,
+    /// rustc-style text, never coloured
+    Plain,
+    /// One JSON object per line
+    Json,
+    /// A single SARIF 2.1.0 log
+    Sarif,
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fancy" | "human" => Ok(MessageFormat::Fancy),
+            "plain" | "short" => Ok(MessageFormat::Plain),
+            "json" => Ok(MessageFormat::Json),
+            "sarif" => Ok(MessageFormat::Sarif),
+            other => Err(format!("unknown message format '{}' (expected fancy, plain, json or sarif)", other)),
+        }
+    }
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            MessageFormat::Fancy => "fancy",
+            MessageFormat::Plain => "plain",
+            MessageFormat::Json => "json",
+            MessageFormat::Sarif => "sarif",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl MessageFormat {
+    /// Machine formats go to stdout so they can be redirected to a file
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, MessageFormat::Json | MessageFormat::Sarif)
+    }
+}
+
+/// True unless `--no-color` was passed or `NO_COLOR` is set to a non-empty value
+pub fn color_requested(no_color_flag: bool) -> bool {
+    if no_color_flag {
+        return false;
+    }
+    !matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty())
+}
+
+pub trait DiagnosticRenderer {
+    /// Render every diagnostic for one file; `path` is used as the file name
+    fn render(&self, diagnostics: &[Diagnostic], source: &str, path: &str) -> String;
+}
+
+pub fn renderer_for(format: MessageFormat, colored: bool) -> Box<dyn DiagnosticRenderer> {
+    match format {
+        MessageFormat::Fancy => Box::new(FancyRenderer { colored }),
+        MessageFormat::Plain => Box::new(PlainRenderer),
+        MessageFormat::Json => Box::new(JsonRenderer),
+        MessageFormat::Sarif => Box::new(SarifRenderer),
+    }
+}
+
+pub struct FancyRenderer {
+    pub colored: bool,
+}
+
+impl DiagnosticRenderer for FancyRenderer {
+    fn render(&self, diagnostics: &[Diagnostic], source: &str, path: &str) -> String {
+        let theme = if self.colored {
+            miette::GraphicalTheme::unicode()
+        } else {
+            miette::GraphicalTheme::unicode_nocolor()
+        };
+        let handler = miette::GraphicalReportHandler::new_themed(theme);
+
+        let mut output = String::new();
+        for diagnostic in diagnostics {
+            let report = miette::Report::new(diagnostic.clone())
+                .with_source_code(miette::NamedSource::new(path, source.to_string()));
+            if handler.render_report(&mut output, report.as_ref()).is_err() {
+                output.push_str(&DiagnosticFormatter::format_diagnostic_with(diagnostic, source, false));
+            }
+        }
+        output
+    }
+}
+
+pub struct PlainRenderer;
+
+impl DiagnosticRenderer for PlainRenderer {
+    fn render(&self, diagnostics: &[Diagnostic], source: &str, path: &str) -> String {
+        diagnostics
+            .iter()
+            .map(|d| {
+                let text = DiagnosticFormatter::format_diagnostic_with(d, source, false);
+                // Put the file name in front of `line:column`
+                text.replacen("--> ", &format!("--> {}:", path), 1)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub struct JsonRenderer;
+
+impl DiagnosticRenderer for JsonRenderer {
+    fn render(&self, diagnostics: &[Diagnostic], source: &str, path: &str) -> String {
+        diagnostics
+            .iter()
+            .map(|d| {
+                let (end_line, end_column) = line_col(source, d.span.end);
+                let value = json!({
+                    "severity": d.severity.label(),
+                    "code": d.code,
+                    "message": d.message,
+                    "file": path,
+                    "span": {
+                        "start": d.span.start,
+                        "end": d.span.end,
+                        "line": d.span.line,
+                        "column": d.span.column,
+                        "end_line": end_line,
+                        "end_column": end_column,
+                    },
+                    "help": d.help,
+                    "notes": d.notes,
+                    "suggestions": d.suggestions.iter().map(|fix| json!({
+                        "message": fix.message,
+                        "start": fix.span.start,
+                        "end": fix.span.end,
+                        "replacement": fix.replacement,
+                        "applicability": fix.applicability.label(),
+                    })).collect::<Vec<_>>(),
+                });
+                format!("{}\n", value)
+            })
+            .collect()
+    }
+}
+
+pub struct SarifRenderer;
+
+impl DiagnosticRenderer for SarifRenderer {
+    fn render(&self, diagnostics: &[Diagnostic], source: &str, path: &str) -> String {
+        let mut rule_ids: Vec<&str> = diagnostics.iter().filter_map(|d| d.code).collect();
+        rule_ids.sort_unstable();
+        rule_ids.dedup();
+
+        let rules: Vec<Value> = rule_ids
+            .iter()
+            .map(|id| match error_codes::lookup(id) {
+                Some(entry) => json!({
+                    "id": entry.code,
+                    "shortDescription": { "text": entry.title },
+                    "fullDescription": { "text": entry.explanation },
+                }),
+                None => json!({ "id": id }),
+            })
+            .collect();
+
+        let results: Vec<Value> = diagnostics
+            .iter()
+            .map(|d| {
+                let (end_line, end_column) = line_col(source, d.span.end);
+                let (char_offset, char_length) = char_range(source, d.span);
+                let mut text = d.message.clone();
+                if let Some(help) = &d.help {
+                    text.push_str(&format!("\nhelp: {}", help));
+                }
+                for note in &d.notes {
+                    text.push_str(&format!("\nnote: {}", note));
+                }
+
+                let mut result = json!({
+                    "level": d.severity.sarif_level(),
+                    "message": { "text": text },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": path },
+                            "region": {
+                                "startLine": d.span.line,
+                                "startColumn": d.span.column,
+                                "endLine": end_line,
+                                "endColumn": end_column,
+                                "charOffset": char_offset,
+                                "charLength": char_length,
+                            }
+                        }
+                    }],
+                });
+                if let Some(code) = d.code {
+                    result["ruleId"] = json!(code);
+                }
+                if !d.suggestions.is_empty() {
+                    result["fixes"] = d.suggestions.iter().map(|fix| {
+                        let (char_offset, char_length) = char_range(source, fix.span);
+                        json!({
+                        "description": { "text": fix.message },
+                        "artifactChanges": [{
+                            "artifactLocation": { "uri": path },
+                            "replacements": [{
+                                "deletedRegion": {
+                                    "charOffset": char_offset,
+                                    "charLength": char_length,
+                                },
+                                "insertedContent": { "text": fix.replacement },
+                            }],
+                        }],
+                        })
+                    }).collect();
+                }
+                result
+            })
+            .collect();
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "stratc",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }]
+        });
+
+        format!("{}\n", serde_json::to_string_pretty(&log).unwrap_or_default())
+    }
+}
+
+/// `span` in code points, as the run's `columnKind` promises: (offset, length)
+fn char_range(source: &str, span: Span) -> (usize, usize) {
+    let chars_before = |offset: usize| source.char_indices().take_while(|&(i, _)| i < offset).count();
+    let start = chars_before(span.start);
+    (start, chars_before(span.end).saturating_sub(start))
+}
+
+/// 1-based line and column of a byte offset
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let point = Span::point(source, offset);
+    (point.line, point.column)
+}
@@ -0,0 +1,399 @@
+//! The EBNF notation of `docs/ubel.ebnf`
+//!
+//! A rule is `Name ::= body` and runs until the next `Name ::=`. Bodies use
+//! `|`, grouping parentheses, the postfix `?`, `*` and `+`, quoted
+//! terminals (`"fn"` or `'"'`), character classes such as `[a-zA-Z]`, and
+//! `A - B` for "an `A` that is not a `B`". `(* ... *)` is a comment.
+
+use std::fmt;
+
+/// A rule body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// Quoted text, written as-is
+    Terminal(String),
+    /// A reference to another rule
+    Rule(String),
+    /// `[a-zA-Z]`, as inclusive ranges
+    Class(Vec<(char, char)>),
+    Seq(Vec<Expr>),
+    Alt(Vec<Expr>),
+    /// `e?`
+    Optional(Box<Expr>),
+    /// `e*` when `min` is 0, `e+` when it is 1
+    Repeat { expr: Box<Expr>, min: usize },
+    /// `a - b`
+    Except(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub name: String,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    /// In file order
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EbnfError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for EbnfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+impl std::error::Error for EbnfError {}
+
+impl Grammar {
+    pub fn parse(text: &str) -> Result<Grammar, EbnfError> {
+        let tokens = tokenize(text)?;
+        let mut parser = RuleParser { tokens, pos: 0 };
+        let mut rules: Vec<Rule> = Vec::new();
+        while !parser.at_end() {
+            let (name, at) = parser.rule_name()?;
+            if rules.iter().any(|r| r.name == name) {
+                return Err(parser.error_at(at, format!("rule `{}` is defined twice", name)));
+            }
+            let body = parser.alt()?;
+            rules.push(Rule { name, body });
+        }
+        Ok(Grammar { rules })
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|r| r.name == name)
+    }
+
+    /// Names referenced by some rule but defined by none, in order of first use
+    pub fn undefined(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for rule in &self.rules {
+            rule.body.references(&mut |name| {
+                if self.rule(name).is_none() && !names.contains(&name) {
+                    names.push(name);
+                }
+            });
+        }
+        names
+    }
+}
+
+impl Expr {
+    /// Calls `f` with every rule name in `self`, in order
+    pub fn references<'a>(&'a self, f: &mut impl FnMut(&'a str)) {
+        match self {
+            Expr::Rule(name) => f(name),
+            Expr::Terminal(_) | Expr::Class(_) => {}
+            Expr::Seq(items) | Expr::Alt(items) => items.iter().for_each(|e| e.references(f)),
+            Expr::Optional(expr) | Expr::Repeat { expr, .. } => expr.references(f),
+            Expr::Except(expr, except) => {
+                expr.references(f);
+                except.references(f);
+            }
+        }
+    }
+}
+
+// ========================================
+// Writing the notation
+// ========================================
+
+/// Widest line `Rule` writes before it breaks between alternatives
+const WIDTH: usize = 80;
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_expr(f, self, Binding::Alt)
+    }
+}
+
+/// How tightly an expression binds, loosest first
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Binding {
+    Alt,
+    Seq,
+    Except,
+    Postfix,
+    Atom,
+}
+
+fn binding(expr: &Expr) -> Binding {
+    match expr {
+        Expr::Alt(_) => Binding::Alt,
+        Expr::Seq(_) => Binding::Seq,
+        Expr::Except(..) => Binding::Except,
+        Expr::Optional(_) | Expr::Repeat { .. } => Binding::Postfix,
+        Expr::Terminal(_) | Expr::Rule(_) | Expr::Class(_) => Binding::Atom,
+    }
+}
+
+/// `expr`, parenthesised if it binds looser than `context` allows
+fn write_expr(f: &mut fmt::Formatter, expr: &Expr, context: Binding) -> fmt::Result {
+    if binding(expr) < context {
+        write!(f, "(")?;
+        write_expr(f, expr, Binding::Alt)?;
+        return write!(f, ")");
+    }
+    match expr {
+        Expr::Terminal(text) if text.contains('"') => write!(f, "'{}'", text),
+        Expr::Terminal(text) => write!(f, "\"{}\"", text),
+        Expr::Rule(name) => write!(f, "{}", name),
+        Expr::Class(ranges) => {
+            write!(f, "[")?;
+            for &(low, high) in ranges {
+                match low == high {
+                    true => write!(f, "{}", low)?,
+                    false => write!(f, "{}-{}", low, high)?,
+                }
+            }
+            write!(f, "]")
+        }
+        Expr::Seq(items) => write_list(f, items, " ", Binding::Except),
+        Expr::Alt(items) => write_list(f, items, " | ", Binding::Seq),
+        Expr::Optional(inner) => {
+            write_expr(f, inner, Binding::Postfix)?;
+            write!(f, "?")
+        }
+        Expr::Repeat { expr, min } => {
+            write_expr(f, expr, Binding::Postfix)?;
+            write!(f, "{}", if *min == 0 { "*" } else { "+" })
+        }
+        Expr::Except(expr, except) => {
+            write_expr(f, expr, Binding::Postfix)?;
+            write!(f, " - ")?;
+            write_expr(f, except, Binding::Postfix)
+        }
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter, items: &[Expr], separator: &str, context: Binding) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write_expr(f, item, context)?;
+    }
+    Ok(())
+}
+
+/// `Name ::= body`; a long choice continues on lines starting with `|`,
+/// under the `=` of `::=`
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let head = format!("{} ::= ", self.name);
+        let Expr::Alt(alternatives) = &self.body else {
+            return write!(f, "{}{}", head, self.body);
+        };
+        let indent = " ".repeat(self.name.len() + 3);
+        let mut line = head;
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let text = match binding(alternative) {
+                Binding::Alt => format!("({})", alternative),
+                _ => alternative.to_string(),
+            };
+            if i == 0 {
+                line.push_str(&text);
+            } else if line.len() + 3 + text.len() <= WIDTH {
+                line.push_str(" | ");
+                line.push_str(&text);
+            } else {
+                writeln!(f, "{}", line)?;
+                line = format!("{}| {}", indent, text);
+            }
+        }
+        write!(f, "{}", line)
+    }
+}
+
+// ========================================
+// Reading the notation
+// ========================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Name(String),
+    Terminal(String),
+    Class(Vec<(char, char)>),
+    Defines,
+    Punct(char),
+}
+
+/// A token and its line and column
+type Spanned = (Tok, usize, usize);
+
+fn tokenize(text: &str) -> Result<Vec<Spanned>, EbnfError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut column) = (0, 1, 1);
+    let error = |message: &str, line, column| EbnfError { message: message.to_string(), line, column };
+
+    while i < chars.len() {
+        let (start_line, start_column) = (line, column);
+        let c = chars[i];
+        let len = if c.is_whitespace() {
+            1
+        } else if chars[i..].starts_with(&['(', '*']) {
+            let close = (i + 2..chars.len()).find(|&j| chars[j..].starts_with(&['*', ')']));
+            let close = close.ok_or_else(|| error("unterminated comment", line, column))?;
+            close + 2 - i
+        } else if c == '"' || c == '\'' {
+            let close = chars[i + 1..].iter().position(|&q| q == c);
+            let close = close.ok_or_else(|| error("unterminated terminal", line, column))?;
+            tokens.push((Tok::Terminal(chars[i + 1..i + 1 + close].iter().collect()), line, column));
+            close + 2
+        } else if c == '[' && chars.get(i + 1) != Some(&']') {
+            let close = chars[i..].iter().position(|&q| q == ']');
+            let close = close.ok_or_else(|| error("unterminated character class", line, column))?;
+            tokens.push((Tok::Class(class(&chars[i + 1..i + close])), line, column));
+            close + 1
+        } else if chars[i..].starts_with(&[':', ':', '=']) {
+            tokens.push((Tok::Defines, line, column));
+            3
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = chars[i..].iter().take_while(|c| c.is_ascii_alphanumeric() || **c == '_').count();
+            tokens.push((Tok::Name(chars[i..i + len].iter().collect()), line, column));
+            len
+        } else if "|()*+?-".contains(c) {
+            tokens.push((Tok::Punct(c), line, column));
+            1
+        } else {
+            return Err(error(&format!("unexpected `{}`", c), start_line, start_column));
+        };
+
+        for &c in &chars[i..i + len] {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// `a-zA-Z_` to ranges
+fn class(chars: &[char]) -> Vec<(char, char)> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars.get(i + 1) == Some(&'-') && i + 2 < chars.len() {
+            ranges.push((chars[i], chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((chars[i], chars[i]));
+            i += 1;
+        }
+    }
+    ranges
+}
+
+struct RuleParser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl RuleParser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|(t, _, _)| t)
+    }
+
+    fn at_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Tok::Punct(c))
+    }
+
+    /// The next token starts another rule
+    fn at_rule_start(&self) -> bool {
+        matches!(self.peek(), Some(Tok::Name(_))) && matches!(self.tokens.get(self.pos + 1), Some((Tok::Defines, _, _)))
+    }
+
+    fn error_at(&self, pos: usize, message: String) -> EbnfError {
+        let (line, column) = match self.tokens.get(pos).or(self.tokens.last()) {
+            Some((_, line, column)) => (*line, *column),
+            None => (1, 1),
+        };
+        EbnfError { message, line, column }
+    }
+
+    fn rule_name(&mut self) -> Result<(String, usize), EbnfError> {
+        let at = self.pos;
+        if let (Some((Tok::Name(name), _, _)), Some((Tok::Defines, _, _))) = (self.tokens.get(at), self.tokens.get(at + 1)) {
+            let name = name.clone();
+            self.pos += 2;
+            return Ok((name, at));
+        }
+        Err(self.error_at(at, "expected `Name ::=`".to_string()))
+    }
+
+    fn alt(&mut self) -> Result<Expr, EbnfError> {
+        let mut alternatives = vec![self.seq()?];
+        while self.at_punct('|') {
+            self.pos += 1;
+            alternatives.push(self.seq()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Expr::Alt(alternatives) })
+    }
+
+    fn seq(&mut self) -> Result<Expr, EbnfError> {
+        let mut items = Vec::new();
+        while !self.at_end() && !self.at_punct('|') && !self.at_punct(')') && !self.at_rule_start() {
+            let mut item = self.postfix()?;
+            if self.at_punct('-') {
+                self.pos += 1;
+                item = Expr::Except(Box::new(item), Box::new(self.postfix()?));
+            }
+            items.push(item);
+        }
+        match items.len() {
+            0 => Err(self.error_at(self.pos, "expected a terminal, a rule name or `(`".to_string())),
+            1 => Ok(items.remove(0)),
+            _ => Ok(Expr::Seq(items)),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, EbnfError> {
+        let mut expr = self.primary()?;
+        loop {
+            expr = match self.peek() {
+                Some(Tok::Punct('?')) => Expr::Optional(Box::new(expr)),
+                Some(Tok::Punct('*')) => Expr::Repeat { expr: Box::new(expr), min: 0 },
+                Some(Tok::Punct('+')) => Expr::Repeat { expr: Box::new(expr), min: 1 },
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, EbnfError> {
+        let expr = match self.peek().cloned() {
+            Some(Tok::Name(name)) => Expr::Rule(name),
+            Some(Tok::Terminal(text)) => Expr::Terminal(text),
+            Some(Tok::Class(ranges)) => Expr::Class(ranges),
+            Some(Tok::Punct('(')) => {
+                self.pos += 1;
+                let inner = self.alt()?;
+                if !self.at_punct(')') {
+                    return Err(self.error_at(self.pos, "expected `)`".to_string()));
+                }
+                inner
+            }
+            _ => return Err(self.error_at(self.pos, "expected a terminal, a rule name or `(`".to_string())),
+        };
+        self.pos += 1;
+        Ok(expr)
+    }
+}
@@ -0,0 +1,589 @@
+//! Random programs from the documented grammar
+//!
+//! `Generator` expands rules of a `Grammar` at random, keeping every
+//! derivation within a depth budget: an alternative, repetition or option
+//! is only taken when its shortest derivation still fits. `check` runs the
+//! lexer and both parsers over the text, and `minimise` shrinks a rejected
+//! derivation while it keeps failing, so a counterexample is always a
+//! program the grammar allows.
+//!
+//! The notation has no layout, so rendering supplies it: tokens are
+//! separated by a space, the characters of a token rule (one that spells
+//! its text with character classes, such as `Ident` or `IntLit`) are not,
+//! and each of `LINE_RULES` ends its line.
+//!
+//! A few restrictions are stated in the grammar's comments rather than its
+//! notation; `UNBRACKETED` and `NOT_AFTER` hold the ones that decide how a
+//! program parses, and the generator keeps to them. It keeps to `A - B`
+//! where `B` names alternatives of `A` or spells words, and ignores it
+//! otherwise.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error_management::error_types::ParseError;
+use crate::error_management::ErrorManager;
+use crate::grammar::ebnf::{Expr, Grammar};
+use crate::lexer::{keywords, LogosLexer, Span};
+use crate::parser;
+
+/// Rules that end at a newline
+pub const LINE_RULES: &[&str] =
+    &["PackageDecl", "Import", "Item", "StructMember", "TraitItem", "ImplItem", "Statement", "MatchArm"];
+
+/// Character-level names the grammar uses without defining, and the
+/// characters they stand for here
+const CHARACTER_SETS: &[(&str, &str)] = &[
+    ("StringChar", "abc xyz_019"),
+    ("InterpolatedChar", "abc xyz_019"),
+    ("VerbatimChar", "abc xyz_019\\"),
+    ("Character", "abc xyz_019"),
+];
+
+/// Words that only act as keywords in context; never generated as names
+const CONTEXTUAL_KEYWORDS: &[&str] = &["lifetime", "outlives"];
+
+/// Rules that rule out others inside them, until a bracket opens: a
+/// struct literal's `{` would open the block after a head, and an `if`
+/// would take the `else` of a `let ... else`
+const UNBRACKETED: &[(&str, &[&str])] = &[("HeadExpr", &["StructLit"]), ("LetElseStmt", &["IfExpr"])];
+
+/// Rules that may not start right after a terminal: there a `{` opens a block
+const NOT_AFTER: &[(&str, &[&str])] = &[("=>", &["ObjectLit", "DictLit"]), (")", &["ObjectLit", "DictLit"])];
+
+/// How many times a name that turned out to be a keyword is redrawn
+const NAME_ATTEMPTS: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub seed: u64,
+    /// Deepest nesting of rule expansions
+    pub max_depth: usize,
+    /// Most items a `*` or `+` produces
+    pub max_repeat: usize,
+    /// Most optional pieces in one program: items of a `?`, `*` or `+`
+    /// beyond its minimum. Once they run out, every choice takes the
+    /// shortest way to finish, so the depth alone doesn't multiply the size.
+    pub max_optional: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { seed: 0, max_depth: 64, max_repeat: 2, max_optional: 40 }
+    }
+}
+
+/// A derivation: the rules and choices that produced a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Text(String),
+    Rule { name: String, child: Box<Node> },
+    Seq(Vec<Node>),
+    /// What a `?`, `*` or `+` produced; never fewer than `min` items
+    Many { min: usize, items: Vec<Node> },
+}
+
+/// The first error the lexer or a parser reported for a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    /// `lexer`, `recursive descent` or `LALR`
+    pub stage: &'static str,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Rejection {
+    fn new(stage: &'static str, message: String, span: Span) -> Self {
+        Rejection { stage, message, line: span.line, column: span.column }
+    }
+
+    /// The same failure, wherever it happens and whatever token it found
+    fn same_as(&self, other: &Rejection) -> bool {
+        let expected = |r: &Rejection| r.message.split(", found").next().unwrap_or_default().to_string();
+        self.stage == other.stage && expected(self) == expected(other)
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} at {}:{}", self.stage, self.message, self.line, self.column)
+    }
+}
+
+/// A rejected program, smallest first
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+    pub seed: u64,
+    pub minimised: String,
+    pub error: Rejection,
+    pub original: String,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "seed {}: the grammar allows this program, but it is rejected:", self.seed)?;
+        writeln!(f, "{}", self.minimised)?;
+        writeln!(f, "error: {}", self.error)?;
+        write!(f, "(minimised from {} bytes)", self.original.len())
+    }
+}
+
+pub struct Generator<'g> {
+    grammar: &'g Grammar,
+    rng: Rng,
+    config: Config,
+    /// Fewest nested expansions any derivation of a rule needs
+    heights: HashMap<&'g str, usize>,
+    /// Optional pieces left for the program being generated
+    optional: usize,
+    /// Rules not to choose here, from `UNBRACKETED`
+    excluded: &'static [&'static str],
+    /// Rules not to choose before the next token, from `NOT_AFTER` and `A - B`
+    leading: Vec<&'g str>,
+}
+
+impl<'g> Generator<'g> {
+    /// Fails with the names the grammar uses but never defines
+    pub fn new(grammar: &'g Grammar, config: Config) -> Result<Self, String> {
+        let undefined: Vec<&str> =
+            grammar.undefined().into_iter().filter(|name| character_set(name).is_none()).collect();
+        if !undefined.is_empty() {
+            return Err(format!("undefined rules: {}", undefined.join(", ")));
+        }
+        Ok(Generator {
+            grammar,
+            rng: Rng(config.seed),
+            config,
+            heights: heights(grammar),
+            optional: 0,
+            excluded: &[],
+            leading: Vec::new(),
+        })
+    }
+
+    /// Start over as if created with `seed`
+    pub fn reseed(&mut self, seed: u64) {
+        self.config.seed = seed;
+        self.rng = Rng(seed);
+    }
+
+    /// A random derivation of `rule`
+    pub fn generate(&mut self, rule: &str) -> Node {
+        self.optional = self.config.max_optional;
+        self.excluded = &[];
+        self.leading.clear();
+        self.rule(rule, self.config.max_depth)
+    }
+
+    /// The derivation of `rule` with the fewest expansions, always the same
+    pub fn shortest(&self, rule: &str) -> Node {
+        let mut generator = Generator {
+            grammar: self.grammar,
+            rng: Rng(0),
+            config: self.config,
+            heights: self.heights.clone(),
+            optional: 0,
+            excluded: &[],
+            leading: Vec::new(),
+        };
+        generator.rule(rule, self.height(&Expr::Rule(rule.to_string())))
+    }
+
+    fn rule(&mut self, name: &str, budget: usize) -> Node {
+        let Some(rule) = self.grammar.rule(name) else {
+            let set: Vec<char> = character_set(name).unwrap_or("x").chars().collect();
+            let c = set[self.rng.below(set.len())];
+            return Node::Rule { name: name.to_string(), child: Box::new(self.text(c.to_string())) };
+        };
+        let budget = budget.saturating_sub(1);
+        let outer = self.excluded;
+        if let Some((_, excluded)) = UNBRACKETED.iter().find(|(n, _)| *n == name) {
+            self.excluded = excluded;
+        }
+        let mut node = None;
+        for _ in 0..NAME_ATTEMPTS {
+            let child = self.expr(&rule.body, budget);
+            let attempt = Node::Rule { name: name.to_string(), child: Box::new(child) };
+            if !(self.is_token_rule(name) && reserved(&text_of(&attempt))) {
+                node = Some(attempt);
+                break;
+            }
+        }
+        let node = node.unwrap_or_else(|| Node::Rule { name: name.to_string(), child: Box::new(self.expr(&rule.body, budget)) });
+        self.excluded = outer;
+        node
+    }
+
+    fn text(&mut self, text: String) -> Node {
+        self.leading = NOT_AFTER.iter().find(|(t, _)| *t == text).map_or(Vec::new(), |(_, rules)| rules.to_vec());
+        Node::Text(text)
+    }
+
+    /// `expr` may be chosen here
+    fn allowed(&self, expr: &Expr) -> bool {
+        !matches!(expr, Expr::Rule(name) if self.excluded.contains(&name.as_str()) || self.leading.contains(&name.as_str()))
+    }
+
+    fn expr(&mut self, expr: &'g Expr, budget: usize) -> Node {
+        match expr {
+            Expr::Terminal(text) => self.text(text.clone()),
+            Expr::Rule(name) => self.rule(name, budget),
+            Expr::Class(ranges) => {
+                let (low, high) = ranges[self.rng.below(ranges.len())];
+                let c = char::from_u32(low as u32 + self.rng.below(high as usize - low as usize + 1) as u32).unwrap_or(low);
+                self.text(c.to_string())
+            }
+            Expr::Seq(items) => {
+                // Exclusions stop at an opening bracket and resume after its closing one
+                let mut outer = Vec::new();
+                let mut nodes = Vec::new();
+                for item in items {
+                    match item {
+                        Expr::Terminal(t) if matches!(t.as_str(), "(" | "[" | "{") => {
+                            outer.push(self.excluded);
+                            self.excluded = &[];
+                        }
+                        Expr::Terminal(t) if matches!(t.as_str(), ")" | "]" | "}") => {
+                            self.excluded = outer.pop().unwrap_or(self.excluded);
+                        }
+                        _ => {}
+                    }
+                    nodes.push(self.expr(item, budget));
+                }
+                Node::Seq(nodes)
+            }
+            Expr::Alt(alternatives) => {
+                let allowed: Vec<&Expr> = alternatives.iter().filter(|e| self.allowed(e)).collect();
+                let alternatives = if allowed.is_empty() { alternatives.iter().collect() } else { allowed };
+                let shortest = alternatives.iter().map(|e| self.height(e)).min().unwrap_or(0);
+                let limit = if self.optional == 0 { shortest } else { budget };
+                let fitting: Vec<&Expr> = alternatives.iter().copied().filter(|e| self.height(e) <= limit).collect();
+                let choice = match fitting.len() {
+                    0 => alternatives.iter().copied().min_by_key(|e| self.height(e)).expect("alternatives are never empty"),
+                    n => fitting[self.rng.below(n)],
+                };
+                self.expr(choice, budget)
+            }
+            Expr::Optional(inner) => self.many(inner, 0, 1, budget),
+            Expr::Repeat { expr, min } => self.many(expr, *min, (*min).max(self.config.max_repeat), budget),
+            // A rule named in `except` is avoided when `expr` chooses among
+            // alternatives, and a word it spells is redrawn
+            Expr::Except(expr, except) => {
+                let leading = self.leading.clone();
+                for _ in 0..NAME_ATTEMPTS {
+                    except.references(&mut |name| self.leading.push(name));
+                    let node = self.expr(expr, budget);
+                    if !self.spells(except, &text_of(&node)) {
+                        return node;
+                    }
+                    self.leading = leading.clone();
+                }
+                self.expr(expr, budget)
+            }
+        }
+    }
+
+    fn many(&mut self, expr: &'g Expr, min: usize, max: usize, budget: usize) -> Node {
+        let mut count = min;
+        if self.height(expr) <= budget {
+            let extra = self.rng.below(max - min + 1).min(self.optional);
+            self.optional -= extra;
+            count += extra;
+        }
+        Node::Many { min, items: (0..count).map(|_| self.expr(expr, budget)).collect() }
+    }
+
+    /// `expr` is `text` or a choice of words that includes it
+    fn spells(&self, expr: &Expr, text: &str) -> bool {
+        let word = |e: &Expr| matches!(e, Expr::Terminal(t) if t == text);
+        match expr {
+            Expr::Terminal(_) => word(expr),
+            Expr::Rule(name) => self.grammar.rule(name).is_some_and(|rule| match &rule.body {
+                Expr::Alt(words) => words.iter().any(word),
+                body => word(body),
+            }),
+            Expr::Alt(items) => items.iter().any(|e| self.spells(e, text)),
+            _ => false,
+        }
+    }
+
+    fn height(&self, expr: &Expr) -> usize {
+        height(expr, &self.heights)
+    }
+
+    fn is_token_rule(&self, name: &str) -> bool {
+        self.grammar.rule(name).is_some_and(|rule| spells_characters(&rule.body, self.grammar))
+    }
+
+    /// `node` as source text
+    pub fn render(&self, node: &Node) -> String {
+        let mut out = String::new();
+        self.render_into(node, &mut out);
+        out.trim_end().to_string() + "\n"
+    }
+
+    fn render_into(&self, node: &Node, out: &mut String) {
+        match node {
+            Node::Text(text) => push_word(out, text),
+            Node::Rule { name, child } if self.is_token_rule(name) => {
+                let mut word = String::new();
+                self.render_tight(child, &mut word);
+                push_word(out, &word);
+            }
+            Node::Rule { name, child } => {
+                self.render_into(child, out);
+                if LINE_RULES.contains(&name.as_str()) {
+                    out.truncate(out.trim_end_matches(' ').len());
+                    out.push('\n');
+                }
+            }
+            Node::Seq(items) | Node::Many { items, .. } => items.iter().for_each(|n| self.render_into(n, out)),
+        }
+    }
+
+    /// Inside a token: no separators, except in the spaced rules it holds
+    /// (the holes of an interpolated string)
+    fn render_tight(&self, node: &Node, out: &mut String) {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Rule { name, child } if self.grammar.rule(name).is_none() || self.is_token_rule(name) || is_character_rule(name, self.grammar) => {
+                self.render_tight(child, out)
+            }
+            Node::Rule { .. } => {
+                let mut inner = String::new();
+                self.render_into(node, &mut inner);
+                out.push_str(inner.trim());
+            }
+            Node::Seq(items) | Node::Many { items, .. } => items.iter().for_each(|n| self.render_tight(n, out)),
+        }
+    }
+}
+
+/// The first error the lexer or either parser reports for `source`.
+/// A parser whose first error is `InvalidSyntax` accepts: those errors
+/// enforce the well-formedness rules (refutable patterns, accessor pairs
+/// and the like) that the grammar states in comments rather than in the
+/// notation, and the errors after one may only be recovery.
+pub fn check(source: &str) -> Result<(), Rejection> {
+    if let Err(manager) = LogosLexer::new(source).tokenize() {
+        let d = &manager.diagnostics()[0];
+        return Err(Rejection::new("lexer", d.message.clone(), d.span));
+    }
+    let syntax_error = |stage, result: Result<_, ErrorManager>| match result.err()?.parse_errors().first()? {
+        ParseError::InvalidSyntax { .. } => None,
+        error => Some(Rejection::new(stage, error.to_string(), error.span())),
+    };
+    if let Some(rejection) = syntax_error("recursive descent", parser::parse(source)) {
+        return Err(rejection);
+    }
+    match syntax_error("LALR", parser::parse_lalr(source)) {
+        Some(rejection) => Err(rejection),
+        None => Ok(()),
+    }
+}
+
+/// Shrink `node` while `check` still rejects it the same way: drop items
+/// of repetitions, and replace a rule's derivation with its shortest one or
+/// with a smaller derivation of the same rule nested inside it
+pub fn minimise(generator: &Generator, mut node: Node, check: impl Fn(&str) -> Result<(), Rejection>) -> Node {
+    let text = generator.render(&node);
+    let Err(rejection) = check(&text) else { return node };
+    let mut size = text.len();
+    'shrink: loop {
+        for candidate in variants(generator, &node) {
+            let text = generator.render(&candidate);
+            if text.len() < size && check(&text).is_err_and(|r| r.same_as(&rejection)) {
+                size = text.len();
+                node = candidate;
+                continue 'shrink;
+            }
+        }
+        return node;
+    }
+}
+
+/// Generate `runs` programs from `start`, one per seed from the
+/// generator's own up, and return the first that `check` rejects, minimised
+pub fn fuzz(
+    generator: &mut Generator,
+    start: &str,
+    runs: u64,
+    check: impl Fn(&str) -> Result<(), Rejection>,
+) -> Result<(), Box<Counterexample>> {
+    let first = generator.config.seed;
+    for seed in first..first + runs {
+        generator.reseed(seed);
+        let node = generator.generate(start);
+        let original = generator.render(&node);
+        if check(&original).is_ok() {
+            continue;
+        }
+        let node = minimise(generator, node, &check);
+        let minimised = generator.render(&node);
+        let Err(error) = check(&minimised) else { unreachable!("minimising keeps the program rejected") };
+        return Err(Box::new(Counterexample { seed, minimised, error, original }));
+    }
+    Ok(())
+}
+
+/// Every tree one shrinking step away from `node`, outermost changes first
+fn variants(generator: &Generator, node: &Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    match node {
+        Node::Text(_) => {}
+        Node::Rule { name, child } => {
+            let shortest = generator.shortest(name);
+            if shortest != *node {
+                out.push(shortest);
+            }
+            let mut nested = Vec::new();
+            nested_rules(child, name, &mut nested);
+            out.extend(nested.into_iter().cloned());
+            for variant in variants(generator, child) {
+                out.push(Node::Rule { name: name.clone(), child: Box::new(variant) });
+            }
+        }
+        Node::Seq(items) => {
+            for (i, item) in items.iter().enumerate() {
+                for variant in variants(generator, item) {
+                    let mut items = items.clone();
+                    items[i] = variant;
+                    out.push(Node::Seq(items));
+                }
+            }
+        }
+        Node::Many { min, items } => {
+            if items.len() > *min {
+                for i in 0..items.len() {
+                    let mut items = items.clone();
+                    items.remove(i);
+                    out.push(Node::Many { min: *min, items });
+                }
+            }
+            for (i, item) in items.iter().enumerate() {
+                for variant in variants(generator, item) {
+                    let mut items = items.clone();
+                    items[i] = variant;
+                    out.push(Node::Many { min: *min, items });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Derivations of `name` inside `node`, outermost first
+fn nested_rules<'n>(node: &'n Node, name: &str, out: &mut Vec<&'n Node>) {
+    match node {
+        Node::Text(_) => {}
+        Node::Rule { name: inner, child } => {
+            if inner == name {
+                out.push(node);
+            }
+            nested_rules(child, name, out);
+        }
+        Node::Seq(items) | Node::Many { items, .. } => items.iter().for_each(|n| nested_rules(n, name, out)),
+    }
+}
+
+fn push_word(out: &mut String, word: &str) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push(' ');
+    }
+    out.push_str(word);
+}
+
+/// The text of a token rule's derivation
+fn text_of(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.clone(),
+        Node::Rule { child, .. } => text_of(child),
+        Node::Seq(items) | Node::Many { items, .. } => items.iter().map(text_of).collect(),
+    }
+}
+
+fn character_set(name: &str) -> Option<&'static str> {
+    CHARACTER_SETS.iter().find(|(n, _)| *n == name).map(|(_, set)| *set)
+}
+
+/// A rule that is a single character: `Letter ::= [a-zA-Z]`
+fn is_character_rule(name: &str, grammar: &Grammar) -> bool {
+    fn classes(expr: &Expr) -> bool {
+        match expr {
+            Expr::Class(_) => true,
+            Expr::Alt(alternatives) => alternatives.iter().all(classes),
+            _ => false,
+        }
+    }
+    match grammar.rule(name) {
+        Some(rule) => classes(&rule.body),
+        None => character_set(name).is_some(),
+    }
+}
+
+/// `expr` spells text character by character, so its pieces are not separate tokens
+fn spells_characters(expr: &Expr, grammar: &Grammar) -> bool {
+    match expr {
+        Expr::Class(_) => true,
+        Expr::Rule(name) => is_character_rule(name, grammar),
+        Expr::Terminal(_) => false,
+        Expr::Seq(items) | Expr::Alt(items) => items.iter().any(|e| spells_characters(e, grammar)),
+        Expr::Optional(expr) | Expr::Repeat { expr, .. } | Expr::Except(expr, _) => spells_characters(expr, grammar),
+    }
+}
+
+fn reserved(word: &str) -> bool {
+    keywords::get_keyword(word).is_some() || keywords::query_keyword(word).is_some() || CONTEXTUAL_KEYWORDS.contains(&word)
+}
+
+/// Fewest nested expansions for each rule, found by iterating to a fixed point
+fn heights(grammar: &Grammar) -> HashMap<&str, usize> {
+    let mut heights: HashMap<&str, usize> = HashMap::new();
+    loop {
+        let mut changed = false;
+        for rule in &grammar.rules {
+            let h = height(&rule.body, &heights).saturating_add(1);
+            if heights.get(rule.name.as_str()).is_none_or(|&old| h < old) {
+                heights.insert(&rule.name, h);
+                changed = true;
+            }
+        }
+        if !changed {
+            return heights;
+        }
+    }
+}
+
+fn height(expr: &Expr, heights: &HashMap<&str, usize>) -> usize {
+    match expr {
+        Expr::Terminal(_) | Expr::Class(_) => 0,
+        Expr::Rule(name) => match heights.get(name.as_str()) {
+            Some(&h) => h,
+            None if character_set(name).is_some() => 1,
+            None => usize::MAX,
+        },
+        Expr::Seq(items) => items.iter().map(|e| height(e, heights)).max().unwrap_or(0),
+        Expr::Alt(alternatives) => alternatives.iter().map(|e| height(e, heights)).min().unwrap_or(0),
+        Expr::Optional(_) | Expr::Repeat { min: 0, .. } => 0,
+        Expr::Repeat { expr, .. } | Expr::Except(expr, _) => height(expr, heights),
+    }
+}
+
+/// SplitMix64; deterministic per seed, so a failing seed can be replayed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
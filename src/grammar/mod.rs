@@ -0,0 +1,12 @@
+//! The documented grammar, `docs/ubel.ebnf`, as data
+//!
+//! `ebnf` reads and writes the notation; `fuzz` generates programs from it
+//! and checks that the lexer and both parsers accept them, and `reference`
+//! rebuilds its keyword and operator sections from the parser's tables,
+//! which together keep the document and the implementation in step.
+
+pub mod ebnf;
+pub mod fuzz;
+pub mod reference;
+
+pub use ebnf::Grammar;
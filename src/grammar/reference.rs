@@ -0,0 +1,247 @@
+//! The grammar reference, generated from the parser's tables
+//!
+//! `docs/ubel.ebnf` is written by hand, except for the sections between
+//! `(* Generated from <file>; do not edit *)` and `(* End of generated
+//! rules *)`: the keywords, the primitive types and the operator ladder.
+//! `ebnf` rebuilds those from the keyword table, `PrimitiveType` and the
+//! precedence table, and the tests check the committed file against it.
+//! `markdown` renders the same grammar as a reference page, with the
+//! keywords and the precedence table spelled out.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grammar::ebnf::{Expr, Rule};
+use crate::lexer::keywords::{KEYWORDS, QUERY_KEYWORDS};
+use crate::parser::ast::PrimitiveType;
+use crate::parser::precedence::{Assoc, Infix, Precedence, PREFIX_OPERATORS};
+
+/// The grammar as committed
+pub const DOCUMENT: &str = include_str!("../../docs/ubel.ebnf");
+
+pub const DOCUMENT_PATH: &str = "docs/ubel.ebnf";
+
+const END: &str = "(* End of generated rules *)";
+
+/// Builds the rules of one generated section
+type Section = fn() -> String;
+
+/// The generated sections, by the file their rules come from
+const SECTIONS: &[(&str, Section)] = &[
+    ("src/lexer/keywords.rs", keyword_rules),
+    ("src/parser/ast.rs", primitive_rules),
+    ("src/parser/precedence.rs", operator_rules),
+];
+
+/// Postfix forms; the Pratt parser reads them outside the operator tables
+const POSTFIX: &[&str] = &["()", "[]", ".", "?.", "?"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceFormat {
+    Markdown,
+    Ebnf,
+}
+
+impl FromStr for ReferenceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ReferenceFormat::Markdown),
+            "ebnf" => Ok(ReferenceFormat::Ebnf),
+            other => Err(format!("unknown grammar format '{}' (expected md or ebnf)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ReferenceFormat::Markdown => "md",
+            ReferenceFormat::Ebnf => "ebnf",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn render(format: ReferenceFormat) -> Result<String, String> {
+    match format {
+        ReferenceFormat::Markdown => markdown(),
+        ReferenceFormat::Ebnf => ebnf(),
+    }
+}
+
+/// `docs/ubel.ebnf` with its generated sections rebuilt
+pub fn ebnf() -> Result<String, String> {
+    splice(DOCUMENT)
+}
+
+/// Replace each generated section of `document` with fresh rules
+pub fn splice(document: &str) -> Result<String, String> {
+    let mut out = document.to_string();
+    for (source, generate) in SECTIONS {
+        let begin = format!("(* Generated from {}; do not edit *)\n", source);
+        let start = out.find(&begin).ok_or_else(|| format!("{} has no section generated from {}", DOCUMENT_PATH, source))? + begin.len();
+        let end = out[start..].find(END).ok_or_else(|| format!("the section generated from {} never ends", source))? + start;
+        out.replace_range(start..end, &generate());
+    }
+    Ok(out)
+}
+
+/// The reference page: keywords, operator precedence, then the grammar
+/// section by section
+pub fn markdown() -> Result<String, String> {
+    let grammar = ebnf()?;
+    let code = |words: &mut dyn Iterator<Item = &str>| words.map(|w| format!("`{}`", w)).collect::<Vec<_>>().join(" ");
+
+    let mut out = String::from("# Ubel Stratum grammar\n\n");
+    out.push_str("Generated by `stratc grammar --format md` from the keyword and precedence\n");
+    out.push_str(&format!("tables and `{}`.\n\n", DOCUMENT_PATH));
+
+    out.push_str("## Keywords\n\n");
+    out.push_str(&format!("Reserved: {}\n\n", code(&mut keywords().into_iter())));
+    out.push_str(&format!("Inside a query only: {}\n\n", code(&mut QUERY_KEYWORDS.iter().copied())));
+
+    out.push_str("## Operator precedence\n\nLoosest to tightest.\n\n");
+    out.push_str("| Level | Operators | Associativity |\n|---|---|---|\n");
+    for level in Precedence::ALL {
+        let operators = match level {
+            Precedence::Prefix => code(&mut PREFIX_OPERATORS.iter().map(|op| op.lexeme)),
+            Precedence::Postfix => code(&mut POSTFIX.iter().copied()),
+            _ => code(&mut level.operators().map(|op| op.lexeme)),
+        };
+        let assoc = match level.assoc() {
+            Assoc::Left => "left",
+            Assoc::Right => "right",
+            Assoc::None => "none",
+        };
+        out.push_str(&format!("| {:?} | {} | {} |\n", level, operators.replace('|', "\\|"), assoc));
+    }
+
+    for (title, body) in sections(&grammar) {
+        out.push_str(&format!("\n## {}\n\n```ebnf\n{}\n```\n", title, body));
+    }
+    Ok(out)
+}
+
+/// The grammar split at its `(* ==== *)` banners, as (title, rules);
+/// the lines before the first banner and the section markers are left out
+fn sections(grammar: &str) -> Vec<(String, String)> {
+    let banner = |line: &str| line.starts_with("(* ===") && line.ends_with("=== *)");
+    let lines: Vec<&str> = grammar.lines().collect();
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if banner(lines[i]) && lines.get(i + 2).is_some_and(|l| banner(l)) {
+            let title = lines[i + 1].trim_start_matches("(*").trim_end_matches("*)").trim();
+            let mut title: Vec<char> = title.to_lowercase().chars().collect();
+            if let Some(first) = title.first_mut() {
+                *first = first.to_ascii_uppercase();
+            }
+            sections.push((title.into_iter().collect(), Vec::new()));
+            i += 3;
+            continue;
+        }
+        let marker = lines[i].starts_with("(* Generated from ") || lines[i] == END;
+        if let Some((_, body)) = sections.last_mut().filter(|_| !marker) {
+            body.push(lines[i]);
+        }
+        i += 1;
+    }
+    sections.into_iter().map(|(title, body)| (title, body.join("\n").trim().to_string())).collect()
+}
+
+// ========================================
+// Generated sections
+// ========================================
+
+/// Reserved words, sorted
+fn keywords() -> Vec<&'static str> {
+    let mut words: Vec<&str> = KEYWORDS.keys().copied().collect();
+    words.sort_unstable();
+    words
+}
+
+fn choice(words: impl Iterator<Item = &'static str>) -> Expr {
+    Expr::Alt(words.map(|w| Expr::Terminal(w.to_string())).collect())
+}
+
+fn keyword_rules() -> String {
+    format!("{}\n", Rule { name: "Keyword".to_string(), body: choice(keywords().into_iter()) })
+}
+
+fn primitive_rules() -> String {
+    format!("{}\n", Rule { name: "PrimitiveType".to_string(), body: choice(PrimitiveType::ALL.iter().map(|t| t.name())) })
+}
+
+/// The rule for the expressions at `level`
+fn level_rule(level: Precedence) -> &'static str {
+    match level {
+        Precedence::Assignment => "AssignExpr",
+        Precedence::Pipe => "PipeExpr",
+        Precedence::Range => "RangeExpr",
+        Precedence::Or => "OrExpr",
+        Precedence::And => "AndExpr",
+        Precedence::Comparison => "CompareExpr",
+        Precedence::BitOr => "BitOrExpr",
+        Precedence::BitXor => "BitXorExpr",
+        Precedence::BitAnd => "BitAndExpr",
+        Precedence::Shift => "ShiftExpr",
+        Precedence::Additive => "AddExpr",
+        Precedence::Multiplicative => "MultExpr",
+        Precedence::Cast => "CastExpr",
+        Precedence::Prefix => "UnaryExpr",
+        Precedence::Postfix => "PostfixExpr",
+    }
+}
+
+/// The rule for the operators at `level`: `AddExpr` takes an `AddOp`
+fn operator_rule(level: Precedence) -> String {
+    level_rule(level).trim_end_matches("Expr").to_string() + "Op"
+}
+
+/// One level per paragraph, from `AssignOp` to `UnaryOp`. `AssignExpr`,
+/// which also takes lambdas and queries, and the postfix forms are
+/// written by hand around them.
+fn operator_rules() -> String {
+    let rule = |name: &str, body: Expr| Rule { name: name.to_string(), body }.to_string();
+    let mut paragraphs = Vec::new();
+    for level in Precedence::ALL {
+        let name = level_rule(level);
+        let operators = choice(level.operators().map(|op| op.lexeme));
+        let paragraph = match level {
+            Precedence::Assignment => rule(&operator_rule(level), operators),
+            Precedence::Postfix => continue,
+            Precedence::Prefix => {
+                let prefix = Expr::Seq(vec![Expr::Rule(operator_rule(level)), Expr::Rule(name.to_string())]);
+                let body = Expr::Alt(vec![prefix, Expr::Rule(level_rule(level.tighter()).to_string())]);
+                let operators = choice(PREFIX_OPERATORS.iter().map(|op| op.lexeme));
+                format!("{}\n{}", rule(name, body), rule(&operator_rule(level), operators))
+            }
+            _ => {
+                let operand = Expr::Rule(level_rule(level.tighter()).to_string());
+                // `as` takes a type on its right
+                let right = match level.operators().all(|op| op.infix == Infix::Cast) {
+                    true => Expr::Rule("CastType".to_string()),
+                    false => operand.clone(),
+                };
+                let operator = match level.operators().count() {
+                    1 => Expr::Terminal(level.operators().map(|op| op.lexeme).collect()),
+                    _ => Expr::Rule(operator_rule(level)),
+                };
+                let tail = Box::new(Expr::Seq(vec![operator.clone(), right]));
+                let tail = match level.assoc() {
+                    Assoc::None => Expr::Optional(tail),
+                    Assoc::Left | Assoc::Right => Expr::Repeat { expr: tail, min: 0 },
+                };
+                let mut paragraph = rule(name, Expr::Seq(vec![operand, tail]));
+                if matches!(operator, Expr::Rule(_)) {
+                    paragraph = format!("{}\n{}", paragraph, rule(&operator_rule(level), operators));
+                }
+                paragraph
+            }
+        };
+        paragraphs.push(paragraph);
+    }
+    paragraphs.join("\n\n") + "\n"
+}
@@ -0,0 +1,220 @@
+//! Turn a token stream into highlight spans covering the whole source
+
+use crate::lexer::{LogosLexer, Token, TokenType};
+use crate::lexer::{keywords, operators, string_parser::StringParser};
+use crate::lexer::operators::OperatorClass;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    Keyword,
+    TierAnnotation,
+    Attribute,
+    Identifier,
+    Number,
+    String,
+    Char,
+    Constant,
+    Operator,
+    Punctuation,
+    InterpolationDelimiter,
+    Comment,
+    DocComment,
+    Error,
+    Whitespace,
+}
+
+impl HighlightKind {
+    /// Stable name used for CSS classes and JSON output
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightKind::Keyword => "keyword",
+            HighlightKind::TierAnnotation => "tier",
+            HighlightKind::Attribute => "attribute",
+            HighlightKind::Identifier => "identifier",
+            HighlightKind::Number => "number",
+            HighlightKind::String => "string",
+            HighlightKind::Char => "char",
+            HighlightKind::Constant => "constant",
+            HighlightKind::Operator => "operator",
+            HighlightKind::Punctuation => "punctuation",
+            HighlightKind::InterpolationDelimiter => "interpolation",
+            HighlightKind::Comment => "comment",
+            HighlightKind::DocComment => "doc-comment",
+            HighlightKind::Error => "error",
+            HighlightKind::Whitespace => "whitespace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: HighlightKind,
+}
+
+/// Classify every byte of `source`; spans are ordered and never overlap
+pub fn highlight(source: &str) -> Vec<HighlightSpan> {
+    let (tokens, _) = LogosLexer::new(source).tokenize_all();
+    let mut spans = Vec::new();
+    let mut cursor = 0;
+    let mut idx = 0;
+
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        if token.kind == TokenType::Eof || token.span.start < cursor {
+            idx += 1;
+            continue;
+        }
+
+        classify_gap(source, cursor, token.span.start, &mut spans);
+
+        if let Some((end, kind, consumed)) = annotation(&tokens[idx..]) {
+            spans.push(HighlightSpan { start: token.span.start, end, kind });
+            cursor = end;
+            idx += consumed;
+            continue;
+        }
+
+        match &token.kind {
+            TokenType::InterpolatedString(_) => interpolated(token, &mut spans),
+            kind => spans.push(HighlightSpan {
+                start: token.span.start,
+                end: token.span.end,
+                kind: token_kind(kind, &token.lexeme),
+            }),
+        }
+
+        cursor = token.span.end;
+        idx += 1;
+    }
+
+    classify_gap(source, cursor, source.len(), &mut spans);
+    spans
+}
+
+fn token_kind(kind: &TokenType, lexeme: &str) -> HighlightKind {
+    match kind {
+        TokenType::True | TokenType::False | TokenType::Null => HighlightKind::Constant,
+        TokenType::IntLit(_) | TokenType::FloatLit(_) | TokenType::DoubleLit(_) => HighlightKind::Number,
+        TokenType::StringLit(_) | TokenType::VerbatimString(_) => HighlightKind::String,
+        TokenType::CharLit(_) => HighlightKind::Char,
+        TokenType::DocComment(_) => HighlightKind::DocComment,
+        TokenType::Comment(_) => HighlightKind::Comment,
+        TokenType::Ident(_) => HighlightKind::Identifier,
+        TokenType::Error(_) => HighlightKind::Error,
+        _ if keywords::get_keyword(lexeme).is_some() => HighlightKind::Keyword,
+        _ => match operators::class_of(kind) {
+            Some(OperatorClass::Delimiter) | Some(OperatorClass::Punctuation) => HighlightKind::Punctuation,
+            Some(_) => HighlightKind::Operator,
+            None => HighlightKind::Error,
+        },
+    }
+}
+
+/// `@tier(high)` as one span, or `@name` as an attribute
+fn annotation(tokens: &[Token]) -> Option<(usize, HighlightKind, usize)> {
+    if tokens[0].kind != TokenType::At {
+        return None;
+    }
+    let name = match tokens.get(1) {
+        Some(Token { kind: TokenType::Ident(name), span, .. }) if span.start == tokens[0].span.end => (name, span.end),
+        _ => return None,
+    };
+
+    if name.0 == "tier" {
+        if let [_, _, open, level, close, ..] = tokens {
+            let is_level = matches!(&level.kind, TokenType::Ident(l) if ["high", "mid", "low"].contains(&l.as_str()));
+            if open.kind == TokenType::LeftParen && is_level && close.kind == TokenType::RightParen {
+                return Some((close.span.end, HighlightKind::TierAnnotation, 5));
+            }
+        }
+    }
+
+    Some((name.1, HighlightKind::Attribute, 2))
+}
+
+/// Text segments as strings, holes highlighted with the real lexer
+fn interpolated(token: &Token, spans: &mut Vec<HighlightSpan>) {
+    let base = token.span.start;
+    let mut cursor = 0;
+
+    for hole in StringParser::hole_ranges(&token.lexeme) {
+        push(spans, base + cursor, base + hole.start - 1, HighlightKind::String);
+        push(spans, base + hole.start - 1, base + hole.start, HighlightKind::InterpolationDelimiter);
+
+        for inner in highlight(&token.lexeme[hole.clone()]) {
+            push(spans, base + hole.start + inner.start, base + hole.start + inner.end, inner.kind);
+        }
+
+        cursor = hole.end;
+        if token.lexeme[hole.end..].starts_with('}') {
+            push(spans, base + hole.end, base + hole.end + 1, HighlightKind::InterpolationDelimiter);
+            cursor += 1;
+        }
+    }
+
+    push(spans, base + cursor, token.span.end, HighlightKind::String);
+}
+
+/// Text logos skipped: whitespace and comments (anything else is an error)
+fn classify_gap(source: &str, start: usize, end: usize, spans: &mut Vec<HighlightSpan>) {
+    let gap = &source[start..end];
+    let mut pos = 0;
+
+    while pos < gap.len() {
+        let rest = &gap[pos..];
+        let (len, kind) = if rest.starts_with("//") {
+            (rest.find('\n').unwrap_or(rest.len()), HighlightKind::Comment)
+        } else if rest.starts_with("/*") {
+            (block_comment_len(rest), HighlightKind::Comment)
+        } else {
+            let ch = rest.chars().next().unwrap_or(' ');
+            if ch.is_whitespace() {
+                let len = rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len());
+                (len, HighlightKind::Whitespace)
+            } else {
+                (ch.len_utf8(), HighlightKind::Error)
+            }
+        };
+
+        push(spans, start + pos, start + pos + len, kind);
+        pos += len;
+    }
+}
+
+/// Length of a (nested) block comment, or the rest of the text if unclosed
+pub(crate) fn block_comment_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (b'/', b'*') => {
+                depth += 1;
+                i += 2;
+            }
+            (b'*', b'/') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    text.len()
+}
+
+fn push(spans: &mut Vec<HighlightSpan>, start: usize, end: usize, kind: HighlightKind) {
+    if start >= end {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.kind == kind && last.end == start => last.end = end,
+        _ => spans.push(HighlightSpan { start, end, kind }),
+    }
+}
@@ -0,0 +1,8 @@
+//! Syntax highlighting driven by the real lexer
+
+pub mod classify;
+pub mod render;
+pub mod textmate;
+
+pub use classify::{highlight, HighlightKind, HighlightSpan};
+pub use render::{render, HighlightFormat};
@@ -0,0 +1,119 @@
+//! Output backends for highlight spans: ANSI, HTML and JSON
+
+use std::fmt;
+use std::str::FromStr;
+use serde_json::json;
+use crate::highlight::classify::{highlight, HighlightKind, HighlightSpan};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightFormat {
+    Ansi,
+    Html,
+    Json,
+}
+
+impl FromStr for HighlightFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ansi" => Ok(HighlightFormat::Ansi),
+            "html" => Ok(HighlightFormat::Html),
+            "json" => Ok(HighlightFormat::Json),
+            other => Err(format!("unknown highlight format '{}' (expected ansi, html or json)", other)),
+        }
+    }
+}
+
+impl fmt::Display for HighlightFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HighlightFormat::Ansi => "ansi",
+            HighlightFormat::Html => "html",
+            HighlightFormat::Json => "json",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Highlight `source` and render it in the requested format
+pub fn render(source: &str, format: HighlightFormat) -> String {
+    let spans = highlight(source);
+    match format {
+        HighlightFormat::Ansi => to_ansi(source, &spans),
+        HighlightFormat::Html => to_html(source, &spans),
+        HighlightFormat::Json => to_json(source, &spans),
+    }
+}
+
+fn ansi_color(kind: HighlightKind) -> Option<&'static str> {
+    match kind {
+        HighlightKind::Keyword => Some("\x1b[35m"),                 // Magenta
+        HighlightKind::TierAnnotation => Some("\x1b[1;33m"),        // Bold yellow
+        HighlightKind::Attribute => Some("\x1b[33m"),               // Yellow
+        HighlightKind::Number | HighlightKind::Constant => Some("\x1b[36m"), // Cyan
+        HighlightKind::String | HighlightKind::Char => Some("\x1b[32m"),     // Green
+        HighlightKind::InterpolationDelimiter => Some("\x1b[1;34m"), // Bold blue
+        HighlightKind::Comment => Some("\x1b[90m"),                 // Grey
+        HighlightKind::DocComment => Some("\x1b[3;90m"),            // Italic grey
+        HighlightKind::Error => Some("\x1b[4;31m"),                 // Underlined red
+        HighlightKind::Operator => Some("\x1b[37m"),
+        HighlightKind::Identifier | HighlightKind::Punctuation | HighlightKind::Whitespace => None,
+    }
+}
+
+pub fn to_ansi(source: &str, spans: &[HighlightSpan]) -> String {
+    let mut output = String::with_capacity(source.len() * 2);
+    for span in spans {
+        let text = &source[span.start..span.end];
+        match ansi_color(span.kind) {
+            Some(color) => output.push_str(&format!("{}{}\x1b[0m", color, text)),
+            None => output.push_str(text),
+        }
+    }
+    output
+}
+
+pub fn to_html(source: &str, spans: &[HighlightSpan]) -> String {
+    let mut output = String::from("<pre class=\"ubel\"><code>");
+    for span in spans {
+        let text = escape_html(&source[span.start..span.end]);
+        if span.kind == HighlightKind::Whitespace {
+            output.push_str(&text);
+        } else {
+            output.push_str(&format!("<span class=\"ub-{}\">{}</span>", span.kind.name(), text));
+        }
+    }
+    output.push_str("</code></pre>\n");
+    output
+}
+
+/// Every non-whitespace span with its byte range and text
+pub fn to_json(source: &str, spans: &[HighlightSpan]) -> String {
+    let items: Vec<_> = spans
+        .iter()
+        .filter(|span| span.kind != HighlightKind::Whitespace)
+        .map(|span| json!({
+            "kind": span.kind.name(),
+            "start": span.start,
+            "end": span.end,
+            "text": &source[span.start..span.end],
+        }))
+        .collect();
+    format!("{}\n", serde_json::to_string_pretty(&items).unwrap_or_default())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
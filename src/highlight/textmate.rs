@@ -0,0 +1,208 @@
+//! TextMate grammar generated from the keyword and operator tables
+//!
+//! The committed grammar in `editors/textmate/` is checked against this
+//! output in the tests, so the editor grammar cannot drift from the lexer.
+
+use serde_json::{json, Value};
+use crate::lexer::TokenType;
+use crate::lexer::keywords::KEYWORDS;
+use crate::lexer::operators::{OperatorClass, OPERATORS};
+
+pub const SCOPE_NAME: &str = "source.ubel";
+
+/// TextMate scope for a keyword
+fn keyword_scope(kind: &TokenType) -> &'static str {
+    match kind {
+        TokenType::If | TokenType::Elif | TokenType::Else | TokenType::Match | TokenType::Where
+        | TokenType::For | TokenType::In | TokenType::While | TokenType::Loop
+        | TokenType::Break | TokenType::Continue | TokenType::Return
+        | TokenType::Try | TokenType::Catch | TokenType::Fail | TokenType::Defer | TokenType::Using
+        | TokenType::Await => "keyword.control.ubel",
+        TokenType::Summon | TokenType::From | TokenType::As | TokenType::Package => "keyword.control.import.ubel",
+        TokenType::Fn | TokenType::Let | TokenType::Const | TokenType::Extract
+        | TokenType::Struct | TokenType::Enum | TokenType::Trait | TokenType::Impl | TokenType::Extend => "storage.type.ubel",
+        TokenType::Mut | TokenType::Pub | TokenType::Edge | TokenType::Unsafe | TokenType::With
+        | TokenType::Async | TokenType::Get | TokenType::Set => "storage.modifier.ubel",
+        TokenType::And | TokenType::Or | TokenType::Not => "keyword.operator.logical.ubel",
+        TokenType::True | TokenType::False | TokenType::Null => "constant.language.ubel",
+        TokenType::SelfKw => "variable.language.self.ubel",
+        _ => "keyword.other.ubel",
+    }
+}
+
+fn operator_scope(class: OperatorClass) -> &'static str {
+    match class {
+        OperatorClass::Arithmetic => "keyword.operator.arithmetic.ubel",
+        OperatorClass::Bitwise => "keyword.operator.bitwise.ubel",
+        OperatorClass::Comparison => "keyword.operator.comparison.ubel",
+        OperatorClass::Logical => "keyword.operator.logical.ubel",
+        OperatorClass::Assignment => "keyword.operator.assignment.ubel",
+        OperatorClass::Special => "keyword.operator.ubel",
+        OperatorClass::Delimiter => "punctuation.section.ubel",
+        OperatorClass::Punctuation => "punctuation.separator.ubel",
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::new();
+    for ch in text.chars() {
+        if "\\^$.|?*+()[]{}/".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Keyword patterns, one per scope, words sorted for stable output
+fn keyword_patterns() -> Vec<Value> {
+    let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (word, kind) in KEYWORDS.entries() {
+        let scope = keyword_scope(kind);
+        match groups.iter_mut().find(|(s, _)| *s == scope) {
+            Some((_, words)) => words.push(word),
+            None => groups.push((scope, vec![word])),
+        }
+    }
+    groups.sort_by_key(|(scope, _)| *scope);
+
+    groups
+        .into_iter()
+        .map(|(scope, mut words)| {
+            words.sort_unstable();
+            json!({ "name": scope, "match": format!("\\b({})\\b", words.join("|")) })
+        })
+        .collect()
+}
+
+/// One pattern per operator, longest lexemes first so `<<=` beats `<`
+fn operator_patterns() -> Vec<Value> {
+    let mut operators: Vec<_> = OPERATORS.iter().collect();
+    operators.sort_by(|a, b| b.lexeme.len().cmp(&a.lexeme.len()).then(a.lexeme.cmp(b.lexeme)));
+
+    operators
+        .into_iter()
+        .map(|op| json!({ "name": operator_scope(op.class), "match": escape_regex(op.lexeme) }))
+        .collect()
+}
+
+fn string_repository() -> Value {
+    json!({
+        "escape": {
+            "name": "constant.character.escape.ubel",
+            "match": "\\\\(n|r|t|\\\\|\"|'|0|u\\{[0-9a-fA-F]{1,6}\\}|.)",
+        },
+        "interpolation": {
+            "name": "meta.interpolation.ubel",
+            "begin": "\\{",
+            "end": "\\}",
+            "beginCaptures": { "0": { "name": "punctuation.section.interpolation.begin.ubel" } },
+            "endCaptures": { "0": { "name": "punctuation.section.interpolation.end.ubel" } },
+            "contentName": "source.ubel.embedded",
+            "patterns": [{ "include": "$self" }],
+        },
+        "strings": {
+            "patterns": [
+                {
+                    "name": "string.interpolated.verbatim.ubel",
+                    "begin": "\\$@\"",
+                    "end": "\"(?!\")",
+                    "patterns": [
+                        { "name": "constant.character.escape.ubel", "match": "\"\"" },
+                        { "include": "#interpolation" },
+                    ],
+                },
+                {
+                    "name": "string.interpolated.ubel",
+                    "begin": "\\$\"",
+                    "end": "\"",
+                    "patterns": [
+                        { "include": "#escape" },
+                        { "include": "#interpolation" },
+                    ],
+                },
+                {
+                    "name": "string.quoted.verbatim.ubel",
+                    "begin": "@\"",
+                    "end": "\"(?!\")",
+                    "patterns": [{ "name": "constant.character.escape.ubel", "match": "\"\"" }],
+                },
+                {
+                    "name": "string.quoted.double.ubel",
+                    "begin": "\"",
+                    "end": "\"",
+                    "patterns": [{ "include": "#escape" }],
+                },
+                {
+                    "name": "string.quoted.single.ubel",
+                    "match": "'(\\\\.|[^'\\\\])'",
+                },
+            ]
+        },
+    })
+}
+
+/// The complete grammar as a JSON value
+pub fn grammar() -> Value {
+    let mut repository = json!({
+        "comments": {
+            "patterns": [
+                { "name": "comment.block.documentation.ubel", "begin": "/\\*[*!]", "end": "\\*/" },
+                { "include": "#block-comment" },
+                { "name": "comment.line.double-slash.ubel", "match": "//.*$" },
+            ]
+        },
+        "block-comment": {
+            "name": "comment.block.ubel",
+            "begin": "/\\*",
+            "end": "\\*/",
+            "patterns": [{ "include": "#block-comment" }],
+        },
+        "annotations": {
+            "patterns": [
+                {
+                    "name": "storage.modifier.tier.ubel",
+                    "match": "@tier\\s*\\(\\s*(high|mid|low)\\s*\\)",
+                    "captures": { "1": { "name": "constant.language.tier.ubel" } },
+                },
+                { "name": "entity.name.function.decorator.ubel", "match": "@[A-Za-z_][A-Za-z0-9_]*" },
+            ]
+        },
+        "numbers": {
+            "patterns": [
+                { "name": "constant.numeric.hex.ubel", "match": "\\b0x[0-9a-fA-F][0-9a-fA-F_]*\\b" },
+                { "name": "constant.numeric.binary.ubel", "match": "\\b0b[01][01_]*\\b" },
+                { "name": "constant.numeric.float.ubel", "match": "\\b[0-9][0-9_]*\\.[0-9_]*([eE][+-]?[0-9][0-9_]*)?[fF]?" },
+                { "name": "constant.numeric.float.ubel", "match": "\\b[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*[fF]?\\b" },
+                { "name": "constant.numeric.integer.ubel", "match": "\\b[0-9][0-9_]*\\b" },
+            ]
+        },
+        "keywords": { "patterns": keyword_patterns() },
+        "operators": { "patterns": operator_patterns() },
+    });
+
+    if let (Some(target), Value::Object(strings)) = (repository.as_object_mut(), string_repository()) {
+        target.extend(strings);
+    }
+
+    json!({
+        "$schema": "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json",
+        "name": "Ubel Stratum",
+        "scopeName": SCOPE_NAME,
+        "fileTypes": ["strat"],
+        "patterns": [
+            { "include": "#comments" },
+            { "include": "#annotations" },
+            { "include": "#strings" },
+            { "include": "#numbers" },
+            { "include": "#keywords" },
+            { "include": "#operators" },
+        ],
+        "repository": repository,
+    })
+}
+
+/// Pretty-printed grammar, byte-for-byte what is committed to the repo
+pub fn grammar_json() -> String {
+    format!("{}\n", serde_json::to_string_pretty(&grammar()).unwrap_or_default())
+}
@@ -9,6 +9,7 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "let" => TokenType::Let,
     "mut" => TokenType::Mut,
     "const" => TokenType::Const,
+    "extract" => TokenType::Extract,
 
     // Control Flow
     "if" => TokenType::If,
@@ -44,6 +45,7 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "enum" => TokenType::Enum,
     "trait" => TokenType::Trait,
     "impl" => TokenType::Impl,
+    "extend" => TokenType::Extend,
 
     // Modifiers
     "pub" => TokenType::Pub,
@@ -51,6 +53,7 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "unsafe" => TokenType::Unsafe,
     "with" => TokenType::With,
     "defer" => TokenType::Defer,
+    "using" => TokenType::Using,
 
     // Logical
     "and" => TokenType::And,
@@ -71,4 +74,16 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
 #[inline]
 pub fn get_keyword(word: &str) -> Option<TokenType> {
     KEYWORDS.get(word).cloned()
-}
\ No newline at end of file
+}
+
+/// Contextual keywords of query expressions. They are identifiers everywhere
+/// except directly inside a query, so `names.join(",")` and a variable called
+/// `group` keep working.
+pub static QUERY_KEYWORDS: &[&str] = &[
+    "select", "orderby", "ascending", "descending", "group", "by", "join", "on", "equals",
+];
+
+/// The `QUERY_KEYWORDS` entry spelled `word`, if any
+pub fn query_keyword(word: &str) -> Option<&'static str> {
+    QUERY_KEYWORDS.iter().copied().find(|k| *k == word)
+}
@@ -2,7 +2,7 @@
 
 use logos::Logos;
 use crate::lexer::{Token, TokenType, Span};
-use crate::error_management::{ErrorManager, error_types::LexicalError};
+use crate::error_management::{ErrorManager, error_types::{LexicalError, LexicalWarning, StringType, ascii_quote_for}};
 use crate::lexer::{keywords, string_parser::StringParser, comment_parser::CommentParser};
 
 #[derive(Logos, Debug, Clone, PartialEq)]
@@ -13,6 +13,7 @@ enum LogosToken {
     #[token("let")] Let,
     #[token("mut")] Mut,
     #[token("const")] Const,
+    #[token("extract")] Extract,
     #[token("if")] If,
     #[token("elif")] Elif,
     #[token("else")] Else,
@@ -38,11 +39,13 @@ enum LogosToken {
     #[token("enum")] Enum,
     #[token("trait")] Trait,
     #[token("impl")] Impl,
+    #[token("extend")] Extend,
     #[token("pub")] Pub,
     #[token("edge")] Edge,
     #[token("unsafe")] Unsafe,
     #[token("with")] With,
     #[token("defer")] Defer,
+    #[token("using")] Using,
     #[token("and")] And,
     #[token("or")] Or,
     #[token("not")] Not,
@@ -64,6 +67,9 @@ enum LogosToken {
     #[token(">=")] GreaterEqual,
     #[token("&&")] AmpAmp,
     #[token("||")] PipePipe,
+    #[token("...")] DotDotDot,
+    #[token("..")] DotDot,
+    #[token("|>")] PipeGreater,
     #[token("?.")] QuestionDot,
     #[token("=>")] FatArrow,
     #[token(":=")] ColonEqual,
@@ -110,7 +116,7 @@ enum LogosToken {
     #[regex(r"0b[01][01_]*", parse_binary)]
     IntLit(i64),
 
-    #[regex(r"[0-9][0-9_]*\.[0-9_]*[fF]?", parse_float)]
+    #[regex(r"[0-9][0-9_]*\.[0-9_]*([eE][+-]?[0-9][0-9_]*)?[fF]?", parse_float)]
     #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*[fF]?", parse_float)]
     FloatLit(f64),
 
@@ -213,6 +219,8 @@ pub struct LogosLexer<'a> {
     input: &'a str,
     logos_lex: logos::Lexer<'a, LogosToken>,
     error_manager: ErrorManager,
+    /// Byte offset of the current logos slice within `input`
+    base: usize,
     position: usize,
     line: usize,
     column: usize,
@@ -225,6 +233,7 @@ impl<'a> LogosLexer<'a> {
             logos_lex: LogosToken::lexer(input),
             error_manager: ErrorManager::new(input.to_string()),
             input,
+            base: 0,
             position: 0,
             line: 1,
             column: 1,
@@ -232,11 +241,38 @@ impl<'a> LogosLexer<'a> {
         }
     }
 
-    pub fn tokenize(mut self) -> Result<Vec<Token>, ErrorManager> {
+    /// Lex only `input[range]`, which starts at `line` and `column`; spans
+    /// stay offsets into the whole of `input` (interpolation holes)
+    pub fn within(input: &'a str, range: std::ops::Range<usize>, line: usize, column: usize) -> Self {
+        let mut lexer = LogosLexer::new(&input[..range.end]);
+        lexer.resync(range.start, line, column);
+        lexer
+    }
+
+    pub fn tokenize(self) -> Result<Vec<Token>, ErrorManager> {
+        self.tokenize_with_diagnostics().map(|(tokens, _)| tokens)
+    }
+
+    /// Tokenize, keeping warnings and notes alongside the tokens on success
+    pub fn tokenize_with_diagnostics(self) -> Result<(Vec<Token>, ErrorManager), ErrorManager> {
+        let (tokens, error_manager) = self.tokenize_all();
+
+        if error_manager.has_errors() {
+            Err(error_manager)
+        } else {
+            Ok((tokens, error_manager))
+        }
+    }
+
+    /// Tokenize without failing: error tokens stay in the stream (for tooling)
+    pub fn tokenize_all(mut self) -> (Vec<Token>, ErrorManager) {
         while let Some(token_result) = self.logos_lex.next() {
-            let span_range = self.logos_lex.span();
+            let relative = self.logos_lex.span();
+            let span_range = (relative.start + self.base)..(relative.end + self.base);
             let lexeme = self.logos_lex.slice().to_string();
 
+            self.advance_to(span_range.start);
+
             match token_result {
                 Ok(logos_token) => {
                     self.handle_logos_token(logos_token, span_range, lexeme);
@@ -247,18 +283,15 @@ impl<'a> LogosLexer<'a> {
             }
         }
 
-        // Add EOF token
+        // Account for trailing whitespace, then add EOF token
+        self.advance_to(self.input.len());
         self.tokens.push(Token::new(
             TokenType::Eof,
             Span::new(self.position, self.position, self.line, self.column),
             String::new(),
         ));
 
-        if self.error_manager.has_errors() {
-            Err(self.error_manager)
-        } else {
-            Ok(self.tokens)
-        }
+        (self.tokens, self.error_manager)
     }
 
     fn handle_logos_token(
@@ -274,12 +307,7 @@ impl<'a> LogosLexer<'a> {
                 match parser.parse_interpolated_string() {
                     Ok((token, pos, line, col)) => {
                         self.tokens.push(token);
-                        self.position = pos;
-                        self.line = line;
-                        self.column = col;
-
-                        // Sync logos lexer
-                        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+                        self.resync(pos, line, col);
                     }
                     Err(err) => {
                         self.error_manager.add_lexical_error(err);
@@ -292,11 +320,9 @@ impl<'a> LogosLexer<'a> {
                 let mut parser = StringParser::new(self.input, span_range.start, self.line, self.column);
                 match parser.parse_verbatim_string() {
                     Ok((token, pos, line, col)) => {
+                        self.check_verbatim_whitespace(&token);
                         self.tokens.push(token);
-                        self.position = pos;
-                        self.line = line;
-                        self.column = col;
-                        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+                        self.resync(pos, line, col);
                     }
                     Err(err) => {
                         self.error_manager.add_lexical_error(err);
@@ -309,11 +335,9 @@ impl<'a> LogosLexer<'a> {
                 let mut parser = StringParser::new(self.input, span_range.start, self.line, self.column);
                 match parser.parse_interpolated_verbatim_string() {
                     Ok((token, pos, line, col)) => {
+                        self.check_verbatim_whitespace(&token);
                         self.tokens.push(token);
-                        self.position = pos;
-                        self.line = line;
-                        self.column = col;
-                        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+                        self.resync(pos, line, col);
                     }
                     Err(err) => {
                         self.error_manager.add_lexical_error(err);
@@ -325,12 +349,9 @@ impl<'a> LogosLexer<'a> {
             LogosToken::BlockCommentStart => {
                 let mut parser = CommentParser::new(self.input, span_range.start, self.line, self.column);
                 match parser.parse_block_comment() {
-                    Ok((token, pos, line, col)) => {
+                    Ok((_token, pos, line, col)) => {
                         // Don't add comment tokens to stream (just skip)
-                        self.position = pos;
-                        self.line = line;
-                        self.column = col;
-                        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+                        self.resync(pos, line, col);
                     }
                     Err(err) => {
                         self.error_manager.add_lexical_error(err);
@@ -345,13 +366,17 @@ impl<'a> LogosLexer<'a> {
                 match parser.parse_doc_comment(marker) {
                     Ok((token, pos, line, col)) => {
                         self.tokens.push(token);
-                        self.position = pos;
-                        self.line = line;
-                        self.column = col;
-                        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+                        self.resync(pos, line, col);
                     }
                     Err(err) => {
-                        self.error_manager.add_lexical_error(err);
+                        // Recoverable: keep everything up to EOF as documentation
+                        let span = err.span();
+                        let lexeme = &self.input[span.start..];
+                        let content = lexeme[marker.len()..].trim().to_string();
+                        self.error_manager.add_lexical_warning(LexicalWarning::UnterminatedDocComment { span });
+                        self.tokens.push(Token::new(TokenType::DocComment(content), span, lexeme.to_string()));
+                        self.update_position(lexeme);
+                        self.resync(self.position, self.line, self.column);
                     }
                 }
                 return;
@@ -366,10 +391,31 @@ impl<'a> LogosLexer<'a> {
             _ => {}
         }
 
+        // `0..10` and `42.is_even()`: the dot starts the next token
+        if matches!(logos_token, LogosToken::FloatLit(_)) {
+            if let Some(digits) = Self::integer_before_dot(self.input, span_range.start, &lexeme) {
+                if let Ok(value) = digits.replace('_', "").parse() {
+                    let span = Span::new(span_range.start, span_range.start + digits.len(), self.line, self.column);
+                    self.update_position(digits);
+                    self.tokens.push(Token::new(TokenType::IntLit(value), span, digits.to_string()));
+                    self.resync(self.position, self.line, self.column);
+                    return;
+                }
+            }
+        }
+
         // Fast path: direct token mapping
         let span = Span::new(span_range.start, span_range.end, self.line, self.column);
         self.update_position(&lexeme);
 
+        if matches!(logos_token, LogosToken::FloatLit(_))
+            && lexeme.trim_end_matches(['f', 'F']).ends_with('.') {
+            self.error_manager.add_lexical_warning(LexicalWarning::TrailingDotFloat {
+                text: lexeme.clone(),
+                span,
+            });
+        }
+
         let token_type = self.map_logos_token(logos_token, &lexeme);
         self.tokens.push(Token::new(token_type, span, lexeme));
     }
@@ -381,6 +427,7 @@ impl<'a> LogosLexer<'a> {
             LogosToken::Let => TokenType::Let,
             LogosToken::Mut => TokenType::Mut,
             LogosToken::Const => TokenType::Const,
+            LogosToken::Extract => TokenType::Extract,
             LogosToken::If => TokenType::If,
             LogosToken::Elif => TokenType::Elif,
             LogosToken::Else => TokenType::Else,
@@ -406,11 +453,13 @@ impl<'a> LogosLexer<'a> {
             LogosToken::Enum => TokenType::Enum,
             LogosToken::Trait => TokenType::Trait,
             LogosToken::Impl => TokenType::Impl,
+            LogosToken::Extend => TokenType::Extend,
             LogosToken::Pub => TokenType::Pub,
             LogosToken::Edge => TokenType::Edge,
             LogosToken::Unsafe => TokenType::Unsafe,
             LogosToken::With => TokenType::With,
             LogosToken::Defer => TokenType::Defer,
+            LogosToken::Using => TokenType::Using,
             LogosToken::And => TokenType::And,
             LogosToken::Or => TokenType::Or,
             LogosToken::Not => TokenType::Not,
@@ -455,6 +504,9 @@ impl<'a> LogosLexer<'a> {
             LogosToken::RightShiftEqual => TokenType::RightShiftEqual,
             LogosToken::Question => TokenType::Question,
             LogosToken::QuestionDot => TokenType::QuestionDot,
+            LogosToken::DotDot => TokenType::DotDot,
+            LogosToken::DotDotDot => TokenType::DotDotDot,
+            LogosToken::PipeGreater => TokenType::PipeGreater,
             LogosToken::FatArrow => TokenType::FatArrow,
             LogosToken::ColonEqual => TokenType::ColonEqual,
 
@@ -493,13 +545,38 @@ impl<'a> LogosLexer<'a> {
     }
 
     fn handle_error(&mut self, span_range: std::ops::Range<usize>, lexeme: String) {
-        let span = Span::new(span_range.start, span_range.end, self.line, self.column);
         let ch = lexeme.chars().next().unwrap_or('\0');
 
+        // A quote logos could not close: the string runs to end of line
+        if ch == '"' {
+            let rest = &self.input[span_range.start..];
+            let end = span_range.start + rest.find('\n').unwrap_or(rest.len());
+            let span = Span::new(span_range.start, end, self.line, self.column);
+
+            self.error_manager.add_lexical_error(LexicalError::UnterminatedString {
+                span,
+                string_type: StringType::Normal,
+            });
+            self.tokens.push(Token::error("Unterminated string literal".to_string(), span));
+
+            self.update_position(&self.input[span_range.start..end]);
+            self.resync(self.position, self.line, self.column);
+            return;
+        }
+
+        // Only skip the offending character; logos may have swallowed more
+        let end = span_range.start + ch.len_utf8();
+        let span = Span::new(span_range.start, end, self.line, self.column);
+
+        let suggestion = match ascii_quote_for(ch) {
+            Some(quote) => format!("Replace smart quote with {}", quote),
+            None => "Remove this character or check for typos".to_string(),
+        };
+
         self.error_manager.add_lexical_error(LexicalError::UnexpectedChar {
             ch,
             span,
-            suggestion: Some("Remove this character or check for typos".to_string()),
+            suggestion: Some(suggestion),
         });
 
         self.tokens.push(Token::error(
@@ -507,7 +584,75 @@ impl<'a> LogosLexer<'a> {
             span,
         ));
 
-        self.update_position(&lexeme);
+        self.update_position(&self.input[span_range.start..end]);
+        if end < span_range.end {
+            self.resync(self.position, self.line, self.column);
+        }
+    }
+
+    /// The integer part of a float literal whose dot really begins `..` or a
+    /// member name. `1.f` and `1.e5` stay floats; `1.foo` and `1.exp()` do not.
+    fn integer_before_dot<'l>(input: &str, start: usize, lexeme: &'l str) -> Option<&'l str> {
+        let dot = lexeme.find('.')?;
+        let after = &input[start + dot + 1..];
+        if after.starts_with('.') {
+            return Some(&lexeme[..dot]);
+        }
+
+        let name_len = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+        let starts_name = after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        (starts_name && dot + 1 + name_len > lexeme.len()).then(|| &lexeme[..dot])
+    }
+
+    /// Restart logos at `pos` after a hand-written parser consumed input
+    fn resync(&mut self, pos: usize, line: usize, column: usize) {
+        self.position = pos;
+        self.line = line;
+        self.column = column;
+        self.base = pos;
+        self.logos_lex = LogosToken::lexer(&self.input[pos..]);
+    }
+
+    /// Walk over input logos skipped (whitespace) up to `target`
+    fn advance_to(&mut self, target: usize) {
+        if target <= self.position {
+            return;
+        }
+
+        let gap = &self.input[self.position..target];
+        let at_line_start = self.column == 1;
+        let starts_code = !self.input[target..].starts_with('\n') && target < self.input.len();
+
+        if at_line_start && starts_code
+            && gap.contains(' ') && gap.contains('\t')
+            && gap.chars().all(|c| c == ' ' || c == '\t') {
+            let span = Span::new(self.position, target, self.line, self.column);
+            self.error_manager.add_lexical_warning(LexicalWarning::MixedIndentation { span });
+        }
+
+        self.update_position(gap);
+    }
+
+    /// Warn about lines in a verbatim string that end in spaces or tabs
+    fn check_verbatim_whitespace(&mut self, token: &Token) {
+        let mut offset = token.span.start;
+        let mut line = token.span.line;
+        let mut column = token.span.column;
+
+        let mut lines = token.lexeme.split('\n').peekable();
+        while let Some(text) = lines.next() {
+            if lines.peek().is_some() {
+                let trimmed = text.trim_end_matches([' ', '\t']);
+                if trimmed.len() < text.len() {
+                    let col = column + trimmed.chars().count();
+                    let span = Span::new(offset + trimmed.len(), offset + text.len(), line, col);
+                    self.error_manager.add_lexical_warning(LexicalWarning::TrailingWhitespaceInVerbatim { span });
+                }
+            }
+            offset += text.len() + 1;
+            line += 1;
+            column = 1;
+        }
     }
 
     fn update_position(&mut self, lexeme: &str) {
@@ -5,14 +5,19 @@ pub mod keywords;
 pub mod logos_lexer;
 pub mod string_parser;
 pub mod comment_parser;
+pub mod operators;
 
 pub use token::{Token, TokenType, Span, InterpolationPart};
 pub use logos_lexer::LogosLexer;
 
+use crate::error_management::ErrorManager;
+
 /// Main tokenization entry point
-pub fn tokenize(input: &str) -> Result<Vec<Token>, crate::error_management::ErrorManager> {
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ErrorManager> {
     LogosLexer::new(input).tokenize()
 }
 
-#[cfg(test)]
-mod tests;
\ No newline at end of file
+/// Tokenize, returning any warnings alongside the tokens
+pub fn tokenize_with_diagnostics(input: &str) -> Result<(Vec<Token>, ErrorManager), ErrorManager> {
+    LogosLexer::new(input).tokenize_with_diagnostics()
+}
@@ -1,3 +1,99 @@
-//! Operator recognition
+//! Operator and punctuation table
+//!
+//! Mirrors the operator `#[token]`s in `LogosLexer`; tooling (highlighting,
+//! grammar export) reads this table instead of repeating the list.
 
-// TODO: Multi-character operator handling
+use crate::lexer::TokenType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorClass {
+    Arithmetic,
+    Bitwise,
+    Comparison,
+    Logical,
+    Assignment,
+    Special,
+    Delimiter,
+    Punctuation,
+}
+
+pub struct OperatorInfo {
+    pub lexeme: &'static str,
+    pub kind: TokenType,
+    pub class: OperatorClass,
+}
+
+macro_rules! operators {
+    ($($lexeme:literal => $kind:ident, $class:ident;)*) => {
+        pub static OPERATORS: &[OperatorInfo] = &[
+            $(OperatorInfo { lexeme: $lexeme, kind: TokenType::$kind, class: OperatorClass::$class },)*
+        ];
+    };
+}
+
+operators! {
+    "+" => Plus, Arithmetic;
+    "-" => Minus, Arithmetic;
+    "*" => Star, Arithmetic;
+    "/" => Slash, Arithmetic;
+    "%" => Percent, Arithmetic;
+
+    "&" => Amp, Bitwise;
+    "|" => Pipe, Bitwise;
+    "^" => Caret, Bitwise;
+    "~" => Tilde, Bitwise;
+    "<<" => LeftShift, Bitwise;
+    ">>" => RightShift, Bitwise;
+
+    "==" => EqualEqual, Comparison;
+    "!=" => BangEqual, Comparison;
+    "<" => Less, Comparison;
+    ">" => Greater, Comparison;
+    "<=" => LessEqual, Comparison;
+    ">=" => GreaterEqual, Comparison;
+
+    "!" => Bang, Logical;
+    "&&" => AmpAmp, Logical;
+    "||" => PipePipe, Logical;
+
+    "=" => Equal, Assignment;
+    "+=" => PlusEqual, Assignment;
+    "-=" => MinusEqual, Assignment;
+    "*=" => StarEqual, Assignment;
+    "/=" => SlashEqual, Assignment;
+    "%=" => PercentEqual, Assignment;
+    "&=" => AmpEqual, Assignment;
+    "|=" => PipeEqual, Assignment;
+    "^=" => CaretEqual, Assignment;
+    "<<=" => LeftShiftEqual, Assignment;
+    ">>=" => RightShiftEqual, Assignment;
+    ":=" => ColonEqual, Assignment;
+
+    "?" => Question, Special;
+    "?." => QuestionDot, Special;
+    "=>" => FatArrow, Special;
+    ".." => DotDot, Special;
+    "..." => DotDotDot, Special;
+    "|>" => PipeGreater, Special;
+
+    "(" => LeftParen, Delimiter;
+    ")" => RightParen, Delimiter;
+    "{" => LeftBrace, Delimiter;
+    "}" => RightBrace, Delimiter;
+    "[" => LeftBracket, Delimiter;
+    "]" => RightBracket, Delimiter;
+
+    "," => Comma, Punctuation;
+    "." => Dot, Punctuation;
+    ":" => Colon, Punctuation;
+    ";" => Semicolon, Punctuation;
+    "@" => At, Punctuation;
+}
+
+pub fn lookup(lexeme: &str) -> Option<&'static OperatorInfo> {
+    OPERATORS.iter().find(|op| op.lexeme == lexeme)
+}
+
+pub fn class_of(kind: &TokenType) -> Option<OperatorClass> {
+    OPERATORS.iter().find(|op| &op.kind == kind).map(|op| op.class)
+}
@@ -154,50 +154,100 @@ impl<'a> StringParser<'a> {
         self.position += 1;
         self.column += 1;
 
-        let mut expr = String::new();
-        let mut depth = 1; // We're inside one {
+        let start = self.position;
+        let end = Self::hole_end(self.input, start);
+        let stop = end.unwrap_or(self.input.len());
+        for ch in self.input[start..stop].chars() {
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.position = stop;
 
-        while self.position < self.input.len() && depth > 0 {
-            let ch = self.char_at(self.position);
+        match end {
+            Some(end) => Ok(self.input[start..end - 1].trim().to_string()),
+            None => Err(LexicalError::InvalidInterpolation {
+                message: "Unclosed interpolation expression".to_string(),
+                span: Span::new(self.position, self.position, self.line, self.column),
+                suggestion: Some("Add closing }".to_string()),
+            }),
+        }
+    }
 
-            match ch {
-                '{' => {
-                    depth += 1;
-                    expr.push(ch);
-                    self.position += 1;
-                    self.column += 1;
-                }
-                '}' => {
+    /// Offset just past the `}` that closes the hole starting at `start`
+    /// (right after its `{`). String and char literals in the hole are
+    /// stepped over, so their quotes and braces don't count.
+    pub fn hole_end(text: &str, start: usize) -> Option<usize> {
+        let bytes = text.as_bytes();
+        let mut depth = 1;
+        let mut i = start;
+
+        while i < bytes.len() {
+            let literal_end = match bytes[i] {
+                b'"' | b'$' | b'@' => Self::string_end(text, i),
+                b'\'' => Self::char_end(text, i),
+                _ => None,
+            };
+            if let Some(end) = literal_end {
+                i = end;
+                continue;
+            }
+            match bytes[i] {
+                b'{' => depth += 1,
+                b'}' => {
                     depth -= 1;
-                    if depth > 0 {
-                        expr.push(ch);
+                    if depth == 0 {
+                        return Some(i + 1);
                     }
-                    self.position += 1;
-                    self.column += 1;
-                }
-                '\n' => {
-                    expr.push(ch);
-                    self.position += 1;
-                    self.line += 1;
-                    self.column = 1;
-                }
-                _ => {
-                    expr.push(ch);
-                    self.position += 1;
-                    self.column += 1;
                 }
+                _ => {}
             }
+            i += 1;
         }
 
-        if depth == 0 {
-            Ok(expr.trim().to_string())
+        None
+    }
+
+    /// Offset just past the string literal (`"`, `@"`, `$"` or `$@"`) at `start`
+    fn string_end(text: &str, start: usize) -> Option<usize> {
+        let rest = &text[start..];
+        let (prefix, verbatim, interpolated) = if rest.starts_with("$@\"") {
+            (3, true, true)
+        } else if rest.starts_with("$\"") {
+            (2, false, true)
+        } else if rest.starts_with("@\"") {
+            (2, true, false)
+        } else if rest.starts_with('"') {
+            (1, false, false)
         } else {
-            Err(LexicalError::InvalidInterpolation {
-                message: "Unclosed interpolation expression".to_string(),
-                span: Span::new(self.position, self.position, self.line, self.column),
-                suggestion: Some("Add closing }".to_string()),
-            })
+            return None;
+        };
+
+        let bytes = text.as_bytes();
+        let mut i = start + prefix;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' if !verbatim => i += 2,
+                b'"' if verbatim && bytes.get(i + 1) == Some(&b'"') => i += 2,
+                b'"' => return Some(i + 1),
+                b'{' if interpolated => i = Self::hole_end(text, i + 1)?,
+                _ => i += 1,
+            }
         }
+        None
+    }
+
+    /// Offset just past the char literal at `start`; `None` for a lone `'`
+    fn char_end(text: &str, start: usize) -> Option<usize> {
+        let rest = &text[start + 1..];
+        let len = match rest.strip_prefix('\\') {
+            Some(escaped) => 1 + escaped.chars().next()?.len_utf8(),
+            None => rest.chars().next()?.len_utf8(),
+        };
+        rest[len..].starts_with('\'').then_some(start + 1 + len + 1)
     }
 
     /// Parse verbatim string: @"C:\path\to\file"
@@ -340,6 +390,38 @@ impl<'a> StringParser<'a> {
         })
     }
 
+    /// Byte ranges of the expression holes (between `{` and `}`) in an
+    /// interpolated string lexeme, relative to the start of the lexeme
+    pub fn hole_ranges(lexeme: &str) -> Vec<std::ops::Range<usize>> {
+        let verbatim = lexeme.starts_with("$@");
+        let bytes = lexeme.as_bytes();
+        let mut ranges = Vec::new();
+        let mut i = if verbatim { 3 } else { 2 };
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' if !verbatim => i += 2,
+                b'"' if verbatim && bytes.get(i + 1) == Some(&b'"') => i += 2,
+                b'{' => {
+                    let start = i + 1;
+                    match Self::hole_end(lexeme, start) {
+                        Some(end) => {
+                            ranges.push(start..end - 1);
+                            i = end;
+                        }
+                        None => {
+                            ranges.push(start..bytes.len());
+                            break;
+                        }
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+
+        ranges
+    }
+
     #[inline]
     fn char_at(&self, pos: usize) -> char {
         self.input.chars().nth(pos).unwrap_or('\0')
@@ -1,6 +1,7 @@
 //! Token types - Separate from lexer logic
 
 use std::fmt;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
@@ -8,15 +9,15 @@ pub enum TokenType {
     // Keywords
     // ========================================
 
-    Fn, Let, Mut, Const,
+    Fn, Let, Mut, Const, Extract,
     If, Elif, Else, Match, Where,
     For, In, While, Loop,
     Break, Continue, Return,
     Summon, From, As, Package,
     Async, Await, Task,
     Try, Catch, Fail,
-    Struct, Enum, Trait, Impl,
-    Pub, Edge, Unsafe, With, Defer,
+    Struct, Enum, Trait, Impl, Extend,
+    Pub, Edge, Unsafe, With, Defer, Using,
     And, Or, Not,
     True, False, Null, SelfKw,
     Get, Set,
@@ -44,6 +45,10 @@ pub enum TokenType {
     // ========================================
 
     Ident(String),
+    /// A query keyword such as `select` (see `keywords::QUERY_KEYWORDS`). The
+    /// lexer never produces it; the LALR token adapter substitutes it for
+    /// `Ident` inside queries.
+    Contextual(&'static str),
 
     // ========================================
     // Operators
@@ -73,6 +78,9 @@ pub enum TokenType {
     QuestionDot,   // ?.
     FatArrow,      // =>
     ColonEqual,    // :=
+    DotDot,        // ..
+    DotDotDot,     // ...
+    PipeGreater,   // |>
 
     // ========================================
     // Delimiters
@@ -80,6 +88,9 @@ pub enum TokenType {
 
     LeftParen, RightParen,       // ( )
     LeftBrace, RightBrace,       // { }
+    /// `{` opening an object or dictionary literal. The lexer never produces
+    /// it; the LALR token adapter substitutes it for `LeftBrace`.
+    ObjectBrace,
     LeftBracket, RightBracket,   // [ ]
 
     // ========================================
@@ -117,7 +128,7 @@ pub enum InterpolationPart {
     Expr(String),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -130,10 +141,23 @@ impl Span {
         Span { start, end, line, column }
     }
 
+    /// Zero-width span at a byte offset, with line and column taken from `source`
+    pub fn point(source: &str, offset: usize) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Span::new(offset, offset, line, before[line_start..].chars().count() + 1)
+    }
+
     pub fn len(&self) -> usize {
         self.end - self.start
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
     pub fn merge(&self, other: &Span) -> Span {
         Span {
             start: self.start.min(other.start),
@@ -2,12 +2,16 @@
 
 pub mod lexer;
 pub mod error_management;
+pub mod grammar;
+pub mod highlight;
+pub mod parser;
+pub mod syntax;
+pub mod semantic;
 
 // TODO: Phase 2 - Implement these modules when ready for tree-walking interpreter
-// pub mod parser;
-// pub mod semantic;
 // pub mod interpreter;
 // pub mod tier_analysis;
 // pub mod stdlib;
 
 pub use lexer::{Token, TokenType, tokenize};
+pub use parser::parse;
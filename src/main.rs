@@ -1,12 +1,15 @@
 //! Ubel Stratum Compiler CLI
- 
-mod lexer;
-mod error_management;
 
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use error_management::Logger;
+use ubel_stratum::lexer;
+use ubel_stratum::parser::{self, AstFormat, Program};
+use ubel_stratum::semantic;
+use ubel_stratum::syntax;
+use ubel_stratum::highlight::{self, HighlightFormat, textmate};
+use ubel_stratum::grammar::reference::{self, ReferenceFormat};
+use ubel_stratum::error_management::{ErrorManager, Logger, MessageFormat, error_codes, fixes, renderer};
 
 #[derive(Parser)]
 #[command(name = "stratc")]
@@ -23,6 +26,49 @@ struct Cli {
     /// Quiet mode (no logs)
     #[arg(short, long, global = true)]
     quiet: bool,
+
+    /// Treat warnings as errors
+    #[arg(long, global = true)]
+    deny_warnings: bool,
+
+    /// Diagnostic output: fancy, plain, json, sarif
+    #[arg(long, global = true, default_value = "fancy")]
+    message_format: MessageFormat,
+}
+
+/// How diagnostics are rendered for this invocation
+struct Reporter {
+    format: MessageFormat,
+    colored: bool,
+    deny_warnings: bool,
+}
+
+impl Reporter {
+    /// Render all diagnostics; machine formats go to stdout, text to stderr
+    fn report(&self, manager: &mut ErrorManager, file: &Path) {
+        manager.set_deny_warnings(self.deny_warnings);
+
+        let renderer = renderer::renderer_for(self.format, self.colored);
+        let output = manager.render(renderer.as_ref(), &file.display().to_string());
+
+        if self.format.is_machine_readable() {
+            print!("{}", output);
+        } else if Logger::is_enabled() && !output.is_empty() {
+            eprint!("{}", output);
+            Logger::info(&manager.summary());
+        }
+    }
+
+    /// Print what the command produces (tokens, a tree, a diff). With a
+    /// machine format stdout holds only the diagnostic document, so the
+    /// output goes to stderr instead.
+    fn output(&self, text: &str) {
+        if self.format.is_machine_readable() {
+            eprint!("{}", text);
+        } else {
+            print!("{}", text);
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -42,15 +88,67 @@ enum Commands {
         /// Input file path
         file: PathBuf,
 
-        /// Output format: text, json, debug
+        /// Output format: text, json, debug, source
         #[arg(short, long, default_value = "text")]
-        format: String,
+        format: AstFormat,
+
+        /// Use the generated LALR(1) parser instead of recursive descent
+        #[arg(long)]
+        lalr: bool,
+
+        /// Print the lossless syntax tree, trivia included, instead of the AST
+        #[arg(long, conflicts_with_all = ["lalr", "format"])]
+        cst: bool,
+
+        /// Rewrite query expressions into method calls and `using` into `try`/`defer`
+        #[arg(long, conflicts_with = "cst")]
+        desugared: bool,
     },
 
     /// Check syntax and types
     Check {
         /// Input file path
         file: PathBuf,
+
+        /// Apply machine-applicable fixes to the file
+        #[arg(long)]
+        fix: bool,
+
+        /// Print the fixes as a unified diff (without --fix, nothing is written)
+        #[arg(long)]
+        diff: bool,
+
+        /// A module the file may `summon`; repeat for each one
+        #[arg(long = "module", value_name = "FILE")]
+        modules: Vec<PathBuf>,
+    },
+
+    /// Syntax-highlight a .strat file using the real lexer
+    Highlight {
+        /// Input file path (not needed with --textmate)
+        #[arg(required_unless_present = "textmate")]
+        file: Option<PathBuf>,
+
+        /// Output format: ansi, html, json
+        #[arg(short, long, default_value = "ansi")]
+        format: HighlightFormat,
+
+        /// Print the generated TextMate grammar instead
+        #[arg(long)]
+        textmate: bool,
+    },
+
+    /// Print the grammar, its keyword and operator sections built from the parser's tables
+    Grammar {
+        /// Output format: md, ebnf
+        #[arg(short, long, default_value = "ebnf")]
+        format: ReferenceFormat,
+    },
+
+    /// Explain a diagnostic code (e.g. E0002), or list all codes
+    Explain {
+        /// Diagnostic code
+        code: Option<String>,
     },
 
     /// Run a .strat file (interpreter)
@@ -71,17 +169,29 @@ fn main() {
         Logger::disable();
     }
 
+    let colored = renderer::color_requested(cli.no_color);
+    Logger::set_color(colored);
+
+    let reporter = Reporter {
+        format: cli.message_format,
+        colored,
+        deny_warnings: cli.deny_warnings,
+    };
+
     let exit_code = match cli.command {
-        Commands::Lex { file, verbose } => handle_lex(file, verbose),
-        Commands::Parse { file, format } => handle_parse(file, format),
-        Commands::Check { file } => handle_check(file),
+        Commands::Lex { file, verbose } => handle_lex(file, verbose, &reporter),
+        Commands::Parse { file, format, lalr, cst, desugared } => handle_parse(file, format, lalr, cst, desugared, &reporter),
+        Commands::Check { file, fix, diff, modules } => handle_check(file, fix, diff, modules, &reporter),
+        Commands::Highlight { file, format, textmate } => handle_highlight(file, format, textmate, &reporter),
+        Commands::Grammar { format } => handle_grammar(format),
+        Commands::Explain { code } => handle_explain(code),
         Commands::Run { file, args } => handle_run(file, args),
     };
 
     std::process::exit(exit_code);
 }
 
-fn handle_lex(file: PathBuf, verbose: bool) -> i32 {
+fn handle_lex(file: PathBuf, verbose: bool, reporter: &Reporter) -> i32 {
     Logger::info(&format!("Lexing: {:?}", file));
 
     let source = match fs::read_to_string(&file) {
@@ -92,36 +202,214 @@ fn handle_lex(file: PathBuf, verbose: bool) -> i32 {
         }
     };
 
-    match lexer::tokenize(&source) {
-        Ok(tokens) => {
+    match lexer::tokenize_with_diagnostics(&source) {
+        Ok((tokens, mut diagnostics)) => {
+            reporter.report(&mut diagnostics, &file);
+            if diagnostics.has_errors() {
+                Logger::error("❌ Lexing failed: warnings denied");
+                return 1;
+            }
+
             if verbose {
-                println!("\n{} tokens:", tokens.len());
-                println!("{:-<80}", "");
+                let mut listing = format!("\n{} tokens:\n{:-<80}\n", tokens.len(), "");
                 for (idx, token) in tokens.iter().enumerate() {
-                    println!("{:4} | {:?}", idx, token);
+                    listing.push_str(&format!("{:4} | {:?}\n", idx, token));
                 }
-                println!("{:-<80}", "");
+                listing.push_str(&format!("{:-<80}\n", ""));
+                reporter.output(&listing);
             } else {
                 Logger::info(&format!("✅ Lexing successful: {} tokens", tokens.len()));
             }
             0
         }
-        Err(error_manager) => {
+        Err(mut error_manager) => {
             Logger::error("❌ Lexing failed:");
-            error_manager.report_all();
+            reporter.report(&mut error_manager, &file);
             1
         }
     }
 }
 
-fn handle_parse(_file: PathBuf, _format: String) -> i32 {
-    Logger::error("Parse command not yet implemented");
-    1
+fn handle_parse(file: PathBuf, format: AstFormat, lalr: bool, cst: bool, desugared: bool, reporter: &Reporter) -> i32 {
+    Logger::info(&format!("Parsing: {:?}", file));
+
+    let source = match fs::read_to_string(&file) {
+        Ok(s) => s,
+        Err(e) => {
+            Logger::error(&format!("Failed to read file: {}", e));
+            return 1;
+        }
+    };
+
+    if cst {
+        let parse = syntax::parse(&source);
+        reporter.output(&syntax::debug_tree(&parse.syntax()));
+        let mut errors = parse.into_errors();
+        reporter.report(&mut errors, &file);
+        return i32::from(errors.has_errors());
+    }
+
+    let parsed = if lalr {
+        parser::lalr::parse_with_diagnostics(&source)
+    } else {
+        parser::parse_with_diagnostics(&source)
+    };
+
+    match parsed {
+        Ok((program, mut diagnostics)) => {
+            reporter.report(&mut diagnostics, &file);
+            if diagnostics.has_errors() {
+                Logger::error("❌ Parsing failed: warnings denied");
+                return 1;
+            }
+            let program = if desugared { parser::desugar::desugar(program) } else { program };
+            reporter.output(&parser::tree::render(&program, format));
+            0
+        }
+        Err(mut error_manager) => {
+            Logger::error("❌ Parsing failed:");
+            reporter.report(&mut error_manager, &file);
+            1
+        }
+    }
 }
 
-fn handle_check(_file: PathBuf) -> i32 {
-    Logger::error("Check command not yet implemented");
-    1
+fn handle_check(file: PathBuf, fix: bool, diff: bool, modules: Vec<PathBuf>, reporter: &Reporter) -> i32 {
+    Logger::info(&format!("Checking: {:?}", file));
+
+    let source = match fs::read_to_string(&file) {
+        Ok(s) => s,
+        Err(e) => {
+            Logger::error(&format!("Failed to read file: {}", e));
+            return 1;
+        }
+    };
+
+    let mut programs = Vec::new();
+    for module in &modules {
+        let parsed = fs::read_to_string(module).map_err(|e| e.to_string()).and_then(|source| {
+            parser::parse(&source).map_err(|_| "it does not parse; check it first".to_string())
+        });
+        match parsed {
+            Ok(program) => programs.push(program),
+            Err(e) => {
+                Logger::error(&format!("Failed to load module {:?}: {}", module, e));
+                return 1;
+            }
+        }
+    }
+    let modules: Vec<&Program> = programs.iter().collect();
+
+    let mut diagnostics = check_source(&source, &modules);
+
+    if fix || diff {
+        let result = fixes::apply_suggestions(&source, &diagnostics.suggestions());
+
+        if diff && result.applied > 0 {
+            reporter.output(&fixes::diff(&file.display().to_string(), &source, &result.output));
+        }
+
+        if fix && result.applied > 0 {
+            if let Err(e) = fixes::write_atomically(&file, &result.output) {
+                Logger::error(&format!("Failed to write fixes: {}", e));
+                return 1;
+            }
+            Logger::info(&format!("Applied {} fix(es) to {:?}", result.applied, file));
+            diagnostics = check_source(&result.output, &modules);
+        }
+
+        if result.conflicting > 0 {
+            Logger::warning(&format!("Skipped {} overlapping fix(es); run --fix again", result.conflicting));
+        }
+    }
+
+    reporter.report(&mut diagnostics, &file);
+    if diagnostics.has_errors() {
+        1
+    } else {
+        Logger::info("✅ No errors found");
+        0
+    }
+}
+
+/// Run every implemented phase and collect diagnostics; `modules` are the
+/// ones the source may `summon`
+fn check_source(source: &str, modules: &[&Program]) -> ErrorManager {
+    let (program, mut diagnostics) = parser::parse_recovering(source);
+    // Recovered trees have holes, so only check programs that parsed cleanly
+    if !diagnostics.has_errors() {
+        let errors = semantic::attributes::check(&program)
+            .into_iter()
+            .chain(semantic::lifetimes::check(&program))
+            .chain(semantic::bounds::check(&program))
+            .chain(semantic::methods::resolve(&program, modules).errors);
+        for error in errors {
+            diagnostics.add_semantic_error(error);
+        }
+    }
+    diagnostics
+}
+
+fn handle_highlight(file: Option<PathBuf>, format: HighlightFormat, textmate: bool, reporter: &Reporter) -> i32 {
+    if textmate {
+        print!("{}", textmate::grammar_json());
+        return 0;
+    }
+
+    let Some(file) = file else {
+        Logger::error("No input file given");
+        return 1;
+    };
+
+    match fs::read_to_string(&file) {
+        Ok(source) => {
+            // Highlighting carries on past lexical errors; report them too
+            let mut diagnostics = match lexer::tokenize_with_diagnostics(&source) {
+                Ok((_, diagnostics)) => diagnostics,
+                Err(diagnostics) => diagnostics,
+            };
+            reporter.output(&highlight::render(&source, format));
+            reporter.report(&mut diagnostics, &file);
+            i32::from(diagnostics.has_errors())
+        }
+        Err(e) => {
+            Logger::error(&format!("Failed to read file: {}", e));
+            1
+        }
+    }
+}
+
+fn handle_grammar(format: ReferenceFormat) -> i32 {
+    match reference::render(format) {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(e) => {
+            Logger::error(&e);
+            1
+        }
+    }
+}
+
+fn handle_explain(code: Option<String>) -> i32 {
+    let Some(code) = code else {
+        for entry in error_codes::ERROR_CODES {
+            println!("{}  {}", entry.code, entry.title);
+        }
+        return 0;
+    };
+
+    match error_codes::lookup(&code) {
+        Some(entry) => {
+            print!("{}", entry.render());
+            0
+        }
+        None => {
+            Logger::error(&format!("Unknown diagnostic code '{}'", code));
+            1
+        }
+    }
 }
 
 fn handle_run(_file: PathBuf, _args: Vec<String>) -> i32 {
@@ -0,0 +1,923 @@
+//! Typed abstract syntax tree - mirrors `docs/ubel.ebnf`
+//!
+//! Every node carries the `Span` of the source text it was parsed from.
+//! Node kinds are internally tagged (`"type": "Binary"`) so the JSON output
+//! of `stratc parse` reads like the grammar.
+
+use serde::Serialize;
+use crate::lexer::Span;
+
+// ========================================
+// Program structure
+// ========================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Program {
+    pub package: Option<PackageDecl>,
+    pub imports: Vec<Import>,
+    pub items: Vec<Item>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+/// `std.collections.List`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct QualifiedIdent {
+    pub segments: Vec<Ident>,
+    pub span: Span,
+}
+
+impl QualifiedIdent {
+    /// Segments joined with `.`
+    pub fn dotted(&self) -> String {
+        self.segments.iter().map(|s| s.name.as_str()).collect::<Vec<_>>().join(".")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct PackageDecl {
+    pub path: QualifiedIdent,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Import {
+    #[serde(flatten)]
+    pub kind: ImportKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum ImportKind {
+    /// `summon std.io as io`
+    Summon { path: QualifiedIdent, alias: Option<Ident> },
+    /// `from database summon [User, Database]`
+    From { path: QualifiedIdent, items: Vec<Ident> },
+}
+
+// ========================================
+// Items
+// ========================================
+
+/// Each declaration names itself in JSON (`"type": "FunctionDecl"`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Item {
+    Function(FunctionDecl),
+    Struct(StructDecl),
+    Enum(EnumDecl),
+    Trait(TraitDecl),
+    Impl(ImplBlock),
+    Extend(ExtendBlock),
+    Const(ConstDecl),
+    Error(ErrorNode),
+}
+
+impl Item {
+    pub fn span(&self) -> Span {
+        match self {
+            Item::Function(decl) => decl.span,
+            Item::Struct(decl) => decl.span,
+            Item::Enum(decl) => decl.span,
+            Item::Trait(decl) => decl.span,
+            Item::Impl(decl) => decl.span,
+            Item::Extend(decl) => decl.span,
+            Item::Const(decl) => decl.span,
+            Item::Error(node) => node.span,
+        }
+    }
+}
+
+/// Input skipped while recovering from a syntax error
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename = "Error")]
+pub struct ErrorNode {
+    pub span: Span,
+}
+
+/// `@name(args)`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Attribute {
+    pub name: Ident,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+/// A top-level function or a method inside a struct or impl
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct FunctionDecl {
+    pub attributes: Vec<Attribute>,
+    pub is_pub: bool,
+    pub is_async: bool,
+    pub name: Ident,
+    pub lifetimes: Option<LifetimeParams>,
+    pub generics: Vec<GenericParam>,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub where_clause: Option<WhereClause>,
+    pub body: Block,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Param {
+    pub is_mut: bool,
+    /// `_` with an empty span when `pattern` destructures the argument
+    pub name: Ident,
+    /// `extract (x, y): (int, int)`
+    pub pattern: Option<Pattern>,
+    pub ty: Option<TypeExpr>,
+    pub default: Option<Expr>,
+    pub span: Span,
+}
+
+impl Param {
+    /// `extract pattern: ty`, a parameter bound through a pattern
+    pub fn destructured(is_mut: bool, pattern: Pattern, ty: Option<TypeExpr>, default: Option<Expr>, span: Span) -> Self {
+        let at = pattern.span;
+        let name = Ident { name: "_".to_string(), span: Span::new(at.start, at.start, at.line, at.column) };
+        Param { is_mut, name, pattern: Some(pattern), ty, default, span }
+    }
+}
+
+/// `[lifetime L, lifetime M where M outlives L]` after a function name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct LifetimeParams {
+    pub names: Vec<Ident>,
+    pub outlives: Vec<Outlives>,
+    pub span: Span,
+}
+
+/// `M outlives L`: every reference with lifetime `M` stays valid as long
+/// as those with `L` do
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Outlives {
+    pub longer: Ident,
+    pub shorter: Ident,
+    pub span: Span,
+}
+
+/// `T: Display + Clone`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct GenericParam {
+    pub name: Ident,
+    pub bounds: Vec<TraitBound>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraitBound {
+    #[serde(flatten)]
+    pub kind: BoundKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum BoundKind {
+    /// `Display`, `Into<string>` or `Iterator<Item = T>`; type arguments
+    /// come before associated type bindings
+    Trait { name: Ident, args: Vec<TypeExpr>, bindings: Vec<AssocBinding> },
+    /// `Fn(int) bool` or `FnOnce(&JsonView) Response`
+    Function { name: Ident, params: Vec<TypeExpr>, ret: Option<Box<TypeExpr>> },
+}
+
+/// `Item = T` in a bound's type arguments
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct AssocBinding {
+    pub name: Ident,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// `where T: Display, F: Fn(T) bool` after a signature or an impl head
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct WhereClause {
+    pub predicates: Vec<WherePredicate>,
+    pub span: Span,
+}
+
+/// `F: FnOnce(&JsonView) Response`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct WherePredicate {
+    pub name: Ident,
+    pub bounds: Vec<TraitBound>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct StructDecl {
+    pub attributes: Vec<Attribute>,
+    pub is_pub: bool,
+    pub is_edge: bool,
+    pub name: Ident,
+    pub generics: Vec<GenericParam>,
+    pub members: Vec<StructMember>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum StructMember {
+    Field(FieldDecl),
+    Method(FunctionDecl),
+    Property(PropertyDecl),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct FieldDecl {
+    pub is_pub: bool,
+    pub name: Ident,
+    pub ty: TypeExpr,
+    pub span: Span,
+}
+
+/// `name: T { get { ... } set { ... } }`, or the auto-property `name: T { get; set; }`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct PropertyDecl {
+    pub is_pub: bool,
+    pub name: Ident,
+    pub ty: TypeExpr,
+    pub getter: Accessor,
+    pub setter: Option<Accessor>,
+    pub span: Span,
+}
+
+impl PropertyDecl {
+    /// `{ get; set; }`: every accessor is backed by a hidden field
+    pub fn is_auto(&self) -> bool {
+        self.getter.body.is_none() && self.setter.as_ref().is_none_or(|s| s.body.is_none())
+    }
+}
+
+/// `get { ... }` or `set { ... }`; `body` is `None` in the shorthand `get;`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Accessor {
+    /// The setter's implicit `value` parameter, spanning the `set` keyword;
+    /// `None` for getters
+    pub value: Option<Ident>,
+    pub body: Option<Block>,
+    pub span: Span,
+}
+
+/// Name of the parameter every setter binds implicitly
+pub const SETTER_VALUE: &str = "value";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct EnumDecl {
+    pub attributes: Vec<Attribute>,
+    pub is_pub: bool,
+    pub name: Ident,
+    pub generics: Vec<GenericParam>,
+    pub variants: Vec<EnumVariant>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct EnumVariant {
+    pub name: Ident,
+    pub payload: Option<EnumPayload>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum EnumPayload {
+    /// `Circle(float)`
+    Tuple { types: Vec<TypeExpr> },
+    /// `Rect { w: int, h: int }`
+    Struct { fields: Vec<FieldDecl> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct TraitDecl {
+    pub attributes: Vec<Attribute>,
+    pub is_pub: bool,
+    pub name: Ident,
+    pub items: Vec<TraitItem>,
+    pub span: Span,
+}
+
+// Traits hold a handful of items, so boxing methods is not worth the noise
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraitItem {
+    Method(MethodSignature),
+    AssociatedType(AssociatedType),
+}
+
+/// `type Item;`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct AssociatedType {
+    pub name: Ident,
+    pub span: Span,
+}
+
+/// A method without a body, as declared in a trait
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct MethodSignature {
+    pub is_async: bool,
+    pub name: Ident,
+    pub lifetimes: Option<LifetimeParams>,
+    pub generics: Vec<GenericParam>,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub where_clause: Option<WhereClause>,
+    pub span: Span,
+}
+
+/// `impl Trait for Type { ... }` or the inherent `impl Type { ... }`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct ImplBlock {
+    pub attributes: Vec<Attribute>,
+    pub generics: Vec<GenericParam>,
+    pub trait_name: Option<TypeExpr>,
+    pub target: TypeExpr,
+    pub where_clause: Option<WhereClause>,
+    pub methods: Vec<FunctionDecl>,
+    pub span: Span,
+}
+
+/// `extend List<T> { ... }`: extension methods on a type declared elsewhere.
+/// Type arguments of `target` that name no type are its parameters, as
+/// `<T>` on an `impl` would make them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct ExtendBlock {
+    pub attributes: Vec<Attribute>,
+    pub generics: Vec<GenericParam>,
+    pub target: TypeExpr,
+    pub where_clause: Option<WhereClause>,
+    pub methods: Vec<FunctionDecl>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct ConstDecl {
+    pub attributes: Vec<Attribute>,
+    pub is_pub: bool,
+    pub name: Ident,
+    pub ty: Option<TypeExpr>,
+    pub value: Expr,
+    pub span: Span,
+}
+
+// ========================================
+// Types
+// ========================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeExpr {
+    #[serde(flatten)]
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum TypeKind {
+    Primitive { name: PrimitiveType },
+    /// `Point` or `Dictionary<string, int>`
+    Named { name: Ident, args: Vec<TypeExpr> },
+    /// `fn(int, int) int`
+    Function { params: Vec<TypeExpr>, ret: Option<Box<TypeExpr>> },
+    /// `(int, string)`; `()` is the empty tuple
+    Tuple { elems: Vec<TypeExpr> },
+    /// `[T]` or `[4]T`
+    Array { elem: Box<TypeExpr>, size: Option<i64> },
+    /// `[]T`
+    Slice { elem: Box<TypeExpr> },
+    /// `T!`
+    Fallible { inner: Box<TypeExpr> },
+    /// `&T`, `&mut T`, `&L T` or `&mut L T`
+    Reference { mutable: bool, lifetime: Option<Ident>, inner: Box<TypeExpr> },
+    /// `Task` or `Task<T>`
+    Task { inner: Option<Box<TypeExpr>> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimitiveType {
+    Int, Uint, Long, Ulong, Short, Ushort,
+    Byte, Ubyte, Float, Double, Bool, Char, String,
+    I8, I16, I32, I64, U8, U16, U32, U64,
+    F32, F64, Isize, Usize, Void,
+}
+
+impl PrimitiveType {
+    pub const ALL: [PrimitiveType; 26] = [
+        PrimitiveType::Int, PrimitiveType::Uint, PrimitiveType::Long, PrimitiveType::Ulong,
+        PrimitiveType::Short, PrimitiveType::Ushort, PrimitiveType::Byte, PrimitiveType::Ubyte,
+        PrimitiveType::Float, PrimitiveType::Double, PrimitiveType::Bool, PrimitiveType::Char,
+        PrimitiveType::String, PrimitiveType::I8, PrimitiveType::I16, PrimitiveType::I32,
+        PrimitiveType::I64, PrimitiveType::U8, PrimitiveType::U16, PrimitiveType::U32,
+        PrimitiveType::U64, PrimitiveType::F32, PrimitiveType::F64, PrimitiveType::Isize,
+        PrimitiveType::Usize, PrimitiveType::Void,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let ty = match name {
+            "int" => PrimitiveType::Int,
+            "uint" => PrimitiveType::Uint,
+            "long" => PrimitiveType::Long,
+            "ulong" => PrimitiveType::Ulong,
+            "short" => PrimitiveType::Short,
+            "ushort" => PrimitiveType::Ushort,
+            "byte" => PrimitiveType::Byte,
+            "ubyte" => PrimitiveType::Ubyte,
+            "float" => PrimitiveType::Float,
+            "double" => PrimitiveType::Double,
+            "bool" => PrimitiveType::Bool,
+            "char" => PrimitiveType::Char,
+            "string" => PrimitiveType::String,
+            "i8" => PrimitiveType::I8,
+            "i16" => PrimitiveType::I16,
+            "i32" => PrimitiveType::I32,
+            "i64" => PrimitiveType::I64,
+            "u8" => PrimitiveType::U8,
+            "u16" => PrimitiveType::U16,
+            "u32" => PrimitiveType::U32,
+            "u64" => PrimitiveType::U64,
+            "f32" => PrimitiveType::F32,
+            "f64" => PrimitiveType::F64,
+            "isize" => PrimitiveType::Isize,
+            "usize" => PrimitiveType::Usize,
+            "void" => PrimitiveType::Void,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PrimitiveType::Int => "int",
+            PrimitiveType::Uint => "uint",
+            PrimitiveType::Long => "long",
+            PrimitiveType::Ulong => "ulong",
+            PrimitiveType::Short => "short",
+            PrimitiveType::Ushort => "ushort",
+            PrimitiveType::Byte => "byte",
+            PrimitiveType::Ubyte => "ubyte",
+            PrimitiveType::Float => "float",
+            PrimitiveType::Double => "double",
+            PrimitiveType::Bool => "bool",
+            PrimitiveType::Char => "char",
+            PrimitiveType::String => "string",
+            PrimitiveType::I8 => "i8",
+            PrimitiveType::I16 => "i16",
+            PrimitiveType::I32 => "i32",
+            PrimitiveType::I64 => "i64",
+            PrimitiveType::U8 => "u8",
+            PrimitiveType::U16 => "u16",
+            PrimitiveType::U32 => "u32",
+            PrimitiveType::U64 => "u64",
+            PrimitiveType::F32 => "f32",
+            PrimitiveType::F64 => "f64",
+            PrimitiveType::Isize => "isize",
+            PrimitiveType::Usize => "usize",
+            PrimitiveType::Void => "void",
+        }
+    }
+}
+
+// ========================================
+// Statements
+// ========================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stmt {
+    #[serde(flatten)]
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum StmtKind {
+    /// `let mut x: int = 1`
+    Let { mutable: bool, name: Ident, ty: Option<TypeExpr>, value: Expr },
+    /// `x := 1`
+    ShortLet { name: Ident, value: Expr },
+    /// `extract (x, y) = point`; the pattern is irrefutable
+    Extract { pattern: Pattern, value: Expr },
+    /// `let Some(x) = value else { return }`; the pattern is refutable, the
+    /// `else` block must not fall through and the bindings stay in scope
+    /// after the statement
+    LetElse { pattern: Pattern, value: Expr, else_block: Block },
+    /// A declaration inside a block, e.g. a local `const`
+    Item { item: Item },
+    Expr { expr: Expr },
+    Return { value: Option<Expr> },
+    Break,
+    Continue,
+    For { pattern: Pattern, iter: Expr, body: Block },
+    /// `while cond { ... }`, or `while let Some(x) = next() { ... }` with a pattern
+    While { pattern: Option<Pattern>, condition: Expr, body: Block },
+    Loop { body: Block },
+    With { allocator: Allocator, body: Block },
+    Try { body: Block, catch: Option<CatchClause> },
+    Unsafe { body: Block },
+    /// `using let a = open(p), let b = open(q) { ... }`; `desugar` lowers it
+    /// to `try` and `defer`
+    Using { resources: Vec<Resource>, body: Block },
+    /// `defer { ... }` runs when the enclosing block exits, however it exits;
+    /// deferred blocks run in reverse order
+    Defer { body: Block },
+    /// Statement skipped while recovering from a syntax error
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Allocator {
+    #[serde(flatten)]
+    pub kind: AllocatorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum AllocatorKind {
+    /// `arena(size)`
+    Arena { size: Expr },
+    /// `pool<T>(capacity)`
+    Pool { ty: TypeExpr, capacity: Expr },
+    Gc,
+    Heap,
+}
+
+/// `let file = File.open(path)` in a `using` statement
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct Resource {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `catch (e) { ... }`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct CatchClause {
+    pub binding: Ident,
+    pub body: Block,
+    pub span: Span,
+}
+
+// ========================================
+// Expressions
+// ========================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Expr {
+    #[serde(flatten)]
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+
+    /// Ends in a block, so it may stand as a statement without a separator
+    pub fn is_block_like(&self) -> bool {
+        matches!(self.kind, ExprKind::If { .. } | ExprKind::Match { .. } | ExprKind::Block { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum ExprKind {
+    Literal { value: Literal },
+    /// `$"total: {n + 1}"`; each hole is parsed in place, so spans inside
+    /// it point into the literal
+    InterpolatedString { parts: Vec<StringPart> },
+    Ident { name: String },
+    SelfRef,
+    Unary { op: UnaryOp, operand: Box<Expr> },
+    Binary { op: BinaryOp, lhs: Box<Expr>, rhs: Box<Expr> },
+    Assign { op: AssignOp, target: Box<Expr>, value: Box<Expr> },
+    Call { callee: Box<Expr>, args: Vec<Expr> },
+    Index { target: Box<Expr>, index: Box<Expr> },
+    /// `a.b`, or `a?.b` when `safe`
+    Field { target: Box<Expr>, name: Ident, safe: bool },
+    /// `a.b(args)`, or `a?.b(args)` when `safe`
+    MethodCall { receiver: Box<Expr>, method: Ident, args: Vec<Expr>, safe: bool },
+    /// `x as float`
+    Cast { expr: Box<Expr>, ty: TypeExpr },
+    /// `a..b`, or `a...b` when `inclusive`
+    Range { start: Box<Expr>, end: Box<Expr>, inclusive: bool },
+    /// Postfix `?`
+    Try { expr: Box<Expr> },
+    /// `(a, b) => a + b`, `x => x * 2` or `fn(x) x * 2`
+    Lambda { params: Vec<Param>, body: Box<Expr> },
+    /// `if` / `elif` chain; `branches` is never empty
+    If { branches: Vec<IfBranch>, else_block: Option<Block> },
+    Match { scrutinee: Box<Expr>, arms: Vec<MatchArm> },
+    #[serde(rename = "BlockExpr")]
+    Block { block: Block },
+    Array { elems: Vec<Expr> },
+    Tuple { elems: Vec<Expr> },
+    /// `Point { x = 1, y = 2 }`
+    StructLit { name: Ident, fields: Vec<FieldInit> },
+    /// `{ name = "Alice", age = 30 }`, an anonymous object
+    ObjectLit { fields: Vec<FieldInit> },
+    /// `{ "Alice" = 100, "Bob" = 85 }`
+    DictLit { entries: Vec<DictEntry> },
+    /// `from u in users where u.age >= 18 select u.name`; `clauses` starts
+    /// with `From` and ends with its only `Select` or `GroupBy`
+    Query { clauses: Vec<QueryClause> },
+    /// A token the lexer already rejected
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Literal {
+    Int(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Verbatim(String),
+    Char(char),
+    Bool(bool),
+    Null,
+}
+
+/// Piece of an interpolated string
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum StringPart {
+    Text(String),
+    /// A `{...}` hole
+    #[serde(rename = "Interpolation")]
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UnaryOp {
+    /// `!` or `not`
+    Not,
+    Neg,
+    Plus,
+    /// `~`
+    BitNot,
+    Await,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BinaryOp {
+    /// `or` or `||`
+    Or,
+    /// `and` or `&&`
+    And,
+    Eq, Ne, Lt, Gt, Le, Ge,
+    BitOr, BitXor, BitAnd, Shl, Shr,
+    Add, Sub, Mul, Div, Rem,
+    /// `x |> f`
+    Pipe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AssignOp {
+    Assign,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl UnaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Not => "!",
+            UnaryOp::Neg => "-",
+            UnaryOp::Plus => "+",
+            UnaryOp::BitNot => "~",
+            UnaryOp::Await => "await",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "or",
+            BinaryOp::And => "and",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Gt => ">",
+            BinaryOp::Le => "<=",
+            BinaryOp::Ge => ">=",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Pipe => "|>",
+        }
+    }
+}
+
+impl AssignOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AssignOp::Assign => "=",
+            AssignOp::Add => "+=",
+            AssignOp::Sub => "-=",
+            AssignOp::Mul => "*=",
+            AssignOp::Div => "/=",
+            AssignOp::Rem => "%=",
+            AssignOp::BitAnd => "&=",
+            AssignOp::BitOr => "|=",
+            AssignOp::BitXor => "^=",
+            AssignOp::Shl => "<<=",
+            AssignOp::Shr => ">>=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct IfBranch {
+    /// `if let Some(x) = condition`: the branch is taken when the condition
+    /// matches, and the bindings are only in scope in its body
+    pub pattern: Option<Pattern>,
+    pub condition: Expr,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// `pattern where guard => body`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+    pub span: Span,
+}
+
+/// `name = value` inside a struct or object literal
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct FieldInit {
+    pub name: Ident,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// `"key" = value` inside a dictionary literal; `key` is a string literal
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct DictEntry {
+    pub key: Expr,
+    pub value: Expr,
+    pub span: Span,
+}
+
+/// One clause of a query expression
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryClause {
+    #[serde(flatten)]
+    pub kind: QueryClauseKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum QueryClauseKind {
+    /// `from x in xs`
+    From { name: Ident, source: Expr },
+    /// `let y = x * 2`
+    Let { name: Ident, value: Expr },
+    /// `where x > 0`
+    Where { condition: Expr },
+    /// `orderby x.name, x.age descending`; `keys` is never empty
+    OrderBy { keys: Vec<OrderKey> },
+    /// `join o in orders on c.id equals o.customer_id`
+    Join { name: Ident, source: Expr, outer_key: Box<Expr>, inner_key: Box<Expr> },
+    /// `select x.name`
+    Select { value: Expr },
+    /// `group x by x.dept`
+    GroupBy { value: Expr, key: Expr },
+}
+
+/// `key` or `key descending` in an `orderby` clause
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct OrderKey {
+    pub key: Expr,
+    pub descending: bool,
+    pub span: Span,
+}
+
+// ========================================
+// Patterns
+// ========================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pattern {
+    #[serde(flatten)]
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum PatternKind {
+    /// `_`
+    Wildcard,
+    Literal { value: Literal },
+    /// A single identifier, e.g. `x` or `None`
+    Binding { name: Ident },
+    /// `(a, b)`
+    Tuple { elems: Vec<Pattern> },
+    /// `Shape.Circle` - a path with no payload
+    Path { path: Vec<Ident> },
+    /// `Ok(x)` or `Shape.Circle(r)`
+    TupleStruct { path: Vec<Ident>, elems: Vec<Pattern> },
+    /// `Point { x, y = 0 }`
+    Struct { path: Vec<Ident>, fields: Vec<FieldPattern> },
+    /// `[first, second, ...rest]`; at most one element is a `Rest`
+    List { elems: Vec<Pattern> },
+    /// `...rest` or `...`, only directly inside a list pattern
+    Rest { name: Option<Ident> },
+    /// `{ id, user = { name } }`
+    Object { fields: Vec<FieldPattern> },
+}
+
+impl Pattern {
+    /// The first subpattern that can fail to match, such as a literal or an
+    /// enum variant. A plain name `Point { x, y }` is taken to be a struct;
+    /// list patterns are checked against the length when they run.
+    pub fn refutable_part(&self) -> Option<&Pattern> {
+        match &self.kind {
+            PatternKind::Wildcard | PatternKind::Binding { .. } | PatternKind::Rest { .. } => None,
+            PatternKind::Literal { .. } | PatternKind::Path { .. } | PatternKind::TupleStruct { .. } => Some(self),
+            PatternKind::Struct { path, .. } if path.len() > 1 => Some(self),
+            PatternKind::Struct { fields, .. } | PatternKind::Object { fields } => {
+                fields.iter().filter_map(|f| f.pattern.as_ref()).find_map(Pattern::refutable_part)
+            }
+            PatternKind::Tuple { elems } | PatternKind::List { elems } => elems.iter().find_map(Pattern::refutable_part),
+        }
+    }
+}
+
+/// `name` or `name = pattern` inside a struct or object pattern
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub struct FieldPattern {
+    pub name: Ident,
+    pub pattern: Option<Pattern>,
+    pub span: Span,
+}
@@ -0,0 +1,293 @@
+//! Surface forms to the core ones they stand for
+//!
+//! Query expressions become method calls, and `using` statements become
+//! `try` blocks that `defer` each resource's `drop`.
+//!
+//! ## Queries
+//!
+//! `from x in xs where c orderby k select e` is the method chain
+//! `xs.where(x => c).orderby(x => k).map(x => e)`, which the README gives as
+//! the equivalent form. A second `from`, a `let` or a `join` brings another
+//! range variable into scope; from then on each element is an object of all
+//! of them, `{ x = x, y = y }`, and every lambda unpacks it with `let`s.
+//!
+//! Clause to method:
+//!
+//! - `where c`: `where(x => c)`
+//! - `orderby a, b descending`: `orderby(x => a).thenby_descending(x => b)`
+//! - `from y in ys`: `flat_map(x => ys.map(y => { x = x, y = y }))`
+//! - `let y = v`: `map(x => { x = x, y = v })`
+//! - `join y in ys on a equals b`: `join(ys, x => a, y => b, (x, y) => { x = x, y = y })`
+//! - `select e`: `map(x => e)`
+//! - `group v by k`: `group_by(x => k, x => v)`, or `group_by(x => k)` when `v` is `x`
+//!
+//! A `from` or `join` directly followed by `select` feeds the selected value
+//! straight into its inner lambda, as C# does.
+//!
+//! ## `using`
+//!
+//! Each resource opens a `try` block of its own, nested inside the previous
+//! one, and defers its `drop` right after it is bound:
+//!
+//! ```text
+//! using let a = open(p), let b = open(q) { work(a, b) }
+//!
+//! try {
+//!     let a = open(p)
+//!     defer { a.drop() }
+//!     try {
+//!         let b = open(q)
+//!         defer { b.drop() }
+//!         work(a, b)
+//!     }
+//! }
+//! ```
+//!
+//! Leaving the body in any way, by falling off the end, `return`, `break`,
+//! `fail` or `?`, unwinds the inner `try` first, so `b` is dropped before
+//! `a`. A resource whose initializer fails was never bound, so it and the
+//! ones after it are not dropped.
+
+use crate::lexer::Span;
+use crate::parser::ast::*;
+use crate::parser::fold::{self, Fold};
+use crate::parser::visit::{self, Visitor};
+
+/// `program` with every query expression replaced by method calls and
+/// every `using` statement by `try` and `defer`
+pub fn desugar(program: Program) -> Program {
+    Desugar.fold_program(program)
+}
+
+/// `expr` with every query expression replaced by method calls
+pub fn desugar_expr(expr: Expr) -> Expr {
+    Desugar.fold_expr(expr)
+}
+
+struct Desugar;
+
+impl Fold for Desugar {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        // Inner queries first, so a query in a clause is already a chain
+        let Expr { kind, span } = fold::walk_expr(self, expr);
+        match kind {
+            ExprKind::Query { clauses } => query(clauses, span),
+            kind => Expr { kind, span },
+        }
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        let Stmt { kind, span } = fold::walk_stmt(self, stmt);
+        match kind {
+            StmtKind::Using { resources, body } if !resources.is_empty() => using(resources, body, span),
+            kind => Stmt { kind, span },
+        }
+    }
+}
+
+/// The innermost resource wraps the body; each outer one wraps the `try` of
+/// the next
+fn using(resources: Vec<Resource>, body: Block, span: Span) -> Stmt {
+    let mut stmts = body.stmts;
+    let mut inner_span = body.span;
+    for Resource { name, value, span: resource_span } in resources.into_iter().rev() {
+        let drop = call(ident(&name.name, name.span), "drop", vec![], name.span);
+        let defer = Block { stmts: vec![Stmt { kind: StmtKind::Expr { expr: drop }, span: name.span }], span: name.span };
+        let mut scope = vec![
+            Stmt { kind: StmtKind::Let { mutable: false, name, ty: None, value }, span: resource_span },
+            Stmt { kind: StmtKind::Defer { body: defer }, span: resource_span },
+        ];
+        scope.extend(stmts);
+        inner_span = Span::new(resource_span.start, inner_span.end, resource_span.line, resource_span.column);
+        let block = Block { stmts: scope, span: inner_span };
+        stmts = vec![Stmt { kind: StmtKind::Try { body: block, catch: None }, span: inner_span }];
+    }
+    let mut outer = stmts.pop().expect("at least one resource");
+    outer.span = span;
+    outer
+}
+
+fn query(clauses: Vec<QueryClause>, span: Span) -> Expr {
+    let row = fresh_name(&clauses);
+    let mut clauses = clauses.into_iter().peekable();
+    let (mut chain, mut scope) = match clauses.next() {
+        Some(QueryClause { kind: QueryClauseKind::From { name, source }, .. }) => (source, Scope { vars: vec![name], row }),
+        // The parser always starts a query with `from`
+        first => return Expr::new(ExprKind::Query { clauses: first.into_iter().chain(clauses).collect() }, span),
+    };
+
+    while let Some(QueryClause { kind, span }) = clauses.next() {
+        chain = match kind {
+            QueryClauseKind::From { name, source } => {
+                let value = match clauses.next_if(is_select) {
+                    Some(QueryClause { kind: QueryClauseKind::Select { value }, .. }) => value,
+                    _ => scope.row_value(Some((name.clone(), ident(&name.name, span))), span),
+                };
+                let inner = call(source, "map", vec![lambda(vec![name.clone()], value, span)], span);
+                let outer = scope.lambda(inner, span);
+                scope.vars.push(name);
+                call(chain, "flat_map", vec![outer], span)
+            }
+            QueryClauseKind::Let { name, value } => {
+                let row = scope.row_value(Some((name.clone(), value)), span);
+                let selector = scope.lambda(row, span);
+                scope.vars.push(name);
+                call(chain, "map", vec![selector], span)
+            }
+            QueryClauseKind::Where { condition } => call(chain, "where", vec![scope.lambda(condition, span)], span),
+            QueryClauseKind::OrderBy { keys } => {
+                keys.into_iter().enumerate().fold(chain, |chain, (i, OrderKey { key, descending, span })| {
+                    let method = match (i, descending) {
+                        (0, false) => "orderby",
+                        (0, true) => "orderby_descending",
+                        (_, false) => "thenby",
+                        (_, true) => "thenby_descending",
+                    };
+                    call(chain, method, vec![scope.lambda(key, span)], span)
+                })
+            }
+            QueryClauseKind::Join { name, source, outer_key, inner_key } => {
+                let value = match clauses.next_if(is_select) {
+                    Some(QueryClause { kind: QueryClauseKind::Select { value }, .. }) => value,
+                    _ => scope.row_value(Some((name.clone(), ident(&name.name, span))), span),
+                };
+                let args = vec![
+                    source,
+                    scope.lambda(*outer_key, span),
+                    lambda(vec![name.clone()], *inner_key, span),
+                    scope.lambda_with(name.clone(), value, span),
+                ];
+                scope.vars.push(name);
+                call(chain, "join", args, span)
+            }
+            QueryClauseKind::Select { value } => call(chain, "map", vec![scope.lambda(value, span)], span),
+            QueryClauseKind::GroupBy { value, key } => {
+                let whole_element = matches!(
+                    (&value.kind, scope.vars.as_slice()),
+                    (ExprKind::Ident { name }, [var]) if *name == var.name
+                );
+                let mut args = vec![scope.lambda(key, span)];
+                if !whole_element {
+                    args.push(scope.lambda(value, span));
+                }
+                call(chain, "group_by", args, span)
+            }
+        };
+    }
+    Expr { span, ..chain }
+}
+
+fn is_select(clause: &QueryClause) -> bool {
+    matches!(clause.kind, QueryClauseKind::Select { .. })
+}
+
+/// The range variables in scope, and how a lambda reaches them
+struct Scope {
+    vars: Vec<Ident>,
+    /// Parameter name for an element that carries several variables
+    row: String,
+}
+
+impl Scope {
+    /// `x => body`, or `row => { let x = row.x ... body }` for several variables
+    fn lambda(&self, body: Expr, span: Span) -> Expr {
+        match self.vars.as_slice() {
+            [var] => lambda(vec![var.clone()], body, span),
+            _ => lambda(vec![Ident { name: self.row.clone(), span }], self.unpack(body, span), span),
+        }
+    }
+
+    /// Like `lambda`, with `extra` as a second parameter
+    fn lambda_with(&self, extra: Ident, body: Expr, span: Span) -> Expr {
+        match self.vars.as_slice() {
+            [var] => lambda(vec![var.clone(), extra], body, span),
+            _ => lambda(vec![Ident { name: self.row.clone(), span }, extra], self.unpack(body, span), span),
+        }
+    }
+
+    /// `{ let x = row.x ... body }`, binding only the variables `body` names,
+    /// or just `row.x` when the body is `x`
+    fn unpack(&self, body: Expr, span: Span) -> Expr {
+        let field = |var: &Ident| {
+            Expr::new(ExprKind::Field { target: Box::new(ident(&self.row, span)), name: var.clone(), safe: false }, span)
+        };
+        // `row => row.y` rather than a block that only returns `y`
+        if let ExprKind::Ident { name } = &body.kind {
+            if let Some(var) = self.vars.iter().find(|var| var.name == *name) {
+                return field(var);
+            }
+        }
+
+        let mut names = Names::default();
+        names.visit_expr(&body);
+        let mut stmts: Vec<Stmt> = self
+            .vars
+            .iter()
+            .filter(|var| names.0.contains(&var.name))
+            .map(|var| Stmt { kind: StmtKind::Let { mutable: false, name: var.clone(), ty: None, value: field(var) }, span })
+            .collect();
+        if stmts.is_empty() {
+            return body;
+        }
+        stmts.push(Stmt { kind: StmtKind::Expr { expr: body }, span });
+        Expr::new(ExprKind::Block { block: Block { stmts, span } }, span)
+    }
+
+    /// `{ x = x, y = y }` for the variables in scope, then `extra`
+    fn row_value(&self, extra: Option<(Ident, Expr)>, span: Span) -> Expr {
+        let fields = self
+            .vars
+            .iter()
+            .map(|var| (var.clone(), ident(&var.name, span)))
+            .chain(extra)
+            .map(|(name, value)| FieldInit { name, value, span })
+            .collect();
+        Expr::new(ExprKind::ObjectLit { fields }, span)
+    }
+}
+
+fn lambda(params: Vec<Ident>, body: Expr, span: Span) -> Expr {
+    let params = params
+        .into_iter()
+        .map(|name| Param { is_mut: false, span: name.span, name, pattern: None, ty: None, default: None })
+        .collect();
+    Expr::new(ExprKind::Lambda { params, body: Box::new(body) }, span)
+}
+
+fn call(receiver: Expr, method: &str, args: Vec<Expr>, span: Span) -> Expr {
+    let method = Ident { name: method.to_string(), span };
+    Expr::new(ExprKind::MethodCall { receiver: Box::new(receiver), method, args, safe: false }, span)
+}
+
+fn ident(name: &str, span: Span) -> Expr {
+    Expr::new(ExprKind::Ident { name: name.to_string() }, span)
+}
+
+/// Every name a subtree mentions, declared or used
+#[derive(Default)]
+struct Names(Vec<String>);
+
+impl<'ast> Visitor<'ast> for Names {
+    fn visit_ident(&mut self, ident: &'ast Ident) {
+        self.0.push(ident.name.clone());
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if let ExprKind::Ident { name } = &expr.kind {
+            self.0.push(name.clone());
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// `row`, or `row1`, `row2`, ... if the query already uses that name
+fn fresh_name(clauses: &[QueryClause]) -> String {
+    let mut names = Names::default();
+    for clause in clauses {
+        names.visit_query_clause(clause);
+    }
+    std::iter::once("row".to_string())
+        .chain((1..).map(|n| format!("row{}", n)))
+        .find(|candidate| !names.0.contains(candidate))
+        .expect("an unused name")
+}
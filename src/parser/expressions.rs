@@ -0,0 +1,566 @@
+//! Expressions
+//!
+//! Infix operators go through one precedence-climbing loop driven by the
+//! table in `precedence`; prefix, postfix and primary forms follow.
+
+use crate::error_management::error_types::ParseError;
+use crate::error_management::Diagnostic;
+use crate::lexer::string_parser::StringParser;
+use crate::lexer::{keywords, InterpolationPart, LogosLexer, Span, Token, TokenType};
+use crate::parser::ast::*;
+use crate::parser::precedence::{self, Assoc, Infix, Precedence};
+use crate::parser::recursive_descent::{PResult, Parser, Restrictions};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn expr(&mut self) -> PResult<Expr> {
+        self.expr_bp(Precedence::Assignment)
+    }
+
+    /// An expression where `Ident {` starts a block (`if`, `while`, `match` heads)
+    pub(crate) fn expr_no_struct(&mut self) -> PResult<Expr> {
+        let restrictions = Restrictions { no_struct_literal: true, ..self.restrictions };
+        self.restricted(restrictions, |p| p.expr())
+    }
+
+    /// An expression in a fresh context: inside `()`, `[]` or argument lists
+    fn expr_unrestricted(&mut self) -> PResult<Expr> {
+        self.restricted(Restrictions::default(), |p| p.expr())
+    }
+
+    /// An expression whose infix operators all bind at least as tightly as `min`
+    pub(crate) fn expr_bp(&mut self, min: Precedence) -> PResult<Expr> {
+        let mut lhs = self.prefix_expr()?;
+        // The non-associative level `lhs` was just built at, if any
+        let mut closed = None;
+
+        while let Some(op) = precedence::infix(self.peek_kind()) {
+            let prec = op.precedence;
+            if prec < min {
+                break;
+            }
+            if closed == Some(prec) {
+                match op.infix {
+                    Infix::Binary(binary) if prec == Precedence::Comparison => {
+                        lhs = self.chained_comparison(lhs, binary, op.lexeme)?;
+                        continue;
+                    }
+                    // `a = b = c` and `a..b..c` end the expression here
+                    _ => break,
+                }
+            }
+            self.advance();
+
+            lhs = match op.infix {
+                Infix::Binary(binary) => {
+                    let rhs = self.expr_bp(prec.rhs())?;
+                    Self::binary(binary, lhs, rhs)
+                }
+                Infix::Assign(assign) => {
+                    let value = self.expr_bp(prec.rhs())?;
+                    let span = lhs.span.merge(&value.span);
+                    Expr::new(ExprKind::Assign { op: assign, target: Box::new(lhs), value: Box::new(value) }, span)
+                }
+                Infix::Range { inclusive } => {
+                    let end = self.expr_bp(prec.rhs())?;
+                    let span = lhs.span.merge(&end.span);
+                    Expr::new(ExprKind::Range { start: Box::new(lhs), end: Box::new(end), inclusive }, span)
+                }
+                Infix::Cast => {
+                    let ty = self.cast_type()?;
+                    let span = lhs.span.merge(&ty.span);
+                    Expr::new(ExprKind::Cast { expr: Box::new(lhs), ty }, span)
+                }
+            };
+            closed = (prec.assoc() == Assoc::None).then_some(prec);
+        }
+        Ok(lhs)
+    }
+
+    /// `a < b < c`: report it with an `a < b and b < c` fix, then keep
+    /// parsing as if it were left-associative
+    fn chained_comparison(&mut self, lhs: Expr, op: BinaryOp, lexeme: &str) -> PResult<Expr> {
+        self.advance();
+        let rhs = self.expr_bp(Precedence::Comparison.rhs())?;
+
+        if let ExprKind::Binary { rhs: middle, .. } = &lhs.kind {
+            let replacement = format!(
+                "{} and {} {} {}",
+                self.text(lhs.span),
+                self.text(middle.span),
+                lexeme,
+                self.text(rhs.span),
+            );
+            let span = lhs.span.merge(&rhs.span);
+            self.record(ParseError::ChainedComparison { span, replacement });
+        }
+        Ok(Self::binary(op, lhs, rhs))
+    }
+
+    fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+        let span = lhs.span.merge(&rhs.span);
+        Expr::new(ExprKind::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) }, span)
+    }
+
+    fn prefix_expr(&mut self) -> PResult<Expr> {
+        let Some(op) = precedence::prefix(self.peek_kind()) else {
+            return self.postfix_expr();
+        };
+        let start = self.advance().span;
+        let operand = self.prefix_expr()?;
+        Ok(Expr::new(ExprKind::Unary { op, operand: Box::new(operand) }, self.span_from(start)))
+    }
+
+    fn postfix_expr(&mut self) -> PResult<Expr> {
+        let mut expr = self.primary_expr()?;
+        let start = expr.span;
+
+        loop {
+            // A call or index on the next line is a new statement, not a postfix
+            let kind = match self.peek_kind() {
+                TokenType::LeftParen if !self.newline_before() => {
+                    let args = self.call_args()?;
+                    ExprKind::Call { callee: Box::new(expr), args }
+                }
+                TokenType::LeftBracket if !self.newline_before() => {
+                    self.advance();
+                    let index = self.expr_unrestricted()?;
+                    self.expect(&TokenType::RightBracket, "`]`")?;
+                    ExprKind::Index { target: Box::new(expr), index: Box::new(index) }
+                }
+                TokenType::Dot | TokenType::QuestionDot => {
+                    let safe = self.advance().kind == TokenType::QuestionDot;
+                    let name = self.expect_member_name()?;
+                    if self.check(&TokenType::LeftParen) && !self.newline_before() {
+                        let args = self.call_args()?;
+                        ExprKind::MethodCall { receiver: Box::new(expr), method: name, args, safe }
+                    } else {
+                        ExprKind::Field { target: Box::new(expr), name, safe }
+                    }
+                }
+                TokenType::Question => {
+                    self.advance();
+                    ExprKind::Try { expr: Box::new(expr) }
+                }
+                _ => return Ok(expr),
+            };
+            expr = Expr::new(kind, self.span_from(start));
+        }
+    }
+
+    /// `(a, b, c)` with an optional trailing comma
+    pub(crate) fn call_args(&mut self) -> PResult<Vec<Expr>> {
+        self.expect(&TokenType::LeftParen, "`(`")?;
+        let mut args = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+            args.push(self.expr_unrestricted()?);
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightParen, &["`,`", "`)`"])?;
+        Ok(args)
+    }
+
+    fn primary_expr(&mut self) -> PResult<Expr> {
+        let token = self.peek().clone();
+        let start = token.span;
+
+        let literal = match &token.kind {
+            TokenType::IntLit(v) => Some(Literal::Int(*v)),
+            TokenType::FloatLit(v) => Some(Literal::Float(*v)),
+            TokenType::DoubleLit(v) => Some(Literal::Double(*v)),
+            TokenType::StringLit(s) => Some(Literal::String(s.clone())),
+            TokenType::VerbatimString(s) => Some(Literal::Verbatim(s.clone())),
+            TokenType::CharLit(c) => Some(Literal::Char(*c)),
+            TokenType::True => Some(Literal::Bool(true)),
+            TokenType::False => Some(Literal::Bool(false)),
+            TokenType::Null => Some(Literal::Null),
+            _ => None,
+        };
+        if let Some(value) = literal {
+            self.advance();
+            return Ok(Expr::new(ExprKind::Literal { value }, start));
+        }
+
+        match &token.kind {
+            TokenType::InterpolatedString(parts) => Ok(self.interpolated_string(parts)),
+            // `where select`: the query word ends the clause, it is not a name
+            TokenType::Ident(_) if self.at_query_keyword() => Err(self.error_expected("an expression")),
+            TokenType::Ident(name) => {
+                if self.check_nth(1, &TokenType::FatArrow) && !self.restrictions.no_arrow_lambda {
+                    return self.ident_lambda();
+                }
+                if !self.restrictions.no_struct_literal && self.at_struct_literal() {
+                    return self.struct_literal();
+                }
+                self.advance();
+                Ok(Expr::new(ExprKind::Ident { name: name.clone() }, start))
+            }
+            // Already reported by the lexer; stand in for the literal it meant to be
+            TokenType::Error(_) => {
+                self.advance();
+                Ok(Expr::new(ExprKind::Error, start))
+            }
+            TokenType::SelfKw => {
+                self.advance();
+                Ok(Expr::new(ExprKind::SelfRef, start))
+            }
+            TokenType::LeftParen if !self.restrictions.no_arrow_lambda && self.at_paren_lambda() => self.paren_lambda(),
+            TokenType::LeftParen => self.paren_or_tuple(),
+            TokenType::Fn => self.fn_lambda(),
+            TokenType::From if self.at_query() => self.query_expr(),
+            TokenType::If => self.if_expr(),
+            TokenType::Match => self.match_expr(),
+            TokenType::LeftBrace if self.at_object_literal() => self.object_literal(),
+            TokenType::LeftBrace => {
+                let block = self.block()?;
+                Ok(Expr::new(ExprKind::Block { block }, self.span_from(start)))
+            }
+            TokenType::LeftBracket => {
+                self.advance();
+                let mut elems = Vec::new();
+                while !self.check(&TokenType::RightBracket) {
+                    elems.push(self.expr_unrestricted()?);
+                    if !self.eat(&TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.expect_one_of(&TokenType::RightBracket, &["`,`", "`]`"])?;
+                Ok(Expr::new(ExprKind::Array { elems }, self.span_from(start)))
+            }
+            _ => Err(self.error_expected("an expression")),
+        }
+    }
+
+    /// `$"..."`: the holes are parsed where they sit, and their errors are
+    /// recorded like any other
+    fn interpolated_string(&mut self, parts: &[InterpolationPart]) -> Expr {
+        let token = self.peek().clone();
+        let (parts, errors, warnings) = Self::interpolated_parts(parts, &token.lexeme, token.span, self.source);
+        for error in errors {
+            self.record(error);
+        }
+        self.warnings.extend(warnings);
+        self.advance();
+        Expr::new(ExprKind::InterpolatedString { parts }, token.span)
+    }
+
+    /// Text and holes of the interpolated string token `lexeme` at `span`,
+    /// with the errors and the lexer's warnings in the holes. Each hole is
+    /// lexed and parsed out of `source` in place, so the spans of its
+    /// expression and of any diagnostics in it point inside the literal.
+    pub(crate) fn interpolated_parts(
+        parts: &[InterpolationPart],
+        lexeme: &str,
+        span: Span,
+        source: &str,
+    ) -> (Vec<StringPart>, Vec<ParseError>, Vec<Diagnostic>) {
+        let mut holes = StringParser::hole_ranges(lexeme).into_iter();
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let parts = parts
+            .iter()
+            .map(|part| match part {
+                InterpolationPart::Text(text) => StringPart::Text(text.clone()),
+                InterpolationPart::Expr(_) => {
+                    let hole = holes.next().expect("the lexer and `hole_ranges` agree on holes");
+                    StringPart::Expr(Self::hole(lexeme, span, hole, source, &mut errors, &mut warnings))
+                }
+            })
+            .collect();
+        (parts, errors, warnings)
+    }
+
+    fn hole(
+        lexeme: &str,
+        span: Span,
+        hole: std::ops::Range<usize>,
+        source: &str,
+        errors: &mut Vec<ParseError>,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Expr {
+        let before = &lexeme[..hole.start];
+        let (line, column) = match before.rfind('\n') {
+            Some(newline) => (span.line + before.matches('\n').count(), before[newline + 1..].chars().count() + 1),
+            None => (span.line, span.column + before.chars().count()),
+        };
+        let range = (span.start + hole.start)..(span.start + hole.end);
+        let hole_span = Span::new(range.start, range.end, line, column);
+        if source[range.clone()].trim().is_empty() {
+            errors.push(ParseError::InvalidSyntax {
+                message: "empty interpolation hole".to_string(),
+                span: hole_span,
+                suggestion: Some("Put an expression between the braces, or write `\\{` for a literal brace".to_string()),
+            });
+            return Expr::new(ExprKind::Error, hole_span);
+        }
+
+        let (mut tokens, mut diagnostics) = LogosLexer::within(source, range.clone(), line, column).tokenize_all();
+        errors.extend(diagnostics.take_errors().into_iter().map(ParseError::Lexical));
+        warnings.extend(diagnostics.diagnostics().iter().cloned());
+        // The hole ends at its closing brace, not at the end of the file
+        if let Some(eof) = tokens.pop() {
+            let close = Span::new(range.end, range.end + 1, eof.span.line, eof.span.column);
+            tokens.push(Token::new(TokenType::RightBrace, close, "}".to_string()));
+        }
+
+        let mut parser = Parser::new(tokens, source);
+        let expr = match parser.expr() {
+            Ok(expr) => {
+                if !parser.check(&TokenType::RightBrace) {
+                    let error = parser.error_expected("`}`");
+                    parser.record(*error);
+                }
+                expr
+            }
+            Err(error) => {
+                parser.record(*error);
+                Expr::new(ExprKind::Error, hole_span)
+            }
+        };
+        errors.extend(parser.take_errors());
+        warnings.extend(parser.take_warnings());
+        expr
+    }
+
+    /// `(expr)` or a tuple `(a, b)`; `()` is the empty tuple
+    fn paren_or_tuple(&mut self) -> PResult<Expr> {
+        let start = self.expect(&TokenType::LeftParen, "`(`")?.span;
+
+        if self.eat(&TokenType::RightParen) {
+            return Ok(Expr::new(ExprKind::Tuple { elems: Vec::new() }, self.span_from(start)));
+        }
+
+        let first = self.expr_unrestricted()?;
+        if !self.check(&TokenType::Comma) {
+            self.expect(&TokenType::RightParen, "`)`")?;
+            // Grouping parentheses leave no node behind; the span covers them
+            return Ok(Expr { span: self.span_from(start), ..first });
+        }
+
+        let mut elems = vec![first];
+        while self.eat(&TokenType::Comma) {
+            if self.check(&TokenType::RightParen) {
+                break;
+            }
+            elems.push(self.expr_unrestricted()?);
+        }
+        self.expect_one_of(&TokenType::RightParen, &["`,`", "`)`"])?;
+        Ok(Expr::new(ExprKind::Tuple { elems }, self.span_from(start)))
+    }
+
+    /// `(` ... matching `)` followed by `=>`
+    fn at_paren_lambda(&self) -> bool {
+        let mut depth = 0usize;
+        let mut n = 0;
+        loop {
+            match &self.peek_nth(n).kind {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return self.check_nth(n + 1, &TokenType::FatArrow);
+                    }
+                }
+                TokenType::Eof => return false,
+                _ => {}
+            }
+            n += 1;
+        }
+    }
+
+    fn paren_lambda(&mut self) -> PResult<Expr> {
+        let start = self.peek().span;
+        let params = self.params()?;
+        self.expect(&TokenType::FatArrow, "`=>`")?;
+        let body = self.expr()?;
+        Ok(Expr::new(ExprKind::Lambda { params, body: Box::new(body) }, self.span_from(start)))
+    }
+
+    fn ident_lambda(&mut self) -> PResult<Expr> {
+        let name = self.expect_ident("a parameter name")?;
+        let start = name.span;
+        self.expect(&TokenType::FatArrow, "`=>`")?;
+        let body = self.expr()?;
+        let param = Param { is_mut: false, span: name.span, name, pattern: None, ty: None, default: None };
+        Ok(Expr::new(ExprKind::Lambda { params: vec![param], body: Box::new(body) }, self.span_from(start)))
+    }
+
+    /// `fn(x) x * 2` or `fn(x) { ... }`
+    fn fn_lambda(&mut self) -> PResult<Expr> {
+        let start = self.expect(&TokenType::Fn, "`fn`")?.span;
+        let params = self.params()?;
+        let body = self.expr()?;
+        Ok(Expr::new(ExprKind::Lambda { params, body: Box::new(body) }, self.span_from(start)))
+    }
+
+    /// `Name {` followed by `}` or `field =`
+    fn at_struct_literal(&self) -> bool {
+        if !self.check_nth(1, &TokenType::LeftBrace) {
+            return false;
+        }
+        self.check_nth(2, &TokenType::RightBrace)
+            || (matches!(self.peek_nth(2).kind, TokenType::Ident(_)) && self.check_nth(3, &TokenType::Equal))
+    }
+
+    fn struct_literal(&mut self) -> PResult<Expr> {
+        let name = self.expect_ident("a struct name")?;
+        let start = name.span;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut fields = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let field = self.expect_ident("a field name")?;
+            self.expect(&TokenType::Equal, "`=`")?;
+            let value = self.expr_unrestricted()?;
+            fields.push(FieldInit { span: field.span.merge(&value.span), name: field, value });
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightBrace, &["`,`", "`}`"])?;
+
+        Ok(Expr::new(ExprKind::StructLit { name, fields }, self.span_from(start)))
+    }
+
+    /// Whether a `{` between `prev` and the tokens `key`, `eq` opens an object
+    /// or dictionary literal rather than a block: `{ name =` and `{ "key" =`.
+    /// After `)` and `=>` a brace always opens a block, so lambda and arm
+    /// bodies stay blocks; after a name it belongs to a struct literal.
+    pub(crate) fn opens_object_literal(prev: Option<&TokenType>, key: &TokenType, eq: &TokenType) -> bool {
+        let opens_body = matches!(prev, Some(TokenType::RightParen | TokenType::FatArrow | TokenType::Ident(_)));
+        !opens_body && matches!(key, TokenType::Ident(_) | TokenType::StringLit(_)) && matches!(eq, TokenType::Equal)
+    }
+
+    fn at_object_literal(&self) -> bool {
+        // After `select` the brace opens a value, as after any keyword
+        let prev = self.prev_token().map(|t| &t.kind).filter(|kind| {
+            !(self.restrictions.query && matches!(kind, TokenType::Ident(name) if keywords::query_keyword(name).is_some()))
+        });
+        Self::opens_object_literal(prev, &self.peek_nth(1).kind, &self.peek_nth(2).kind)
+    }
+
+    /// `{ name = value, ... }` or `{ "key" = value, ... }`; the first key
+    /// decides which, and the rest must agree
+    fn object_literal(&mut self) -> PResult<Expr> {
+        let start = self.expect(&TokenType::LeftBrace, "`{`")?.span;
+        let is_dict = matches!(self.peek_kind(), TokenType::StringLit(_));
+
+        let mut fields = Vec::new();
+        let mut entries = Vec::new();
+        let mut keys: Vec<String> = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let span = self.peek().span;
+            // Keys as written: `name` or `"Alice"`
+            let (key, kind) = match self.peek_kind().clone() {
+                TokenType::Ident(name) => (format!("`{}`", name), ExprKind::Ident { name }),
+                TokenType::StringLit(text) => (format!("{:?}", text), ExprKind::Literal { value: Literal::String(text) }),
+                _ => {
+                    let expected = if is_dict { "a string key" } else { "a field name" };
+                    return Err(self.error_expected_one_of(&[expected, "`}`"]));
+                }
+            };
+            self.advance();
+            self.expect(&TokenType::Equal, "`=`")?;
+            let value = self.expr_unrestricted()?;
+            let entry_span = span.merge(&value.span);
+
+            if matches!(kind, ExprKind::Literal { .. }) != is_dict {
+                self.record(ParseError::InvalidSyntax {
+                    message: "a literal cannot mix field names and string keys".to_string(),
+                    span,
+                    suggestion: Some("Use names for an object, or quoted strings for a dictionary".to_string()),
+                });
+            } else if keys.contains(&key) {
+                self.record(ParseError::InvalidSyntax {
+                    message: format!("duplicate key {}", key),
+                    span,
+                    suggestion: Some("Remove one of the entries".to_string()),
+                });
+            } else {
+                keys.push(key);
+                match kind {
+                    ExprKind::Ident { name } => fields.push(FieldInit { name: Ident { name, span }, value, span: entry_span }),
+                    kind => entries.push(DictEntry { key: Expr::new(kind, span), value, span: entry_span }),
+                }
+            }
+
+            if !self.eat(&TokenType::Comma) && !self.check(&TokenType::RightBrace) {
+                // `{ x = 1; y = 2 }` was probably meant as a block
+                let error = match fields.as_slice() {
+                    [field] if !is_dict => Box::new(ParseError::InvalidSyntax {
+                        message: format!("expected one of `,` or `}}`, found {}", Self::describe(self.peek())),
+                        span: self.peek().span,
+                        suggestion: Some(format!(
+                            "`{{ {0} = ...` starts an object literal; to begin a block with this assignment, write `({0} = ...)`",
+                            field.name.name,
+                        )),
+                    }),
+                    _ => self.error_expected_one_of(&["`,`", "`}`"]),
+                };
+                // Skip the rest of the literal, so its `}` is not taken for
+                // the end of the enclosing block
+                self.record(*error);
+                self.skip_past_close_brace();
+                return Ok(Expr::new(ExprKind::Error, self.span_from(start)));
+            }
+        }
+        self.expect_one_of(&TokenType::RightBrace, &["`,`", "`}`"])?;
+
+        let kind = if is_dict { ExprKind::DictLit { entries } } else { ExprKind::ObjectLit { fields } };
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    fn if_expr(&mut self) -> PResult<Expr> {
+        let start = self.expect(&TokenType::If, "`if`")?.span;
+        let mut branches = vec![self.if_branch(start)?];
+
+        while self.check(&TokenType::Elif) {
+            let branch_start = self.advance().span;
+            branches.push(self.if_branch(branch_start)?);
+        }
+
+        let else_block = if self.eat(&TokenType::Else) { Some(self.block()?) } else { None };
+        Ok(Expr::new(ExprKind::If { branches, else_block }, self.span_from(start)))
+    }
+
+    fn if_branch(&mut self, start: crate::lexer::Span) -> PResult<IfBranch> {
+        let pattern = self.let_condition()?;
+        let condition = self.expr_no_struct()?;
+        let body = self.block()?;
+        Ok(IfBranch { pattern, condition, body, span: self.span_from(start) })
+    }
+
+    fn match_expr(&mut self) -> PResult<Expr> {
+        let start = self.expect(&TokenType::Match, "`match`")?.span;
+        let scrutinee = self.expr_no_struct()?;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.at_eof() {
+            arms.push(self.match_arm()?);
+        }
+        self.expect(&TokenType::RightBrace, "`}`")?;
+
+        Ok(Expr::new(ExprKind::Match { scrutinee: Box::new(scrutinee), arms }, self.span_from(start)))
+    }
+
+    fn match_arm(&mut self) -> PResult<MatchArm> {
+        let pattern = self.pattern()?;
+        let start = pattern.span;
+
+        let guard = if self.eat(&TokenType::Where) {
+            let restrictions = Restrictions { no_arrow_lambda: true, ..Restrictions::default() };
+            Some(self.restricted(restrictions, |p| p.expr())?)
+        } else {
+            None
+        };
+
+        self.expect(&TokenType::FatArrow, "`=>`")?;
+        let body = self.expr_unrestricted()?;
+        let span = self.span_from(start);
+        self.eat(&TokenType::Comma);
+
+        Ok(MatchArm { pattern, guard, body, span })
+    }
+}
@@ -0,0 +1,586 @@
+//! Owned AST transformation
+//!
+//! A `Fold` takes each node by value and returns its replacement. The
+//! default `fold_*` methods rebuild the node from its folded children, so a
+//! pass only overrides the nodes it rewrites, e.g. `fold_expr` for constant
+//! folding. Like the visitors, every `walk_*` destructures its node in full.
+
+use crate::parser::ast::*;
+
+pub trait Fold {
+    fn fold_program(&mut self, program: Program) -> Program { walk_program(self, program) }
+    fn fold_package_decl(&mut self, package: PackageDecl) -> PackageDecl { walk_package_decl(self, package) }
+    fn fold_import(&mut self, import: Import) -> Import { walk_import(self, import) }
+    fn fold_qualified_ident(&mut self, path: QualifiedIdent) -> QualifiedIdent { walk_qualified_ident(self, path) }
+    fn fold_ident(&mut self, ident: Ident) -> Ident { ident }
+    fn fold_item(&mut self, item: Item) -> Item { walk_item(self, item) }
+    fn fold_error_node(&mut self, node: ErrorNode) -> ErrorNode { node }
+    fn fold_attribute(&mut self, attribute: Attribute) -> Attribute { walk_attribute(self, attribute) }
+    fn fold_function(&mut self, decl: FunctionDecl) -> FunctionDecl { walk_function(self, decl) }
+    fn fold_param(&mut self, param: Param) -> Param { walk_param(self, param) }
+    fn fold_lifetime_params(&mut self, params: LifetimeParams) -> LifetimeParams { walk_lifetime_params(self, params) }
+    fn fold_outlives(&mut self, outlives: Outlives) -> Outlives { walk_outlives(self, outlives) }
+    fn fold_generic_param(&mut self, param: GenericParam) -> GenericParam { walk_generic_param(self, param) }
+    fn fold_trait_bound(&mut self, bound: TraitBound) -> TraitBound { walk_trait_bound(self, bound) }
+    fn fold_assoc_binding(&mut self, binding: AssocBinding) -> AssocBinding { walk_assoc_binding(self, binding) }
+    fn fold_where_clause(&mut self, clause: WhereClause) -> WhereClause { walk_where_clause(self, clause) }
+    fn fold_where_predicate(&mut self, predicate: WherePredicate) -> WherePredicate { walk_where_predicate(self, predicate) }
+    fn fold_struct_decl(&mut self, decl: StructDecl) -> StructDecl { walk_struct_decl(self, decl) }
+    fn fold_struct_member(&mut self, member: StructMember) -> StructMember { walk_struct_member(self, member) }
+    fn fold_field(&mut self, field: FieldDecl) -> FieldDecl { walk_field(self, field) }
+    fn fold_property(&mut self, property: PropertyDecl) -> PropertyDecl { walk_property(self, property) }
+    fn fold_accessor(&mut self, accessor: Accessor) -> Accessor { walk_accessor(self, accessor) }
+    fn fold_enum_decl(&mut self, decl: EnumDecl) -> EnumDecl { walk_enum_decl(self, decl) }
+    fn fold_variant(&mut self, variant: EnumVariant) -> EnumVariant { walk_variant(self, variant) }
+    fn fold_trait_decl(&mut self, decl: TraitDecl) -> TraitDecl { walk_trait_decl(self, decl) }
+    fn fold_trait_item(&mut self, item: TraitItem) -> TraitItem { walk_trait_item(self, item) }
+    fn fold_method_signature(&mut self, method: MethodSignature) -> MethodSignature { walk_method_signature(self, method) }
+    fn fold_associated_type(&mut self, assoc: AssociatedType) -> AssociatedType { walk_associated_type(self, assoc) }
+    fn fold_impl_block(&mut self, block: ImplBlock) -> ImplBlock { walk_impl_block(self, block) }
+    fn fold_extend_block(&mut self, block: ExtendBlock) -> ExtendBlock { walk_extend_block(self, block) }
+    fn fold_const_decl(&mut self, decl: ConstDecl) -> ConstDecl { walk_const_decl(self, decl) }
+    fn fold_type_expr(&mut self, ty: TypeExpr) -> TypeExpr { walk_type_expr(self, ty) }
+    fn fold_block(&mut self, block: Block) -> Block { walk_block(self, block) }
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt { walk_stmt(self, stmt) }
+    fn fold_allocator(&mut self, allocator: Allocator) -> Allocator { walk_allocator(self, allocator) }
+    fn fold_catch_clause(&mut self, catch: CatchClause) -> CatchClause { walk_catch_clause(self, catch) }
+    fn fold_resource(&mut self, resource: Resource) -> Resource { walk_resource(self, resource) }
+    fn fold_expr(&mut self, expr: Expr) -> Expr { walk_expr(self, expr) }
+    fn fold_literal(&mut self, literal: Literal) -> Literal { walk_literal(self, literal) }
+    fn fold_if_branch(&mut self, branch: IfBranch) -> IfBranch { walk_if_branch(self, branch) }
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm { walk_match_arm(self, arm) }
+    fn fold_field_init(&mut self, init: FieldInit) -> FieldInit { walk_field_init(self, init) }
+    fn fold_dict_entry(&mut self, entry: DictEntry) -> DictEntry { walk_dict_entry(self, entry) }
+    fn fold_query_clause(&mut self, clause: QueryClause) -> QueryClause { walk_query_clause(self, clause) }
+    fn fold_order_key(&mut self, key: OrderKey) -> OrderKey { walk_order_key(self, key) }
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern { walk_pattern(self, pattern) }
+    fn fold_field_pattern(&mut self, field: FieldPattern) -> FieldPattern { walk_field_pattern(self, field) }
+}
+
+fn fold_vec<T>(items: Vec<T>, f: impl FnMut(T) -> T) -> Vec<T> {
+    items.into_iter().map(f).collect()
+}
+
+/// Folds a boxed child, reusing its allocation
+fn fold_box<F: Fold + ?Sized>(f: &mut F, mut expr: Box<Expr>) -> Box<Expr> {
+    *expr = f.fold_expr(*expr);
+    expr
+}
+
+fn fold_idents<F: Fold + ?Sized>(f: &mut F, idents: Vec<Ident>) -> Vec<Ident> {
+    fold_vec(idents, |i| f.fold_ident(i))
+}
+
+pub fn walk_program<F: Fold + ?Sized>(f: &mut F, program: Program) -> Program {
+    let Program { package, imports, items, span } = program;
+    Program {
+        package: package.map(|p| f.fold_package_decl(p)),
+        imports: fold_vec(imports, |i| f.fold_import(i)),
+        items: fold_vec(items, |i| f.fold_item(i)),
+        span,
+    }
+}
+
+pub fn walk_package_decl<F: Fold + ?Sized>(f: &mut F, package: PackageDecl) -> PackageDecl {
+    let PackageDecl { path, span } = package;
+    PackageDecl { path: f.fold_qualified_ident(path), span }
+}
+
+pub fn walk_import<F: Fold + ?Sized>(f: &mut F, import: Import) -> Import {
+    let Import { kind, span } = import;
+    let kind = match kind {
+        ImportKind::Summon { path, alias } => ImportKind::Summon {
+            path: f.fold_qualified_ident(path),
+            alias: alias.map(|a| f.fold_ident(a)),
+        },
+        ImportKind::From { path, items } => ImportKind::From {
+            path: f.fold_qualified_ident(path),
+            items: fold_idents(f, items),
+        },
+    };
+    Import { kind, span }
+}
+
+pub fn walk_qualified_ident<F: Fold + ?Sized>(f: &mut F, path: QualifiedIdent) -> QualifiedIdent {
+    let QualifiedIdent { segments, span } = path;
+    QualifiedIdent { segments: fold_idents(f, segments), span }
+}
+
+pub fn walk_item<F: Fold + ?Sized>(f: &mut F, item: Item) -> Item {
+    match item {
+        Item::Function(decl) => Item::Function(f.fold_function(decl)),
+        Item::Struct(decl) => Item::Struct(f.fold_struct_decl(decl)),
+        Item::Enum(decl) => Item::Enum(f.fold_enum_decl(decl)),
+        Item::Trait(decl) => Item::Trait(f.fold_trait_decl(decl)),
+        Item::Impl(block) => Item::Impl(f.fold_impl_block(block)),
+        Item::Extend(block) => Item::Extend(f.fold_extend_block(block)),
+        Item::Const(decl) => Item::Const(f.fold_const_decl(decl)),
+        Item::Error(node) => Item::Error(f.fold_error_node(node)),
+    }
+}
+
+pub fn walk_attribute<F: Fold + ?Sized>(f: &mut F, attribute: Attribute) -> Attribute {
+    let Attribute { name, args, span } = attribute;
+    Attribute { name: f.fold_ident(name), args: fold_vec(args, |a| f.fold_expr(a)), span }
+}
+
+pub fn walk_function<F: Fold + ?Sized>(f: &mut F, decl: FunctionDecl) -> FunctionDecl {
+    let FunctionDecl { attributes, is_pub, is_async, name, lifetimes, generics, params, return_type, where_clause, body, span } = decl;
+    FunctionDecl {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        is_pub,
+        is_async,
+        name: f.fold_ident(name),
+        lifetimes: lifetimes.map(|l| f.fold_lifetime_params(l)),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        params: fold_vec(params, |p| f.fold_param(p)),
+        return_type: return_type.map(|t| f.fold_type_expr(t)),
+        where_clause: where_clause.map(|w| f.fold_where_clause(w)),
+        body: f.fold_block(body),
+        span,
+    }
+}
+
+pub fn walk_param<F: Fold + ?Sized>(f: &mut F, param: Param) -> Param {
+    let Param { is_mut, name, pattern, ty, default, span } = param;
+    Param {
+        is_mut,
+        name: f.fold_ident(name),
+        pattern: pattern.map(|p| f.fold_pattern(p)),
+        ty: ty.map(|t| f.fold_type_expr(t)),
+        default: default.map(|d| f.fold_expr(d)),
+        span,
+    }
+}
+
+pub fn walk_lifetime_params<F: Fold + ?Sized>(f: &mut F, params: LifetimeParams) -> LifetimeParams {
+    let LifetimeParams { names, outlives, span } = params;
+    LifetimeParams { names: fold_idents(f, names), outlives: fold_vec(outlives, |o| f.fold_outlives(o)), span }
+}
+
+pub fn walk_outlives<F: Fold + ?Sized>(f: &mut F, outlives: Outlives) -> Outlives {
+    let Outlives { longer, shorter, span } = outlives;
+    Outlives { longer: f.fold_ident(longer), shorter: f.fold_ident(shorter), span }
+}
+
+pub fn walk_generic_param<F: Fold + ?Sized>(f: &mut F, param: GenericParam) -> GenericParam {
+    let GenericParam { name, bounds, span } = param;
+    GenericParam { name: f.fold_ident(name), bounds: fold_vec(bounds, |b| f.fold_trait_bound(b)), span }
+}
+
+pub fn walk_trait_bound<F: Fold + ?Sized>(f: &mut F, bound: TraitBound) -> TraitBound {
+    let TraitBound { kind, span } = bound;
+    let kind = match kind {
+        BoundKind::Trait { name, args, bindings } => BoundKind::Trait {
+            name: f.fold_ident(name),
+            args: fold_vec(args, |a| f.fold_type_expr(a)),
+            bindings: fold_vec(bindings, |b| f.fold_assoc_binding(b)),
+        },
+        BoundKind::Function { name, params, ret } => BoundKind::Function {
+            name: f.fold_ident(name),
+            params: fold_vec(params, |p| f.fold_type_expr(p)),
+            ret: ret.map(|r| Box::new(f.fold_type_expr(*r))),
+        },
+    };
+    TraitBound { kind, span }
+}
+
+pub fn walk_assoc_binding<F: Fold + ?Sized>(f: &mut F, binding: AssocBinding) -> AssocBinding {
+    let AssocBinding { name, ty, span } = binding;
+    AssocBinding { name: f.fold_ident(name), ty: f.fold_type_expr(ty), span }
+}
+
+pub fn walk_where_clause<F: Fold + ?Sized>(f: &mut F, clause: WhereClause) -> WhereClause {
+    let WhereClause { predicates, span } = clause;
+    WhereClause { predicates: fold_vec(predicates, |p| f.fold_where_predicate(p)), span }
+}
+
+pub fn walk_where_predicate<F: Fold + ?Sized>(f: &mut F, predicate: WherePredicate) -> WherePredicate {
+    let WherePredicate { name, bounds, span } = predicate;
+    WherePredicate { name: f.fold_ident(name), bounds: fold_vec(bounds, |b| f.fold_trait_bound(b)), span }
+}
+
+pub fn walk_struct_decl<F: Fold + ?Sized>(f: &mut F, decl: StructDecl) -> StructDecl {
+    let StructDecl { attributes, is_pub, is_edge, name, generics, members, span } = decl;
+    StructDecl {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        is_pub,
+        is_edge,
+        name: f.fold_ident(name),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        members: fold_vec(members, |m| f.fold_struct_member(m)),
+        span,
+    }
+}
+
+pub fn walk_struct_member<F: Fold + ?Sized>(f: &mut F, member: StructMember) -> StructMember {
+    match member {
+        StructMember::Field(field) => StructMember::Field(f.fold_field(field)),
+        StructMember::Method(method) => StructMember::Method(f.fold_function(method)),
+        StructMember::Property(property) => StructMember::Property(f.fold_property(property)),
+    }
+}
+
+pub fn walk_field<F: Fold + ?Sized>(f: &mut F, field: FieldDecl) -> FieldDecl {
+    let FieldDecl { is_pub, name, ty, span } = field;
+    FieldDecl { is_pub, name: f.fold_ident(name), ty: f.fold_type_expr(ty), span }
+}
+
+pub fn walk_property<F: Fold + ?Sized>(f: &mut F, property: PropertyDecl) -> PropertyDecl {
+    let PropertyDecl { is_pub, name, ty, getter, setter, span } = property;
+    PropertyDecl {
+        is_pub,
+        name: f.fold_ident(name),
+        ty: f.fold_type_expr(ty),
+        getter: f.fold_accessor(getter),
+        setter: setter.map(|s| f.fold_accessor(s)),
+        span,
+    }
+}
+
+pub fn walk_accessor<F: Fold + ?Sized>(f: &mut F, accessor: Accessor) -> Accessor {
+    let Accessor { value, body, span } = accessor;
+    Accessor { value: value.map(|v| f.fold_ident(v)), body: body.map(|b| f.fold_block(b)), span }
+}
+
+pub fn walk_enum_decl<F: Fold + ?Sized>(f: &mut F, decl: EnumDecl) -> EnumDecl {
+    let EnumDecl { attributes, is_pub, name, generics, variants, span } = decl;
+    EnumDecl {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        is_pub,
+        name: f.fold_ident(name),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        variants: fold_vec(variants, |v| f.fold_variant(v)),
+        span,
+    }
+}
+
+pub fn walk_variant<F: Fold + ?Sized>(f: &mut F, variant: EnumVariant) -> EnumVariant {
+    let EnumVariant { name, payload, span } = variant;
+    let payload = payload.map(|payload| match payload {
+        EnumPayload::Tuple { types } => EnumPayload::Tuple { types: fold_vec(types, |t| f.fold_type_expr(t)) },
+        EnumPayload::Struct { fields } => EnumPayload::Struct { fields: fold_vec(fields, |x| f.fold_field(x)) },
+    });
+    EnumVariant { name: f.fold_ident(name), payload, span }
+}
+
+pub fn walk_trait_decl<F: Fold + ?Sized>(f: &mut F, decl: TraitDecl) -> TraitDecl {
+    let TraitDecl { attributes, is_pub, name, items, span } = decl;
+    TraitDecl {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        is_pub,
+        name: f.fold_ident(name),
+        items: fold_vec(items, |i| f.fold_trait_item(i)),
+        span,
+    }
+}
+
+pub fn walk_trait_item<F: Fold + ?Sized>(f: &mut F, item: TraitItem) -> TraitItem {
+    match item {
+        TraitItem::Method(method) => TraitItem::Method(f.fold_method_signature(method)),
+        TraitItem::AssociatedType(assoc) => TraitItem::AssociatedType(f.fold_associated_type(assoc)),
+    }
+}
+
+pub fn walk_method_signature<F: Fold + ?Sized>(f: &mut F, method: MethodSignature) -> MethodSignature {
+    let MethodSignature { is_async, name, lifetimes, generics, params, return_type, where_clause, span } = method;
+    MethodSignature {
+        is_async,
+        name: f.fold_ident(name),
+        lifetimes: lifetimes.map(|l| f.fold_lifetime_params(l)),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        params: fold_vec(params, |p| f.fold_param(p)),
+        return_type: return_type.map(|t| f.fold_type_expr(t)),
+        where_clause: where_clause.map(|w| f.fold_where_clause(w)),
+        span,
+    }
+}
+
+pub fn walk_associated_type<F: Fold + ?Sized>(f: &mut F, assoc: AssociatedType) -> AssociatedType {
+    let AssociatedType { name, span } = assoc;
+    AssociatedType { name: f.fold_ident(name), span }
+}
+
+pub fn walk_impl_block<F: Fold + ?Sized>(f: &mut F, block: ImplBlock) -> ImplBlock {
+    let ImplBlock { attributes, generics, trait_name, target, where_clause, methods, span } = block;
+    ImplBlock {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        trait_name: trait_name.map(|t| f.fold_type_expr(t)),
+        target: f.fold_type_expr(target),
+        where_clause: where_clause.map(|w| f.fold_where_clause(w)),
+        methods: fold_vec(methods, |m| f.fold_function(m)),
+        span,
+    }
+}
+
+pub fn walk_extend_block<F: Fold + ?Sized>(f: &mut F, block: ExtendBlock) -> ExtendBlock {
+    let ExtendBlock { attributes, generics, target, where_clause, methods, span } = block;
+    ExtendBlock {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        generics: fold_vec(generics, |g| f.fold_generic_param(g)),
+        target: f.fold_type_expr(target),
+        where_clause: where_clause.map(|w| f.fold_where_clause(w)),
+        methods: fold_vec(methods, |m| f.fold_function(m)),
+        span,
+    }
+}
+
+pub fn walk_const_decl<F: Fold + ?Sized>(f: &mut F, decl: ConstDecl) -> ConstDecl {
+    let ConstDecl { attributes, is_pub, name, ty, value, span } = decl;
+    ConstDecl {
+        attributes: fold_vec(attributes, |a| f.fold_attribute(a)),
+        is_pub,
+        name: f.fold_ident(name),
+        ty: ty.map(|t| f.fold_type_expr(t)),
+        value: f.fold_expr(value),
+        span,
+    }
+}
+
+pub fn walk_type_expr<F: Fold + ?Sized>(f: &mut F, ty: TypeExpr) -> TypeExpr {
+    let TypeExpr { kind, span } = ty;
+    let mut fold_type = |t: Box<TypeExpr>| Box::new(f.fold_type_expr(*t));
+    let kind = match kind {
+        TypeKind::Primitive { name } => TypeKind::Primitive { name },
+        TypeKind::Named { name, args } => TypeKind::Named {
+            name: f.fold_ident(name),
+            args: fold_vec(args, |a| f.fold_type_expr(a)),
+        },
+        TypeKind::Function { params, ret } => TypeKind::Function {
+            params: fold_vec(params, |p| f.fold_type_expr(p)),
+            ret: ret.map(|r| Box::new(f.fold_type_expr(*r))),
+        },
+        TypeKind::Tuple { elems } => TypeKind::Tuple { elems: fold_vec(elems, |e| f.fold_type_expr(e)) },
+        TypeKind::Array { elem, size } => TypeKind::Array { elem: fold_type(elem), size },
+        TypeKind::Slice { elem } => TypeKind::Slice { elem: fold_type(elem) },
+        TypeKind::Fallible { inner } => TypeKind::Fallible { inner: fold_type(inner) },
+        TypeKind::Reference { mutable, lifetime, inner } => TypeKind::Reference {
+            mutable,
+            lifetime: lifetime.map(|l| f.fold_ident(l)),
+            inner: Box::new(f.fold_type_expr(*inner)),
+        },
+        TypeKind::Task { inner } => TypeKind::Task { inner: inner.map(fold_type) },
+    };
+    TypeExpr { kind, span }
+}
+
+pub fn walk_block<F: Fold + ?Sized>(f: &mut F, block: Block) -> Block {
+    let Block { stmts, span } = block;
+    Block { stmts: fold_vec(stmts, |s| f.fold_stmt(s)), span }
+}
+
+pub fn walk_stmt<F: Fold + ?Sized>(f: &mut F, stmt: Stmt) -> Stmt {
+    let Stmt { kind, span } = stmt;
+    let kind = match kind {
+        StmtKind::Let { mutable, name, ty, value } => StmtKind::Let {
+            mutable,
+            name: f.fold_ident(name),
+            ty: ty.map(|t| f.fold_type_expr(t)),
+            value: f.fold_expr(value),
+        },
+        StmtKind::ShortLet { name, value } => StmtKind::ShortLet { name: f.fold_ident(name), value: f.fold_expr(value) },
+        StmtKind::Extract { pattern, value } => StmtKind::Extract { pattern: f.fold_pattern(pattern), value: f.fold_expr(value) },
+        StmtKind::LetElse { pattern, value, else_block } => StmtKind::LetElse {
+            pattern: f.fold_pattern(pattern),
+            value: f.fold_expr(value),
+            else_block: f.fold_block(else_block),
+        },
+        StmtKind::Item { item } => StmtKind::Item { item: f.fold_item(item) },
+        StmtKind::Expr { expr } => StmtKind::Expr { expr: f.fold_expr(expr) },
+        StmtKind::Return { value } => StmtKind::Return { value: value.map(|v| f.fold_expr(v)) },
+        StmtKind::Break => StmtKind::Break,
+        StmtKind::Continue => StmtKind::Continue,
+        StmtKind::Error => StmtKind::Error,
+        StmtKind::For { pattern, iter, body } => StmtKind::For {
+            pattern: f.fold_pattern(pattern),
+            iter: f.fold_expr(iter),
+            body: f.fold_block(body),
+        },
+        StmtKind::While { pattern, condition, body } => StmtKind::While {
+            pattern: pattern.map(|p| f.fold_pattern(p)),
+            condition: f.fold_expr(condition),
+            body: f.fold_block(body),
+        },
+        StmtKind::Loop { body } => StmtKind::Loop { body: f.fold_block(body) },
+        StmtKind::Unsafe { body } => StmtKind::Unsafe { body: f.fold_block(body) },
+        StmtKind::With { allocator, body } => StmtKind::With { allocator: f.fold_allocator(allocator), body: f.fold_block(body) },
+        StmtKind::Try { body, catch } => StmtKind::Try {
+            body: f.fold_block(body),
+            catch: catch.map(|c| f.fold_catch_clause(c)),
+        },
+        StmtKind::Using { resources, body } => StmtKind::Using {
+            resources: fold_vec(resources, |r| f.fold_resource(r)),
+            body: f.fold_block(body),
+        },
+        StmtKind::Defer { body } => StmtKind::Defer { body: f.fold_block(body) },
+    };
+    Stmt { kind, span }
+}
+
+pub fn walk_allocator<F: Fold + ?Sized>(f: &mut F, allocator: Allocator) -> Allocator {
+    let Allocator { kind, span } = allocator;
+    let kind = match kind {
+        AllocatorKind::Arena { size } => AllocatorKind::Arena { size: f.fold_expr(size) },
+        AllocatorKind::Pool { ty, capacity } => AllocatorKind::Pool { ty: f.fold_type_expr(ty), capacity: f.fold_expr(capacity) },
+        AllocatorKind::Gc => AllocatorKind::Gc,
+        AllocatorKind::Heap => AllocatorKind::Heap,
+    };
+    Allocator { kind, span }
+}
+
+pub fn walk_catch_clause<F: Fold + ?Sized>(f: &mut F, catch: CatchClause) -> CatchClause {
+    let CatchClause { binding, body, span } = catch;
+    CatchClause { binding: f.fold_ident(binding), body: f.fold_block(body), span }
+}
+
+pub fn walk_resource<F: Fold + ?Sized>(f: &mut F, resource: Resource) -> Resource {
+    let Resource { name, value, span } = resource;
+    Resource { name: f.fold_ident(name), value: f.fold_expr(value), span }
+}
+
+pub fn walk_expr<F: Fold + ?Sized>(f: &mut F, expr: Expr) -> Expr {
+    let Expr { kind, span } = expr;
+    let kind = match kind {
+        ExprKind::Literal { value } => ExprKind::Literal { value: f.fold_literal(value) },
+        ExprKind::InterpolatedString { parts } => ExprKind::InterpolatedString {
+            parts: fold_vec(parts, |part| match part {
+                StringPart::Text(text) => StringPart::Text(text),
+                StringPart::Expr(expr) => StringPart::Expr(f.fold_expr(expr)),
+            }),
+        },
+        ExprKind::Ident { name } => ExprKind::Ident { name },
+        ExprKind::SelfRef => ExprKind::SelfRef,
+        ExprKind::Error => ExprKind::Error,
+        ExprKind::Unary { op, operand } => ExprKind::Unary { op, operand: fold_box(f, operand) },
+        ExprKind::Binary { op, lhs, rhs } => ExprKind::Binary { op, lhs: fold_box(f, lhs), rhs: fold_box(f, rhs) },
+        ExprKind::Assign { op, target, value } => ExprKind::Assign { op, target: fold_box(f, target), value: fold_box(f, value) },
+        ExprKind::Call { callee, args } => ExprKind::Call {
+            callee: fold_box(f, callee),
+            args: fold_vec(args, |a| f.fold_expr(a)),
+        },
+        ExprKind::Index { target, index } => ExprKind::Index { target: fold_box(f, target), index: fold_box(f, index) },
+        ExprKind::Field { target, name, safe } => ExprKind::Field { target: fold_box(f, target), name: f.fold_ident(name), safe },
+        ExprKind::MethodCall { receiver, method, args, safe } => ExprKind::MethodCall {
+            receiver: fold_box(f, receiver),
+            method: f.fold_ident(method),
+            args: fold_vec(args, |a| f.fold_expr(a)),
+            safe,
+        },
+        ExprKind::Cast { expr, ty } => ExprKind::Cast { expr: fold_box(f, expr), ty: f.fold_type_expr(ty) },
+        ExprKind::Range { start, end, inclusive } => ExprKind::Range { start: fold_box(f, start), end: fold_box(f, end), inclusive },
+        ExprKind::Try { expr } => ExprKind::Try { expr: fold_box(f, expr) },
+        ExprKind::Lambda { params, body } => ExprKind::Lambda {
+            params: fold_vec(params, |p| f.fold_param(p)),
+            body: fold_box(f, body),
+        },
+        ExprKind::If { branches, else_block } => ExprKind::If {
+            branches: fold_vec(branches, |b| f.fold_if_branch(b)),
+            else_block: else_block.map(|b| f.fold_block(b)),
+        },
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: fold_box(f, scrutinee),
+            arms: fold_vec(arms, |a| f.fold_match_arm(a)),
+        },
+        ExprKind::Block { block } => ExprKind::Block { block: f.fold_block(block) },
+        ExprKind::Array { elems } => ExprKind::Array { elems: fold_vec(elems, |e| f.fold_expr(e)) },
+        ExprKind::Tuple { elems } => ExprKind::Tuple { elems: fold_vec(elems, |e| f.fold_expr(e)) },
+        ExprKind::StructLit { name, fields } => ExprKind::StructLit {
+            name: f.fold_ident(name),
+            fields: fold_vec(fields, |x| f.fold_field_init(x)),
+        },
+        ExprKind::ObjectLit { fields } => ExprKind::ObjectLit { fields: fold_vec(fields, |x| f.fold_field_init(x)) },
+        ExprKind::DictLit { entries } => ExprKind::DictLit { entries: fold_vec(entries, |e| f.fold_dict_entry(e)) },
+        ExprKind::Query { clauses } => ExprKind::Query { clauses: fold_vec(clauses, |c| f.fold_query_clause(c)) },
+    };
+    Expr { kind, span }
+}
+
+pub fn walk_literal<F: Fold + ?Sized>(_f: &mut F, literal: Literal) -> Literal {
+    match literal {
+        Literal::Int(_) | Literal::Float(_) | Literal::Double(_) | Literal::String(_)
+        | Literal::Verbatim(_) | Literal::Char(_) | Literal::Bool(_) | Literal::Null => literal,
+    }
+}
+
+pub fn walk_if_branch<F: Fold + ?Sized>(f: &mut F, branch: IfBranch) -> IfBranch {
+    let IfBranch { pattern, condition, body, span } = branch;
+    IfBranch {
+        pattern: pattern.map(|p| f.fold_pattern(p)),
+        condition: f.fold_expr(condition),
+        body: f.fold_block(body),
+        span,
+    }
+}
+
+pub fn walk_match_arm<F: Fold + ?Sized>(f: &mut F, arm: MatchArm) -> MatchArm {
+    let MatchArm { pattern, guard, body, span } = arm;
+    MatchArm {
+        pattern: f.fold_pattern(pattern),
+        guard: guard.map(|g| f.fold_expr(g)),
+        body: f.fold_expr(body),
+        span,
+    }
+}
+
+pub fn walk_field_init<F: Fold + ?Sized>(f: &mut F, init: FieldInit) -> FieldInit {
+    let FieldInit { name, value, span } = init;
+    FieldInit { name: f.fold_ident(name), value: f.fold_expr(value), span }
+}
+
+pub fn walk_dict_entry<F: Fold + ?Sized>(f: &mut F, entry: DictEntry) -> DictEntry {
+    let DictEntry { key, value, span } = entry;
+    DictEntry { key: f.fold_expr(key), value: f.fold_expr(value), span }
+}
+
+pub fn walk_query_clause<F: Fold + ?Sized>(f: &mut F, clause: QueryClause) -> QueryClause {
+    let QueryClause { kind, span } = clause;
+    let kind = match kind {
+        QueryClauseKind::From { name, source } => QueryClauseKind::From { name: f.fold_ident(name), source: f.fold_expr(source) },
+        QueryClauseKind::Let { name, value } => QueryClauseKind::Let { name: f.fold_ident(name), value: f.fold_expr(value) },
+        QueryClauseKind::Where { condition } => QueryClauseKind::Where { condition: f.fold_expr(condition) },
+        QueryClauseKind::OrderBy { keys } => QueryClauseKind::OrderBy { keys: fold_vec(keys, |k| f.fold_order_key(k)) },
+        QueryClauseKind::Join { name, source, outer_key, inner_key } => QueryClauseKind::Join {
+            name: f.fold_ident(name),
+            source: f.fold_expr(source),
+            outer_key: fold_box(f, outer_key),
+            inner_key: fold_box(f, inner_key),
+        },
+        QueryClauseKind::Select { value } => QueryClauseKind::Select { value: f.fold_expr(value) },
+        QueryClauseKind::GroupBy { value, key } => QueryClauseKind::GroupBy { value: f.fold_expr(value), key: f.fold_expr(key) },
+    };
+    QueryClause { kind, span }
+}
+
+pub fn walk_order_key<F: Fold + ?Sized>(f: &mut F, key: OrderKey) -> OrderKey {
+    let OrderKey { key, descending, span } = key;
+    OrderKey { key: f.fold_expr(key), descending, span }
+}
+
+pub fn walk_pattern<F: Fold + ?Sized>(f: &mut F, pattern: Pattern) -> Pattern {
+    let Pattern { kind, span } = pattern;
+    let kind = match kind {
+        PatternKind::Wildcard => PatternKind::Wildcard,
+        PatternKind::Literal { value } => PatternKind::Literal { value: f.fold_literal(value) },
+        PatternKind::Binding { name } => PatternKind::Binding { name: f.fold_ident(name) },
+        PatternKind::Tuple { elems } => PatternKind::Tuple { elems: fold_vec(elems, |e| f.fold_pattern(e)) },
+        PatternKind::Path { path } => PatternKind::Path { path: fold_idents(f, path) },
+        PatternKind::TupleStruct { path, elems } => PatternKind::TupleStruct {
+            path: fold_idents(f, path),
+            elems: fold_vec(elems, |e| f.fold_pattern(e)),
+        },
+        PatternKind::Struct { path, fields } => PatternKind::Struct {
+            path: fold_idents(f, path),
+            fields: fold_vec(fields, |x| f.fold_field_pattern(x)),
+        },
+        PatternKind::List { elems } => PatternKind::List { elems: fold_vec(elems, |e| f.fold_pattern(e)) },
+        PatternKind::Rest { name } => PatternKind::Rest { name: name.map(|n| f.fold_ident(n)) },
+        PatternKind::Object { fields } => PatternKind::Object { fields: fold_vec(fields, |x| f.fold_field_pattern(x)) },
+    };
+    Pattern { kind, span }
+}
+
+pub fn walk_field_pattern<F: Fold + ?Sized>(f: &mut F, field: FieldPattern) -> FieldPattern {
+    let FieldPattern { name, pattern, span } = field;
+    FieldPattern { name: f.fold_ident(name), pattern: pattern.map(|p| f.fold_pattern(p)), span }
+}
@@ -0,0 +1,575 @@
+//! Program structure and declarations
+
+use crate::error_management::error_types::ParseError;
+use crate::lexer::{Span, TokenType};
+use crate::parser::ast::*;
+use crate::parser::recursive_descent::{PResult, Parser};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn program(&mut self) -> Program {
+        let package = if self.check(&TokenType::Package) {
+            let from = self.position();
+            match self.package_decl() {
+                Ok(package) => Some(package),
+                Err(error) => {
+                    self.record(*error);
+                    self.synchronize_statement(from);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut imports = Vec::new();
+        while self.check(&TokenType::Summon) || self.check(&TokenType::From) {
+            let from = self.position();
+            match self.import() {
+                Ok(import) => imports.push(import),
+                Err(error) => {
+                    self.record(*error);
+                    self.synchronize_statement(from);
+                }
+            }
+        }
+
+        let mut items = Vec::new();
+        while !self.at_eof() {
+            let from = self.position();
+            match self.item() {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    self.record(*error);
+                    self.synchronize_item(from);
+                    items.push(Item::Error(ErrorNode { span: self.span_since(from) }));
+                }
+            }
+        }
+
+        let end = self.peek().span.end;
+        Program { package, imports, items, span: Span::new(0, end, 1, 1) }
+    }
+
+    fn package_decl(&mut self) -> PResult<PackageDecl> {
+        let start = self.advance().span;
+        let path = self.qualified_ident()?;
+        self.eat(&TokenType::Semicolon);
+        Ok(PackageDecl { path, span: self.span_from(start) })
+    }
+
+    fn import(&mut self) -> PResult<Import> {
+        let start = self.advance();
+
+        let kind = if start.kind == TokenType::Summon {
+            let path = self.qualified_ident()?;
+            let alias = if self.eat(&TokenType::As) {
+                Some(self.expect_ident("an alias after `as`")?)
+            } else {
+                None
+            };
+            ImportKind::Summon { path, alias }
+        } else {
+            let path = self.qualified_ident()?;
+            self.expect(&TokenType::Summon, "`summon`")?;
+            let mut items = Vec::new();
+            if self.eat(&TokenType::LeftBracket) {
+                while !self.check(&TokenType::RightBracket) {
+                    items.push(self.expect_ident("an imported name")?);
+                    if !self.eat(&TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.expect_one_of(&TokenType::RightBracket, &["`,`", "`]`"])?;
+            } else {
+                items.push(self.expect_ident("an imported name")?);
+                while self.eat(&TokenType::Comma) {
+                    items.push(self.expect_ident("an imported name")?);
+                }
+            }
+            ImportKind::From { path, items }
+        };
+
+        self.eat(&TokenType::Semicolon);
+        Ok(Import { kind, span: self.span_from(start.span) })
+    }
+
+    pub(crate) fn qualified_ident(&mut self) -> PResult<QualifiedIdent> {
+        let first = self.expect_ident("a name")?;
+        let start = first.span;
+        let mut segments = vec![first];
+        while self.eat(&TokenType::Dot) {
+            segments.push(self.expect_ident("a name after `.`")?);
+        }
+        Ok(QualifiedIdent { segments, span: self.span_from(start) })
+    }
+
+    /// True if the current token can only start an item, not a statement
+    pub(crate) fn at_item_start(&self) -> bool {
+        match self.peek_kind() {
+            TokenType::At | TokenType::Pub | TokenType::Edge | TokenType::Struct
+            | TokenType::Enum | TokenType::Trait | TokenType::Impl | TokenType::Extend | TokenType::Const => true,
+            // `fn(x) ...` at the start of a statement is a lambda
+            TokenType::Fn => matches!(self.peek_nth(1).kind, TokenType::Ident(_)),
+            TokenType::Async => self.check_nth(1, &TokenType::Fn),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn item(&mut self) -> PResult<Item> {
+        let start = self.peek().span;
+        let attributes = self.attributes()?;
+        let is_pub = self.eat(&TokenType::Pub);
+
+        match self.peek_kind() {
+            TokenType::Fn | TokenType::Async => Ok(Item::Function(self.function(start, attributes, is_pub)?)),
+            TokenType::Struct | TokenType::Edge => Ok(Item::Struct(self.struct_decl(start, attributes, is_pub)?)),
+            TokenType::Enum => Ok(Item::Enum(self.enum_decl(start, attributes, is_pub)?)),
+            TokenType::Trait => Ok(Item::Trait(self.trait_decl(start, attributes, is_pub)?)),
+            TokenType::Impl if !is_pub => Ok(Item::Impl(self.impl_block(start, attributes)?)),
+            TokenType::Extend if !is_pub => Ok(Item::Extend(self.extend_block(start, attributes)?)),
+            TokenType::Const => Ok(Item::Const(self.const_decl(start, attributes, is_pub)?)),
+            _ => Err(self.error_expected_one_of(&["`fn`", "`struct`", "`enum`", "`trait`", "`impl`", "`extend`", "`const`"])),
+        }
+    }
+
+    pub(crate) fn attributes(&mut self) -> PResult<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        while self.check(&TokenType::At) {
+            let start = self.advance().span;
+            let name = self.expect_ident("an attribute name")?;
+            let mut args = Vec::new();
+            if self.check(&TokenType::LeftParen) && !self.newline_before() {
+                args = self.call_args()?;
+            }
+            attributes.push(Attribute { name, args, span: self.span_from(start) });
+        }
+        Ok(attributes)
+    }
+
+    pub(crate) fn function(&mut self, start: Span, attributes: Vec<Attribute>, is_pub: bool) -> PResult<FunctionDecl> {
+        let is_async = self.eat(&TokenType::Async);
+        self.expect(&TokenType::Fn, "`fn`")?;
+        let name = self.expect_ident("a function name")?;
+        let lifetimes = self.lifetime_params()?;
+        let generics = self.generic_params()?;
+        let params = self.params()?;
+        let return_type = self.return_type()?;
+        let where_clause = self.where_clause()?;
+        let body = self.block()?;
+
+        Ok(FunctionDecl {
+            attributes,
+            is_pub,
+            is_async,
+            name,
+            lifetimes,
+            generics,
+            params,
+            return_type,
+            where_clause,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    /// `(a: int, mut b = 1, extract (x, y): (int, int))`
+    pub(crate) fn params(&mut self) -> PResult<Vec<Param>> {
+        self.expect(&TokenType::LeftParen, "`(`")?;
+        let mut params = Vec::new();
+
+        while !self.check(&TokenType::RightParen) {
+            let start = self.peek().span;
+            let is_mut = self.eat(&TokenType::Mut);
+            if self.check(&TokenType::Extract) {
+                let pattern = self.extract_pattern()?;
+                let (ty, default) = self.param_type_and_default()?;
+                params.push(Param::destructured(is_mut, pattern, ty, default, self.span_from(start)));
+            } else {
+                let name = if self.check(&TokenType::SelfKw) {
+                    let token = self.advance();
+                    Ident { name: token.lexeme, span: token.span }
+                } else {
+                    self.expect_ident("a parameter name")?
+                };
+                let (ty, default) = self.param_type_and_default()?;
+                params.push(Param { is_mut, name, pattern: None, ty, default, span: self.span_from(start) });
+            }
+
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.expect_one_of(&TokenType::RightParen, &["`,`", "`)`"])?;
+        Ok(params)
+    }
+
+    /// `: ty` and `= default`, both optional
+    fn param_type_and_default(&mut self) -> PResult<(Option<TypeExpr>, Option<Expr>)> {
+        let ty = if self.eat(&TokenType::Colon) { Some(self.type_expr()?) } else { None };
+        let default = if self.eat(&TokenType::Equal) { Some(self.expr()?) } else { None };
+        Ok((ty, default))
+    }
+
+    /// A return type follows `)` on the same line: `fn f() int {`
+    pub(crate) fn return_type(&mut self) -> PResult<Option<TypeExpr>> {
+        if self.newline_before() || !self.at_type_start() {
+            return Ok(None);
+        }
+        self.type_expr().map(Some)
+    }
+
+    /// `[lifetime L, lifetime M where M outlives L]`
+    fn lifetime_params(&mut self) -> PResult<Option<LifetimeParams>> {
+        let start = self.peek().span;
+        if !self.eat(&TokenType::LeftBracket) {
+            return Ok(None);
+        }
+
+        let mut names = Vec::new();
+        loop {
+            self.expect_word("lifetime")?;
+            names.push(self.expect_ident("a lifetime name")?);
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+
+        let mut outlives = Vec::new();
+        if self.eat(&TokenType::Where) {
+            loop {
+                let longer = self.expect_ident("a lifetime name")?;
+                self.expect_word("outlives")?;
+                let shorter = self.expect_ident("a lifetime name")?;
+                outlives.push(Outlives { span: self.span_from(longer.span), longer, shorter });
+                if !self.eat(&TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+        self.expect_one_of(&TokenType::RightBracket, &["`,`", "`]`"])?;
+
+        Ok(Some(LifetimeParams { names, outlives, span: self.span_from(start) }))
+    }
+
+    /// `<T, U: Display + Clone>`
+    pub(crate) fn generic_params(&mut self) -> PResult<Vec<GenericParam>> {
+        let mut generics = Vec::new();
+        if !self.eat(&TokenType::Less) {
+            return Ok(generics);
+        }
+
+        loop {
+            let name = self.expect_ident("a generic parameter name")?;
+            let start = name.span;
+            let bounds = if self.eat(&TokenType::Colon) { self.trait_bounds()? } else { Vec::new() };
+            generics.push(GenericParam { name, bounds, span: self.span_from(start) });
+
+            if !self.eat(&TokenType::Comma) || self.check(&TokenType::Greater) {
+                break;
+            }
+        }
+
+        self.expect_closing_angle()?;
+        Ok(generics)
+    }
+
+    /// `where T: Display, F: Fn(T) bool`, trailing comma allowed
+    pub(crate) fn where_clause(&mut self) -> PResult<Option<WhereClause>> {
+        let start = self.peek().span;
+        if !self.eat(&TokenType::Where) {
+            return Ok(None);
+        }
+
+        let mut predicates = Vec::new();
+        loop {
+            let name = self.expect_ident("a generic parameter name")?;
+            let predicate_start = name.span;
+            self.expect(&TokenType::Colon, "`:`")?;
+            let bounds = self.trait_bounds()?;
+            predicates.push(WherePredicate { name, bounds, span: self.span_from(predicate_start) });
+
+            if !self.eat(&TokenType::Comma) || !matches!(self.peek_kind(), TokenType::Ident(_)) {
+                break;
+            }
+        }
+
+        Ok(Some(WhereClause { predicates, span: self.span_from(start) }))
+    }
+
+    /// `Display + Clone`
+    fn trait_bounds(&mut self) -> PResult<Vec<TraitBound>> {
+        let mut bounds = vec![self.trait_bound()?];
+        while self.eat(&TokenType::Plus) {
+            bounds.push(self.trait_bound()?);
+        }
+        Ok(bounds)
+    }
+
+    /// `Display`, `Iterator<Item = T>` or `FnOnce(&JsonView) Response`
+    fn trait_bound(&mut self) -> PResult<TraitBound> {
+        let start = self.peek().span;
+        let name = self.expect_ident("a trait bound")?;
+
+        let kind = if self.check(&TokenType::LeftParen) {
+            let params = self.type_list(&TokenType::LeftParen, &TokenType::RightParen)?;
+            let ret = if !self.newline_before() && self.at_type_start() { Some(Box::new(self.type_expr()?)) } else { None };
+            BoundKind::Function { name, params, ret }
+        } else if self.eat(&TokenType::Less) {
+            let mut args = Vec::new();
+            let mut bindings = Vec::new();
+            loop {
+                let arg_start = self.peek().span;
+                if matches!(self.peek_kind(), TokenType::Ident(_)) && self.check_nth(1, &TokenType::Equal) {
+                    let name = self.expect_ident("an associated type")?;
+                    self.advance();
+                    let ty = self.type_expr()?;
+                    bindings.push(AssocBinding { name, ty, span: self.span_from(arg_start) });
+                } else {
+                    let ty = self.type_expr()?;
+                    if !bindings.is_empty() {
+                        return Err(Box::new(type_arg_after_binding(&ty)));
+                    }
+                    args.push(ty);
+                }
+                if !self.eat(&TokenType::Comma) {
+                    break;
+                }
+            }
+            self.expect_closing_angle()?;
+            BoundKind::Trait { name, args, bindings }
+        } else {
+            BoundKind::Trait { name, args: Vec::new(), bindings: Vec::new() }
+        };
+
+        Ok(TraitBound { kind, span: self.span_from(start) })
+    }
+
+    fn struct_decl(&mut self, start: Span, attributes: Vec<Attribute>, is_pub: bool) -> PResult<StructDecl> {
+        let is_edge = self.eat(&TokenType::Edge);
+        self.expect(&TokenType::Struct, "`struct`")?;
+        let name = self.expect_ident("a struct name")?;
+        let generics = self.generic_params()?;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut members = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.at_eof() {
+            members.push(self.struct_member()?);
+            self.eat(&TokenType::Comma);
+        }
+        self.expect(&TokenType::RightBrace, "`}`")?;
+
+        Ok(StructDecl { attributes, is_pub, is_edge, name, generics, members, span: self.span_from(start) })
+    }
+
+    fn struct_member(&mut self) -> PResult<StructMember> {
+        let start = self.peek().span;
+        let attributes = self.attributes()?;
+        let is_pub = self.eat(&TokenType::Pub);
+
+        if self.check(&TokenType::Fn) || self.check(&TokenType::Async) || !attributes.is_empty() {
+            return Ok(StructMember::Method(self.function(start, attributes, is_pub)?));
+        }
+
+        if !matches!(self.peek_kind(), TokenType::Ident(_)) {
+            return Err(self.error_expected_one_of(&["a field name", "`fn`"]));
+        }
+        let name = self.expect_ident("a field name")?;
+        self.expect(&TokenType::Colon, "`:`")?;
+        let ty = self.type_expr()?;
+
+        if !self.check(&TokenType::LeftBrace) {
+            return Ok(StructMember::Field(FieldDecl { is_pub, name, ty, span: self.span_from(start) }));
+        }
+
+        self.advance();
+        let mut getter = None;
+        let mut setter = None;
+        while !self.check(&TokenType::RightBrace) {
+            let (slot, keyword) = match self.peek_kind() {
+                TokenType::Get => (&mut getter, "get"),
+                TokenType::Set => (&mut setter, "set"),
+                _ => return Err(self.error_expected_one_of(&["`get`", "`set`", "`}`"])),
+            };
+            let accessor = self.accessor()?;
+            if slot.is_some() {
+                self.record(ParseError::InvalidSyntax {
+                    message: format!("property `{}` already has a `{}` accessor", name.name, keyword),
+                    span: accessor.span,
+                    suggestion: Some(format!("Remove the second `{}`", keyword)),
+                });
+            } else {
+                *slot = Some(accessor);
+            }
+        }
+        self.advance();
+
+        let span = self.span_from(start);
+        // Keep the property, with an auto getter in place of the missing
+        // one, so the rest of the struct still parses
+        let getter = getter.unwrap_or_else(|| {
+            self.record(ParseError::InvalidSyntax {
+                message: format!("property `{}` has no `get` accessor", name.name),
+                span,
+                suggestion: Some("Add a `get` accessor; write-only properties are not supported".to_string()),
+            });
+            Accessor { value: None, body: None, span }
+        });
+        Ok(StructMember::Property(PropertyDecl { is_pub, name, ty, getter, setter, span }))
+    }
+
+    /// `get { ... }`, `set { ... }`, or the auto-property forms `get;` and `set;`
+    fn accessor(&mut self) -> PResult<Accessor> {
+        let keyword = self.advance();
+        let value = (keyword.kind == TokenType::Set).then(|| Ident { name: SETTER_VALUE.to_string(), span: keyword.span });
+        let body = if self.eat(&TokenType::Semicolon) { None } else { Some(self.block()?) };
+        Ok(Accessor { value, body, span: self.span_from(keyword.span) })
+    }
+
+    fn enum_decl(&mut self, start: Span, attributes: Vec<Attribute>, is_pub: bool) -> PResult<EnumDecl> {
+        self.expect(&TokenType::Enum, "`enum`")?;
+        let name = self.expect_ident("an enum name")?;
+        let generics = self.generic_params()?;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut variants = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let name = self.expect_ident("a variant name")?;
+            let variant_start = name.span;
+            let payload = if self.check(&TokenType::LeftParen) {
+                Some(EnumPayload::Tuple { types: self.type_list(&TokenType::LeftParen, &TokenType::RightParen)? })
+            } else if self.eat(&TokenType::LeftBrace) {
+                let mut fields = Vec::new();
+                while !self.check(&TokenType::RightBrace) {
+                    let field_start = self.peek().span;
+                    let is_pub = self.eat(&TokenType::Pub);
+                    let name = self.expect_ident("a field name")?;
+                    self.expect(&TokenType::Colon, "`:`")?;
+                    let ty = self.type_expr()?;
+                    fields.push(FieldDecl { is_pub, name, ty, span: self.span_from(field_start) });
+                    if !self.eat(&TokenType::Comma) {
+                        break;
+                    }
+                }
+                self.expect_one_of(&TokenType::RightBrace, &["`,`", "`}`"])?;
+                Some(EnumPayload::Struct { fields })
+            } else {
+                None
+            };
+            variants.push(EnumVariant { name, payload, span: self.span_from(variant_start) });
+
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightBrace, &["`,`", "`}`"])?;
+
+        Ok(EnumDecl { attributes, is_pub, name, generics, variants, span: self.span_from(start) })
+    }
+
+    fn trait_decl(&mut self, start: Span, attributes: Vec<Attribute>, is_pub: bool) -> PResult<TraitDecl> {
+        self.expect(&TokenType::Trait, "`trait`")?;
+        let name = self.expect_ident("a trait name")?;
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+
+        let mut items = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.at_eof() {
+            let item_start = self.peek().span;
+            if self.check_ident("type") {
+                self.advance();
+                let name = self.expect_ident("an associated type name")?;
+                self.eat(&TokenType::Semicolon);
+                items.push(TraitItem::AssociatedType(AssociatedType { name, span: self.span_from(item_start) }));
+                continue;
+            }
+
+            let is_async = self.eat(&TokenType::Async);
+            self.expect_one_of(&TokenType::Fn, &["`fn`", "`type`"])?;
+            let name = self.expect_ident("a method name")?;
+            let lifetimes = self.lifetime_params()?;
+            let generics = self.generic_params()?;
+            let params = self.params()?;
+            let return_type = self.return_type()?;
+            let where_clause = self.where_clause()?;
+            self.eat(&TokenType::Semicolon);
+            items.push(TraitItem::Method(MethodSignature {
+                is_async,
+                name,
+                lifetimes,
+                generics,
+                params,
+                return_type,
+                where_clause,
+                span: self.span_from(item_start),
+            }));
+        }
+        self.expect(&TokenType::RightBrace, "`}`")?;
+
+        Ok(TraitDecl { attributes, is_pub, name, items, span: self.span_from(start) })
+    }
+
+    /// `impl Trait for Type`, `impl for Type` or `impl Type`
+    fn impl_block(&mut self, start: Span, attributes: Vec<Attribute>) -> PResult<ImplBlock> {
+        self.expect(&TokenType::Impl, "`impl`")?;
+        let generics = self.generic_params()?;
+
+        let (trait_name, target) = if self.eat(&TokenType::For) {
+            (None, self.type_expr()?)
+        } else {
+            let first = self.type_expr()?;
+            if self.eat(&TokenType::For) {
+                (Some(first), self.type_expr()?)
+            } else {
+                (None, first)
+            }
+        };
+
+        let where_clause = self.where_clause()?;
+        let methods = self.methods()?;
+        Ok(ImplBlock { attributes, generics, trait_name, target, where_clause, methods, span: self.span_from(start) })
+    }
+
+    /// `extend Type { ... }` or `extend<T> Type { ... }`
+    fn extend_block(&mut self, start: Span, attributes: Vec<Attribute>) -> PResult<ExtendBlock> {
+        self.expect(&TokenType::Extend, "`extend`")?;
+        let generics = self.generic_params()?;
+        let target = self.type_expr()?;
+        let where_clause = self.where_clause()?;
+        let methods = self.methods()?;
+        Ok(ExtendBlock { attributes, generics, target, where_clause, methods, span: self.span_from(start) })
+    }
+
+    /// `{ fn ... }` of an `impl` or `extend` block
+    fn methods(&mut self) -> PResult<Vec<FunctionDecl>> {
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.at_eof() {
+            let method_start = self.peek().span;
+            let attributes = self.attributes()?;
+            let is_pub = self.eat(&TokenType::Pub);
+            methods.push(self.function(method_start, attributes, is_pub)?);
+        }
+        self.expect(&TokenType::RightBrace, "`}`")?;
+        Ok(methods)
+    }
+
+    fn const_decl(&mut self, start: Span, attributes: Vec<Attribute>, is_pub: bool) -> PResult<ConstDecl> {
+        self.expect(&TokenType::Const, "`const`")?;
+        let name = self.expect_ident("a constant name")?;
+        let ty = if self.eat(&TokenType::Colon) { Some(self.type_expr()?) } else { None };
+        self.expect(&TokenType::Equal, "`=`")?;
+        let value = self.expr()?;
+        self.eat(&TokenType::Semicolon);
+
+        Ok(ConstDecl { attributes, is_pub, name, ty, value, span: self.span_from(start) })
+    }
+}
+
+/// `Iterator<Item = T, U>`: a type argument after an associated type binding
+pub(crate) fn type_arg_after_binding(ty: &TypeExpr) -> ParseError {
+    ParseError::InvalidSyntax {
+        message: "type arguments must come before associated type bindings".to_string(),
+        span: ty.span,
+        suggestion: Some("Move the `Name = Type` bindings to the end".to_string()),
+    }
+}
@@ -0,0 +1,688 @@
+//! LALRPOP-generated parser - alternative entry point to the recursive-descent parser
+//!
+//! `grammar.lalrpop` mirrors `docs/ubel.ebnf` and builds the same AST. It runs
+//! on the `LogosLexer` token stream, adapted here into `(start, token, end)`
+//! triples.
+//!
+//! The lexer drops line breaks, but the grammar needs them to end statements
+//! and to keep `f\n(x)` from being a call. `Tokens` therefore re-inserts a
+//! `TokenType::Newline` where a line break sits between a token that can end a
+//! statement and one that can start the next, and nowhere else.
+//!
+//! An LR(1) parser cannot tell `{ x = 1 }` the object from a block that
+//! starts with an assignment until after `x`, so `Tokens` also hands it
+//! `TokenType::ObjectBrace` for each `{` that opens an object or dictionary
+//! literal, decided by the same rule as the recursive-descent parser.
+//!
+//! Query words such as `select` and `orderby` are identifiers outside
+//! queries, so `Tokens` also tracks the queries open at each bracket depth
+//! and hands the parser `TokenType::Contextual` for those words directly
+//! inside one. A line break before a query clause does not end a statement.
+//!
+//! A `{` on a new line usually continues the line before (`fn main()\n{`),
+//! but directly inside the braces of a `match` it starts an arm with an
+//! object pattern, so `Tokens` tracks those braces too.
+
+use std::cell::RefCell;
+use lalrpop_util::lalrpop_mod;
+use crate::error_management::{Diagnostic, ErrorManager};
+use crate::error_management::error_types::ParseError;
+use crate::lexer::{self, keywords, Span, Token, TokenType};
+use crate::parser::ast::*;
+use crate::parser::{items, patterns, queries, Parser};
+
+lalrpop_mod!(
+    #[allow(clippy::all, clippy::pedantic, unused_parens)]
+    grammar,
+    "/parser/grammar.rs"
+);
+
+/// A token with its byte range, as LALRPOP expects from an external lexer
+pub type Spanned = (usize, TokenType, usize);
+
+/// Parse with the generated LR(1) parser
+pub fn parse(source: &str) -> Result<Program, ErrorManager> {
+    parse_with_diagnostics(source).map(|(program, _)| program)
+}
+
+/// Parse with the generated LR(1) parser, returning lexer warnings alongside the tree
+pub fn parse_with_diagnostics(source: &str) -> Result<(Program, ErrorManager), ErrorManager> {
+    let (tokens, mut diagnostics) = lexer::tokenize_with_diagnostics(source)?;
+    let index = LineIndex::new(source);
+
+    match grammar::ProgramParser::new().parse(&index, Tokens::new(tokens, source)) {
+        Ok(program) => {
+            for warning in index.warnings.take() {
+                diagnostics.add_diagnostic(warning);
+            }
+            Ok((program, diagnostics))
+        }
+        Err(error) => {
+            diagnostics.add_parse_error(index.parse_error(error));
+            Err(diagnostics)
+        }
+    }
+}
+
+// ========================================
+// Token adapter
+// ========================================
+
+/// `LogosLexer` tokens as `(start, token, end)` triples, with line breaks re-inserted
+pub struct Tokens<'a> {
+    tokens: std::vec::IntoIter<Token>,
+    source: &'a str,
+    prev: Option<Token>,
+    pending: Option<Token>,
+    /// Brackets open around the current token
+    depth: usize,
+    /// The bracket depth of each query not yet ended by `select` or `by`
+    queries: Vec<usize>,
+    /// The bracket depth of each `match` still waiting for its `{`
+    scrutinees: Vec<usize>,
+    /// The bracket depth directly inside each open `match { ... }`
+    arms: Vec<usize>,
+    /// The bracket depth inside each open `fn(` parameter list
+    lambda_params: Vec<usize>,
+    /// The previous token closed a `fn(` parameter list, so a body follows
+    after_params: bool,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(tokens: Vec<Token>, source: &'a str) -> Self {
+        Tokens {
+            tokens: tokens.into_iter(),
+            source,
+            prev: None,
+            pending: None,
+            depth: 0,
+            queries: Vec::new(),
+            scrutinees: Vec::new(),
+            arms: Vec::new(),
+            lambda_params: Vec::new(),
+            after_params: false,
+        }
+    }
+
+    /// Tokens after which a line break may end a statement
+    fn can_end(kind: &TokenType) -> bool {
+        matches!(
+            kind,
+            TokenType::Ident(_)
+                | TokenType::IntLit(_)
+                | TokenType::FloatLit(_)
+                | TokenType::DoubleLit(_)
+                | TokenType::StringLit(_)
+                | TokenType::InterpolatedString(_)
+                | TokenType::VerbatimString(_)
+                | TokenType::CharLit(_)
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Null
+                | TokenType::SelfKw
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::RightParen
+                | TokenType::RightBracket
+                | TokenType::RightBrace
+                | TokenType::Question
+                | TokenType::Bang
+                // Closing generics: `items: List<int>`
+                | TokenType::Greater
+                | TokenType::RightShift
+        )
+    }
+
+    /// Tokens that start a statement, item or member but never continue an expression.
+    /// `-`, `+` and `{` are left out: the recursive-descent parser treats them as
+    /// continuing the previous line.
+    fn can_start(kind: &TokenType) -> bool {
+        matches!(
+            kind,
+            TokenType::Ident(_)
+                | TokenType::IntLit(_)
+                | TokenType::FloatLit(_)
+                | TokenType::DoubleLit(_)
+                | TokenType::StringLit(_)
+                | TokenType::InterpolatedString(_)
+                | TokenType::VerbatimString(_)
+                | TokenType::CharLit(_)
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Null
+                | TokenType::SelfKw
+                | TokenType::LeftParen
+                | TokenType::LeftBracket
+                | TokenType::Bang
+                | TokenType::Tilde
+                | TokenType::At
+                | TokenType::Fn
+                | TokenType::Let
+                | TokenType::Const
+                | TokenType::Extract
+                | TokenType::If
+                | TokenType::Match
+                | TokenType::For
+                | TokenType::While
+                | TokenType::Loop
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Return
+                | TokenType::Summon
+                | TokenType::From
+                | TokenType::Package
+                | TokenType::Async
+                | TokenType::Await
+                | TokenType::Try
+                | TokenType::Unsafe
+                | TokenType::With
+                | TokenType::Using
+                | TokenType::Defer
+                | TokenType::Struct
+                | TokenType::Enum
+                | TokenType::Trait
+                | TokenType::Impl
+                | TokenType::Extend
+                | TokenType::Pub
+                | TokenType::Edge
+                | TokenType::Not
+        )
+    }
+
+    /// The `{` just taken opens an object literal, judged by the two tokens after it
+    fn opens_object(&self) -> bool {
+        let mut ahead = self.ahead();
+        let (Some(key), Some(eq)) = (ahead.next(), ahead.next()) else { return false };
+        Parser::opens_object_literal(self.prev.as_ref().map(|t| &t.kind), &key.kind, &eq.kind)
+    }
+
+    /// The tokens after the current one, comments and line breaks skipped
+    fn ahead(&self) -> impl Iterator<Item = &Token> {
+        self.tokens.as_slice().iter().filter(|t| !matches!(t.kind, TokenType::Comment(_) | TokenType::DocComment(_) | TokenType::Newline))
+    }
+
+    /// Directly inside a query, where query words are keywords
+    fn in_query(&self) -> bool {
+        self.queries.last() == Some(&self.depth)
+    }
+
+    /// `token` starts another clause of the current query
+    fn continues_query(&self, token: &Token) -> bool {
+        self.in_query()
+            && match &token.kind {
+                TokenType::From | TokenType::Let => true,
+                TokenType::Ident(word) => keywords::query_keyword(word).is_some(),
+                _ => false,
+            }
+    }
+
+    /// `token` is a `{` that starts a match arm: `{ kind, data } => ...`
+    fn starts_arm(&self, token: &Token) -> bool {
+        if self.arms.last() != Some(&self.depth) || token.kind != TokenType::LeftBrace {
+            return false;
+        }
+        // After a name, `{ x = 1 }` and `{}` are a struct literal, as in the
+        // recursive-descent parser
+        let mut ahead = self.ahead();
+        let struct_literal = matches!(
+            (ahead.next().map(|t| &t.kind), ahead.next().map(|t| &t.kind)),
+            (Some(TokenType::RightBrace), _) | (Some(TokenType::Ident(_)), Some(TokenType::Equal))
+        );
+        !(struct_literal && matches!(self.prev.as_ref().map(|t| &t.kind), Some(TokenType::Ident(_))))
+    }
+
+    /// Substitute query words and track brackets and queries for `token`
+    fn classify(&mut self, token: Token) -> Token {
+        let after_dot = matches!(self.prev.as_ref().map(|t| &t.kind), Some(TokenType::Dot | TokenType::QuestionDot));
+        let kind = match &token.kind {
+            TokenType::LeftBrace if self.opens_object() => TokenType::ObjectBrace,
+            TokenType::Ident(word) if self.in_query() && !after_dot => match keywords::query_keyword(word) {
+                Some(keyword) => TokenType::Contextual(keyword),
+                None => return token,
+            },
+            _ => return self.track(token),
+        };
+        self.track(Token { kind, ..token })
+    }
+
+    fn track(&mut self, token: Token) -> Token {
+        let closes_params = token.kind == TokenType::RightParen && self.lambda_params.last() == Some(&self.depth);
+        match &token.kind {
+            TokenType::LeftBrace if self.scrutinees.last() == Some(&self.depth) => {
+                self.scrutinees.pop();
+                self.depth += 1;
+                self.arms.push(self.depth);
+            }
+            TokenType::LeftParen if matches!(self.prev.as_ref().map(|t| &t.kind), Some(TokenType::Fn)) => {
+                self.depth += 1;
+                self.lambda_params.push(self.depth);
+            }
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace | TokenType::ObjectBrace => self.depth += 1,
+            TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                self.depth = self.depth.saturating_sub(1);
+                while self.lambda_params.last().is_some_and(|&d| d > self.depth) {
+                    self.lambda_params.pop();
+                }
+                while self.queries.last().is_some_and(|&d| d > self.depth) {
+                    self.queries.pop();
+                }
+                while self.arms.last().is_some_and(|&d| d > self.depth) {
+                    self.arms.pop();
+                }
+            }
+            // Not `regex.match(text)`
+            TokenType::Match if !matches!(self.prev.as_ref().map(|t| &t.kind), Some(TokenType::Dot | TokenType::QuestionDot)) => {
+                self.scrutinees.push(self.depth);
+            }
+            // `from x in` opens a query, unless it is the next `from` clause of
+            // this one. After `fn()` it is the lambda's body.
+            TokenType::From => {
+                let opens = {
+                    let mut ahead = self.ahead();
+                    matches!(ahead.next().map(|t| &t.kind), Some(TokenType::Ident(_)))
+                        && matches!(ahead.next().map(|t| &t.kind), Some(TokenType::In))
+                };
+                let next_clause =
+                    self.in_query() && !self.after_params && self.prev.as_ref().is_some_and(|t| Self::can_end(&t.kind));
+                if opens && !next_clause {
+                    self.queries.push(self.depth);
+                }
+            }
+            TokenType::Contextual("select" | "by") if self.in_query() => {
+                self.queries.pop();
+            }
+            _ => {}
+        }
+        self.after_params = closes_params;
+        token
+    }
+
+    fn newline_between(&self, prev: &Token, next: &Token) -> bool {
+        let start = next.span.start.max(prev.span.end);
+        self.source[prev.span.end..start].contains('\n')
+    }
+
+    fn triple(token: Token) -> Result<Spanned, Box<ParseError>> {
+        match token.kind {
+            TokenType::Error(message) => {
+                Err(Box::new(ParseError::InvalidSyntax { message, span: token.span, suggestion: None }))
+            }
+            kind => Ok((token.span.start, kind, token.span.end)),
+        }
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Result<Spanned, Box<ParseError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending.take() {
+            let token = self.classify(token);
+            self.prev = Some(token.clone());
+            return Some(Self::triple(token));
+        }
+
+        let token = self.tokens.by_ref().find(|t| {
+            !matches!(t.kind, TokenType::Comment(_) | TokenType::DocComment(_) | TokenType::Newline)
+        })?;
+        if token.kind == TokenType::Eof {
+            return None;
+        }
+
+        let separated = match &self.prev {
+            Some(prev) => {
+                Self::can_end(&prev.kind)
+                    && (Self::can_start(&token.kind) || self.starts_arm(&token))
+                    && !self.continues_query(&token)
+                    && self.newline_between(prev, &token)
+            }
+            None => false,
+        };
+        if separated {
+            // Zero-width at the next token so `@L` never lands on the line break
+            let at = token.span.start;
+            self.pending = Some(token);
+            return Some(Ok((at, TokenType::Newline, at)));
+        }
+
+        let token = self.classify(token);
+        self.prev = Some(token.clone());
+        Some(Self::triple(token))
+    }
+}
+
+// ========================================
+// Spans and errors
+// ========================================
+
+/// Byte offsets to line and column, for building `Span`s in grammar actions
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    /// Lexer warnings from interpolation holes, collected as actions run
+    warnings: RefCell<Vec<Diagnostic>>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { source, line_starts, warnings: RefCell::new(Vec::new()) }
+    }
+
+    /// Span from `start` to `end`, positioned at `start`
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let line = self.line_starts.partition_point(|&s| s <= start);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..start].chars().count() + 1;
+        Span::new(start, end, line, column)
+    }
+
+    /// Length of the source, where the program span ends
+    pub fn len(&self) -> usize {
+        self.source.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    /// Source text between two offsets
+    pub fn text(&self, start: usize, end: usize) -> &'a str {
+        &self.source[start..end]
+    }
+
+    /// A user error for a fallible grammar action
+    pub(crate) fn error(&self, message: impl Into<String>, start: usize, end: usize) -> LalrError {
+        let error = ParseError::InvalidSyntax { message: message.into(), span: self.span(start, end), suggestion: None };
+        LalrError::User { error: Box::new(error) }
+    }
+
+    fn parse_error(&self, error: LalrError) -> ParseError {
+        match error {
+            LalrError::User { error } => *error,
+            LalrError::InvalidToken { location } => ParseError::InvalidSyntax {
+                message: "invalid token".to_string(),
+                span: self.span(location, location),
+                suggestion: None,
+            },
+            LalrError::UnrecognizedEof { location, expected } => ParseError::UnexpectedEof {
+                expected: Self::expected(&expected),
+                span: self.span(location, location),
+            },
+            LalrError::UnrecognizedToken { token: (start, kind, end), expected } => {
+                let found = match kind {
+                    TokenType::Newline => "a newline".to_string(),
+                    _ => format!("`{}`", &self.source[start..end]),
+                };
+                ParseError::UnexpectedToken { expected: Self::expected(&expected), found, span: self.span(start, end) }
+            }
+            LalrError::ExtraToken { token: (start, _, end) } => ParseError::UnexpectedToken {
+                expected: vec!["end of file".to_string()],
+                found: format!("`{}`", &self.source[start..end]),
+                span: self.span(start, end),
+            },
+        }
+    }
+
+    /// LALRPOP terminal names as prose: `"fn"` becomes `` `fn` ``
+    fn expected(terminals: &[String]) -> Vec<String> {
+        terminals
+            .iter()
+            .map(|t| match t.as_str() {
+                "Identifier" => "an identifier".to_string(),
+                "IntLit" => "an integer".to_string(),
+                "FloatLit" | "DoubleLit" => "a number".to_string(),
+                "StringLit" | "Interpolated" | "Verbatim" => "a string".to_string(),
+                "CharLit" => "a character".to_string(),
+                "Nl" => "a newline".to_string(),
+                quoted => format!("`{}`", quoted.trim_matches('"')),
+            })
+            .fold(Vec::new(), |mut names, name| {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                names
+            })
+    }
+}
+
+pub(crate) type LalrError = lalrpop_util::ParseError<usize, TokenType, Box<ParseError>>;
+
+// ========================================
+// Grammar helpers
+// ========================================
+
+/// An element of `( ... )` before we know whether it is a tuple or lambda parameters
+pub(crate) enum Cover {
+    Expr(Expr),
+    Param(Box<Param>),
+}
+
+/// An argument of a trait bound, before bindings are split from types
+pub(crate) enum BoundArg {
+    Type(TypeExpr),
+    Binding(AssocBinding),
+}
+
+/// `Name<args>` as a trait bound; bindings must come after the types
+pub(crate) fn trait_bound(name: Ident, args: Vec<BoundArg>, span: Span) -> Result<TraitBound, LalrError> {
+    let mut types = Vec::new();
+    let mut bindings = Vec::new();
+    for arg in args {
+        match arg {
+            BoundArg::Type(ty) if !bindings.is_empty() => {
+                return Err(LalrError::User { error: Box::new(items::type_arg_after_binding(&ty)) });
+            }
+            BoundArg::Type(ty) => types.push(ty),
+            BoundArg::Binding(binding) => bindings.push(binding),
+        }
+    }
+    Ok(TraitBound { kind: BoundKind::Trait { name, args: types, bindings }, span })
+}
+
+pub(crate) fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    let span = lhs.span.merge(&rhs.span);
+    Expr::new(ExprKind::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) }, span)
+}
+
+pub(crate) fn assign(op: AssignOp, target: Expr, value: Expr) -> Expr {
+    let span = target.span.merge(&value.span);
+    Expr::new(ExprKind::Assign { op, target: Box::new(target), value: Box::new(value) }, span)
+}
+
+pub(crate) fn range(start: Expr, end: Expr, inclusive: bool) -> Expr {
+    let span = start.span.merge(&end.span);
+    Expr::new(ExprKind::Range { start: Box::new(start), end: Box::new(end), inclusive }, span)
+}
+
+/// `int`, `Task<T>` or a named type, from a name and optional type arguments
+pub(crate) fn named_type(index: &LineIndex, name: Ident, mut args: Vec<TypeExpr>, span: Span) -> Result<TypeExpr, LalrError> {
+    let kind = if name.name == "Task" {
+        if args.len() > 1 {
+            return Err(index.error("expected `>`, found `,`", span.start, span.end));
+        }
+        TypeKind::Task { inner: args.pop().map(Box::new) }
+    } else if let (Some(primitive), true) = (PrimitiveType::from_name(&name.name), args.is_empty()) {
+        TypeKind::Primitive { name: primitive }
+    } else {
+        TypeKind::Named { name, args }
+    };
+    Ok(TypeExpr { kind, span })
+}
+
+/// Check that `name` is the contextual keyword `word` (`lifetime`, `outlives`)
+pub(crate) fn word(index: &LineIndex, name: &Ident, word: &str) -> Result<(), LalrError> {
+    if name.name != word {
+        return Err(index.error(format!("expected `{}`, found `{}`", word, name.name), name.span.start, name.span.end));
+    }
+    Ok(())
+}
+
+/// A property from its accessors in source order; setters carry the implicit `value`
+pub(crate) fn property(is_pub: bool, name: Ident, ty: TypeExpr, accessors: Vec<Accessor>, span: Span) -> Result<StructMember, LalrError> {
+    let mut getter = None;
+    let mut setter = None;
+    for accessor in accessors {
+        let (slot, keyword) = if accessor.value.is_some() { (&mut setter, "set") } else { (&mut getter, "get") };
+        if slot.is_some() {
+            let error = ParseError::InvalidSyntax {
+                message: format!("property `{}` already has a `{}` accessor", name.name, keyword),
+                span: accessor.span,
+                suggestion: Some(format!("Remove the second `{}`", keyword)),
+            };
+            return Err(LalrError::User { error: Box::new(error) });
+        }
+        *slot = Some(accessor);
+    }
+
+    let Some(getter) = getter else {
+        let error = ParseError::InvalidSyntax {
+            message: format!("property `{}` has no `get` accessor", name.name),
+            span,
+            suggestion: Some("Add a `get` accessor; write-only properties are not supported".to_string()),
+        };
+        return Err(LalrError::User { error: Box::new(error) });
+    };
+    Ok(StructMember::Property(PropertyDecl { is_pub, name, ty, getter, setter, span }))
+}
+
+/// A query, rejecting a range variable that is defined twice
+pub(crate) fn query(clauses: Vec<QueryClause>, span: Span) -> Result<Expr, LalrError> {
+    if let Some(error) = queries::duplicate_range_variable(&clauses) {
+        return Err(LalrError::User { error: Box::new(error) });
+    }
+    Ok(Expr::new(ExprKind::Query { clauses }, span))
+}
+
+/// `$"..."` with its holes parsed in place; the first error in a hole fails the parse
+pub(crate) fn interpolated_string(
+    index: &LineIndex,
+    parts: &[lexer::InterpolationPart],
+    start: usize,
+    end: usize,
+) -> Result<Expr, LalrError> {
+    let span = index.span(start, end);
+    let (parts, errors, warnings) = Parser::interpolated_parts(parts, index.text(start, end), span, index.source);
+    index.warnings.borrow_mut().extend(warnings);
+    match errors.into_iter().next() {
+        Some(error) => Err(LalrError::User { error: Box::new(error) }),
+        None => Ok(Expr::new(ExprKind::InterpolatedString { parts }, span)),
+    }
+}
+
+pub(crate) fn extract_pattern(pattern: Pattern) -> Result<Pattern, LalrError> {
+    match patterns::refutable_extract(&pattern) {
+        Some(error) => Err(LalrError::User { error: Box::new(error) }),
+        None => Ok(pattern),
+    }
+}
+
+pub(crate) fn let_else_pattern(pattern: Pattern) -> Result<Pattern, LalrError> {
+    match patterns::irrefutable_let_else(&pattern) {
+        Some(error) => Err(LalrError::User { error: Box::new(error) }),
+        None => Ok(pattern),
+    }
+}
+
+pub(crate) fn list_pattern(elems: Vec<Pattern>, span: Span) -> Result<Pattern, LalrError> {
+    if let Some(error) = patterns::second_rest(&elems) {
+        return Err(LalrError::User { error: Box::new(error) });
+    }
+    Ok(Pattern { kind: PatternKind::List { elems }, span })
+}
+
+/// `{ name = value }` or `{ "key" = value }` from its entries; the first key decides which
+pub(crate) fn object_literal(entries: Vec<DictEntry>, span: Span) -> Result<Expr, LalrError> {
+    let is_dict = entries.first().is_some_and(|e| matches!(e.key.kind, ExprKind::Literal { .. }));
+    let mut keys = Vec::new();
+    for entry in &entries {
+        let key = match &entry.key.kind {
+            ExprKind::Ident { name } => format!("`{}`", name),
+            ExprKind::Literal { value: Literal::String(text) } => format!("{:?}", text),
+            _ => unreachable!("the grammar only builds name and string keys"),
+        };
+        let error = if matches!(entry.key.kind, ExprKind::Literal { .. }) != is_dict {
+            ParseError::InvalidSyntax {
+                message: "a literal cannot mix field names and string keys".to_string(),
+                span: entry.key.span,
+                suggestion: Some("Use names for an object, or quoted strings for a dictionary".to_string()),
+            }
+        } else if keys.contains(&key) {
+            ParseError::InvalidSyntax {
+                message: format!("duplicate key {}", key),
+                span: entry.key.span,
+                suggestion: Some("Remove one of the entries".to_string()),
+            }
+        } else {
+            keys.push(key);
+            continue;
+        };
+        return Err(LalrError::User { error: Box::new(error) });
+    }
+
+    let kind = if is_dict {
+        ExprKind::DictLit { entries }
+    } else {
+        let fields = entries
+            .into_iter()
+            .map(|DictEntry { key, value, span }| {
+                let ExprKind::Ident { name } = key.kind else { unreachable!("checked above") };
+                FieldInit { name: Ident { name, span: key.span }, value, span }
+            })
+            .collect();
+        ExprKind::ObjectLit { fields }
+    };
+    Ok(Expr::new(kind, span))
+}
+
+/// `(e)` groups, `(a, b)` and `()` are tuples
+pub(crate) fn paren_expr(index: &LineIndex, mut elems: Vec<Cover>, trailing_comma: bool, span: Span) -> Result<Expr, LalrError> {
+    let mut exprs = Vec::new();
+    for elem in elems.drain(..) {
+        match elem {
+            Cover::Expr(expr) => exprs.push(expr),
+            Cover::Param(param) => {
+                return Err(index.error("expected `=>` after lambda parameters", param.span.start, param.span.end));
+            }
+        }
+    }
+
+    if exprs.len() == 1 && !trailing_comma {
+        let inner = exprs.remove(0);
+        return Ok(Expr { span, ..inner });
+    }
+    Ok(Expr::new(ExprKind::Tuple { elems: exprs }, span))
+}
+
+/// The parameters of `(a, b: int, c = 1) => ...`
+pub(crate) fn lambda_params(index: &LineIndex, elems: Vec<Cover>) -> Result<Vec<Param>, LalrError> {
+    elems
+        .into_iter()
+        .map(|elem| {
+            let expr = match elem {
+                Cover::Param(param) => return Ok(*param),
+                Cover::Expr(expr) => expr,
+            };
+            let span = expr.span;
+            match expr.kind {
+                ExprKind::Ident { name } => Ok(Param { is_mut: false, name: Ident { name, span }, pattern: None, ty: None, default: None, span }),
+                ExprKind::SelfRef => Ok(Param { is_mut: false, name: Ident { name: "self".to_string(), span }, pattern: None, ty: None, default: None, span }),
+                ExprKind::Assign { op: AssignOp::Assign, target, value } => match target.kind {
+                    ExprKind::Ident { name } => Ok(Param {
+                        is_mut: false,
+                        name: Ident { name, span: target.span },
+                        pattern: None,
+                        ty: None,
+                        default: Some(*value),
+                        span,
+                    }),
+                    _ => Err(index.error("expected a parameter name", target.span.start, target.span.end)),
+                },
+                _ => Err(index.error("expected a parameter name", span.start, span.end)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+//! Parser module - tokens to typed AST
+
+pub mod ast;
+pub mod desugar;
+pub mod lalr;
+pub mod precedence;
+pub mod printer;
+pub mod recursive_descent;
+pub mod tree;
+pub mod visit;
+pub mod visit_mut;
+pub mod fold;
+
+mod items;
+mod statements;
+mod expressions;
+mod types;
+mod patterns;
+mod queries;
+
+pub use ast::Program;
+pub use recursive_descent::{Parser, PResult};
+pub use tree::AstFormat;
+pub use lalr::parse as parse_lalr;
+
+use crate::error_management::ErrorManager;
+use crate::lexer::{LogosLexer, Token};
+
+/// Main parsing entry point
+pub fn parse(source: &str) -> Result<Program, ErrorManager> {
+    parse_with_diagnostics(source).map(|(program, _)| program)
+}
+
+/// Parse, returning any lexer warnings alongside the tree
+pub fn parse_with_diagnostics(source: &str) -> Result<(Program, ErrorManager), ErrorManager> {
+    let (program, diagnostics) = parse_recovering(source);
+    if diagnostics.has_errors() {
+        Err(diagnostics)
+    } else {
+        Ok((program, diagnostics))
+    }
+}
+
+/// Parse past errors: lexical and syntax errors are all collected, and the
+/// tree has `Error` nodes wherever input was skipped
+pub fn parse_recovering(source: &str) -> (Program, ErrorManager) {
+    let (tokens, diagnostics) = LogosLexer::new(source).tokenize_all();
+    parse_tokens(tokens, diagnostics, source)
+}
+
+/// `parse_recovering` over tokens the caller already has
+pub(crate) fn parse_tokens(tokens: Vec<Token>, mut diagnostics: ErrorManager, source: &str) -> (Program, ErrorManager) {
+    let mut parser = Parser::new(tokens, source);
+    let program = parser.parse_program();
+    for warning in parser.take_warnings() {
+        diagnostics.add_diagnostic(warning);
+    }
+    for error in parser.take_errors() {
+        diagnostics.add_parse_error(error);
+    }
+    (program, diagnostics)
+}
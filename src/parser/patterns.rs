@@ -0,0 +1,243 @@
+//! Patterns for `match` arms, `for` loops, `extract` and `let` conditions
+
+use crate::error_management::error_types::ParseError;
+use crate::lexer::TokenType;
+use crate::parser::ast::*;
+use crate::parser::printer;
+use crate::parser::recursive_descent::{PResult, Parser};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn pattern(&mut self) -> PResult<Pattern> {
+        let token = self.peek().clone();
+        let start = token.span;
+
+        if let Some(value) = self.literal_pattern()? {
+            return Ok(Pattern { kind: PatternKind::Literal { value }, span: self.span_from(start) });
+        }
+
+        let kind = match &token.kind {
+            TokenType::Ident(name) if name == "_" => {
+                self.advance();
+                PatternKind::Wildcard
+            }
+            TokenType::Ident(_) => {
+                let mut path = vec![self.expect_ident("a pattern")?];
+                while self.eat(&TokenType::Dot) {
+                    path.push(self.expect_ident("a variant name after `.`")?);
+                }
+
+                if self.check(&TokenType::LeftParen) {
+                    let elems = self.pattern_list()?;
+                    PatternKind::TupleStruct { path, elems }
+                } else if self.at_struct_pattern() {
+                    PatternKind::Struct { path, fields: self.field_patterns()? }
+                } else if path.len() == 1 {
+                    PatternKind::Binding { name: path.remove(0) }
+                } else {
+                    PatternKind::Path { path }
+                }
+            }
+            TokenType::LeftParen => {
+                self.advance();
+                let mut elems = Vec::new();
+                let mut trailing_comma = false;
+                while !self.check(&TokenType::RightParen) {
+                    elems.push(self.pattern()?);
+                    trailing_comma = self.eat(&TokenType::Comma);
+                    if !trailing_comma {
+                        break;
+                    }
+                }
+                self.expect_one_of(&TokenType::RightParen, &["`,`", "`)`"])?;
+
+                // `(p)` only groups; `(p,)` is a one-element tuple
+                if elems.len() == 1 && !trailing_comma {
+                    let inner = elems.remove(0);
+                    return Ok(Pattern { span: self.span_from(start), ..inner });
+                }
+                PatternKind::Tuple { elems }
+            }
+            TokenType::LeftBracket => PatternKind::List { elems: self.list_elems()? },
+            TokenType::LeftBrace => PatternKind::Object { fields: self.field_patterns()? },
+            // `Err(extract { code })`: the keyword only marks the destructuring
+            TokenType::Extract => {
+                self.advance();
+                let inner = self.pattern()?;
+                return Ok(Pattern { span: self.span_from(start), ..inner });
+            }
+            _ => return Err(self.error_expected("a pattern")),
+        };
+
+        Ok(Pattern { kind, span: self.span_from(start) })
+    }
+
+    /// `extract pattern`, which must match every value
+    pub(crate) fn extract_pattern(&mut self) -> PResult<Pattern> {
+        self.expect(&TokenType::Extract, "`extract`")?;
+        let pattern = self.pattern()?;
+        if let Some(error) = refutable_extract(&pattern) {
+            self.record(error);
+        }
+        Ok(pattern)
+    }
+
+    /// `let pattern =` at the head of `if let`, `elif let` and `while let`
+    pub(crate) fn let_condition(&mut self) -> PResult<Option<Pattern>> {
+        if !self.eat(&TokenType::Let) {
+            return Ok(None);
+        }
+        let pattern = self.pattern()?;
+        self.expect(&TokenType::Equal, "`=`")?;
+        Ok(Some(pattern))
+    }
+
+    /// `let pattern = value else { ... }`, after the `let`
+    pub(crate) fn let_else(&mut self) -> PResult<StmtKind> {
+        let pattern = self.pattern()?;
+        if let Some(error) = irrefutable_let_else(&pattern) {
+            self.record(error);
+        }
+        self.expect(&TokenType::Equal, "`=`")?;
+        let value = self.expr()?;
+        self.expect(&TokenType::Else, "`else`")?;
+        let else_block = self.block()?;
+        Ok(StmtKind::LetElse { pattern, value, else_block })
+    }
+
+    /// Literals, including negative numbers
+    fn literal_pattern(&mut self) -> PResult<Option<Literal>> {
+        let negative = self.check(&TokenType::Minus)
+            && matches!(self.peek_nth(1).kind, TokenType::IntLit(_) | TokenType::FloatLit(_) | TokenType::DoubleLit(_));
+        if negative {
+            self.advance();
+        }
+
+        let value = match self.peek_kind().clone() {
+            TokenType::IntLit(v) => Literal::Int(if negative { -v } else { v }),
+            TokenType::FloatLit(v) => Literal::Float(if negative { -v } else { v }),
+            TokenType::DoubleLit(v) => Literal::Double(if negative { -v } else { v }),
+            TokenType::StringLit(s) => Literal::String(s),
+            TokenType::VerbatimString(s) => Literal::Verbatim(s),
+            TokenType::CharLit(c) => Literal::Char(c),
+            TokenType::True => Literal::Bool(true),
+            TokenType::False => Literal::Bool(false),
+            TokenType::Null => Literal::Null,
+            _ => return Ok(None),
+        };
+        self.advance();
+        Ok(Some(value))
+    }
+
+    /// `(p, q)` with an optional trailing comma
+    fn pattern_list(&mut self) -> PResult<Vec<Pattern>> {
+        self.expect(&TokenType::LeftParen, "`(`")?;
+        let mut elems = Vec::new();
+        while !self.check(&TokenType::RightParen) {
+            elems.push(self.pattern()?);
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightParen, &["`,`", "`)`"])?;
+        Ok(elems)
+    }
+
+    /// `[p, q, ...rest]`
+    fn list_elems(&mut self) -> PResult<Vec<Pattern>> {
+        self.expect(&TokenType::LeftBracket, "`[`")?;
+        let mut elems = Vec::new();
+        while !self.check(&TokenType::RightBracket) {
+            if self.check(&TokenType::DotDotDot) {
+                let start = self.advance().span;
+                let name = if matches!(self.peek_kind(), TokenType::Ident(_)) { Some(self.expect_ident("a name")?) } else { None };
+                elems.push(Pattern { kind: PatternKind::Rest { name }, span: self.span_from(start) });
+            } else {
+                elems.push(self.pattern()?);
+            }
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightBracket, &["`,`", "`]`"])?;
+        if let Some(error) = second_rest(&elems) {
+            self.record(error);
+        }
+        Ok(elems)
+    }
+
+    /// `{` followed by `}` or `field` and then `,` `=` or `}`
+    fn at_struct_pattern(&self) -> bool {
+        if !self.check(&TokenType::LeftBrace) {
+            return false;
+        }
+        if self.check_nth(1, &TokenType::RightBrace) {
+            return true;
+        }
+        matches!(self.peek_nth(1).kind, TokenType::Ident(_))
+            && (self.check_nth(2, &TokenType::Comma) || self.check_nth(2, &TokenType::Equal) || self.check_nth(2, &TokenType::RightBrace))
+    }
+
+    fn field_patterns(&mut self) -> PResult<Vec<FieldPattern>> {
+        self.expect(&TokenType::LeftBrace, "`{`")?;
+        let mut fields = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let name = self.expect_ident("a field name")?;
+            let start = name.span;
+            let pattern = if self.eat(&TokenType::Equal) { Some(self.pattern()?) } else { None };
+            fields.push(FieldPattern { name, pattern, span: self.span_from(start) });
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(&TokenType::RightBrace, &["`,`", "`}`"])?;
+        Ok(fields)
+    }
+}
+
+/// A second `...` in a list pattern
+pub(crate) fn second_rest(elems: &[Pattern]) -> Option<ParseError> {
+    let second = elems.iter().filter(|e| matches!(e.kind, PatternKind::Rest { .. })).nth(1)?;
+    Some(ParseError::InvalidSyntax {
+        message: "a list pattern can only have one `...`".to_string(),
+        span: second.span,
+        suggestion: Some("Keep one `...` and match the other elements by position".to_string()),
+    })
+}
+
+/// The `else` of `let ... else` runs when the pattern fails, so a pattern
+/// that cannot fail makes it dead code
+pub(crate) fn irrefutable_let_else(pattern: &Pattern) -> Option<ParseError> {
+    if pattern.refutable_part().is_some() || checks_length(pattern) {
+        return None;
+    }
+    Some(ParseError::InvalidSyntax {
+        message: format!("irrefutable pattern in `let ... else`: `{}` matches every value", printer::print_pattern(pattern)),
+        span: pattern.span,
+        suggestion: Some("Drop the `else` block and destructure with `extract`".to_string()),
+    })
+}
+
+/// A list pattern in `pattern` fails on some lengths: it has no `...`, or
+/// elements besides it. `[...rest]` matches every list.
+fn checks_length(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::List { elems } => {
+            !matches!(elems.as_slice(), [Pattern { kind: PatternKind::Rest { .. }, .. }]) || elems.iter().any(checks_length)
+        }
+        PatternKind::Tuple { elems } => elems.iter().any(checks_length),
+        PatternKind::Struct { fields, .. } | PatternKind::Object { fields } => {
+            fields.iter().filter_map(|f| f.pattern.as_ref()).any(checks_length)
+        }
+        _ => false,
+    }
+}
+
+/// `extract` binds without checking, so every value must match the pattern
+pub(crate) fn refutable_extract(pattern: &Pattern) -> Option<ParseError> {
+    let part = pattern.refutable_part()?;
+    Some(ParseError::InvalidSyntax {
+        message: format!("refutable pattern in `extract`: `{}` does not match every value", printer::print_pattern(part)),
+        span: part.span,
+        suggestion: Some("Use `match` to handle the values it does not match".to_string()),
+    })
+}
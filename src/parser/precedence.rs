@@ -0,0 +1,211 @@
+//! Operator precedence and associativity
+//!
+//! The one table the Pratt parser in `expressions` reads. Levels, from
+//! loosest to tightest binding:
+//!
+//! | Level          | Operators                                  | Associativity   |
+//! |----------------|--------------------------------------------|-----------------|
+//! | Assignment     | `=` `+=` `-=` `*=` `/=` `%=` `&=` `\|=` `^=` `<<=` `>>=` | none |
+//! | Pipe           | `\|>`                                      | left            |
+//! | Range          | `..` `...`                                 | none            |
+//! | Or             | `or` `\|\|`                                | left            |
+//! | And            | `and` `&&`                                 | left            |
+//! | Comparison     | `==` `!=` `<` `>` `<=` `>=`                | none            |
+//! | BitOr          | `\|`                                       | left            |
+//! | BitXor         | `^`                                        | left            |
+//! | BitAnd         | `&`                                        | left            |
+//! | Shift          | `<<` `>>`                                  | left            |
+//! | Additive       | `+` `-`                                    | left            |
+//! | Multiplicative | `*` `/` `%`                                | left            |
+//! | Cast           | `as`                                       | left            |
+//! | Prefix         | `!` `not` `-` `+` `~` `await`              | right           |
+//! | Postfix        | `()` `[]` `.` `?.` `?`                     | left            |
+//!
+//! Bitwise operators bind tighter than comparisons, so `a & mask == 0` tests
+//! the masked value. A non-associative operator cannot follow one of its own
+//! level: `a < b < c` is an error, and `a = b = c` ends the statement early.
+
+use crate::lexer::TokenType;
+use crate::parser::ast::{AssignOp, BinaryOp, UnaryOp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precedence {
+    Assignment,
+    Pipe,
+    Range,
+    Or,
+    And,
+    Comparison,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
+    Additive,
+    Multiplicative,
+    Cast,
+    Prefix,
+    Postfix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+/// What an infix operator builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Infix {
+    Binary(BinaryOp),
+    Assign(AssignOp),
+    Range { inclusive: bool },
+    /// `as`, whose right-hand side is a type
+    Cast,
+}
+
+pub struct InfixOperator {
+    pub lexeme: &'static str,
+    pub token: TokenType,
+    pub infix: Infix,
+    pub precedence: Precedence,
+}
+
+pub struct PrefixOperator {
+    pub lexeme: &'static str,
+    pub token: TokenType,
+    pub op: UnaryOp,
+}
+
+macro_rules! infix_operators {
+    ($($lexeme:literal => $token:ident, $infix:expr, $precedence:ident;)*) => {
+        pub static INFIX_OPERATORS: &[InfixOperator] = &[
+            $(InfixOperator {
+                lexeme: $lexeme,
+                token: TokenType::$token,
+                infix: $infix,
+                precedence: Precedence::$precedence,
+            },)*
+        ];
+    };
+}
+
+infix_operators! {
+    "=" => Equal, Infix::Assign(AssignOp::Assign), Assignment;
+    "+=" => PlusEqual, Infix::Assign(AssignOp::Add), Assignment;
+    "-=" => MinusEqual, Infix::Assign(AssignOp::Sub), Assignment;
+    "*=" => StarEqual, Infix::Assign(AssignOp::Mul), Assignment;
+    "/=" => SlashEqual, Infix::Assign(AssignOp::Div), Assignment;
+    "%=" => PercentEqual, Infix::Assign(AssignOp::Rem), Assignment;
+    "&=" => AmpEqual, Infix::Assign(AssignOp::BitAnd), Assignment;
+    "|=" => PipeEqual, Infix::Assign(AssignOp::BitOr), Assignment;
+    "^=" => CaretEqual, Infix::Assign(AssignOp::BitXor), Assignment;
+    "<<=" => LeftShiftEqual, Infix::Assign(AssignOp::Shl), Assignment;
+    ">>=" => RightShiftEqual, Infix::Assign(AssignOp::Shr), Assignment;
+
+    "|>" => PipeGreater, Infix::Binary(BinaryOp::Pipe), Pipe;
+
+    ".." => DotDot, Infix::Range { inclusive: false }, Range;
+    "..." => DotDotDot, Infix::Range { inclusive: true }, Range;
+
+    "or" => Or, Infix::Binary(BinaryOp::Or), Or;
+    "||" => PipePipe, Infix::Binary(BinaryOp::Or), Or;
+
+    "and" => And, Infix::Binary(BinaryOp::And), And;
+    "&&" => AmpAmp, Infix::Binary(BinaryOp::And), And;
+
+    "==" => EqualEqual, Infix::Binary(BinaryOp::Eq), Comparison;
+    "!=" => BangEqual, Infix::Binary(BinaryOp::Ne), Comparison;
+    "<" => Less, Infix::Binary(BinaryOp::Lt), Comparison;
+    ">" => Greater, Infix::Binary(BinaryOp::Gt), Comparison;
+    "<=" => LessEqual, Infix::Binary(BinaryOp::Le), Comparison;
+    ">=" => GreaterEqual, Infix::Binary(BinaryOp::Ge), Comparison;
+
+    "|" => Pipe, Infix::Binary(BinaryOp::BitOr), BitOr;
+    "^" => Caret, Infix::Binary(BinaryOp::BitXor), BitXor;
+    "&" => Amp, Infix::Binary(BinaryOp::BitAnd), BitAnd;
+
+    "<<" => LeftShift, Infix::Binary(BinaryOp::Shl), Shift;
+    ">>" => RightShift, Infix::Binary(BinaryOp::Shr), Shift;
+
+    "+" => Plus, Infix::Binary(BinaryOp::Add), Additive;
+    "-" => Minus, Infix::Binary(BinaryOp::Sub), Additive;
+
+    "*" => Star, Infix::Binary(BinaryOp::Mul), Multiplicative;
+    "/" => Slash, Infix::Binary(BinaryOp::Div), Multiplicative;
+    "%" => Percent, Infix::Binary(BinaryOp::Rem), Multiplicative;
+
+    "as" => As, Infix::Cast, Cast;
+}
+
+pub static PREFIX_OPERATORS: &[PrefixOperator] = &[
+    PrefixOperator { lexeme: "!", token: TokenType::Bang, op: UnaryOp::Not },
+    PrefixOperator { lexeme: "not", token: TokenType::Not, op: UnaryOp::Not },
+    PrefixOperator { lexeme: "-", token: TokenType::Minus, op: UnaryOp::Neg },
+    PrefixOperator { lexeme: "+", token: TokenType::Plus, op: UnaryOp::Plus },
+    PrefixOperator { lexeme: "~", token: TokenType::Tilde, op: UnaryOp::BitNot },
+    PrefixOperator { lexeme: "await", token: TokenType::Await, op: UnaryOp::Await },
+];
+
+impl Precedence {
+    pub const ALL: [Precedence; 15] = [
+        Precedence::Assignment,
+        Precedence::Pipe,
+        Precedence::Range,
+        Precedence::Or,
+        Precedence::And,
+        Precedence::Comparison,
+        Precedence::BitOr,
+        Precedence::BitXor,
+        Precedence::BitAnd,
+        Precedence::Shift,
+        Precedence::Additive,
+        Precedence::Multiplicative,
+        Precedence::Cast,
+        Precedence::Prefix,
+        Precedence::Postfix,
+    ];
+
+    pub fn assoc(self) -> Assoc {
+        match self {
+            Precedence::Assignment | Precedence::Range | Precedence::Comparison => Assoc::None,
+            Precedence::Prefix => Assoc::Right,
+            _ => Assoc::Left,
+        }
+    }
+
+    /// The next tighter level
+    pub fn tighter(self) -> Precedence {
+        let index = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL.get(index + 1).copied().unwrap_or(Precedence::Postfix)
+    }
+
+    /// Minimum level of an operator's right operand
+    pub fn rhs(self) -> Precedence {
+        match self.assoc() {
+            Assoc::Right => self,
+            Assoc::Left | Assoc::None => self.tighter(),
+        }
+    }
+
+    /// Infix operators at this level, in table order
+    pub fn operators(self) -> impl Iterator<Item = &'static InfixOperator> {
+        INFIX_OPERATORS.iter().filter(move |op| op.precedence == self)
+    }
+}
+
+pub fn infix(kind: &TokenType) -> Option<&'static InfixOperator> {
+    INFIX_OPERATORS.iter().find(|op| &op.token == kind)
+}
+
+pub fn prefix(kind: &TokenType) -> Option<UnaryOp> {
+    PREFIX_OPERATORS.iter().find(|op| &op.token == kind).map(|op| op.op)
+}
+
+/// Level of the operator that builds `infix`
+pub fn level(infix: Infix) -> Precedence {
+    INFIX_OPERATORS
+        .iter()
+        .find(|op| op.infix == infix)
+        .map_or(Precedence::Postfix, |op| op.precedence)
+}
@@ -0,0 +1,1111 @@
+//! AST back to canonical `.strat` source
+//!
+//! The output parses back to the same tree, spans aside: parentheses are
+//! added only where the precedence table in `precedence` or the parser's
+//! restrictions (no struct literals in `if` heads, no `x => ...` lambdas
+//! in match guards) would otherwise read the text differently. Comments and
+//! the original layout are not kept; `syntax` is the lossless tree.
+//!
+//! `Error` nodes print as `/* error */`, so a tree with errors does not
+//! round-trip.
+
+use crate::lexer::keywords;
+use crate::parser::ast::*;
+use crate::parser::precedence::{self, Assoc, Infix, Precedence};
+use crate::parser::recursive_descent::Restrictions;
+
+const INDENT: &str = "    ";
+
+/// Canonical source for a whole program
+pub fn print(program: &Program) -> String {
+    let mut printer = Printer::default();
+    printer.program(program);
+    printer.out
+}
+
+pub fn print_expr(expr: &Expr) -> String {
+    let mut printer = Printer::default();
+    printer.expr(expr, Precedence::Assignment);
+    printer.out
+}
+
+pub fn print_type(ty: &TypeExpr) -> String {
+    let mut printer = Printer::default();
+    printer.ty(ty);
+    printer.out
+}
+
+pub fn print_pattern(pattern: &Pattern) -> String {
+    let mut printer = Printer::default();
+    printer.pattern(pattern);
+    printer.out
+}
+
+#[derive(Default)]
+struct Printer {
+    out: String,
+    indent: usize,
+    restrictions: Restrictions,
+}
+
+impl Printer {
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    /// `items` separated by `, `
+    fn comma_list<T>(&mut self, items: &[T], mut each: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            each(self, item);
+        }
+    }
+
+    /// Run `f` under different restrictions, as the parser does
+    fn restricted(&mut self, restrictions: Restrictions, f: impl FnOnce(&mut Self)) {
+        let saved = std::mem::replace(&mut self.restrictions, restrictions);
+        f(self);
+        self.restrictions = saved;
+    }
+
+    /// Print into a fresh buffer at the current indentation
+    fn capture(&mut self, f: impl FnOnce(&mut Self)) -> String {
+        let saved = std::mem::take(&mut self.out);
+        f(self);
+        std::mem::replace(&mut self.out, saved)
+    }
+
+    // ========================================
+    // Program structure
+    // ========================================
+
+    fn program(&mut self, program: &Program) {
+        let Program { package, imports, items, span: _ } = program;
+        let mut sections = Vec::new();
+        if let Some(package) = package {
+            sections.push(format!("package {}\n", package.path.dotted()));
+        }
+        if !imports.is_empty() {
+            sections.push(self.capture(|p| imports.iter().for_each(|i| p.import(i))));
+        }
+        for item in items {
+            sections.push(self.capture(|p| {
+                p.item(item);
+                p.write("\n");
+            }));
+        }
+        self.write(&sections.join("\n"));
+    }
+
+    fn import(&mut self, import: &Import) {
+        match &import.kind {
+            ImportKind::Summon { path, alias } => {
+                self.write(&format!("summon {}", path.dotted()));
+                if let Some(alias) = alias {
+                    self.write(&format!(" as {}", alias.name));
+                }
+            }
+            ImportKind::From { path, items } => {
+                let names: Vec<&str> = items.iter().map(|i| i.name.as_str()).collect();
+                if names.is_empty() {
+                    self.write(&format!("from {} summon []", path.dotted()));
+                } else {
+                    self.write(&format!("from {} summon {}", path.dotted(), names.join(", ")));
+                }
+            }
+        }
+        self.write("\n");
+    }
+
+    fn item(&mut self, item: &Item) {
+        match item {
+            Item::Function(decl) => self.function(decl),
+            Item::Struct(decl) => self.struct_decl(decl),
+            Item::Enum(decl) => self.enum_decl(decl),
+            Item::Trait(decl) => self.trait_decl(decl),
+            Item::Impl(block) => self.impl_block(block),
+            Item::Extend(block) => self.extend_block(block),
+            Item::Const(decl) => self.const_decl(decl),
+            Item::Error(_) => self.write("/* error */"),
+        }
+    }
+
+    /// Attributes, one per line, then `pub` if set
+    fn item_head(&mut self, attributes: &[Attribute], is_pub: bool) {
+        for attribute in attributes {
+            self.write(&format!("@{}", attribute.name.name));
+            if !attribute.args.is_empty() {
+                self.write("(");
+                self.args(&attribute.args);
+                self.write(")");
+            }
+            self.newline();
+        }
+        if is_pub {
+            self.write("pub ");
+        }
+    }
+
+    fn function(&mut self, decl: &FunctionDecl) {
+        let FunctionDecl { attributes, is_pub, is_async, name, lifetimes, generics, params, return_type, where_clause, body, span: _ } =
+            decl;
+        self.item_head(attributes, *is_pub);
+        self.signature(*is_async, name, lifetimes.as_ref(), generics, params, return_type.as_ref());
+        self.where_clause(where_clause.as_ref());
+        self.write(" ");
+        self.block(body);
+    }
+
+    /// `async fn name[lifetime L]<T>(params) ret`
+    fn signature(
+        &mut self,
+        is_async: bool,
+        name: &Ident,
+        lifetimes: Option<&LifetimeParams>,
+        generics: &[GenericParam],
+        params: &[Param],
+        ret: Option<&TypeExpr>,
+    ) {
+        if is_async {
+            self.write("async ");
+        }
+        self.write(&format!("fn {}", name.name));
+        if let Some(lifetimes) = lifetimes {
+            self.lifetime_params(lifetimes);
+        }
+        self.generics(generics);
+        self.write("(");
+        self.comma_list(params, |p, param| p.param(param));
+        self.write(")");
+        if let Some(ret) = ret {
+            self.write(" ");
+            self.ty(ret);
+        }
+    }
+
+    fn param(&mut self, param: &Param) {
+        if param.is_mut {
+            self.write("mut ");
+        }
+        match &param.pattern {
+            Some(pattern) => {
+                self.write("extract ");
+                self.pattern(pattern);
+            }
+            None => self.write(&param.name.name),
+        }
+        if let Some(ty) = &param.ty {
+            self.write(": ");
+            self.ty(ty);
+        }
+        if let Some(default) = &param.default {
+            self.write(" = ");
+            self.expr(default, Precedence::Assignment);
+        }
+    }
+
+    /// `[lifetime L, lifetime M where M outlives L]`
+    fn lifetime_params(&mut self, lifetimes: &LifetimeParams) {
+        self.write("[");
+        self.comma_list(&lifetimes.names, |p, name| p.write(&format!("lifetime {}", name.name)));
+        if !lifetimes.outlives.is_empty() {
+            self.write(" where ");
+            self.comma_list(&lifetimes.outlives, |p, o| p.write(&format!("{} outlives {}", o.longer.name, o.shorter.name)));
+        }
+        self.write("]");
+    }
+
+    fn generics(&mut self, generics: &[GenericParam]) {
+        if generics.is_empty() {
+            return;
+        }
+        self.write("<");
+        self.comma_list(generics, |p, param| p.bounded(&param.name, &param.bounds));
+        self.write(">");
+    }
+
+    /// ` where T: Display, F: Fn(T) bool`
+    fn where_clause(&mut self, clause: Option<&WhereClause>) {
+        if let Some(clause) = clause {
+            self.write(" where ");
+            self.comma_list(&clause.predicates, |p, predicate| p.bounded(&predicate.name, &predicate.bounds));
+        }
+    }
+
+    /// `T` or `T: Display + Clone`
+    fn bounded(&mut self, name: &Ident, bounds: &[TraitBound]) {
+        self.write(&name.name);
+        for (i, bound) in bounds.iter().enumerate() {
+            self.write(if i == 0 { ": " } else { " + " });
+            self.trait_bound(bound);
+        }
+    }
+
+    fn trait_bound(&mut self, bound: &TraitBound) {
+        match &bound.kind {
+            BoundKind::Trait { name, args, bindings } => {
+                self.write(&name.name);
+                if !args.is_empty() || !bindings.is_empty() {
+                    self.write("<");
+                    self.comma_list(args, |p, arg| p.ty(arg));
+                    if !args.is_empty() && !bindings.is_empty() {
+                        self.write(", ");
+                    }
+                    self.comma_list(bindings, |p, binding| {
+                        p.write(&format!("{} = ", binding.name.name));
+                        p.ty(&binding.ty);
+                    });
+                    self.write(">");
+                }
+            }
+            BoundKind::Function { name, params, ret } => {
+                self.write(&format!("{}(", name.name));
+                self.comma_list(params, |p, param| p.ty(param));
+                self.write(")");
+                if let Some(ret) = ret {
+                    self.write(" ");
+                    self.ty(ret);
+                }
+            }
+        }
+    }
+
+    fn struct_decl(&mut self, decl: &StructDecl) {
+        let StructDecl { attributes, is_pub, is_edge, name, generics, members, span: _ } = decl;
+        self.item_head(attributes, *is_pub);
+        if *is_edge {
+            self.write("edge ");
+        }
+        self.write(&format!("struct {}", name.name));
+        self.generics(generics);
+        self.write(" ");
+        self.braced(members, |p, member| match member {
+            StructMember::Field(field) => p.field(field),
+            StructMember::Method(method) => p.function(method),
+            StructMember::Property(property) => p.property(property),
+        });
+    }
+
+    /// `{`, one entry per line, `}`; `{}` when empty
+    fn braced<T>(&mut self, entries: &[T], mut each: impl FnMut(&mut Self, &T)) {
+        if entries.is_empty() {
+            self.write("{}");
+            return;
+        }
+        self.write("{");
+        self.indent += 1;
+        for entry in entries {
+            self.newline();
+            each(self, entry);
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    fn field(&mut self, field: &FieldDecl) {
+        if field.is_pub {
+            self.write("pub ");
+        }
+        self.write(&format!("{}: ", field.name.name));
+        self.ty(&field.ty);
+    }
+
+    fn property(&mut self, property: &PropertyDecl) {
+        if property.is_pub {
+            self.write("pub ");
+        }
+        self.write(&format!("{}: ", property.name.name));
+        self.ty(&property.ty);
+        let accessors = std::iter::once(("get", &property.getter)).chain(property.setter.iter().map(|s| ("set", s)));
+        if property.is_auto() {
+            self.write(" {");
+            for (keyword, _) in accessors {
+                self.write(&format!(" {};", keyword));
+            }
+            self.write(" }");
+            return;
+        }
+
+        self.write(" {");
+        self.indent += 1;
+        for (keyword, accessor) in accessors {
+            self.newline();
+            self.write(keyword);
+            match &accessor.body {
+                Some(body) => {
+                    self.write(" ");
+                    self.block(body);
+                }
+                None => self.write(";"),
+            }
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    fn enum_decl(&mut self, decl: &EnumDecl) {
+        let EnumDecl { attributes, is_pub, name, generics, variants, span: _ } = decl;
+        self.item_head(attributes, *is_pub);
+        self.write(&format!("enum {}", name.name));
+        self.generics(generics);
+        self.write(" ");
+        let last = variants.len().saturating_sub(1);
+        let variants: Vec<(usize, &EnumVariant)> = variants.iter().enumerate().collect();
+        self.braced(&variants, |p, (i, variant)| {
+            p.write(&variant.name.name);
+            match &variant.payload {
+                Some(EnumPayload::Tuple { types }) => {
+                    p.write("(");
+                    p.comma_list(types, |p, ty| p.ty(ty));
+                    p.write(")");
+                }
+                Some(EnumPayload::Struct { fields }) => {
+                    p.write(" { ");
+                    p.comma_list(fields, |p, field| p.field(field));
+                    p.write(if fields.is_empty() { "}" } else { " }" });
+                }
+                None => {}
+            }
+            if *i != last {
+                p.write(",");
+            }
+        });
+    }
+
+    fn trait_decl(&mut self, decl: &TraitDecl) {
+        let TraitDecl { attributes, is_pub, name, items, span: _ } = decl;
+        self.item_head(attributes, *is_pub);
+        self.write(&format!("trait {} ", name.name));
+        self.braced(items, |p, item| match item {
+            TraitItem::Method(method) => {
+                let MethodSignature { is_async, name, lifetimes, generics, params, return_type, where_clause, span: _ } = method;
+                p.signature(*is_async, name, lifetimes.as_ref(), generics, params, return_type.as_ref());
+                p.where_clause(where_clause.as_ref());
+            }
+            TraitItem::AssociatedType(assoc) => p.write(&format!("type {}", assoc.name.name)),
+        });
+    }
+
+    fn impl_block(&mut self, block: &ImplBlock) {
+        let ImplBlock { attributes, generics, trait_name, target, where_clause, methods, span: _ } = block;
+        self.item_head(attributes, false);
+        self.write("impl");
+        self.generics(generics);
+        self.write(" ");
+        if let Some(trait_name) = trait_name {
+            self.ty(trait_name);
+            self.write(" for ");
+        }
+        self.ty(target);
+        self.where_clause(where_clause.as_ref());
+        self.write(" ");
+        self.braced(methods, |p, method| p.function(method));
+    }
+
+    fn extend_block(&mut self, block: &ExtendBlock) {
+        let ExtendBlock { attributes, generics, target, where_clause, methods, span: _ } = block;
+        self.item_head(attributes, false);
+        self.write("extend");
+        self.generics(generics);
+        self.write(" ");
+        self.ty(target);
+        self.where_clause(where_clause.as_ref());
+        self.write(" ");
+        self.braced(methods, |p, method| p.function(method));
+    }
+
+    fn const_decl(&mut self, decl: &ConstDecl) {
+        let ConstDecl { attributes, is_pub, name, ty, value, span: _ } = decl;
+        self.item_head(attributes, *is_pub);
+        self.write(&format!("const {}", name.name));
+        if let Some(ty) = ty {
+            self.write(": ");
+            self.ty(ty);
+        }
+        self.write(" = ");
+        self.expr(value, Precedence::Assignment);
+    }
+
+    // ========================================
+    // Types
+    // ========================================
+
+    fn ty(&mut self, ty: &TypeExpr) {
+        match &ty.kind {
+            TypeKind::Primitive { name } => self.write(name.name()),
+            TypeKind::Named { name, args } => {
+                self.write(&name.name);
+                if !args.is_empty() {
+                    self.write("<");
+                    self.comma_list(args, |p, arg| p.ty(arg));
+                    self.write(">");
+                }
+            }
+            TypeKind::Function { params, ret } => {
+                self.write("fn(");
+                self.comma_list(params, |p, param| p.ty(param));
+                self.write(")");
+                if let Some(ret) = ret {
+                    self.write(" ");
+                    self.ty(ret);
+                }
+            }
+            TypeKind::Tuple { elems } => {
+                self.write("(");
+                self.comma_list(elems, |p, elem| p.ty(elem));
+                self.write(")");
+            }
+            TypeKind::Array { elem, size: Some(size) } => {
+                self.write(&format!("[{}]", size));
+                self.ty(elem);
+            }
+            TypeKind::Array { elem, size: None } => {
+                self.write("[");
+                self.ty(elem);
+                self.write("]");
+            }
+            TypeKind::Slice { elem } => {
+                self.write("[]");
+                self.ty(elem);
+            }
+            TypeKind::Fallible { inner } => {
+                self.ty(inner);
+                self.write("!");
+            }
+            TypeKind::Reference { mutable, lifetime, inner } => {
+                self.write("&");
+                if *mutable {
+                    self.write("mut ");
+                }
+                if let Some(lifetime) = lifetime {
+                    self.write(&format!("{} ", lifetime.name));
+                }
+                self.ty(inner);
+            }
+            TypeKind::Task { inner } => {
+                self.write("Task");
+                if let Some(inner) = inner {
+                    self.write("<");
+                    self.ty(inner);
+                    self.write(">");
+                }
+            }
+        }
+    }
+
+    // ========================================
+    // Statements
+    // ========================================
+
+    fn block(&mut self, block: &Block) {
+        self.block_with(block, false);
+    }
+
+    /// `paren_first` wraps the first statement, so `{ (x = 1) }` stays a block
+    fn block_with(&mut self, block: &Block, paren_first: bool) {
+        if block.stmts.is_empty() {
+            self.write("{}");
+            return;
+        }
+
+        self.write("{");
+        self.indent += 1;
+        let mut stmts: Vec<String> = self.restricted_default(|p| {
+            block.stmts.iter().map(|stmt| p.capture(|p| p.stmt(stmt))).collect()
+        });
+        if paren_first {
+            stmts[0] = format!("({})", stmts[0]);
+        }
+        for (i, stmt) in stmts.iter().enumerate() {
+            self.newline();
+            self.write(stmt);
+            // A line starting with `-`, `+` or `{` would continue the previous statement
+            if stmts.get(i + 1).is_some_and(|next| next.starts_with(['-', '+', '{'])) {
+                self.write(";");
+            }
+        }
+        self.indent -= 1;
+        self.newline();
+        self.write("}");
+    }
+
+    fn restricted_default<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let saved = std::mem::take(&mut self.restrictions);
+        let result = f(self);
+        self.restrictions = saved;
+        result
+    }
+
+    /// An expression in a `for`, `while`, `if` or `match` head
+    fn head_expr(&mut self, expr: &Expr) {
+        let restrictions = Restrictions { no_struct_literal: true, ..self.restrictions };
+        self.restricted(restrictions, |p| p.expr(expr, Precedence::Assignment));
+    }
+
+    /// `let pattern = ` before the condition of `if let` and `while let`
+    fn let_pattern(&mut self, pattern: Option<&Pattern>) {
+        if let Some(pattern) = pattern {
+            self.write("let ");
+            self.pattern(pattern);
+            self.write(" = ");
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::Let { mutable, name, ty, value } => {
+                self.write(if *mutable { "let mut " } else { "let " });
+                self.write(&name.name);
+                if let Some(ty) = ty {
+                    self.write(": ");
+                    self.ty(ty);
+                }
+                self.write(" = ");
+                self.expr(value, Precedence::Assignment);
+            }
+            StmtKind::ShortLet { name, value } => {
+                self.write(&format!("{} := ", name.name));
+                self.expr(value, Precedence::Assignment);
+            }
+            StmtKind::Extract { pattern, value } => {
+                self.write("extract ");
+                self.pattern(pattern);
+                self.write(" = ");
+                self.expr(value, Precedence::Assignment);
+            }
+            StmtKind::LetElse { pattern, value, else_block } => {
+                self.write("let ");
+                self.pattern(pattern);
+                self.write(" = ");
+                // `else` would attach to a trailing `if`, and a lambda or
+                // query would take it as part of its body
+                let bare = !value.is_block_like() && !matches!(value.kind, ExprKind::Lambda { .. } | ExprKind::Query { .. });
+                if bare {
+                    self.expr(value, Precedence::Assignment);
+                } else {
+                    self.write("(");
+                    self.restricted_default(|p| p.expr(value, Precedence::Assignment));
+                    self.write(")");
+                }
+                self.write(" else ");
+                self.block(else_block);
+            }
+            StmtKind::Item { item } => self.item(item),
+            StmtKind::Expr { expr } => self.expr(expr, Precedence::Assignment),
+            StmtKind::Return { value: Some(value) } => {
+                self.write("return ");
+                self.expr(value, Precedence::Assignment);
+            }
+            StmtKind::Return { value: None } => self.write("return"),
+            StmtKind::Break => self.write("break"),
+            StmtKind::Continue => self.write("continue"),
+            StmtKind::For { pattern, iter, body } => {
+                self.write("for ");
+                self.pattern(pattern);
+                self.write(" in ");
+                self.head_expr(iter);
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::While { pattern, condition, body } => {
+                self.write("while ");
+                self.let_pattern(pattern.as_ref());
+                self.head_expr(condition);
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::Loop { body } => {
+                self.write("loop ");
+                self.block(body);
+            }
+            StmtKind::With { allocator, body } => {
+                self.write("with ");
+                self.allocator(allocator);
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::Try { body, catch } => {
+                self.write("try ");
+                self.block(body);
+                if let Some(catch) = catch {
+                    self.write(&format!(" catch ({}) ", catch.binding.name));
+                    self.block(&catch.body);
+                }
+            }
+            StmtKind::Unsafe { body } => {
+                self.write("unsafe ");
+                self.block(body);
+            }
+            StmtKind::Using { resources, body } => {
+                self.write("using ");
+                for (i, resource) in resources.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.write(&format!("let {} = ", resource.name.name));
+                    self.head_expr(&resource.value);
+                }
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::Defer { body } => {
+                self.write("defer ");
+                self.block(body);
+            }
+            StmtKind::Error => self.write("/* error */"),
+        }
+    }
+
+    fn allocator(&mut self, allocator: &Allocator) {
+        match &allocator.kind {
+            AllocatorKind::Arena { size } => {
+                self.write("arena(");
+                self.expr(size, Precedence::Assignment);
+                self.write(")");
+            }
+            AllocatorKind::Pool { ty, capacity } => {
+                self.write("pool<");
+                self.ty(ty);
+                self.write(">(");
+                self.expr(capacity, Precedence::Assignment);
+                self.write(")");
+            }
+            AllocatorKind::Gc => self.write("gc"),
+            AllocatorKind::Heap => self.write("heap"),
+        }
+    }
+
+    // ========================================
+    // Expressions
+    // ========================================
+
+    /// Binding level of `expr`; `None` for lambdas, whose body takes
+    /// everything to their right
+    fn level(expr: &Expr) -> Option<Precedence> {
+        match &expr.kind {
+            ExprKind::Lambda { .. } | ExprKind::Query { .. } => None,
+            ExprKind::Assign { .. } => Some(Precedence::Assignment),
+            ExprKind::Binary { op, .. } => Some(precedence::level(Infix::Binary(*op))),
+            ExprKind::Range { .. } => Some(Precedence::Range),
+            ExprKind::Cast { .. } => Some(Precedence::Cast),
+            ExprKind::Unary { .. } => Some(Precedence::Prefix),
+            _ => Some(Precedence::Postfix),
+        }
+    }
+
+    /// `expr` where the parser expects operators of at least `min`
+    fn expr(&mut self, expr: &Expr, min: Precedence) {
+        let too_loose = match Self::level(expr) {
+            Some(level) => level < min,
+            None => min > Precedence::Assignment || self.restrictions.no_arrow_lambda,
+        };
+        let struct_in_head = self.restrictions.no_struct_literal && matches!(expr.kind, ExprKind::StructLit { .. });
+        // `where (select)` keeps a variable named like a query word
+        let query_word = self.restrictions.query
+            && matches!(&expr.kind, ExprKind::Ident { name } if keywords::query_keyword(name).is_some());
+
+        if too_loose || struct_in_head || query_word {
+            self.write("(");
+            self.restricted_default(|p| p.expr_kind(expr));
+            self.write(")");
+        } else {
+            self.expr_kind(expr);
+        }
+    }
+
+    /// An expression in a fresh context: inside `()`, `[]` or argument lists
+    fn expr_unrestricted(&mut self, expr: &Expr) {
+        self.restricted_default(|p| p.expr(expr, Precedence::Assignment));
+    }
+
+    fn args(&mut self, args: &[Expr]) {
+        self.comma_list(args, |p, arg| p.expr_unrestricted(arg));
+    }
+
+    /// The receiver of a postfix operator
+    fn postfix_target(&mut self, target: &Expr, member: Option<&str>) {
+        let digit_member = member.is_some_and(|m| m.starts_with(|c: char| c.is_ascii_digit()));
+        let ambiguous = match &target.kind {
+            // `a?.b` would lex as a safe access
+            ExprKind::Try { .. } => member.is_some(),
+            // `t.0.1` and `1.0` would lex as floats
+            ExprKind::Field { name, .. } => digit_member && name.name.starts_with(|c: char| c.is_ascii_digit()),
+            ExprKind::Literal { value: Literal::Int(_) | Literal::Float(_) | Literal::Double(_) } => digit_member,
+            _ => false,
+        };
+        if ambiguous {
+            self.write("(");
+            self.expr_unrestricted(target);
+            self.write(")");
+        } else {
+            self.expr(target, Precedence::Postfix);
+        }
+    }
+
+    fn expr_kind(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Literal { value } => self.literal(value),
+            ExprKind::InterpolatedString { parts } => {
+                self.write("$\"");
+                for part in parts {
+                    match part {
+                        StringPart::Text(s) => self.write(&escape(s, &['{', '}'])),
+                        StringPart::Expr(expr) => {
+                            self.write("{");
+                            self.expr_unrestricted(expr);
+                            self.write("}");
+                        }
+                    }
+                }
+                self.write("\"");
+            }
+            ExprKind::Ident { name } => self.write(name),
+            ExprKind::SelfRef => self.write("self"),
+            ExprKind::Unary { op, operand } => {
+                self.write(op.symbol());
+                if *op == UnaryOp::Await {
+                    self.write(" ");
+                }
+                self.expr(operand, Precedence::Prefix);
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let level = precedence::level(Infix::Binary(*op));
+                self.infix(lhs, op.symbol(), level);
+                self.expr(rhs, level.rhs());
+            }
+            ExprKind::Assign { op, target, value } => {
+                self.infix(target, op.symbol(), Precedence::Assignment);
+                // Nothing can follow the value, so a lambda needs no parentheses
+                let min = if Self::level(value).is_none() { Precedence::Assignment } else { Precedence::Assignment.rhs() };
+                self.expr(value, min);
+            }
+            ExprKind::Range { start, end, inclusive } => {
+                self.expr(start, Precedence::Range.tighter());
+                // `a?..b` would lex as `a ?. .b`
+                if self.out.ends_with('?') {
+                    self.write(" ");
+                }
+                self.write(if *inclusive { "..." } else { ".." });
+                self.expr(end, Precedence::Range.rhs());
+            }
+            ExprKind::Cast { expr, ty } => {
+                self.infix(expr, "as", Precedence::Cast);
+                self.ty(ty);
+            }
+            ExprKind::Call { callee, args } => {
+                // `a.b(x)` would be a method call
+                if matches!(callee.kind, ExprKind::Field { .. }) {
+                    self.write("(");
+                    self.expr_unrestricted(callee);
+                    self.write(")");
+                } else {
+                    self.postfix_target(callee, None);
+                }
+                self.write("(");
+                self.args(args);
+                self.write(")");
+            }
+            ExprKind::Index { target, index } => {
+                self.postfix_target(target, None);
+                self.write("[");
+                self.expr_unrestricted(index);
+                self.write("]");
+            }
+            ExprKind::Field { target, name, safe } => {
+                self.postfix_target(target, Some(&name.name));
+                self.write(if *safe { "?." } else { "." });
+                self.write(&name.name);
+            }
+            ExprKind::MethodCall { receiver, method, args, safe } => {
+                self.postfix_target(receiver, Some(&method.name));
+                self.write(if *safe { "?." } else { "." });
+                self.write(&method.name);
+                self.write("(");
+                self.args(args);
+                self.write(")");
+            }
+            ExprKind::Try { expr } => {
+                self.postfix_target(expr, None);
+                self.write("?");
+            }
+            ExprKind::Lambda { params, body } => {
+                match params.as_slice() {
+                    [Param { is_mut: false, name, pattern: None, ty: None, default: None, .. }] if name.name != "self" => {
+                        self.write(&name.name);
+                    }
+                    _ => {
+                        self.write("(");
+                        self.comma_list(params, |p, param| p.param(param));
+                        self.write(")");
+                    }
+                }
+                self.write(" => ");
+                self.expr(body, Precedence::Assignment);
+            }
+            ExprKind::If { branches, else_block } => {
+                for (i, branch) in branches.iter().enumerate() {
+                    self.write(if i == 0 { "if " } else { " elif " });
+                    self.let_pattern(branch.pattern.as_ref());
+                    self.head_expr(&branch.condition);
+                    self.write(" ");
+                    self.block(&branch.body);
+                }
+                if let Some(block) = else_block {
+                    self.write(" else ");
+                    self.block(block);
+                }
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.write("match ");
+                self.head_expr(scrutinee);
+                self.write(" ");
+                self.restricted_default(|p| {
+                    p.braced(arms, |p, arm| {
+                        p.pattern(&arm.pattern);
+                        if let Some(guard) = &arm.guard {
+                            p.write(" where ");
+                            let restrictions = Restrictions { no_arrow_lambda: true, ..Restrictions::default() };
+                            p.restricted(restrictions, |p| p.expr(guard, Precedence::Assignment));
+                        }
+                        p.write(" => ");
+                        p.expr(&arm.body, Precedence::Assignment);
+                        // Without it a `-1 =>` arm would continue the body
+                        p.write(",");
+                    });
+                });
+            }
+            ExprKind::Block { block } => {
+                // `{ x = 1 }` would open an object literal, except as a lambda or arm body
+                let reads_as_object = !self.out.ends_with("=> ") && block.stmts.first().is_some_and(|stmt| {
+                    matches!(&stmt.kind, StmtKind::Expr { expr: Expr { kind: ExprKind::Assign { op: AssignOp::Assign, target, .. }, .. } }
+                        if matches!(target.kind, ExprKind::Ident { .. } | ExprKind::Literal { value: Literal::String(_) }))
+                });
+                self.block_with(block, reads_as_object);
+            }
+            ExprKind::Array { elems } => {
+                self.write("[");
+                self.args(elems);
+                self.write("]");
+            }
+            ExprKind::Tuple { elems } => {
+                self.write("(");
+                self.args(elems);
+                if elems.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            ExprKind::StructLit { name, fields } => {
+                self.write(&name.name);
+                if fields.is_empty() {
+                    self.write(" {}");
+                    return;
+                }
+                self.write(" { ");
+                self.comma_list(fields, |p, field| {
+                    p.write(&format!("{} = ", field.name.name));
+                    p.expr_unrestricted(&field.value);
+                });
+                self.write(" }");
+            }
+            ExprKind::ObjectLit { fields } => self.object_literal(fields, |p, field| {
+                p.write(&format!("{} = ", field.name.name));
+                p.expr_unrestricted(&field.value);
+            }),
+            ExprKind::DictLit { entries } => self.object_literal(entries, |p, entry| {
+                p.expr_unrestricted(&entry.key);
+                p.write(" = ");
+                p.expr_unrestricted(&entry.value);
+            }),
+            ExprKind::Query { clauses } => {
+                let restrictions = Restrictions { query: true, ..self.restrictions };
+                self.restricted(restrictions, |p| {
+                    for (i, clause) in clauses.iter().enumerate() {
+                        if i > 0 {
+                            p.write(" ");
+                        }
+                        p.query_clause(clause);
+                    }
+                });
+            }
+            ExprKind::Error => self.write("/* error */"),
+        }
+    }
+
+    fn query_clause(&mut self, clause: &QueryClause) {
+        match &clause.kind {
+            QueryClauseKind::From { name, source } => {
+                self.write(&format!("from {} in ", name.name));
+                self.expr(source, Precedence::Assignment);
+            }
+            QueryClauseKind::Let { name, value } => {
+                self.write(&format!("let {} = ", name.name));
+                self.expr(value, Precedence::Assignment);
+            }
+            QueryClauseKind::Where { condition } => {
+                self.write("where ");
+                self.expr(condition, Precedence::Assignment);
+            }
+            QueryClauseKind::OrderBy { keys } => {
+                self.write("orderby ");
+                self.comma_list(keys, |p, key| {
+                    p.expr(&key.key, Precedence::Assignment);
+                    if key.descending {
+                        p.write(" descending");
+                    }
+                });
+            }
+            QueryClauseKind::Join { name, source, outer_key, inner_key } => {
+                self.write(&format!("join {} in ", name.name));
+                self.expr(source, Precedence::Assignment);
+                self.write(" on ");
+                self.expr(outer_key, Precedence::Assignment);
+                self.write(" equals ");
+                self.expr(inner_key, Precedence::Assignment);
+            }
+            QueryClauseKind::Select { value } => {
+                self.write("select ");
+                self.expr(value, Precedence::Assignment);
+            }
+            QueryClauseKind::GroupBy { value, key } => {
+                self.write("group ");
+                self.expr(value, Precedence::Assignment);
+                self.write(" by ");
+                self.expr(key, Precedence::Assignment);
+            }
+        }
+    }
+
+    /// `{ a = 1, b = 2 }`. After `)`, `=>` or a name the brace would open a
+    /// block, as in a lambda or arm body or at the start of a statement.
+    fn object_literal<T>(&mut self, entries: &[T], entry: impl FnMut(&mut Self, &T)) {
+        let before = self.out.trim_end();
+        let word = before.rsplit(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or("");
+        let is_keyword = keywords::get_keyword(word).is_some()
+            || (self.restrictions.query && keywords::query_keyword(word).is_some());
+        let opens_body = before.ends_with("=>") || before.ends_with(')') || (!word.is_empty() && !is_keyword);
+        self.write(if opens_body { "({ " } else { "{ " });
+        self.comma_list(entries, entry);
+        self.write(if opens_body { " })" } else { " }" });
+    }
+
+    /// `lhs op ` for an operator at `level`
+    fn infix(&mut self, lhs: &Expr, op: &str, level: Precedence) {
+        let min = if level.assoc() == Assoc::Left { level } else { level.tighter() };
+        self.expr(lhs, min);
+        self.write(&format!(" {} ", op));
+    }
+
+    fn literal(&mut self, literal: &Literal) {
+        let text = match literal {
+            Literal::Int(v) => v.to_string(),
+            Literal::Float(v) => format!("{:?}f", v),
+            Literal::Double(v) => format!("{:?}", v),
+            Literal::String(s) => format!("\"{}\"", escape(s, &[])),
+            Literal::Verbatim(s) => format!("@\"{}\"", s.replace('"', "\"\"")),
+            Literal::Char(c) => match c {
+                '\'' => "'\\''".to_string(),
+                _ => format!("'{}'", escape(&c.to_string(), &[]).replace("\\\"", "\"")),
+            },
+            Literal::Bool(b) => b.to_string(),
+            Literal::Null => "null".to_string(),
+        };
+        self.write(&text);
+    }
+
+    // ========================================
+    // Patterns
+    // ========================================
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Wildcard => self.write("_"),
+            PatternKind::Literal { value } => self.literal(value),
+            PatternKind::Binding { name } => self.write(&name.name),
+            PatternKind::Tuple { elems } => {
+                self.write("(");
+                self.comma_list(elems, |p, elem| p.pattern(elem));
+                if elems.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            PatternKind::Path { path } => self.path(path),
+            PatternKind::TupleStruct { path, elems } => {
+                self.path(path);
+                self.write("(");
+                self.comma_list(elems, |p, elem| p.pattern(elem));
+                self.write(")");
+            }
+            PatternKind::Struct { path, fields } => {
+                self.path(path);
+                self.write(" ");
+                self.field_patterns(fields);
+            }
+            PatternKind::List { elems } => {
+                self.write("[");
+                self.comma_list(elems, |p, elem| p.pattern(elem));
+                self.write("]");
+            }
+            PatternKind::Rest { name } => {
+                self.write("...");
+                if let Some(name) = name {
+                    self.write(&name.name);
+                }
+            }
+            PatternKind::Object { fields } => self.field_patterns(fields),
+        }
+    }
+
+    fn field_patterns(&mut self, fields: &[FieldPattern]) {
+        if fields.is_empty() {
+            self.write("{}");
+            return;
+        }
+        self.write("{ ");
+        self.comma_list(fields, |p, field| {
+            p.write(&field.name.name);
+            if let Some(pattern) = &field.pattern {
+                p.write(" = ");
+                p.pattern(pattern);
+            }
+        });
+        self.write(" }");
+    }
+
+    fn path(&mut self, path: &[Ident]) {
+        let segments: Vec<&str> = path.iter().map(|s| s.name.as_str()).collect();
+        self.write(&segments.join("."));
+    }
+}
+
+/// Backslash escapes for a `"..."` string, plus `extra` characters
+fn escape(text: &str, extra: &[char]) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if extra.contains(&c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,147 @@
+//! Query expressions
+//!
+//! `from x in xs where ... orderby ... select ...`. Apart from `from`, `in`,
+//! `let` and `where`, the clause words are contextual: they are only keywords
+//! at the top level of a query, which the `query` restriction tracks.
+//! `desugar` turns the parsed clauses into method calls.
+
+use crate::error_management::error_types::ParseError;
+use crate::lexer::keywords;
+use crate::lexer::TokenType;
+use crate::parser::ast::*;
+use crate::parser::recursive_descent::{PResult, Parser, Restrictions};
+
+/// What may follow a finished clause
+const CLAUSE_STARTS: &[&str] = &["`where`", "`orderby`", "`let`", "`from`", "`join`", "`select`", "`group`"];
+
+impl<'a> Parser<'a> {
+    /// `from` starts a query only when a name and `in` follow; otherwise it
+    /// is the start of an import
+    pub(crate) fn at_query(&self) -> bool {
+        self.check(&TokenType::From)
+            && matches!(self.peek_nth(1).kind, TokenType::Ident(_))
+            && self.check_nth(2, &TokenType::In)
+    }
+
+    /// A query word where an expression would start, e.g. `where select`
+    pub(crate) fn at_query_keyword(&self) -> bool {
+        self.restrictions.query
+            && matches!(self.peek_kind(), TokenType::Ident(name) if keywords::query_keyword(name).is_some())
+    }
+
+    pub(crate) fn query_expr(&mut self) -> PResult<Expr> {
+        let start = self.peek().span;
+        let restrictions = Restrictions { query: true, ..self.restrictions };
+        let clauses = self.restricted(restrictions, |p| {
+            let mut clauses = vec![p.range_clause()?];
+            loop {
+                let clause = p.query_clause()?;
+                let last = matches!(clause.kind, QueryClauseKind::Select { .. } | QueryClauseKind::GroupBy { .. });
+                clauses.push(clause);
+                if last {
+                    return Ok(clauses);
+                }
+            }
+        })?;
+
+        if let Some(error) = duplicate_range_variable(&clauses) {
+            self.record(error);
+        }
+        Ok(Expr::new(ExprKind::Query { clauses }, self.span_from(start)))
+    }
+
+    /// `from x in xs`
+    fn range_clause(&mut self) -> PResult<QueryClause> {
+        let start = self.expect(&TokenType::From, "`from`")?.span;
+        let name = self.expect_ident("a range variable")?;
+        self.expect(&TokenType::In, "`in`")?;
+        let source = self.expr()?;
+        Ok(QueryClause { kind: QueryClauseKind::From { name, source }, span: self.span_from(start) })
+    }
+
+    fn query_clause(&mut self) -> PResult<QueryClause> {
+        let start = self.peek().span;
+        let kind = match self.peek_kind() {
+            TokenType::From => return self.range_clause(),
+            TokenType::Let => {
+                self.advance();
+                let name = self.expect_ident("a range variable")?;
+                self.expect(&TokenType::Equal, "`=`")?;
+                QueryClauseKind::Let { name, value: self.expr()? }
+            }
+            TokenType::Where => {
+                self.advance();
+                QueryClauseKind::Where { condition: self.expr()? }
+            }
+            _ if self.check_ident("orderby") => {
+                self.advance();
+                let mut keys = vec![self.order_key()?];
+                while self.eat(&TokenType::Comma) {
+                    keys.push(self.order_key()?);
+                }
+                QueryClauseKind::OrderBy { keys }
+            }
+            _ if self.check_ident("join") => {
+                self.advance();
+                let name = self.expect_ident("a range variable")?;
+                self.expect(&TokenType::In, "`in`")?;
+                let source = self.expr()?;
+                self.expect_word("on")?;
+                let outer_key = Box::new(self.expr()?);
+                self.expect_word("equals")?;
+                let inner_key = Box::new(self.expr()?);
+                QueryClauseKind::Join { name, source, outer_key, inner_key }
+            }
+            _ if self.check_ident("select") => {
+                self.advance();
+                QueryClauseKind::Select { value: self.expr()? }
+            }
+            _ if self.check_ident("group") => {
+                self.advance();
+                let value = self.expr()?;
+                self.expect_word("by")?;
+                QueryClauseKind::GroupBy { value, key: self.expr()? }
+            }
+            _ => {
+                let expected = CLAUSE_STARTS.join(", ");
+                return Err(Box::new(ParseError::InvalidSyntax {
+                    message: format!("expected one of {}, found {}", expected, Self::describe(self.peek())),
+                    span: self.peek().span,
+                    suggestion: Some("A query ends with `select` or `group ... by`".to_string()),
+                }));
+            }
+        };
+        Ok(QueryClause { kind, span: self.span_from(start) })
+    }
+
+    /// `key`, `key ascending` or `key descending`
+    fn order_key(&mut self) -> PResult<OrderKey> {
+        let start = self.peek().span;
+        let key = self.expr()?;
+        let descending = self.check_ident("descending");
+        if descending || self.check_ident("ascending") {
+            self.advance();
+        }
+        Ok(OrderKey { key, descending, span: self.span_from(start) })
+    }
+}
+
+/// A `from`, `let` or `join` that reuses an earlier range variable's name
+pub(crate) fn duplicate_range_variable(clauses: &[QueryClause]) -> Option<ParseError> {
+    let mut seen: Vec<&str> = Vec::new();
+    for clause in clauses {
+        let name = match &clause.kind {
+            QueryClauseKind::From { name, .. } | QueryClauseKind::Let { name, .. } | QueryClauseKind::Join { name, .. } => name,
+            _ => continue,
+        };
+        if seen.contains(&name.name.as_str()) {
+            return Some(ParseError::InvalidSyntax {
+                message: format!("range variable `{}` is already defined in this query", name.name),
+                span: name.span,
+                suggestion: Some("Choose a different name".to_string()),
+            });
+        }
+        seen.push(&name.name);
+    }
+    None
+}
@@ -0,0 +1,363 @@
+//! Recursive-descent parser - token cursor and shared helpers
+//!
+//! The grammar productions live in `items`, `statements`, `expressions`,
+//! `types` and `patterns`; each adds an `impl Parser` block.
+
+use std::mem;
+use crate::error_management::error_types::ParseError;
+use crate::error_management::Diagnostic;
+use crate::lexer::{Span, Token, TokenType, keywords};
+use crate::parser::ast::{Ident, Program};
+
+/// Boxed so `Result`s stay small on the hot path
+pub type PResult<T> = Result<T, Box<ParseError>>;
+
+/// Context-dependent limits on what an expression may contain
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Restrictions {
+    /// `Ident {` opens a block, not a struct literal (`if`, `while`, `for`, `match` heads)
+    pub no_struct_literal: bool,
+    /// `x =>` and `(...) =>` end the expression instead of starting a lambda (match guards)
+    pub no_arrow_lambda: bool,
+    /// Query keywords such as `select` end the expression (query clauses)
+    pub query: bool,
+}
+
+pub struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    pub(crate) source: &'a str,
+    pub(crate) restrictions: Restrictions,
+    /// Errors recovered from so far, in source order
+    errors: Vec<ParseError>,
+    /// Warnings and notes from lexing interpolation holes, which happens
+    /// only as the parser reaches them
+    pub(crate) warnings: Vec<Diagnostic>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Token>, source: &'a str) -> Self {
+        // Comments carry no syntax; the tree only needs the significant tokens
+        let mut tokens: Vec<Token> = tokens
+            .into_iter()
+            .filter(|t| !matches!(t.kind, TokenType::Comment(_) | TokenType::DocComment(_) | TokenType::Newline))
+            .collect();
+
+        if !matches!(tokens.last(), Some(Token { kind: TokenType::Eof, .. })) {
+            let end = Span::point(source, source.len());
+            tokens.push(Token::new(TokenType::Eof, end, String::new()));
+        }
+
+        Parser {
+            tokens,
+            pos: 0,
+            source,
+            restrictions: Restrictions::default(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Parse a whole file. Syntax errors leave `Error` nodes in the tree;
+    /// collect them with `take_errors`.
+    pub fn parse_program(&mut self) -> Program {
+        self.program()
+    }
+
+    pub fn take_errors(&mut self) -> Vec<ParseError> {
+        mem::take(&mut self.errors)
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        mem::take(&mut self.warnings)
+    }
+
+    // ========================================
+    // Cursor
+    // ========================================
+
+    pub(crate) fn peek(&self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    pub(crate) fn peek_nth(&self, n: usize) -> &Token {
+        let idx = (self.pos + n).min(self.tokens.len() - 1);
+        &self.tokens[idx]
+    }
+
+    pub(crate) fn peek_kind(&self) -> &TokenType {
+        &self.peek().kind
+    }
+
+    /// Same token variant, ignoring any payload
+    pub(crate) fn check(&self, kind: &TokenType) -> bool {
+        Self::same_kind(self.peek_kind(), kind)
+    }
+
+    pub(crate) fn check_nth(&self, n: usize, kind: &TokenType) -> bool {
+        Self::same_kind(&self.peek_nth(n).kind, kind)
+    }
+
+    pub(crate) fn same_kind(a: &TokenType, b: &TokenType) -> bool {
+        mem::discriminant(a) == mem::discriminant(b)
+    }
+
+    /// An identifier with this exact text (contextual keywords like `arena`)
+    pub(crate) fn check_ident(&self, name: &str) -> bool {
+        matches!(self.peek_kind(), TokenType::Ident(n) if n == name)
+    }
+
+    pub(crate) fn at_eof(&self) -> bool {
+        self.check(&TokenType::Eof)
+    }
+
+    pub(crate) fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    pub(crate) fn eat(&mut self, kind: &TokenType) -> bool {
+        if self.check(kind) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume `kind` or fail with "expected `what`, found ..."
+    pub(crate) fn expect(&mut self, kind: &TokenType, what: &str) -> PResult<Token> {
+        self.expect_one_of(kind, &[what])
+    }
+
+    /// Consume `kind` or fail with "expected one of ..., found ...", listing
+    /// the other tokens that were acceptable before it (`,` before `)`)
+    pub(crate) fn expect_one_of(&mut self, kind: &TokenType, expected: &[&str]) -> PResult<Token> {
+        if self.check(kind) {
+            Ok(self.advance())
+        } else {
+            Err(self.error_expected_one_of(expected))
+        }
+    }
+
+    /// Consume the contextual keyword `word` (`on`, `lifetime`), which lexes
+    /// as an identifier
+    pub(crate) fn expect_word(&mut self, word: &str) -> PResult<()> {
+        if !self.check_ident(word) {
+            return Err(self.error_expected(&format!("`{}`", word)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    pub(crate) fn expect_ident(&mut self, what: &str) -> PResult<Ident> {
+        match self.peek_kind() {
+            TokenType::Ident(name) => {
+                let ident = Ident { name: name.clone(), span: self.peek().span };
+                self.advance();
+                Ok(ident)
+            }
+            _ => Err(self.error_expected(what)),
+        }
+    }
+
+    /// Identifier or keyword, as allowed after `.` (`dict.get(key)`)
+    pub(crate) fn expect_member_name(&mut self) -> PResult<Ident> {
+        let token = self.peek();
+        let is_name = match &token.kind {
+            TokenType::Ident(_) | TokenType::IntLit(_) => true,
+            _ => keywords::get_keyword(&token.lexeme).is_some(),
+        };
+        if !is_name {
+            return Err(self.error_expected("a field or method name"));
+        }
+        let token = self.advance();
+        Ok(Ident { name: token.lexeme, span: token.span })
+    }
+
+    /// Consume one `>`, splitting `>>`, `>=` and `>>=` (`List<List<int>>`)
+    pub(crate) fn expect_closing_angle(&mut self) -> PResult<()> {
+        let token = self.peek().clone();
+        let rest = match token.kind {
+            TokenType::Greater => {
+                self.advance();
+                return Ok(());
+            }
+            TokenType::RightShift => TokenType::Greater,
+            TokenType::GreaterEqual => TokenType::Equal,
+            TokenType::RightShiftEqual => TokenType::GreaterEqual,
+            _ => return Err(self.error_expected("`>`")),
+        };
+
+        // Split into two real tokens so the closed type's span ends at its own `>`
+        let head = Span::new(token.span.start, token.span.start + 1, token.span.line, token.span.column);
+        let tail = Span::new(token.span.start + 1, token.span.end, token.span.line, token.span.column + 1);
+        self.tokens[self.pos] = Token::new(TokenType::Greater, head, ">".to_string());
+        self.tokens.insert(self.pos + 1, Token::new(rest, tail, token.lexeme[1..].to_string()));
+        self.advance();
+        Ok(())
+    }
+
+    // ========================================
+    // Spans and layout
+    // ========================================
+
+    /// The most recently consumed token
+    pub(crate) fn prev_token(&self) -> Option<&Token> {
+        self.pos.checked_sub(1).map(|i| &self.tokens[i])
+    }
+
+    /// Span of the most recently consumed token
+    pub(crate) fn prev_span(&self) -> Span {
+        if self.pos == 0 {
+            return self.peek().span;
+        }
+        self.tokens[self.pos - 1].span
+    }
+
+    /// From `start` to the end of the last consumed token
+    pub(crate) fn span_from(&self, start: Span) -> Span {
+        start.merge(&self.prev_span())
+    }
+
+    /// Source text covered by `span`
+    pub(crate) fn text(&self, span: Span) -> &'a str {
+        &self.source[span.start..span.end]
+    }
+
+    /// A line break separates the previous token from the current one
+    pub(crate) fn newline_before(&self) -> bool {
+        if self.pos == 0 {
+            return false;
+        }
+        let prev_end = self.tokens[self.pos - 1].span.end;
+        let start = self.peek().span.start.max(prev_end);
+        self.source[prev_end..start].contains('\n')
+    }
+
+    /// Run `f` with different restrictions, restoring the old ones afterwards
+    pub(crate) fn restricted<T>(&mut self, restrictions: Restrictions, f: impl FnOnce(&mut Self) -> PResult<T>) -> PResult<T> {
+        let saved = mem::replace(&mut self.restrictions, restrictions);
+        let result = f(self);
+        self.restrictions = saved;
+        result
+    }
+
+    // ========================================
+    // Errors
+    // ========================================
+
+    pub(crate) fn describe(token: &Token) -> String {
+        match token.kind {
+            TokenType::Eof => "end of file".to_string(),
+            _ => format!("`{}`", token.lexeme),
+        }
+    }
+
+    pub(crate) fn error_expected(&self, expected: &str) -> Box<ParseError> {
+        self.error_expected_one_of(&[expected])
+    }
+
+    pub(crate) fn error_expected_one_of(&self, expected: &[&str]) -> Box<ParseError> {
+        let token = self.peek();
+        let expected = expected.iter().map(|e| e.to_string()).collect();
+        Box::new(match token.kind {
+            TokenType::Eof => ParseError::UnexpectedEof { expected, span: token.span },
+            _ => ParseError::UnexpectedToken { expected, found: Self::describe(token), span: token.span },
+        })
+    }
+
+    // ========================================
+    // Recovery
+    // ========================================
+
+    /// Keep `error` unless it only echoes a token the lexer already rejected
+    pub(crate) fn record(&mut self, error: ParseError) {
+        if !self.peek().is_error() {
+            self.errors.push(error);
+        }
+    }
+
+    /// Skip to the next token that can start a declaration, stepping over
+    /// bracketed groups. Always moves past at least one token.
+    pub(crate) fn synchronize_item(&mut self, from: usize) {
+        let mut depth = 0usize;
+        while !self.at_eof() {
+            if depth == 0 && self.pos > from && self.at_item_keyword() {
+                return;
+            }
+            match self.advance().kind {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip to the end of the current statement: past a `;`, or up to a line
+    /// break, a declaration keyword or the `}` closing the enclosing block
+    pub(crate) fn synchronize_statement(&mut self, from: usize) {
+        let mut depth = 0usize;
+        while !self.at_eof() {
+            if depth == 0 && self.pos > from && (self.newline_before() || self.at_item_keyword()) {
+                return;
+            }
+            match self.peek_kind() {
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Skip past the `}` closing a group whose `{` is already consumed,
+    /// stepping over the groups nested in it
+    pub(crate) fn skip_past_close_brace(&mut self) {
+        let mut depth = 0usize;
+        while !self.at_eof() {
+            match self.advance().kind {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    fn at_item_keyword(&self) -> bool {
+        matches!(
+            self.peek_kind(),
+            TokenType::Fn
+                | TokenType::Edge
+                | TokenType::Struct
+                | TokenType::Enum
+                | TokenType::Trait
+                | TokenType::Impl
+                | TokenType::Extend
+                | TokenType::Const
+        )
+    }
+
+    /// Position of the cursor, for `synchronize_*` and `span_since`
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Span of the tokens consumed since `from`, or of the current token if none were
+    pub(crate) fn span_since(&self, from: usize) -> Span {
+        if self.pos > from {
+            self.tokens[from].span.merge(&self.prev_span())
+        } else {
+            self.peek().span
+        }
+    }
+}
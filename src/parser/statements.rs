@@ -0,0 +1,215 @@
+//! Blocks and statements
+
+use crate::lexer::TokenType;
+use crate::parser::ast::*;
+use crate::parser::recursive_descent::{PResult, Parser, Restrictions};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn block(&mut self) -> PResult<Block> {
+        let start = self.expect(&TokenType::LeftBrace, "`{`")?.span;
+
+        // Struct literals are allowed again inside braces: `if x { Point { x = 1 } }`
+        let stmts = self.restricted(Restrictions::default(), |p| {
+            let mut stmts = Vec::new();
+            while !p.check(&TokenType::RightBrace) && !p.at_eof() {
+                let from = p.position();
+                match p.statement() {
+                    Ok(stmt) => stmts.push(stmt),
+                    Err(error) => {
+                        p.record(*error);
+                        p.synchronize_statement(from);
+                        stmts.push(Stmt { kind: StmtKind::Error, span: p.span_since(from) });
+                    }
+                }
+            }
+            Ok(stmts)
+        })?;
+
+        self.expect(&TokenType::RightBrace, "`}`")?;
+        Ok(Block { stmts, span: self.span_from(start) })
+    }
+
+    pub(crate) fn statement(&mut self) -> PResult<Stmt> {
+        let start = self.peek().span;
+
+        if self.at_item_start() {
+            let item = self.item()?;
+            return Ok(Stmt { kind: StmtKind::Item { item }, span: self.span_from(start) });
+        }
+
+        let (kind, needs_separator) = match self.peek_kind() {
+            // `let x = v` unless a pattern follows: `let Some(x) = v else { ... }`
+            TokenType::Let if self.at_let_pattern() => {
+                self.advance();
+                (self.let_else()?, false)
+            }
+            TokenType::Let => {
+                self.advance();
+                let mutable = self.eat(&TokenType::Mut);
+                let name = self.expect_ident("a variable name")?;
+                let ty = if self.eat(&TokenType::Colon) { Some(self.type_expr()?) } else { None };
+                self.expect(&TokenType::Equal, "`=`")?;
+                let value = self.expr()?;
+                (StmtKind::Let { mutable, name, ty, value }, true)
+            }
+            TokenType::Extract => {
+                let pattern = self.extract_pattern()?;
+                self.expect(&TokenType::Equal, "`=`")?;
+                let value = self.expr()?;
+                (StmtKind::Extract { pattern, value }, true)
+            }
+            TokenType::Ident(_) if self.check_nth(1, &TokenType::ColonEqual) => {
+                let name = self.expect_ident("a variable name")?;
+                self.advance();
+                let value = self.expr()?;
+                (StmtKind::ShortLet { name, value }, true)
+            }
+            TokenType::Return => {
+                self.advance();
+                let value = if self.at_statement_end() { None } else { Some(self.expr()?) };
+                (StmtKind::Return { value }, true)
+            }
+            TokenType::Break => {
+                self.advance();
+                (StmtKind::Break, true)
+            }
+            TokenType::Continue => {
+                self.advance();
+                (StmtKind::Continue, true)
+            }
+            TokenType::For => {
+                self.advance();
+                let pattern = self.pattern()?;
+                self.expect(&TokenType::In, "`in`")?;
+                let iter = self.expr_no_struct()?;
+                let body = self.block()?;
+                (StmtKind::For { pattern, iter, body }, false)
+            }
+            TokenType::While => {
+                self.advance();
+                let pattern = self.let_condition()?;
+                let condition = self.expr_no_struct()?;
+                let body = self.block()?;
+                (StmtKind::While { pattern, condition, body }, false)
+            }
+            TokenType::Loop => {
+                self.advance();
+                (StmtKind::Loop { body: self.block()? }, false)
+            }
+            TokenType::With => {
+                self.advance();
+                let allocator = self.allocator()?;
+                let body = self.block()?;
+                (StmtKind::With { allocator, body }, false)
+            }
+            TokenType::Try => {
+                self.advance();
+                let body = self.block()?;
+                let catch = if self.check(&TokenType::Catch) { Some(self.catch_clause()?) } else { None };
+                (StmtKind::Try { body, catch }, false)
+            }
+            TokenType::Unsafe => {
+                self.advance();
+                (StmtKind::Unsafe { body: self.block()? }, false)
+            }
+            TokenType::Using => {
+                self.advance();
+                let mut resources = vec![self.resource()?];
+                while self.eat(&TokenType::Comma) {
+                    resources.push(self.resource()?);
+                }
+                let body = self.block()?;
+                (StmtKind::Using { resources, body }, false)
+            }
+            TokenType::Defer => {
+                self.advance();
+                (StmtKind::Defer { body: self.block()? }, false)
+            }
+            _ => {
+                let expr = self.expr()?;
+                let needs_separator = !expr.is_block_like();
+                (StmtKind::Expr { expr }, needs_separator)
+            }
+        };
+
+        if needs_separator && !self.eat(&TokenType::Semicolon) && !self.at_statement_end() {
+            return Err(self.error_expected_one_of(&["`;`", "a newline"]));
+        }
+        self.eat(&TokenType::Semicolon);
+
+        Ok(Stmt { kind, span: self.span_from(start) })
+    }
+
+    /// `let` followed by a pattern other than a plain name
+    fn at_let_pattern(&self) -> bool {
+        match self.peek_nth(1).kind {
+            TokenType::Ident(_) => !matches!(self.peek_nth(2).kind, TokenType::Colon | TokenType::Equal),
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace | TokenType::Extract => true,
+            TokenType::IntLit(_) | TokenType::FloatLit(_) | TokenType::DoubleLit(_) | TokenType::Minus => true,
+            TokenType::StringLit(_) | TokenType::VerbatimString(_) | TokenType::CharLit(_) => true,
+            TokenType::True | TokenType::False | TokenType::Null => true,
+            _ => false,
+        }
+    }
+
+    /// `;`, `}`, end of file or a line break ends a statement
+    pub(crate) fn at_statement_end(&self) -> bool {
+        self.check(&TokenType::Semicolon)
+            || self.check(&TokenType::RightBrace)
+            || self.at_eof()
+            || self.newline_before()
+    }
+
+    /// `arena(size)`, `pool<T>(capacity)`, `gc` or `heap`
+    fn allocator(&mut self) -> PResult<Allocator> {
+        let start = self.peek().span;
+
+        let kind = if self.check_ident("arena") {
+            self.advance();
+            self.expect(&TokenType::LeftParen, "`(`")?;
+            let size = self.expr()?;
+            self.expect(&TokenType::RightParen, "`)`")?;
+            AllocatorKind::Arena { size }
+        } else if self.check_ident("pool") {
+            self.advance();
+            self.expect(&TokenType::Less, "`<`")?;
+            let ty = self.type_expr()?;
+            self.expect_closing_angle()?;
+            self.expect(&TokenType::LeftParen, "`(`")?;
+            let capacity = self.expr()?;
+            self.expect(&TokenType::RightParen, "`)`")?;
+            AllocatorKind::Pool { ty, capacity }
+        } else if self.check_ident("gc") {
+            self.advance();
+            AllocatorKind::Gc
+        } else if self.check_ident("heap") {
+            self.advance();
+            AllocatorKind::Heap
+        } else {
+            return Err(self.error_expected_one_of(&["`arena`", "`pool`", "`gc`", "`heap`"]));
+        };
+
+        Ok(Allocator { kind, span: self.span_from(start) })
+    }
+
+    /// `catch (e) { ... }`; the parentheses are optional
+    fn catch_clause(&mut self) -> PResult<CatchClause> {
+        let start = self.expect(&TokenType::Catch, "`catch`")?.span;
+        let parenthesized = self.eat(&TokenType::LeftParen);
+        let binding = self.expect_ident("an error binding")?;
+        if parenthesized {
+            self.expect(&TokenType::RightParen, "`)`")?;
+        }
+        let body = self.block()?;
+        Ok(CatchClause { binding, body, span: self.span_from(start) })
+    }
+
+    /// `let name = value` in a `using` statement
+    fn resource(&mut self) -> PResult<Resource> {
+        let start = self.expect(&TokenType::Let, "`let`")?.span;
+        let name = self.expect_ident("a resource name")?;
+        self.expect(&TokenType::Equal, "`=`")?;
+        let value = self.expr_no_struct()?;
+        Ok(Resource { name, value, span: self.span_from(start) })
+    }
+}
@@ -0,0 +1,140 @@
+//! `stratc parse` output: indented tree, JSON, Rust debug form or source
+
+use std::fmt;
+use std::str::FromStr;
+use serde_json::Value;
+use crate::parser::ast::Program;
+use crate::parser::printer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AstFormat {
+    /// Indented tree, one node per line
+    Text,
+    Json,
+    /// `{:#?}` of the AST
+    Debug,
+    /// Canonical source, from `printer`
+    Source,
+}
+
+impl FromStr for AstFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(AstFormat::Text),
+            "json" => Ok(AstFormat::Json),
+            "debug" => Ok(AstFormat::Debug),
+            "source" => Ok(AstFormat::Source),
+            other => Err(format!("unknown AST format '{}' (expected text, json, debug or source)", other)),
+        }
+    }
+}
+
+impl fmt::Display for AstFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AstFormat::Text => "text",
+            AstFormat::Json => "json",
+            AstFormat::Debug => "debug",
+            AstFormat::Source => "source",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn render(program: &Program, format: AstFormat) -> String {
+    match format {
+        AstFormat::Text => to_text(program),
+        AstFormat::Json => format!("{}\n", serde_json::to_string_pretty(program).unwrap_or_default()),
+        AstFormat::Debug => format!("{:#?}\n", program),
+        AstFormat::Source => printer::print(program),
+    }
+}
+
+/// Indented tree built from the JSON form, so it never lags behind the AST:
+///
+/// ```text
+/// Program @1:1
+///   items:
+///     Function @1:1 name=main
+/// ```
+pub fn to_text(program: &Program) -> String {
+    let value = serde_json::to_value(program).unwrap_or(Value::Null);
+    let mut output = String::new();
+    write_node(&mut output, None, &value, 0);
+    output
+}
+
+fn write_node(output: &mut String, label: Option<&str>, value: &Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let Value::Object(fields) = value else {
+        output.push_str(&format!("{}{}{}\n", indent, label.map(|l| format!("{}: ", l)).unwrap_or_default(), value));
+        return;
+    };
+
+    let mut header = String::new();
+    if let Some(label) = label {
+        header.push_str(&format!("{}: ", label));
+    }
+    header.push_str(fields.get("type").and_then(Value::as_str).unwrap_or("{}"));
+    if let Some(span) = fields.get("span") {
+        header.push_str(&format!(" @{}:{}", span["line"], span["column"]));
+    }
+
+    let mut children = Vec::new();
+    for (key, field) in fields {
+        if key == "type" || key == "span" {
+            continue;
+        }
+        match inline(field) {
+            Some(text) if text.is_empty() => {}
+            Some(text) if text == "true" => header.push_str(&format!(" {}", key)),
+            Some(text) => header.push_str(&format!(" {}={}", key, text)),
+            None => children.push((key, field)),
+        }
+    }
+
+    output.push_str(&format!("{}{}\n", indent, header));
+    for (key, field) in children {
+        match field {
+            Value::Array(items) => {
+                output.push_str(&format!("{}  {}:\n", indent, key));
+                for item in items {
+                    write_node(output, None, item, depth + 2);
+                }
+            }
+            _ => write_node(output, Some(key), field, depth + 1),
+        }
+    }
+}
+
+/// Short values shown on the node's own line; `None` for child nodes.
+/// An empty string means "omit" (false, null and empty lists).
+fn inline(value: &Value) -> Option<String> {
+    match value {
+        Value::Null | Value::Bool(false) => Some(String::new()),
+        Value::Bool(true) => Some("true".to_string()),
+        Value::Number(_) | Value::String(_) => Some(value.to_string()),
+        Value::Array(items) if items.is_empty() => Some(String::new()),
+        Value::Array(items) if items.iter().all(is_ident) => {
+            let names: Vec<String> = items.iter().filter_map(ident_name).collect();
+            Some(format!("[{}]", names.join(", ")))
+        }
+        Value::Array(_) => None,
+        Value::Object(_) if is_ident(value) => ident_name(value),
+        // Literals and operators, e.g. {"Int": 1} shown as Int(1)
+        Value::Object(fields) if fields.len() == 1 && !fields.contains_key("type") => {
+            fields.iter().next().map(|(tag, inner)| format!("{}({})", tag, inner))
+        }
+        Value::Object(_) => None,
+    }
+}
+
+fn is_ident(value: &Value) -> bool {
+    value.get("type").and_then(Value::as_str) == Some("Ident")
+}
+
+fn ident_name(value: &Value) -> Option<String> {
+    value.get("name").and_then(Value::as_str).map(str::to_string)
+}
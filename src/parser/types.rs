@@ -0,0 +1,162 @@
+//! Type expressions
+
+use crate::lexer::TokenType;
+use crate::parser::ast::*;
+use crate::parser::recursive_descent::{PResult, Parser};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn at_type_start(&self) -> bool {
+        matches!(
+            self.peek_kind(),
+            TokenType::Ident(_) | TokenType::LeftParen | TokenType::LeftBracket | TokenType::Fn | TokenType::Amp
+        )
+    }
+
+    pub(crate) fn type_expr(&mut self) -> PResult<TypeExpr> {
+        let start = self.peek().span;
+
+        let kind = match self.peek_kind().clone() {
+            TokenType::Ident(_) => {
+                let name = self.expect_ident("a type")?;
+                if name.name == "Task" {
+                    let inner = if self.check(&TokenType::Less) {
+                        self.advance();
+                        let inner = self.type_expr()?;
+                        self.expect_closing_angle()?;
+                        Some(Box::new(inner))
+                    } else {
+                        None
+                    };
+                    TypeKind::Task { inner }
+                } else if let Some(primitive) = PrimitiveType::from_name(&name.name) {
+                    TypeKind::Primitive { name: primitive }
+                } else {
+                    let args = if self.check(&TokenType::Less) {
+                        self.generic_args()?
+                    } else {
+                        Vec::new()
+                    };
+                    TypeKind::Named { name, args }
+                }
+            }
+            TokenType::Fn => {
+                self.advance();
+                let params = self.type_list(&TokenType::LeftParen, &TokenType::RightParen)?;
+                let ret = if !self.newline_before() && self.at_type_start() {
+                    Some(Box::new(self.type_expr()?))
+                } else {
+                    None
+                };
+                TypeKind::Function { params, ret }
+            }
+            TokenType::LeftParen => {
+                let elems = self.type_list(&TokenType::LeftParen, &TokenType::RightParen)?;
+                TypeKind::Tuple { elems }
+            }
+            TokenType::LeftBracket => {
+                self.advance();
+                if self.eat(&TokenType::RightBracket) {
+                    TypeKind::Slice { elem: Box::new(self.type_expr()?) }
+                } else if let (TokenType::IntLit(size), true) = (self.peek_kind().clone(), self.check_nth(1, &TokenType::RightBracket)) {
+                    self.advance();
+                    self.advance();
+                    TypeKind::Array { elem: Box::new(self.type_expr()?), size: Some(size) }
+                } else {
+                    let elem = self.type_expr()?;
+                    self.expect(&TokenType::RightBracket, "`]`")?;
+                    TypeKind::Array { elem: Box::new(elem), size: None }
+                }
+            }
+            TokenType::Amp => {
+                self.advance();
+                let mutable = self.eat(&TokenType::Mut);
+                let lifetime = if self.at_lifetime() { Some(self.expect_ident("a lifetime")?) } else { None };
+                TypeKind::Reference { mutable, lifetime, inner: Box::new(self.type_expr()?) }
+            }
+            _ => return Err(self.error_expected("a type")),
+        };
+
+        let ty = TypeExpr { kind, span: self.span_from(start) };
+        Ok(self.fallible_suffix(ty))
+    }
+
+    /// After `&` or `&mut`: a name followed on the same line by another
+    /// type is a lifetime, as in `&L str`
+    fn at_lifetime(&self) -> bool {
+        let next = self.peek_nth(1);
+        matches!(self.peek_kind(), TokenType::Ident(_))
+            && next.span.line == self.peek().span.line
+            && matches!(
+                next.kind,
+                TokenType::Ident(_) | TokenType::LeftParen | TokenType::LeftBracket | TokenType::Fn | TokenType::Amp
+            )
+    }
+
+    /// Target of `as`: a bare name, a tuple or `[T]`. Names take no `<...>`,
+    /// so `x as int < y` is a comparison.
+    pub(crate) fn cast_type(&mut self) -> PResult<TypeExpr> {
+        let start = self.peek().span;
+
+        let kind = match self.peek_kind() {
+            TokenType::Ident(_) => {
+                let name = self.expect_ident("a type")?;
+                if name.name == "Task" {
+                    TypeKind::Task { inner: None }
+                } else if let Some(primitive) = PrimitiveType::from_name(&name.name) {
+                    TypeKind::Primitive { name: primitive }
+                } else {
+                    TypeKind::Named { name, args: Vec::new() }
+                }
+            }
+            TokenType::LeftParen => {
+                let elems = self.type_list(&TokenType::LeftParen, &TokenType::RightParen)?;
+                TypeKind::Tuple { elems }
+            }
+            TokenType::LeftBracket => {
+                self.advance();
+                let elem = self.type_expr()?;
+                self.expect(&TokenType::RightBracket, "`]`")?;
+                TypeKind::Array { elem: Box::new(elem), size: None }
+            }
+            _ => return Err(self.error_expected("a type")),
+        };
+
+        let ty = TypeExpr { kind, span: self.span_from(start) };
+        Ok(self.fallible_suffix(ty))
+    }
+
+    /// `T!`, `T!!`, ... on the same line
+    fn fallible_suffix(&mut self, mut ty: TypeExpr) -> TypeExpr {
+        let start = ty.span;
+        while self.check(&TokenType::Bang) && !self.newline_before() {
+            self.advance();
+            ty = TypeExpr { kind: TypeKind::Fallible { inner: Box::new(ty) }, span: self.span_from(start) };
+        }
+        ty
+    }
+
+    /// `<A, B>` after a generic type name
+    pub(crate) fn generic_args(&mut self) -> PResult<Vec<TypeExpr>> {
+        self.expect(&TokenType::Less, "`<`")?;
+        let mut args = vec![self.type_expr()?];
+        while self.eat(&TokenType::Comma) {
+            args.push(self.type_expr()?);
+        }
+        self.expect_closing_angle()?;
+        Ok(args)
+    }
+
+    /// Comma-separated types between `open` and `close`, trailing comma allowed
+    pub(crate) fn type_list(&mut self, open: &TokenType, close: &TokenType) -> PResult<Vec<TypeExpr>> {
+        self.expect(open, "`(`")?;
+        let mut types = Vec::new();
+        while !self.check(close) {
+            types.push(self.type_expr()?);
+            if !self.eat(&TokenType::Comma) {
+                break;
+            }
+        }
+        self.expect_one_of(close, &["`,`", "`)`"])?;
+        Ok(types)
+    }
+}
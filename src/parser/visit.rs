@@ -0,0 +1,792 @@
+//! Read-only AST traversal
+//!
+//! Override the `visit_*` methods a pass cares about and call the matching
+//! `walk_*` function from the override to keep descending. `visit_mut`
+//! generates `MutVisitor` from the same walk bodies, and `fold` rebuilds
+//! owned trees.
+//!
+//! Every walk destructures its node without `..` and matches every variant,
+//! so adding a field or a variant to `ast` fails to compile here until the
+//! traversal covers it.
+
+use crate::parser::ast::*;
+
+/// Expands to a visitor trait plus one `walk_*` function per node. The walk
+/// bodies only bind through match ergonomics, so the same text walks `&` and
+/// `&mut` trees.
+macro_rules! visitor {
+    (
+        $(#[$meta:meta])*
+        trait $Trait:ident $(<$lt:lifetime>)?;
+        ref = [$($r:tt)*];
+    ) => {
+        $(#[$meta])*
+        pub trait $Trait $(<$lt>)? {
+            fn visit_program(&mut self, program: $($r)* Program) { walk_program(self, program) }
+            fn visit_package_decl(&mut self, package: $($r)* PackageDecl) { walk_package_decl(self, package) }
+            fn visit_import(&mut self, import: $($r)* Import) { walk_import(self, import) }
+            fn visit_qualified_ident(&mut self, path: $($r)* QualifiedIdent) { walk_qualified_ident(self, path) }
+            fn visit_ident(&mut self, _ident: $($r)* Ident) {}
+            fn visit_item(&mut self, item: $($r)* Item) { walk_item(self, item) }
+            fn visit_error_node(&mut self, _node: $($r)* ErrorNode) {}
+            fn visit_attribute(&mut self, attribute: $($r)* Attribute) { walk_attribute(self, attribute) }
+            fn visit_function(&mut self, decl: $($r)* FunctionDecl) { walk_function(self, decl) }
+            fn visit_param(&mut self, param: $($r)* Param) { walk_param(self, param) }
+            fn visit_lifetime_params(&mut self, params: $($r)* LifetimeParams) { walk_lifetime_params(self, params) }
+            fn visit_outlives(&mut self, outlives: $($r)* Outlives) { walk_outlives(self, outlives) }
+            fn visit_generic_param(&mut self, param: $($r)* GenericParam) { walk_generic_param(self, param) }
+            fn visit_trait_bound(&mut self, bound: $($r)* TraitBound) { walk_trait_bound(self, bound) }
+            fn visit_assoc_binding(&mut self, binding: $($r)* AssocBinding) { walk_assoc_binding(self, binding) }
+            fn visit_where_clause(&mut self, clause: $($r)* WhereClause) { walk_where_clause(self, clause) }
+            fn visit_where_predicate(&mut self, predicate: $($r)* WherePredicate) { walk_where_predicate(self, predicate) }
+            fn visit_struct_decl(&mut self, decl: $($r)* StructDecl) { walk_struct_decl(self, decl) }
+            fn visit_struct_member(&mut self, member: $($r)* StructMember) { walk_struct_member(self, member) }
+            fn visit_field(&mut self, field: $($r)* FieldDecl) { walk_field(self, field) }
+            fn visit_property(&mut self, property: $($r)* PropertyDecl) { walk_property(self, property) }
+            fn visit_accessor(&mut self, accessor: $($r)* Accessor) { walk_accessor(self, accessor) }
+            fn visit_enum_decl(&mut self, decl: $($r)* EnumDecl) { walk_enum_decl(self, decl) }
+            fn visit_variant(&mut self, variant: $($r)* EnumVariant) { walk_variant(self, variant) }
+            fn visit_trait_decl(&mut self, decl: $($r)* TraitDecl) { walk_trait_decl(self, decl) }
+            fn visit_trait_item(&mut self, item: $($r)* TraitItem) { walk_trait_item(self, item) }
+            fn visit_method_signature(&mut self, method: $($r)* MethodSignature) { walk_method_signature(self, method) }
+            fn visit_associated_type(&mut self, assoc: $($r)* AssociatedType) { walk_associated_type(self, assoc) }
+            fn visit_impl_block(&mut self, block: $($r)* ImplBlock) { walk_impl_block(self, block) }
+            fn visit_extend_block(&mut self, block: $($r)* ExtendBlock) { walk_extend_block(self, block) }
+            fn visit_const_decl(&mut self, decl: $($r)* ConstDecl) { walk_const_decl(self, decl) }
+            fn visit_type_expr(&mut self, ty: $($r)* TypeExpr) { walk_type_expr(self, ty) }
+            fn visit_block(&mut self, block: $($r)* Block) { walk_block(self, block) }
+            fn visit_stmt(&mut self, stmt: $($r)* Stmt) { walk_stmt(self, stmt) }
+            fn visit_allocator(&mut self, allocator: $($r)* Allocator) { walk_allocator(self, allocator) }
+            fn visit_catch_clause(&mut self, catch: $($r)* CatchClause) { walk_catch_clause(self, catch) }
+            fn visit_resource(&mut self, resource: $($r)* Resource) { walk_resource(self, resource) }
+            fn visit_expr(&mut self, expr: $($r)* Expr) { walk_expr(self, expr) }
+            fn visit_literal(&mut self, literal: $($r)* Literal) { walk_literal(self, literal) }
+            fn visit_if_branch(&mut self, branch: $($r)* IfBranch) { walk_if_branch(self, branch) }
+            fn visit_match_arm(&mut self, arm: $($r)* MatchArm) { walk_match_arm(self, arm) }
+            fn visit_field_init(&mut self, init: $($r)* FieldInit) { walk_field_init(self, init) }
+            fn visit_dict_entry(&mut self, entry: $($r)* DictEntry) { walk_dict_entry(self, entry) }
+            fn visit_query_clause(&mut self, clause: $($r)* QueryClause) { walk_query_clause(self, clause) }
+            fn visit_order_key(&mut self, key: $($r)* OrderKey) { walk_order_key(self, key) }
+            fn visit_pattern(&mut self, pattern: $($r)* Pattern) { walk_pattern(self, pattern) }
+            fn visit_field_pattern(&mut self, field: $($r)* FieldPattern) { walk_field_pattern(self, field) }
+        }
+
+        pub fn walk_program<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, program: $($r)* Program) {
+            let Program { package, imports, items, span: _ } = program;
+            if let Some(package) = package {
+                v.visit_package_decl(package);
+            }
+            for import in imports {
+                v.visit_import(import);
+            }
+            for item in items {
+                v.visit_item(item);
+            }
+        }
+
+        pub fn walk_package_decl<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, package: $($r)* PackageDecl) {
+            let PackageDecl { path, span: _ } = package;
+            v.visit_qualified_ident(path);
+        }
+
+        pub fn walk_import<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, import: $($r)* Import) {
+            let Import { kind, span: _ } = import;
+            match kind {
+                ImportKind::Summon { path, alias } => {
+                    v.visit_qualified_ident(path);
+                    if let Some(alias) = alias {
+                        v.visit_ident(alias);
+                    }
+                }
+                ImportKind::From { path, items } => {
+                    v.visit_qualified_ident(path);
+                    for item in items {
+                        v.visit_ident(item);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_qualified_ident<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, path: $($r)* QualifiedIdent) {
+            let QualifiedIdent { segments, span: _ } = path;
+            for segment in segments {
+                v.visit_ident(segment);
+            }
+        }
+
+        pub fn walk_item<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, item: $($r)* Item) {
+            match item {
+                Item::Function(decl) => v.visit_function(decl),
+                Item::Struct(decl) => v.visit_struct_decl(decl),
+                Item::Enum(decl) => v.visit_enum_decl(decl),
+                Item::Trait(decl) => v.visit_trait_decl(decl),
+                Item::Impl(block) => v.visit_impl_block(block),
+                Item::Extend(block) => v.visit_extend_block(block),
+                Item::Const(decl) => v.visit_const_decl(decl),
+                Item::Error(node) => v.visit_error_node(node),
+            }
+        }
+
+        pub fn walk_attribute<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, attribute: $($r)* Attribute) {
+            let Attribute { name, args, span: _ } = attribute;
+            v.visit_ident(name);
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+
+        pub fn walk_function<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, decl: $($r)* FunctionDecl) {
+            let FunctionDecl { attributes, is_pub: _, is_async: _, name, lifetimes, generics, params, return_type, where_clause, body, span: _ } = decl;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            v.visit_ident(name);
+            if let Some(lifetimes) = lifetimes {
+                v.visit_lifetime_params(lifetimes);
+            }
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            for param in params {
+                v.visit_param(param);
+            }
+            if let Some(ty) = return_type {
+                v.visit_type_expr(ty);
+            }
+            if let Some(clause) = where_clause {
+                v.visit_where_clause(clause);
+            }
+            v.visit_block(body);
+        }
+
+        pub fn walk_param<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, param: $($r)* Param) {
+            let Param { is_mut: _, name, pattern, ty, default, span: _ } = param;
+            v.visit_ident(name);
+            if let Some(pattern) = pattern {
+                v.visit_pattern(pattern);
+            }
+            if let Some(ty) = ty {
+                v.visit_type_expr(ty);
+            }
+            if let Some(default) = default {
+                v.visit_expr(default);
+            }
+        }
+
+        pub fn walk_lifetime_params<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, params: $($r)* LifetimeParams) {
+            let LifetimeParams { names, outlives, span: _ } = params;
+            for name in names {
+                v.visit_ident(name);
+            }
+            for outlives in outlives {
+                v.visit_outlives(outlives);
+            }
+        }
+
+        pub fn walk_outlives<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, outlives: $($r)* Outlives) {
+            let Outlives { longer, shorter, span: _ } = outlives;
+            v.visit_ident(longer);
+            v.visit_ident(shorter);
+        }
+
+        pub fn walk_generic_param<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, param: $($r)* GenericParam) {
+            let GenericParam { name, bounds, span: _ } = param;
+            v.visit_ident(name);
+            for bound in bounds {
+                v.visit_trait_bound(bound);
+            }
+        }
+
+        pub fn walk_trait_bound<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, bound: $($r)* TraitBound) {
+            let TraitBound { kind, span: _ } = bound;
+            match kind {
+                BoundKind::Trait { name, args, bindings } => {
+                    v.visit_ident(name);
+                    for arg in args {
+                        v.visit_type_expr(arg);
+                    }
+                    for binding in bindings {
+                        v.visit_assoc_binding(binding);
+                    }
+                }
+                BoundKind::Function { name, params, ret } => {
+                    v.visit_ident(name);
+                    for param in params {
+                        v.visit_type_expr(param);
+                    }
+                    if let Some(ret) = ret {
+                        v.visit_type_expr(ret);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_assoc_binding<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, binding: $($r)* AssocBinding) {
+            let AssocBinding { name, ty, span: _ } = binding;
+            v.visit_ident(name);
+            v.visit_type_expr(ty);
+        }
+
+        pub fn walk_where_clause<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, clause: $($r)* WhereClause) {
+            let WhereClause { predicates, span: _ } = clause;
+            for predicate in predicates {
+                v.visit_where_predicate(predicate);
+            }
+        }
+
+        pub fn walk_where_predicate<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, predicate: $($r)* WherePredicate) {
+            let WherePredicate { name, bounds, span: _ } = predicate;
+            v.visit_ident(name);
+            for bound in bounds {
+                v.visit_trait_bound(bound);
+            }
+        }
+
+        pub fn walk_struct_decl<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, decl: $($r)* StructDecl) {
+            let StructDecl { attributes, is_pub: _, is_edge: _, name, generics, members, span: _ } = decl;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            v.visit_ident(name);
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            for member in members {
+                v.visit_struct_member(member);
+            }
+        }
+
+        pub fn walk_struct_member<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, member: $($r)* StructMember) {
+            match member {
+                StructMember::Field(field) => v.visit_field(field),
+                StructMember::Method(method) => v.visit_function(method),
+                StructMember::Property(property) => v.visit_property(property),
+            }
+        }
+
+        pub fn walk_field<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, field: $($r)* FieldDecl) {
+            let FieldDecl { is_pub: _, name, ty, span: _ } = field;
+            v.visit_ident(name);
+            v.visit_type_expr(ty);
+        }
+
+        pub fn walk_property<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, property: $($r)* PropertyDecl) {
+            let PropertyDecl { is_pub: _, name, ty, getter, setter, span: _ } = property;
+            v.visit_ident(name);
+            v.visit_type_expr(ty);
+            v.visit_accessor(getter);
+            if let Some(setter) = setter {
+                v.visit_accessor(setter);
+            }
+        }
+
+        pub fn walk_accessor<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, accessor: $($r)* Accessor) {
+            let Accessor { value, body, span: _ } = accessor;
+            if let Some(value) = value {
+                v.visit_ident(value);
+            }
+            if let Some(body) = body {
+                v.visit_block(body);
+            }
+        }
+
+        pub fn walk_enum_decl<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, decl: $($r)* EnumDecl) {
+            let EnumDecl { attributes, is_pub: _, name, generics, variants, span: _ } = decl;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            v.visit_ident(name);
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            for variant in variants {
+                v.visit_variant(variant);
+            }
+        }
+
+        pub fn walk_variant<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, variant: $($r)* EnumVariant) {
+            let EnumVariant { name, payload, span: _ } = variant;
+            v.visit_ident(name);
+            match payload {
+                Some(EnumPayload::Tuple { types }) => {
+                    for ty in types {
+                        v.visit_type_expr(ty);
+                    }
+                }
+                Some(EnumPayload::Struct { fields }) => {
+                    for field in fields {
+                        v.visit_field(field);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        pub fn walk_trait_decl<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, decl: $($r)* TraitDecl) {
+            let TraitDecl { attributes, is_pub: _, name, items, span: _ } = decl;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            v.visit_ident(name);
+            for item in items {
+                v.visit_trait_item(item);
+            }
+        }
+
+        pub fn walk_trait_item<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, item: $($r)* TraitItem) {
+            match item {
+                TraitItem::Method(method) => v.visit_method_signature(method),
+                TraitItem::AssociatedType(assoc) => v.visit_associated_type(assoc),
+            }
+        }
+
+        pub fn walk_method_signature<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, method: $($r)* MethodSignature) {
+            let MethodSignature { is_async: _, name, lifetimes, generics, params, return_type, where_clause, span: _ } = method;
+            v.visit_ident(name);
+            if let Some(lifetimes) = lifetimes {
+                v.visit_lifetime_params(lifetimes);
+            }
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            for param in params {
+                v.visit_param(param);
+            }
+            if let Some(ty) = return_type {
+                v.visit_type_expr(ty);
+            }
+            if let Some(clause) = where_clause {
+                v.visit_where_clause(clause);
+            }
+        }
+
+        pub fn walk_associated_type<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, assoc: $($r)* AssociatedType) {
+            let AssociatedType { name, span: _ } = assoc;
+            v.visit_ident(name);
+        }
+
+        pub fn walk_impl_block<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, block: $($r)* ImplBlock) {
+            let ImplBlock { attributes, generics, trait_name, target, where_clause, methods, span: _ } = block;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            if let Some(trait_name) = trait_name {
+                v.visit_type_expr(trait_name);
+            }
+            v.visit_type_expr(target);
+            if let Some(clause) = where_clause {
+                v.visit_where_clause(clause);
+            }
+            for method in methods {
+                v.visit_function(method);
+            }
+        }
+
+        pub fn walk_extend_block<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, block: $($r)* ExtendBlock) {
+            let ExtendBlock { attributes, generics, target, where_clause, methods, span: _ } = block;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            for param in generics {
+                v.visit_generic_param(param);
+            }
+            v.visit_type_expr(target);
+            if let Some(clause) = where_clause {
+                v.visit_where_clause(clause);
+            }
+            for method in methods {
+                v.visit_function(method);
+            }
+        }
+
+        pub fn walk_const_decl<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, decl: $($r)* ConstDecl) {
+            let ConstDecl { attributes, is_pub: _, name, ty, value, span: _ } = decl;
+            for attribute in attributes {
+                v.visit_attribute(attribute);
+            }
+            v.visit_ident(name);
+            if let Some(ty) = ty {
+                v.visit_type_expr(ty);
+            }
+            v.visit_expr(value);
+        }
+
+        pub fn walk_type_expr<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, ty: $($r)* TypeExpr) {
+            let TypeExpr { kind, span: _ } = ty;
+            match kind {
+                TypeKind::Primitive { name: _ } => {}
+                TypeKind::Named { name, args } => {
+                    v.visit_ident(name);
+                    for arg in args {
+                        v.visit_type_expr(arg);
+                    }
+                }
+                TypeKind::Function { params, ret } => {
+                    for param in params {
+                        v.visit_type_expr(param);
+                    }
+                    if let Some(ret) = ret {
+                        v.visit_type_expr(ret);
+                    }
+                }
+                TypeKind::Tuple { elems } => {
+                    for elem in elems {
+                        v.visit_type_expr(elem);
+                    }
+                }
+                TypeKind::Array { elem, size: _ } => v.visit_type_expr(elem),
+                TypeKind::Slice { elem } => v.visit_type_expr(elem),
+                TypeKind::Fallible { inner } => v.visit_type_expr(inner),
+                TypeKind::Reference { mutable: _, lifetime, inner } => {
+                    if let Some(lifetime) = lifetime {
+                        v.visit_ident(lifetime);
+                    }
+                    v.visit_type_expr(inner);
+                }
+                TypeKind::Task { inner } => {
+                    if let Some(inner) = inner {
+                        v.visit_type_expr(inner);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_block<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, block: $($r)* Block) {
+            let Block { stmts, span: _ } = block;
+            for stmt in stmts {
+                v.visit_stmt(stmt);
+            }
+        }
+
+        pub fn walk_stmt<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, stmt: $($r)* Stmt) {
+            let Stmt { kind, span: _ } = stmt;
+            match kind {
+                StmtKind::Let { mutable: _, name, ty, value } => {
+                    v.visit_ident(name);
+                    if let Some(ty) = ty {
+                        v.visit_type_expr(ty);
+                    }
+                    v.visit_expr(value);
+                }
+                StmtKind::ShortLet { name, value } => {
+                    v.visit_ident(name);
+                    v.visit_expr(value);
+                }
+                StmtKind::Extract { pattern, value } => {
+                    v.visit_pattern(pattern);
+                    v.visit_expr(value);
+                }
+                StmtKind::LetElse { pattern, value, else_block } => {
+                    v.visit_pattern(pattern);
+                    v.visit_expr(value);
+                    v.visit_block(else_block);
+                }
+                StmtKind::Item { item } => v.visit_item(item),
+                StmtKind::Expr { expr } => v.visit_expr(expr),
+                StmtKind::Return { value } => {
+                    if let Some(value) = value {
+                        v.visit_expr(value);
+                    }
+                }
+                StmtKind::Break | StmtKind::Continue | StmtKind::Error => {}
+                StmtKind::For { pattern, iter, body } => {
+                    v.visit_pattern(pattern);
+                    v.visit_expr(iter);
+                    v.visit_block(body);
+                }
+                StmtKind::While { pattern, condition, body } => {
+                    if let Some(pattern) = pattern {
+                        v.visit_pattern(pattern);
+                    }
+                    v.visit_expr(condition);
+                    v.visit_block(body);
+                }
+                StmtKind::Loop { body } | StmtKind::Unsafe { body } | StmtKind::Defer { body } => v.visit_block(body),
+                StmtKind::With { allocator, body } => {
+                    v.visit_allocator(allocator);
+                    v.visit_block(body);
+                }
+                StmtKind::Try { body, catch } => {
+                    v.visit_block(body);
+                    if let Some(catch) = catch {
+                        v.visit_catch_clause(catch);
+                    }
+                }
+                StmtKind::Using { resources, body } => {
+                    for resource in resources {
+                        v.visit_resource(resource);
+                    }
+                    v.visit_block(body);
+                }
+            }
+        }
+
+        pub fn walk_allocator<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, allocator: $($r)* Allocator) {
+            let Allocator { kind, span: _ } = allocator;
+            match kind {
+                AllocatorKind::Arena { size } => v.visit_expr(size),
+                AllocatorKind::Pool { ty, capacity } => {
+                    v.visit_type_expr(ty);
+                    v.visit_expr(capacity);
+                }
+                AllocatorKind::Gc | AllocatorKind::Heap => {}
+            }
+        }
+
+        pub fn walk_catch_clause<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, catch: $($r)* CatchClause) {
+            let CatchClause { binding, body, span: _ } = catch;
+            v.visit_ident(binding);
+            v.visit_block(body);
+        }
+
+        pub fn walk_resource<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, resource: $($r)* Resource) {
+            let Resource { name, value, span: _ } = resource;
+            v.visit_ident(name);
+            v.visit_expr(value);
+        }
+
+        pub fn walk_expr<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, expr: $($r)* Expr) {
+            let Expr { kind, span: _ } = expr;
+            match kind {
+                ExprKind::Literal { value } => v.visit_literal(value),
+                ExprKind::InterpolatedString { parts } => {
+                    for part in parts {
+                        match part {
+                            StringPart::Text(_) => {}
+                            StringPart::Expr(expr) => v.visit_expr(expr),
+                        }
+                    }
+                }
+                ExprKind::Ident { name: _ } | ExprKind::SelfRef | ExprKind::Error => {}
+                ExprKind::Unary { op: _, operand } => v.visit_expr(operand),
+                ExprKind::Binary { op: _, lhs, rhs } => {
+                    v.visit_expr(lhs);
+                    v.visit_expr(rhs);
+                }
+                ExprKind::Assign { op: _, target, value } => {
+                    v.visit_expr(target);
+                    v.visit_expr(value);
+                }
+                ExprKind::Call { callee, args } => {
+                    v.visit_expr(callee);
+                    for arg in args {
+                        v.visit_expr(arg);
+                    }
+                }
+                ExprKind::Index { target, index } => {
+                    v.visit_expr(target);
+                    v.visit_expr(index);
+                }
+                ExprKind::Field { target, name, safe: _ } => {
+                    v.visit_expr(target);
+                    v.visit_ident(name);
+                }
+                ExprKind::MethodCall { receiver, method, args, safe: _ } => {
+                    v.visit_expr(receiver);
+                    v.visit_ident(method);
+                    for arg in args {
+                        v.visit_expr(arg);
+                    }
+                }
+                ExprKind::Cast { expr, ty } => {
+                    v.visit_expr(expr);
+                    v.visit_type_expr(ty);
+                }
+                ExprKind::Range { start, end, inclusive: _ } => {
+                    v.visit_expr(start);
+                    v.visit_expr(end);
+                }
+                ExprKind::Try { expr } => v.visit_expr(expr),
+                ExprKind::Lambda { params, body } => {
+                    for param in params {
+                        v.visit_param(param);
+                    }
+                    v.visit_expr(body);
+                }
+                ExprKind::If { branches, else_block } => {
+                    for branch in branches {
+                        v.visit_if_branch(branch);
+                    }
+                    if let Some(block) = else_block {
+                        v.visit_block(block);
+                    }
+                }
+                ExprKind::Match { scrutinee, arms } => {
+                    v.visit_expr(scrutinee);
+                    for arm in arms {
+                        v.visit_match_arm(arm);
+                    }
+                }
+                ExprKind::Block { block } => v.visit_block(block),
+                ExprKind::Array { elems } | ExprKind::Tuple { elems } => {
+                    for elem in elems {
+                        v.visit_expr(elem);
+                    }
+                }
+                ExprKind::StructLit { name, fields } => {
+                    v.visit_ident(name);
+                    for field in fields {
+                        v.visit_field_init(field);
+                    }
+                }
+                ExprKind::ObjectLit { fields } => {
+                    for field in fields {
+                        v.visit_field_init(field);
+                    }
+                }
+                ExprKind::DictLit { entries } => {
+                    for entry in entries {
+                        v.visit_dict_entry(entry);
+                    }
+                }
+                ExprKind::Query { clauses } => {
+                    for clause in clauses {
+                        v.visit_query_clause(clause);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_literal<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(_v: &mut V, literal: $($r)* Literal) {
+            match literal {
+                Literal::Int(_) | Literal::Float(_) | Literal::Double(_) | Literal::String(_)
+                | Literal::Verbatim(_) | Literal::Char(_) | Literal::Bool(_) | Literal::Null => {}
+            }
+        }
+
+        pub fn walk_if_branch<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, branch: $($r)* IfBranch) {
+            let IfBranch { pattern, condition, body, span: _ } = branch;
+            if let Some(pattern) = pattern {
+                v.visit_pattern(pattern);
+            }
+            v.visit_expr(condition);
+            v.visit_block(body);
+        }
+
+        pub fn walk_match_arm<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, arm: $($r)* MatchArm) {
+            let MatchArm { pattern, guard, body, span: _ } = arm;
+            v.visit_pattern(pattern);
+            if let Some(guard) = guard {
+                v.visit_expr(guard);
+            }
+            v.visit_expr(body);
+        }
+
+        pub fn walk_field_init<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, init: $($r)* FieldInit) {
+            let FieldInit { name, value, span: _ } = init;
+            v.visit_ident(name);
+            v.visit_expr(value);
+        }
+
+        pub fn walk_dict_entry<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, entry: $($r)* DictEntry) {
+            let DictEntry { key, value, span: _ } = entry;
+            v.visit_expr(key);
+            v.visit_expr(value);
+        }
+
+        pub fn walk_pattern<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, pattern: $($r)* Pattern) {
+            let Pattern { kind, span: _ } = pattern;
+            match kind {
+                PatternKind::Wildcard => {}
+                PatternKind::Literal { value } => v.visit_literal(value),
+                PatternKind::Binding { name } => v.visit_ident(name),
+                PatternKind::Tuple { elems } => {
+                    for elem in elems {
+                        v.visit_pattern(elem);
+                    }
+                }
+                PatternKind::Path { path } => {
+                    for segment in path {
+                        v.visit_ident(segment);
+                    }
+                }
+                PatternKind::TupleStruct { path, elems } => {
+                    for segment in path {
+                        v.visit_ident(segment);
+                    }
+                    for elem in elems {
+                        v.visit_pattern(elem);
+                    }
+                }
+                PatternKind::Struct { path, fields } => {
+                    for segment in path {
+                        v.visit_ident(segment);
+                    }
+                    for field in fields {
+                        v.visit_field_pattern(field);
+                    }
+                }
+                PatternKind::List { elems } => {
+                    for elem in elems {
+                        v.visit_pattern(elem);
+                    }
+                }
+                PatternKind::Rest { name } => {
+                    if let Some(name) = name {
+                        v.visit_ident(name);
+                    }
+                }
+                PatternKind::Object { fields } => {
+                    for field in fields {
+                        v.visit_field_pattern(field);
+                    }
+                }
+            }
+        }
+
+        pub fn walk_query_clause<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, clause: $($r)* QueryClause) {
+            let QueryClause { kind, span: _ } = clause;
+            match kind {
+                QueryClauseKind::From { name, source } => {
+                    v.visit_ident(name);
+                    v.visit_expr(source);
+                }
+                QueryClauseKind::Let { name, value } => {
+                    v.visit_ident(name);
+                    v.visit_expr(value);
+                }
+                QueryClauseKind::Where { condition } => v.visit_expr(condition),
+                QueryClauseKind::OrderBy { keys } => {
+                    for key in keys {
+                        v.visit_order_key(key);
+                    }
+                }
+                QueryClauseKind::Join { name, source, outer_key, inner_key } => {
+                    v.visit_ident(name);
+                    v.visit_expr(source);
+                    v.visit_expr(outer_key);
+                    v.visit_expr(inner_key);
+                }
+                QueryClauseKind::Select { value } => v.visit_expr(value),
+                QueryClauseKind::GroupBy { value, key } => {
+                    v.visit_expr(value);
+                    v.visit_expr(key);
+                }
+            }
+        }
+
+        pub fn walk_order_key<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, key: $($r)* OrderKey) {
+            let OrderKey { key, descending: _, span: _ } = key;
+            v.visit_expr(key);
+        }
+
+        pub fn walk_field_pattern<$($lt,)? V: $Trait $(<$lt>)? + ?Sized>(v: &mut V, field: $($r)* FieldPattern) {
+            let FieldPattern { name, pattern, span: _ } = field;
+            v.visit_ident(name);
+            if let Some(pattern) = pattern {
+                v.visit_pattern(pattern);
+            }
+        }
+    };
+}
+
+pub(crate) use visitor;
+
+visitor! {
+    /// Read-only traversal; `'ast` lets a visitor keep references into the tree
+    trait Visitor<'ast>;
+    ref = [&'ast];
+}
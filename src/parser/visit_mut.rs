@@ -0,0 +1,12 @@
+//! In-place AST rewriting
+//!
+//! Same methods and walk order as `visit::Visitor`, over `&mut` nodes.
+
+use crate::parser::ast::*;
+use crate::parser::visit::visitor;
+
+visitor! {
+    /// Traversal that may change nodes in place
+    trait MutVisitor;
+    ref = [&mut];
+}
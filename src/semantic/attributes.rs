@@ -0,0 +1,381 @@
+//! Known attributes, their arguments and the items they decorate
+//!
+//! The grammar accepts any `@name(args)`; `REGISTRY` says which names
+//! mean something. Each entry fixes the arguments the attribute takes and
+//! the kinds of item it may decorate. `check` reports unknown names
+//! (E0205), misplaced attributes (E0206), bad arguments (E0207), repeats
+//! (E0208) and combinations that contradict each other, such as two
+//! different tiers or `@test` with `@bench` (E0209).
+//!
+//! Passes read attributes through `Attributed`, which every item with an
+//! attribute list implements; it sees only well-formed known attributes.
+
+use std::fmt;
+
+use crate::error_management::error_types::SemanticError;
+use crate::parser::ast::*;
+use crate::parser::printer;
+use crate::parser::visit::{self, Visitor};
+
+/// The kinds of item an attribute can decorate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Function,
+    /// A function inside a struct, `impl` or `extend` block
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Impl,
+    Extend,
+    Const,
+}
+
+/// Plural, as in "cannot be applied to structs"
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Target::Function => "functions",
+            Target::Method => "methods",
+            Target::Struct => "structs",
+            Target::Enum => "enums",
+            Target::Trait => "traits",
+            Target::Impl => "`impl` blocks",
+            Target::Extend => "`extend` blocks",
+            Target::Const => "constants",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What goes between the parentheses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgSchema {
+    /// No parentheses: `@inline`
+    None,
+    /// Exactly one of these names: `@tier(high)`
+    OneOf(&'static [&'static str]),
+    /// An optional string: `@deprecated` or `@deprecated("use f2")`
+    OptionalNote,
+    /// One or more names: `@allow(unused, shadowing)`
+    Lints,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeSpec {
+    pub name: &'static str,
+    pub args: ArgSchema,
+    pub targets: &'static [Target],
+}
+
+impl AttributeSpec {
+    /// How to write the attribute, e.g. `@tier(high|mid|low)`
+    pub fn usage(&self) -> String {
+        match self.args {
+            ArgSchema::None => format!("@{}", self.name),
+            ArgSchema::OneOf(values) => format!("@{}({})", self.name, values.join("|")),
+            ArgSchema::OptionalNote => format!("@{}(\"note\")", self.name),
+            ArgSchema::Lints => format!("@{}(lint)", self.name),
+        }
+    }
+}
+
+const FUNCTIONS: &[Target] = &[Target::Function, Target::Method];
+const ALL: &[Target] = &[
+    Target::Function,
+    Target::Method,
+    Target::Struct,
+    Target::Enum,
+    Target::Trait,
+    Target::Impl,
+    Target::Extend,
+    Target::Const,
+];
+
+/// Every attribute the compiler understands
+pub static REGISTRY: &[AttributeSpec] = &[
+    AttributeSpec {
+        name: "tier",
+        args: ArgSchema::OneOf(&["high", "mid", "low"]),
+        targets: &[Target::Function, Target::Method, Target::Struct, Target::Enum, Target::Impl, Target::Extend],
+    },
+    AttributeSpec { name: "inline", args: ArgSchema::None, targets: FUNCTIONS },
+    AttributeSpec { name: "test", args: ArgSchema::None, targets: &[Target::Function] },
+    AttributeSpec { name: "bench", args: ArgSchema::None, targets: &[Target::Function] },
+    AttributeSpec {
+        name: "deprecated",
+        args: ArgSchema::OptionalNote,
+        targets: &[Target::Function, Target::Method, Target::Struct, Target::Enum, Target::Trait, Target::Const],
+    },
+    AttributeSpec { name: "allow", args: ArgSchema::Lints, targets: ALL },
+    AttributeSpec {
+        name: "export",
+        args: ArgSchema::None,
+        targets: &[Target::Function, Target::Struct, Target::Enum, Target::Trait, Target::Const],
+    },
+];
+
+/// Pairs that cannot decorate the same item
+const CONFLICTS: &[(&str, &str)] = &[("test", "bench")];
+
+pub fn lookup(name: &str) -> Option<&'static AttributeSpec> {
+    REGISTRY.iter().find(|spec| spec.name == name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    High,
+    Mid,
+    Low,
+}
+
+/// A known attribute with well-formed arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attr {
+    Tier(Tier),
+    Inline,
+    Test,
+    Bench,
+    Deprecated { note: Option<String> },
+    Allow { lints: Vec<String> },
+    Export,
+}
+
+impl Attr {
+    /// `None` for unknown names and malformed arguments, which `check` reports
+    pub fn from_attribute(attribute: &Attribute) -> Option<Attr> {
+        let spec = lookup(&attribute.name.name)?;
+        let args = args(spec, &attribute.args)?;
+        Some(match (spec.name, args.as_slice()) {
+            ("tier", [level]) => Attr::Tier(match level.as_str() {
+                "high" => Tier::High,
+                "mid" => Tier::Mid,
+                _ => Tier::Low,
+            }),
+            ("inline", _) => Attr::Inline,
+            ("test", _) => Attr::Test,
+            ("bench", _) => Attr::Bench,
+            ("deprecated", note) => Attr::Deprecated { note: note.first().cloned() },
+            ("allow", lints) => Attr::Allow { lints: lints.to_vec() },
+            ("export", _) => Attr::Export,
+            _ => unreachable!("every registry entry is handled"),
+        })
+    }
+}
+
+/// The arguments as text, checked against the schema
+fn args(spec: &AttributeSpec, args: &[Expr]) -> Option<Vec<String>> {
+    let name = |arg: &Expr| match &arg.kind {
+        ExprKind::Ident { name } => Some(name.clone()),
+        _ => None,
+    };
+    match spec.args {
+        ArgSchema::None if args.is_empty() => Some(Vec::new()),
+        ArgSchema::OneOf(values) => match args {
+            [arg] => name(arg).filter(|n| values.contains(&n.as_str())).map(|n| vec![n]),
+            _ => None,
+        },
+        ArgSchema::OptionalNote => match args {
+            [] => Some(Vec::new()),
+            [Expr { kind: ExprKind::Literal { value: Literal::String(note) }, .. }] => Some(vec![note.clone()]),
+            _ => None,
+        },
+        ArgSchema::Lints if !args.is_empty() => args.iter().map(name).collect(),
+        _ => None,
+    }
+}
+
+/// Typed access to the attributes of an item
+pub trait Attributed {
+    fn attributes(&self) -> &[Attribute];
+
+    /// Known, well-formed attributes in source order
+    fn attrs(&self) -> Vec<Attr> {
+        self.attributes().iter().filter_map(Attr::from_attribute).collect()
+    }
+
+    /// The first `@tier`; `check` rejects a second one
+    fn tier(&self) -> Option<Tier> {
+        self.attrs().into_iter().find_map(|attr| match attr {
+            Attr::Tier(tier) => Some(tier),
+            _ => None,
+        })
+    }
+
+    fn has_attr(&self, attr: &Attr) -> bool {
+        self.attrs().contains(attr)
+    }
+
+    /// `Some(note)` when the item is `@deprecated`
+    fn deprecation(&self) -> Option<Option<String>> {
+        self.attrs().into_iter().find_map(|attr| match attr {
+            Attr::Deprecated { note } => Some(note),
+            _ => None,
+        })
+    }
+
+    /// Whether an `@allow` on the item names `lint`
+    fn allows(&self, lint: &str) -> bool {
+        self.attrs().iter().any(|attr| matches!(attr, Attr::Allow { lints } if lints.iter().any(|l| l == lint)))
+    }
+}
+
+macro_rules! attributed {
+    ($($ty:ty),*) => {
+        $(impl Attributed for $ty {
+            fn attributes(&self) -> &[Attribute] {
+                &self.attributes
+            }
+        })*
+    };
+}
+
+attributed!(FunctionDecl, StructDecl, EnumDecl, TraitDecl, ImplBlock, ExtendBlock, ConstDecl);
+
+impl Attributed for Item {
+    fn attributes(&self) -> &[Attribute] {
+        match self {
+            Item::Function(decl) => &decl.attributes,
+            Item::Struct(decl) => &decl.attributes,
+            Item::Enum(decl) => &decl.attributes,
+            Item::Trait(decl) => &decl.attributes,
+            Item::Impl(block) => &block.attributes,
+            Item::Extend(block) => &block.attributes,
+            Item::Const(decl) => &decl.attributes,
+            Item::Error(_) => &[],
+        }
+    }
+}
+
+/// Every attribute problem in `program`, in source order
+pub fn check(program: &Program) -> Vec<SemanticError> {
+    let mut checker = Checker { in_type_body: false, errors: Vec::new() };
+    checker.visit_program(program);
+    checker.errors
+}
+
+struct Checker {
+    /// Functions here are methods; a block inside starts over
+    in_type_body: bool,
+    errors: Vec<SemanticError>,
+}
+
+impl Checker {
+    fn check_list(&mut self, attributes: &[Attribute], target: Target) {
+        // Earlier well-formed attributes, with their arguments as text
+        let mut seen: Vec<(&'static str, Vec<String>, String)> = Vec::new();
+        for attribute in attributes {
+            let name = attribute.name.name.as_str();
+            let Some(spec) = lookup(name) else {
+                self.errors.push(SemanticError::UnknownAttribute {
+                    name: name.to_string(),
+                    known: REGISTRY.iter().map(|spec| format!("@{}", spec.name)).collect(),
+                    span: attribute.name.span,
+                });
+                continue;
+            };
+            if !spec.targets.contains(&target) {
+                self.errors.push(SemanticError::MisplacedAttribute {
+                    name: spec.name.to_string(),
+                    target: target.to_string(),
+                    allowed: spec.targets.iter().map(Target::to_string).collect(),
+                    span: attribute.span,
+                });
+                continue;
+            }
+            let Some(args) = args(spec, &attribute.args) else {
+                self.errors.push(SemanticError::InvalidAttributeArgs {
+                    name: spec.name.to_string(),
+                    usage: spec.usage(),
+                    span: attribute.span,
+                });
+                continue;
+            };
+
+            let text = source(attribute);
+            let earlier = seen.iter().find(|(other, other_args, _)| {
+                *other == spec.name && (spec.args != ArgSchema::Lints || other_args.iter().any(|l| args.contains(l)))
+            });
+            if let Some((_, other_args, other_text)) = earlier {
+                let repeat = spec.args == ArgSchema::Lints || *other_args == args;
+                self.errors.push(if repeat {
+                    SemanticError::DuplicateAttribute { name: spec.name.to_string(), span: attribute.span }
+                } else {
+                    SemanticError::ConflictingAttributes { first: other_text.clone(), second: text, span: attribute.span }
+                });
+                continue;
+            }
+            let conflict = seen.iter().find(|(other, _, _)| {
+                CONFLICTS.iter().any(|&(a, b)| (a, b) == (*other, spec.name) || (b, a) == (*other, spec.name))
+            });
+            if let Some((_, _, other_text)) = conflict {
+                self.errors.push(SemanticError::ConflictingAttributes {
+                    first: other_text.clone(),
+                    second: text,
+                    span: attribute.span,
+                });
+                continue;
+            }
+            seen.push((spec.name, args, text));
+        }
+    }
+
+    fn in_type(&mut self, walk: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.in_type_body, true);
+        walk(self);
+        self.in_type_body = outer;
+    }
+}
+
+/// `@tier(high)`, as written
+fn source(attribute: &Attribute) -> String {
+    if attribute.args.is_empty() {
+        return format!("@{}", attribute.name.name);
+    }
+    let args: Vec<String> = attribute.args.iter().map(printer::print_expr).collect();
+    format!("@{}({})", attribute.name.name, args.join(", "))
+}
+
+impl<'ast> Visitor<'ast> for Checker {
+    fn visit_function(&mut self, decl: &'ast FunctionDecl) {
+        let target = if self.in_type_body { Target::Method } else { Target::Function };
+        self.check_list(&decl.attributes, target);
+        visit::walk_function(self, decl);
+    }
+
+    fn visit_struct_decl(&mut self, decl: &'ast StructDecl) {
+        self.check_list(&decl.attributes, Target::Struct);
+        self.in_type(|checker| visit::walk_struct_decl(checker, decl));
+    }
+
+    fn visit_enum_decl(&mut self, decl: &'ast EnumDecl) {
+        self.check_list(&decl.attributes, Target::Enum);
+        visit::walk_enum_decl(self, decl);
+    }
+
+    fn visit_trait_decl(&mut self, decl: &'ast TraitDecl) {
+        self.check_list(&decl.attributes, Target::Trait);
+        visit::walk_trait_decl(self, decl);
+    }
+
+    fn visit_impl_block(&mut self, block: &'ast ImplBlock) {
+        self.check_list(&block.attributes, Target::Impl);
+        self.in_type(|checker| visit::walk_impl_block(checker, block));
+    }
+
+    fn visit_extend_block(&mut self, block: &'ast ExtendBlock) {
+        self.check_list(&block.attributes, Target::Extend);
+        self.in_type(|checker| visit::walk_extend_block(checker, block));
+    }
+
+    fn visit_const_decl(&mut self, decl: &'ast ConstDecl) {
+        self.check_list(&decl.attributes, Target::Const);
+        visit::walk_const_decl(self, decl);
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        let outer = std::mem::replace(&mut self.in_type_body, false);
+        visit::walk_block(self, block);
+        self.in_type_body = outer;
+    }
+}
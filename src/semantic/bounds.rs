@@ -0,0 +1,80 @@
+//! Where-clauses name declared generic parameters
+//!
+//! `where T: Display` constrains a parameter, so `T` must be one of the
+//! item's own generics or, for a method, one of the generics of the struct,
+//! `impl` or `extend` block around it (E0204). As in `methods`, the bare
+//! type arguments of an `extend` target that name no declared type are
+//! parameters too. Items declared inside a function body start over.
+
+use std::collections::HashSet;
+
+use crate::error_management::error_types::SemanticError;
+use crate::parser::ast::*;
+use crate::parser::visit::{self, Visitor};
+use crate::semantic::methods::{declared_types, extend_generics, generic_names};
+
+/// Every where-clause bound on an undeclared generic in `program`, in source order
+pub fn check(program: &Program) -> Vec<SemanticError> {
+    let mut checker = Checker { declared: declared_types(&[program]), generics: Vec::new(), errors: Vec::new() };
+    checker.visit_program(program);
+    checker.errors
+}
+
+struct Checker<'a> {
+    declared: HashSet<&'a str>,
+    /// Generics in scope: the enclosing block's, then the function's own
+    generics: Vec<String>,
+    errors: Vec<SemanticError>,
+}
+
+impl Checker<'_> {
+    /// Run `walk` with `generics` added to those in scope
+    fn with_generics(&mut self, generics: Vec<String>, walk: impl FnOnce(&mut Self)) {
+        let outer = self.generics.len();
+        self.generics.extend(generics);
+        walk(self);
+        self.generics.truncate(outer);
+    }
+}
+
+impl<'ast> Visitor<'ast> for Checker<'_> {
+    fn visit_function(&mut self, decl: &'ast FunctionDecl) {
+        self.with_generics(generic_names(&decl.generics), |checker| visit::walk_function(checker, decl));
+    }
+
+    fn visit_method_signature(&mut self, method: &'ast MethodSignature) {
+        self.with_generics(generic_names(&method.generics), |checker| visit::walk_method_signature(checker, method));
+    }
+
+    fn visit_struct_decl(&mut self, decl: &'ast StructDecl) {
+        self.with_generics(generic_names(&decl.generics), |checker| visit::walk_struct_decl(checker, decl));
+    }
+
+    fn visit_impl_block(&mut self, block: &'ast ImplBlock) {
+        self.with_generics(generic_names(&block.generics), |checker| visit::walk_impl_block(checker, block));
+    }
+
+    fn visit_extend_block(&mut self, block: &'ast ExtendBlock) {
+        let generics = extend_generics(block, &self.declared);
+        self.with_generics(generics, |checker| visit::walk_extend_block(checker, block));
+    }
+
+    /// A nested item sees none of the generics around it
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        if let StmtKind::Item { item } = &stmt.kind {
+            let outer = std::mem::take(&mut self.generics);
+            self.visit_item(item);
+            self.generics = outer;
+        } else {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_where_predicate(&mut self, predicate: &'ast WherePredicate) {
+        if !self.generics.contains(&predicate.name.name) {
+            let name = predicate.name.name.clone();
+            self.errors.push(SemanticError::UndeclaredGeneric { name, span: predicate.name.span });
+        }
+        visit::walk_where_predicate(self, predicate);
+    }
+}
@@ -0,0 +1,138 @@
+//! Lifetime declarations and `outlives` constraints
+//!
+//! A function declares its lifetimes after its name,
+//! `fn f[lifetime L, lifetime M where M outlives L]`, and every `&L T` in
+//! its signature or body must name one of them (E0202). Structs and
+//! constants declare none, so any lifetime there is undeclared. A nested
+//! function starts over with its own list.
+//!
+//! The constraints of one function form a graph from the longer lifetime to
+//! the shorter. Outliving is an ordering, so that graph must be acyclic
+//! (E0203); `L outlives L` counts as a cycle too.
+
+use std::collections::HashMap;
+
+use crate::error_management::error_types::SemanticError;
+use crate::parser::ast::*;
+use crate::parser::visit::{self, Visitor};
+
+/// Every undeclared lifetime and constraint cycle in `program`, in source order
+pub fn check(program: &Program) -> Vec<SemanticError> {
+    let mut checker = Checker { declared: Vec::new(), errors: Vec::new() };
+    checker.visit_program(program);
+    checker.errors
+}
+
+struct Checker {
+    /// Lifetimes of the innermost enclosing function
+    declared: Vec<String>,
+    errors: Vec<SemanticError>,
+}
+
+impl Checker {
+    fn with_lifetimes(&mut self, lifetimes: Option<&LifetimeParams>, walk: impl FnOnce(&mut Self)) {
+        let names = lifetimes.map(|l| l.names.iter().map(|n| n.name.clone()).collect()).unwrap_or_default();
+        let outer = std::mem::replace(&mut self.declared, names);
+        if let Some(lifetimes) = lifetimes {
+            self.constraints(lifetimes);
+        }
+        walk(self);
+        self.declared = outer;
+    }
+
+    fn check_declared(&mut self, name: &Ident) -> bool {
+        if self.declared.contains(&name.name) {
+            return true;
+        }
+        self.errors.push(SemanticError::UndeclaredLifetime { name: name.name.clone(), span: name.span });
+        false
+    }
+
+    /// Report undeclared names, then every cycle the remaining edges form
+    fn constraints(&mut self, lifetimes: &LifetimeParams) {
+        let mut edges: HashMap<&str, Vec<&Outlives>> = HashMap::new();
+        for outlives in &lifetimes.outlives {
+            let longer = self.check_declared(&outlives.longer);
+            let shorter = self.check_declared(&outlives.shorter);
+            if longer && shorter {
+                edges.entry(outlives.longer.name.as_str()).or_default().push(outlives);
+            }
+        }
+
+        let mut search = CycleSearch { edges, state: HashMap::new(), stack: Vec::new(), errors: &mut self.errors };
+        for name in &lifetimes.names {
+            search.visit(&name.name);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    OnStack,
+    Done,
+}
+
+/// Depth-first search that reports the cycle closed by each back edge
+struct CycleSearch<'a, 'e> {
+    edges: HashMap<&'a str, Vec<&'a Outlives>>,
+    state: HashMap<&'a str, State>,
+    stack: Vec<&'a str>,
+    errors: &'e mut Vec<SemanticError>,
+}
+
+impl<'a> CycleSearch<'a, '_> {
+    fn visit(&mut self, node: &'a str) {
+        if self.state.contains_key(node) {
+            return;
+        }
+        self.state.insert(node, State::OnStack);
+        self.stack.push(node);
+        for edge in self.edges.get(node).cloned().unwrap_or_default() {
+            let next = edge.shorter.name.as_str();
+            match self.state.get(next) {
+                Some(State::OnStack) => {
+                    let from = self.stack.iter().position(|n| *n == next).unwrap_or(0);
+                    let mut cycle: Vec<String> = self.stack[from..].iter().map(|n| n.to_string()).collect();
+                    cycle.push(next.to_string());
+                    self.errors.push(SemanticError::OutlivesCycle { cycle, span: edge.span });
+                }
+                Some(State::Done) => {}
+                None => self.visit(next),
+            }
+        }
+        self.stack.pop();
+        self.state.insert(node, State::Done);
+    }
+}
+
+impl<'ast> Visitor<'ast> for Checker {
+    fn visit_function(&mut self, decl: &'ast FunctionDecl) {
+        self.with_lifetimes(decl.lifetimes.as_ref(), |checker| visit::walk_function(checker, decl));
+    }
+
+    fn visit_method_signature(&mut self, method: &'ast MethodSignature) {
+        self.with_lifetimes(method.lifetimes.as_ref(), |checker| visit::walk_method_signature(checker, method));
+    }
+
+    /// The constraints were checked on entering the function
+    fn visit_lifetime_params(&mut self, _params: &'ast LifetimeParams) {}
+
+    fn visit_struct_decl(&mut self, decl: &'ast StructDecl) {
+        self.with_lifetimes(None, |checker| visit::walk_struct_decl(checker, decl));
+    }
+
+    fn visit_enum_decl(&mut self, decl: &'ast EnumDecl) {
+        self.with_lifetimes(None, |checker| visit::walk_enum_decl(checker, decl));
+    }
+
+    fn visit_const_decl(&mut self, decl: &'ast ConstDecl) {
+        self.with_lifetimes(None, |checker| visit::walk_const_decl(checker, decl));
+    }
+
+    fn visit_type_expr(&mut self, ty: &'ast TypeExpr) {
+        if let TypeKind::Reference { lifetime: Some(lifetime), .. } = &ty.kind {
+            self.check_declared(lifetime);
+        }
+        visit::walk_type_expr(self, ty);
+    }
+}
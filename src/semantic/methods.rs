@@ -0,0 +1,548 @@
+//! Method lookup, including extension methods from `extend` blocks
+//!
+//! `receiver.method(...)` resolves to the first of these that has a method
+//! of that name for the receiver's type:
+//!
+//! 1. an inherent `impl` of the type, in any module
+//! 2. an extension summoned by name, `from m summon [method]` or
+//!    `summon m.method`
+//! 3. an extension in the current module or in a module summoned whole,
+//!    `summon m`
+//!
+//! Inherent methods always win, so summoning a module never changes what an
+//! existing call means. Extensions from two modules at the same step are
+//! ambiguous (E0200). A call that only an unsummoned module's extension
+//! could answer gets E0201. Calls on receivers of unknown type, and calls
+//! that nothing here answers, are left alone: they may be built in.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::error_management::error_types::SemanticError;
+use crate::lexer::Span;
+use crate::parser::ast::*;
+use crate::parser::visit::{self, Visitor};
+
+/// A type as far as method lookup can tell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ty {
+    /// `int`, `Point` or `List<int>`
+    Named { name: String, args: Vec<Ty> },
+    Unknown,
+}
+
+impl Ty {
+    fn named(name: &str) -> Ty {
+        Ty::Named { name: name.to_string(), args: Vec::new() }
+    }
+
+    /// The type `ty` spells, seeing through references; function, tuple and
+    /// array types are `Unknown`
+    pub fn from_type_expr(ty: &TypeExpr) -> Ty {
+        match &ty.kind {
+            TypeKind::Reference { inner, .. } => Ty::from_type_expr(inner),
+            TypeKind::Primitive { name } => Ty::named(name.name()),
+            TypeKind::Named { name, args } => {
+                Ty::Named { name: name.name.clone(), args: args.iter().map(Ty::from_type_expr).collect() }
+            }
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn mentions(&self, names: &[String]) -> bool {
+        match self {
+            Ty::Named { name, args } => names.contains(name) || args.iter().any(|arg| arg.mentions(names)),
+            Ty::Unknown => false,
+        }
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ty::Named { name, args } if args.is_empty() => write!(f, "{}", name),
+            Ty::Named { name, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{}<{}>", name, args.join(", "))
+            }
+            Ty::Unknown => write!(f, "_"),
+        }
+    }
+}
+
+/// Where a method call goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MethodTarget {
+    /// A method of `impl ty { ... }`
+    Inherent { ty: String },
+    /// A method of `extend target { ... }` in `module`
+    Extension { module: String, target: String },
+}
+
+/// A `receiver.method(...)` that lookup found a method for
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedCall {
+    pub method: Ident,
+    pub receiver: Ty,
+    pub target: MethodTarget,
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    /// In source order
+    pub calls: Vec<ResolvedCall>,
+    pub errors: Vec<SemanticError>,
+}
+
+impl Resolution {
+    /// Where the call whose method name is at `span` goes
+    pub fn target_at(&self, span: Span) -> Option<&MethodTarget> {
+        self.calls.iter().find(|call| call.method.span == span).map(|call| &call.target)
+    }
+}
+
+/// Resolve the method calls in `program`, which may summon any of
+/// `modules`. A module's path is its `package` declaration.
+pub fn resolve(program: &Program, modules: &[&Program]) -> Resolution {
+    let current = module_path(program);
+    let all: Vec<&Program> = std::iter::once(program).chain(modules.iter().copied()).collect();
+
+    let mut resolver = Resolver {
+        methods: collect_methods(&all),
+        functions: HashMap::new(),
+        visible: Visible::new(program, &current, modules),
+        locals: Vec::new(),
+        self_ty: Ty::Unknown,
+        call_types: HashMap::new(),
+        resolution: Resolution::default(),
+    };
+    for item in &program.items {
+        if let Item::Function(function) = item {
+            if let Some(ret) = &function.return_type {
+                resolver.functions.insert(function.name.name.clone(), Ty::from_type_expr(ret));
+            }
+        }
+    }
+    resolver.visit_program(program);
+    resolver.resolution
+}
+
+fn module_path(program: &Program) -> String {
+    program.package.as_ref().map(|package| package.path.dotted()).unwrap_or_default()
+}
+
+/// A method some `impl` or `extend` block declares
+struct Method<'a> {
+    module: String,
+    target: Ty,
+    generics: Vec<String>,
+    decl: &'a FunctionDecl,
+    inherent: bool,
+}
+
+/// Names of the structs, enums and traits declared at the top of `programs`
+pub(crate) fn declared_types<'a>(programs: &[&'a Program]) -> HashSet<&'a str> {
+    programs
+        .iter()
+        .flat_map(|program| &program.items)
+        .filter_map(|item| match item {
+            Item::Struct(decl) => Some(decl.name.name.as_str()),
+            Item::Enum(decl) => Some(decl.name.name.as_str()),
+            Item::Trait(decl) => Some(decl.name.name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn collect_methods<'a>(programs: &[&'a Program]) -> Vec<Method<'a>> {
+    let declared = declared_types(programs);
+    let mut methods = Vec::new();
+    for program in programs {
+        let module = module_path(program);
+        for item in &program.items {
+            let (target, generics, decls, inherent) = match item {
+                // Trait methods need trait resolution, which does not exist yet
+                Item::Impl(block) if block.trait_name.is_none() => {
+                    (&block.target, generic_names(&block.generics), &block.methods, true)
+                }
+                Item::Extend(block) => (&block.target, extend_generics(block, &declared), &block.methods, false),
+                _ => continue,
+            };
+            let target = Ty::from_type_expr(target);
+            for decl in decls {
+                methods.push(Method { module: module.clone(), target: target.clone(), generics: generics.clone(), decl, inherent });
+            }
+        }
+    }
+    methods
+}
+
+pub(crate) fn generic_names(generics: &[GenericParam]) -> Vec<String> {
+    generics.iter().map(|param| param.name.name.clone()).collect()
+}
+
+/// Declared parameters plus the bare type arguments of the target that name
+/// no declared type, such as `T` in `extend List<T>`
+pub(crate) fn extend_generics(block: &ExtendBlock, declared: &HashSet<&str>) -> Vec<String> {
+    let mut generics = generic_names(&block.generics);
+    if let TypeKind::Named { args, .. } = &block.target.kind {
+        for arg in args {
+            if let TypeKind::Named { name, args } = &arg.kind {
+                if args.is_empty() && !declared.contains(name.name.as_str()) {
+                    generics.push(name.name.clone());
+                }
+            }
+        }
+    }
+    generics
+}
+
+/// Does a method declared for `target` apply to a receiver of type `ty`?
+/// Unknown type arguments match anything.
+fn matches(target: &Ty, generics: &[String], ty: &Ty) -> bool {
+    match (target, ty) {
+        (Ty::Named { name, args }, _) if args.is_empty() && generics.contains(name) => true,
+        (_, Ty::Unknown) => true,
+        (Ty::Named { name, args }, Ty::Named { name: ty_name, args: ty_args }) => {
+            name == ty_name
+                && (ty_args.is_empty()
+                    || args.len() == ty_args.len() && args.iter().zip(ty_args).all(|(arg, ty)| matches(arg, generics, ty)))
+        }
+        (Ty::Unknown, _) => false,
+    }
+}
+
+/// The extensions the current module can see
+struct Visible {
+    /// The current module and every module summoned whole
+    modules: Vec<String>,
+    /// `(module, method)` pairs summoned by name
+    methods: Vec<(String, String)>,
+}
+
+impl Visible {
+    fn new(program: &Program, current: &str, modules: &[&Program]) -> Self {
+        let known: Vec<String> = modules.iter().map(|module| module_path(module)).collect();
+        let mut visible = Visible { modules: vec![current.to_string()], methods: Vec::new() };
+        for import in &program.imports {
+            match &import.kind {
+                ImportKind::Summon { path, .. } => {
+                    let path = path.dotted();
+                    if known.contains(&path) {
+                        visible.modules.push(path);
+                    } else if let Some((module, method)) = path.rsplit_once('.') {
+                        visible.methods.push((module.to_string(), method.to_string()));
+                    }
+                }
+                ImportKind::From { path, items } => {
+                    for item in items {
+                        visible.methods.push((path.dotted(), item.name.clone()));
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    fn by_name(&self, method: &Method) -> bool {
+        self.methods.iter().any(|(module, name)| *module == method.module && *name == method.decl.name.name)
+    }
+
+    fn whole(&self, method: &Method) -> bool {
+        self.modules.contains(&method.module)
+    }
+}
+
+enum Lookup<'m, 'a> {
+    Found(&'m Method<'a>),
+    Ambiguous(Vec<String>),
+    NotSummoned(&'m Method<'a>),
+    Missing,
+}
+
+struct Resolver<'a> {
+    methods: Vec<Method<'a>>,
+    /// Declared return types of the current module's functions
+    functions: HashMap<String, Ty>,
+    visible: Visible,
+    /// Innermost scope last
+    locals: Vec<HashMap<String, Ty>>,
+    self_ty: Ty,
+    /// Return types of resolved calls, by the start of the method name
+    call_types: HashMap<usize, Ty>,
+    resolution: Resolution,
+}
+
+impl<'a> Resolver<'a> {
+    fn lookup(&self, ty: &Ty, name: &str) -> Lookup<'_, 'a> {
+        let candidates: Vec<&Method<'a>> = self
+            .methods
+            .iter()
+            .filter(|method| method.decl.name.name == name && matches(&method.target, &method.generics, ty))
+            .collect();
+
+        if let Some(method) = candidates.iter().find(|method| method.inherent) {
+            return Lookup::Found(method);
+        }
+        let steps: [&dyn Fn(&Method) -> bool; 2] = [&|m| self.visible.by_name(m), &|m| self.visible.whole(m)];
+        for visible in steps {
+            let found: Vec<&Method<'a>> = candidates.iter().copied().filter(|method| visible(method)).collect();
+            let mut modules: Vec<String> = Vec::new();
+            for method in &found {
+                if !modules.contains(&method.module) {
+                    modules.push(method.module.clone());
+                }
+            }
+            match modules.len() {
+                0 => continue,
+                // Two blocks of one module are a duplicate, not an ambiguity
+                1 => return Lookup::Found(found[0]),
+                _ => return Lookup::Ambiguous(modules),
+            }
+        }
+        match candidates.first() {
+            Some(method) => Lookup::NotSummoned(method),
+            None => Lookup::Missing,
+        }
+    }
+
+    fn resolve_call(&mut self, receiver: &Expr, method: &Ident) {
+        let ty = self.type_of(receiver);
+        if ty == Ty::Unknown {
+            return;
+        }
+        let (method, receiver) = (method.clone(), ty.clone());
+        match self.lookup(&ty, &method.name) {
+            Lookup::Found(found) => {
+                let target = if found.inherent {
+                    MethodTarget::Inherent { ty: found.target.to_string() }
+                } else {
+                    MethodTarget::Extension { module: found.module.clone(), target: found.target.to_string() }
+                };
+                let ret = match &found.decl.return_type {
+                    Some(ret) => Ty::from_type_expr(ret),
+                    None => Ty::Unknown,
+                };
+                let ret = if ret.mentions(&found.generics) { Ty::Unknown } else { ret };
+                self.call_types.insert(method.span.start, ret);
+                self.resolution.calls.push(ResolvedCall { method, receiver, target });
+            }
+            Lookup::Ambiguous(modules) => {
+                self.resolution.errors.push(SemanticError::AmbiguousMethod {
+                    method: method.name,
+                    ty: receiver.to_string(),
+                    modules,
+                    span: method.span,
+                });
+            }
+            Lookup::NotSummoned(found) => {
+                let module = found.module.clone();
+                self.resolution.errors.push(SemanticError::MethodNotSummoned {
+                    method: method.name,
+                    ty: receiver.to_string(),
+                    module,
+                    span: method.span,
+                });
+            }
+            Lookup::Missing => {}
+        }
+    }
+
+    fn type_of(&self, expr: &Expr) -> Ty {
+        match &expr.kind {
+            ExprKind::Literal { value } => match value {
+                Literal::Int(_) => Ty::named("int"),
+                Literal::Float(_) => Ty::named("float"),
+                Literal::Double(_) => Ty::named("double"),
+                Literal::String(_) | Literal::Verbatim(_) => Ty::named("string"),
+                Literal::Char(_) => Ty::named("char"),
+                Literal::Bool(_) => Ty::named("bool"),
+                Literal::Null => Ty::Unknown,
+            },
+            ExprKind::InterpolatedString { .. } => Ty::named("string"),
+            ExprKind::Ident { name } => self.local(name).cloned().unwrap_or(Ty::Unknown),
+            ExprKind::SelfRef => self.self_ty.clone(),
+            ExprKind::Cast { ty, .. } => Ty::from_type_expr(ty),
+            ExprKind::StructLit { name, .. } => Ty::named(&name.name),
+            ExprKind::Array { elems } => Ty::Named {
+                name: "List".to_string(),
+                args: vec![elems.first().map_or(Ty::Unknown, |elem| self.type_of(elem))],
+            },
+            ExprKind::Call { callee, .. } => match &callee.kind {
+                ExprKind::Ident { name } if self.local(name).is_none() => {
+                    self.functions.get(name).cloned().unwrap_or(Ty::Unknown)
+                }
+                _ => Ty::Unknown,
+            },
+            ExprKind::MethodCall { method, .. } => self.call_types.get(&method.span.start).cloned().unwrap_or(Ty::Unknown),
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn local(&self, name: &str) -> Option<&Ty> {
+        self.locals.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn bind(&mut self, name: &str, ty: Ty) {
+        if let Some(scope) = self.locals.last_mut() {
+            scope.insert(name.to_string(), ty);
+        }
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.locals.push(HashMap::new());
+        f(self);
+        self.locals.pop();
+    }
+
+    fn with_self(&mut self, target: &TypeExpr, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.self_ty, Ty::from_type_expr(target));
+        f(self);
+        self.self_ty = outer;
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver<'_> {
+    fn visit_impl_block(&mut self, block: &'ast ImplBlock) {
+        self.with_self(&block.target, |r| visit::walk_impl_block(r, block));
+    }
+
+    fn visit_extend_block(&mut self, block: &'ast ExtendBlock) {
+        self.with_self(&block.target, |r| visit::walk_extend_block(r, block));
+    }
+
+    fn visit_function(&mut self, decl: &'ast FunctionDecl) {
+        self.scoped(|r| visit::walk_function(r, decl));
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        if param.pattern.is_none() {
+            let ty = match &param.ty {
+                Some(ty) => Ty::from_type_expr(ty),
+                None if param.name.name == "self" => self.self_ty.clone(),
+                None => Ty::Unknown,
+            };
+            self.bind(&param.name.name, ty);
+        }
+        visit::walk_param(self, param);
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|r| visit::walk_block(r, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        match &stmt.kind {
+            StmtKind::Let { name, ty, value, .. } => {
+                self.visit_expr(value);
+                let ty = match ty {
+                    Some(ty) => Ty::from_type_expr(ty),
+                    None => self.type_of(value),
+                };
+                self.bind(&name.name, ty);
+            }
+            StmtKind::ShortLet { name, value } => {
+                self.visit_expr(value);
+                let ty = self.type_of(value);
+                self.bind(&name.name, ty);
+            }
+            StmtKind::For { pattern, iter, body } => {
+                self.visit_expr(iter);
+                self.scoped(|r| {
+                    r.visit_pattern(pattern);
+                    r.visit_block(body);
+                });
+            }
+            StmtKind::While { pattern: Some(pattern), condition, body } => {
+                self.visit_expr(condition);
+                self.scoped(|r| {
+                    r.visit_pattern(pattern);
+                    r.visit_block(body);
+                });
+            }
+            // The bindings are only in scope after the statement, where the
+            // `else` block has not run
+            StmtKind::LetElse { pattern, value, else_block } => {
+                self.visit_expr(value);
+                self.visit_block(else_block);
+                self.visit_pattern(pattern);
+            }
+            StmtKind::Using { .. } => self.scoped(|r| visit::walk_stmt(r, stmt)),
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_resource(&mut self, resource: &'ast Resource) {
+        self.visit_expr(&resource.value);
+        let ty = self.type_of(&resource.value);
+        self.bind(&resource.name.name, ty);
+    }
+
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        self.scoped(|r| {
+            r.bind(&catch.binding.name, Ty::Unknown);
+            visit::walk_catch_clause(r, catch);
+        });
+    }
+
+    /// `if let` bindings are only in scope in the branch they guard
+    fn visit_if_branch(&mut self, branch: &'ast IfBranch) {
+        self.visit_expr(&branch.condition);
+        self.scoped(|r| {
+            if let Some(pattern) = &branch.pattern {
+                r.visit_pattern(pattern);
+            }
+            r.visit_block(&branch.body);
+        });
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.scoped(|r| visit::walk_match_arm(r, arm));
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            ExprKind::MethodCall { receiver, method, .. } => {
+                visit::walk_expr(self, expr);
+                self.resolve_call(receiver, method);
+            }
+            ExprKind::Lambda { .. } | ExprKind::Query { .. } => self.scoped(|r| visit::walk_expr(r, expr)),
+            // `x = 1` declares `x` unless it is already in scope
+            ExprKind::Assign { op: AssignOp::Assign, target, value } => {
+                visit::walk_expr(self, expr);
+                if let ExprKind::Ident { name } = &target.kind {
+                    if self.local(name).is_none() {
+                        let ty = self.type_of(value);
+                        self.bind(name, ty);
+                    }
+                }
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_query_clause(&mut self, clause: &'ast QueryClause) {
+        visit::walk_query_clause(self, clause);
+        match &clause.kind {
+            QueryClauseKind::From { name, .. } | QueryClauseKind::Let { name, .. } | QueryClauseKind::Join { name, .. } => {
+                self.bind(&name.name, Ty::Unknown);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        match &pattern.kind {
+            PatternKind::Binding { name } | PatternKind::Rest { name: Some(name) } => self.bind(&name.name, Ty::Unknown),
+            _ => {}
+        }
+        visit::walk_pattern(self, pattern);
+    }
+
+    fn visit_field_pattern(&mut self, field: &'ast FieldPattern) {
+        if field.pattern.is_none() {
+            self.bind(&field.name.name, Ty::Unknown);
+        }
+        visit::walk_field_pattern(self, field);
+    }
+}
@@ -0,0 +1,10 @@
+//! Analyses of parsed programs
+//!
+//! There is no type checker yet, so these passes only know the types the
+//! syntax spells out: literals, annotations, struct literals and the
+//! declared return types of functions.
+
+pub mod attributes;
+pub mod bounds;
+pub mod lifetimes;
+pub mod methods;
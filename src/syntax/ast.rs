@@ -0,0 +1,437 @@
+//! Typed views over the concrete syntax tree
+//!
+//! Each view wraps a `SyntaxNode` of a known kind and reads its children on
+//! demand, so it always reflects the tree it came from, edits included.
+//! `Type`, `Stmt`, `Expr` and `Pattern` accept any node of their category;
+//! cast them to a specific view such as `BinaryExpr` to look inside.
+
+use std::marker::PhantomData;
+
+use crate::parser::ast::BinaryOp;
+use crate::parser::precedence::{self, Infix};
+use crate::syntax::kind::SyntaxKind::{self, *};
+use crate::syntax::kind::{SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+/// Children of one view type, in source order
+pub struct AstChildren<N> {
+    inner: rowan::SyntaxNodeChildren<crate::syntax::UbelLanguage>,
+    ph: PhantomData<N>,
+}
+
+impl<N: AstNode> Iterator for AstChildren<N> {
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        self.inner.by_ref().find_map(N::cast)
+    }
+}
+
+fn children<N: AstNode>(parent: &SyntaxNode) -> AstChildren<N> {
+    AstChildren { inner: parent.children(), ph: PhantomData }
+}
+
+fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+    children(parent).next()
+}
+
+/// First direct token child that is not trivia and has one of `kinds`
+fn token(parent: &SyntaxNode, kinds: &[SyntaxKind]) -> Option<SyntaxToken> {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| kinds.contains(&token.kind()))
+}
+
+fn has_keyword(parent: &SyntaxNode, keyword: &str) -> bool {
+    parent
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .any(|token| token.kind() == KEYWORD && token.text() == keyword)
+}
+
+macro_rules! ast_node {
+    ($(#[$meta:meta])* $name:ident, $kinds:pat) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name {
+            syntax: SyntaxNode,
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $kinds)
+            }
+
+            fn cast(syntax: SyntaxNode) -> Option<Self> {
+                Self::can_cast(syntax.kind()).then_some($name { syntax })
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.syntax
+            }
+        }
+    };
+}
+
+/// Views with a declared name
+pub trait HasName: AstNode {
+    fn name(&self) -> Option<Name> {
+        child(self.syntax())
+    }
+}
+
+/// Declarations, which own the comments directly above them
+pub trait HasDocComments: AstNode {
+    /// Leading comments, text included, in source order
+    fn doc_comments(&self) -> Vec<SyntaxToken> {
+        self.syntax()
+            .children_with_tokens()
+            .map_while(|element| element.into_token().filter(|t| t.kind().is_trivia()))
+            .filter(|token| matches!(token.kind(), COMMENT | DOC_COMMENT))
+            .collect()
+    }
+}
+
+// ========================================
+// Items
+// ========================================
+
+ast_node!(SourceFile, SOURCE_FILE);
+ast_node!(Name, NAME);
+ast_node!(Attribute, ATTRIBUTE);
+ast_node!(Fn, FN);
+ast_node!(Param, PARAM);
+ast_node!(Struct, STRUCT);
+ast_node!(Field, FIELD);
+ast_node!(Enum, ENUM);
+ast_node!(Variant, VARIANT);
+ast_node!(Trait, TRAIT);
+ast_node!(Impl, IMPL);
+ast_node!(Extend, EXTEND);
+ast_node!(Const, CONST);
+
+/// A top-level declaration
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Item {
+    Fn(Fn),
+    Struct(Struct),
+    Enum(Enum),
+    Trait(Trait),
+    Impl(Impl),
+    Extend(Extend),
+    Const(Const),
+}
+
+impl AstNode for Item {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        matches!(kind, FN | STRUCT | ENUM | TRAIT | IMPL | EXTEND | CONST)
+    }
+
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        let item = match syntax.kind() {
+            FN => Item::Fn(Fn { syntax }),
+            STRUCT => Item::Struct(Struct { syntax }),
+            ENUM => Item::Enum(Enum { syntax }),
+            TRAIT => Item::Trait(Trait { syntax }),
+            IMPL => Item::Impl(Impl { syntax }),
+            EXTEND => Item::Extend(Extend { syntax }),
+            CONST => Item::Const(Const { syntax }),
+            _ => return None,
+        };
+        Some(item)
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Item::Fn(item) => item.syntax(),
+            Item::Struct(item) => item.syntax(),
+            Item::Enum(item) => item.syntax(),
+            Item::Trait(item) => item.syntax(),
+            Item::Impl(item) => item.syntax(),
+            Item::Extend(item) => item.syntax(),
+            Item::Const(item) => item.syntax(),
+        }
+    }
+}
+
+impl SourceFile {
+    pub fn items(&self) -> AstChildren<Item> {
+        children(&self.syntax)
+    }
+}
+
+impl Name {
+    pub fn ident(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, &[IDENT, KEYWORD])
+    }
+
+    pub fn text(&self) -> String {
+        self.ident().map(|t| t.text().to_string()).unwrap_or_default()
+    }
+}
+
+impl Attribute {
+    /// `tier` in `@tier(high)`
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, &[IDENT, KEYWORD])
+    }
+
+    pub fn args(&self) -> AstChildren<Expr> {
+        children(&self.syntax)
+    }
+}
+
+impl Fn {
+    pub fn attributes(&self) -> AstChildren<Attribute> {
+        children(&self.syntax)
+    }
+
+    pub fn is_pub(&self) -> bool {
+        has_keyword(&self.syntax, "pub")
+    }
+
+    pub fn is_async(&self) -> bool {
+        has_keyword(&self.syntax, "async")
+    }
+
+    pub fn params(&self) -> AstChildren<Param> {
+        children(&self.syntax)
+    }
+
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+}
+
+impl Param {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn default(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl Struct {
+    pub fn fields(&self) -> AstChildren<Field> {
+        children(&self.syntax)
+    }
+
+    pub fn methods(&self) -> AstChildren<Fn> {
+        children(&self.syntax)
+    }
+}
+
+impl Field {
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+}
+
+impl Enum {
+    pub fn variants(&self) -> AstChildren<Variant> {
+        children(&self.syntax)
+    }
+}
+
+impl Impl {
+    pub fn methods(&self) -> AstChildren<Fn> {
+        children(&self.syntax)
+    }
+}
+
+impl Extend {
+    pub fn methods(&self) -> AstChildren<Fn> {
+        children(&self.syntax)
+    }
+}
+
+impl Const {
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl HasName for Fn {}
+impl HasName for Param {}
+impl HasName for Struct {}
+impl HasName for Field {}
+impl HasName for Enum {}
+impl HasName for Variant {}
+impl HasName for Trait {}
+impl HasName for Const {}
+impl HasName for LetStmt {}
+
+impl HasDocComments for Fn {}
+impl HasDocComments for Struct {}
+impl HasDocComments for Field {}
+impl HasDocComments for Enum {}
+impl HasDocComments for Variant {}
+impl HasDocComments for Trait {}
+impl HasDocComments for Impl {}
+impl HasDocComments for Extend {}
+impl HasDocComments for Const {}
+
+// ========================================
+// Types, statements, expressions, patterns
+// ========================================
+
+ast_node!(
+    /// Any type node
+    Type, NAMED_TYPE | FN_TYPE | TUPLE_TYPE | ARRAY_TYPE | SLICE_TYPE | FALLIBLE_TYPE | REF_TYPE
+);
+ast_node!(
+    /// Any statement node except declarations, which appear as `Item`s
+    Stmt, LET_STMT | SHORT_LET_STMT | EXTRACT_STMT | LET_ELSE_STMT | EXPR_STMT | RETURN_STMT | BREAK_STMT
+        | CONTINUE_STMT | FOR_STMT | WHILE_STMT | LOOP_STMT | WITH_STMT | TRY_STMT | UNSAFE_STMT | USING_STMT | DEFER_STMT
+);
+ast_node!(
+    /// Any expression node
+    Expr, LITERAL | NAME_REF | SELF_EXPR | PREFIX_EXPR | BINARY_EXPR | ASSIGN_EXPR | CALL_EXPR
+        | INDEX_EXPR | FIELD_EXPR | METHOD_CALL_EXPR | CAST_EXPR | RANGE_EXPR | TRY_EXPR
+        | LAMBDA_EXPR | IF_EXPR | MATCH_EXPR | BLOCK_EXPR | ARRAY_EXPR | TUPLE_EXPR | STRUCT_LIT
+        | OBJECT_LIT | DICT_LIT | QUERY_EXPR
+);
+ast_node!(
+    /// Any pattern node
+    Pattern, WILDCARD_PAT | LITERAL_PAT | BINDING_PAT | TUPLE_PAT | PATH_PAT | TUPLE_STRUCT_PAT
+        | STRUCT_PAT | LIST_PAT | REST_PAT | OBJECT_PAT
+);
+
+ast_node!(Block, BLOCK);
+ast_node!(LetStmt, LET_STMT);
+ast_node!(ExprStmt, EXPR_STMT);
+ast_node!(NameRef, NAME_REF);
+ast_node!(Literal, LITERAL);
+ast_node!(BinaryExpr, BINARY_EXPR);
+ast_node!(CallExpr, CALL_EXPR);
+ast_node!(IfExpr, IF_EXPR);
+
+impl Type {
+    pub fn kind(&self) -> SyntaxKind {
+        self.syntax.kind()
+    }
+}
+
+impl Stmt {
+    pub fn kind(&self) -> SyntaxKind {
+        self.syntax.kind()
+    }
+}
+
+impl Expr {
+    pub fn kind(&self) -> SyntaxKind {
+        self.syntax.kind()
+    }
+}
+
+impl Pattern {
+    pub fn kind(&self) -> SyntaxKind {
+        self.syntax.kind()
+    }
+}
+
+impl Block {
+    /// Statements, not counting local declarations
+    pub fn stmts(&self) -> AstChildren<Stmt> {
+        children(&self.syntax)
+    }
+
+    pub fn items(&self) -> AstChildren<Item> {
+        children(&self.syntax)
+    }
+}
+
+impl LetStmt {
+    pub fn is_mut(&self) -> bool {
+        has_keyword(&self.syntax, "mut")
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.syntax)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl ExprStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+}
+
+impl NameRef {
+    pub fn text(&self) -> String {
+        self.syntax.text().to_string()
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        self.syntax.first_token()
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        self.operands().next()
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        self.operands().nth(1)
+    }
+
+    fn operands(&self) -> AstChildren<Expr> {
+        children(&self.syntax)
+    }
+
+    /// The operator token between the operands
+    pub fn op_token(&self) -> Option<SyntaxToken> {
+        token(&self.syntax, &[OPERATOR, KEYWORD])
+    }
+
+    pub fn op(&self) -> Option<BinaryOp> {
+        let token = self.op_token()?;
+        precedence::INFIX_OPERATORS
+            .iter()
+            .find(|op| op.lexeme == token.text())
+            .and_then(|op| match op.infix {
+                Infix::Binary(op) => Some(op),
+                _ => None,
+            })
+    }
+}
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.syntax)
+    }
+
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        children::<Expr>(&self.syntax).skip(1)
+    }
+}
+
+impl IfExpr {
+    /// `if` and `elif` conditions, in order
+    pub fn conditions(&self) -> impl Iterator<Item = Expr> {
+        self.syntax.children().filter(|n| n.kind() == IF_BRANCH).filter_map(|branch| child(&branch))
+    }
+
+    pub fn else_block(&self) -> Option<Block> {
+        child(&self.syntax)
+    }
+}
@@ -0,0 +1,267 @@
+//! Token and node kinds of the concrete syntax tree
+
+use crate::lexer::operators::{self, OperatorClass};
+use crate::lexer::{keywords, TokenType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(non_camel_case_types)]
+#[repr(u16)]
+pub enum SyntaxKind {
+    // ========================================
+    // Tokens
+    // ========================================
+
+    WHITESPACE,
+    COMMENT,
+    DOC_COMMENT,
+    IDENT,
+    KEYWORD,
+    INT_NUMBER,
+    FLOAT_NUMBER,
+    STRING,
+    CHAR,
+    OPERATOR,
+    /// Delimiters and punctuation: `( ) { } [ ] , . : ; @`
+    PUNCT,
+    /// Text the lexer rejected
+    ERROR_TOKEN,
+
+    // ========================================
+    // Items
+    // ========================================
+
+    SOURCE_FILE,
+    PACKAGE_DECL,
+    IMPORT,
+    ATTRIBUTE,
+    FN,
+    PARAM,
+    GENERIC_PARAM,
+    /// `Display`, `Iterator<Item = T>` or `Fn(int) bool`
+    TRAIT_BOUND,
+    /// `Item = T` in a bound
+    ASSOC_BINDING,
+    WHERE_CLAUSE,
+    WHERE_PREDICATE,
+    /// `[lifetime L where M outlives L]` after a function name
+    LIFETIME_PARAMS,
+    OUTLIVES,
+    STRUCT,
+    FIELD,
+    PROPERTY,
+    /// `get { ... }`, `set { ... }` or the auto-property `get;`
+    ACCESSOR,
+    ENUM,
+    VARIANT,
+    TRAIT,
+    METHOD_SIGNATURE,
+    ASSOCIATED_TYPE,
+    IMPL,
+    EXTEND,
+    CONST,
+    /// An identifier outside expressions (a declared name, a field, a type
+    /// or attribute name), wrapping its `IDENT`
+    NAME,
+    /// Input skipped while recovering from a syntax error
+    ERROR,
+
+    // ========================================
+    // Types
+    // ========================================
+
+    /// `int`, `Point`, `List<T>` or `Task<T>`
+    NAMED_TYPE,
+    FN_TYPE,
+    TUPLE_TYPE,
+    ARRAY_TYPE,
+    SLICE_TYPE,
+    FALLIBLE_TYPE,
+    /// `&T`, `&mut T` or `&L T`
+    REF_TYPE,
+
+    // ========================================
+    // Statements
+    // ========================================
+
+    BLOCK,
+    LET_STMT,
+    SHORT_LET_STMT,
+    EXTRACT_STMT,
+    LET_ELSE_STMT,
+    EXPR_STMT,
+    RETURN_STMT,
+    BREAK_STMT,
+    CONTINUE_STMT,
+    FOR_STMT,
+    WHILE_STMT,
+    LOOP_STMT,
+    WITH_STMT,
+    ALLOCATOR,
+    TRY_STMT,
+    CATCH_CLAUSE,
+    UNSAFE_STMT,
+    USING_STMT,
+    RESOURCE,
+    DEFER_STMT,
+
+    // ========================================
+    // Expressions
+    // ========================================
+
+    LITERAL,
+    NAME_REF,
+    SELF_EXPR,
+    PREFIX_EXPR,
+    BINARY_EXPR,
+    ASSIGN_EXPR,
+    CALL_EXPR,
+    INDEX_EXPR,
+    FIELD_EXPR,
+    METHOD_CALL_EXPR,
+    CAST_EXPR,
+    RANGE_EXPR,
+    TRY_EXPR,
+    LAMBDA_EXPR,
+    IF_EXPR,
+    /// `if` or `elif` condition and body
+    IF_BRANCH,
+    MATCH_EXPR,
+    MATCH_ARM,
+    BLOCK_EXPR,
+    ARRAY_EXPR,
+    TUPLE_EXPR,
+    STRUCT_LIT,
+    FIELD_INIT,
+    /// `{ name = value }`
+    OBJECT_LIT,
+    /// `{ "key" = value }`
+    DICT_LIT,
+    DICT_ENTRY,
+    /// `from x in xs ... select e`
+    QUERY_EXPR,
+    QUERY_CLAUSE,
+    ORDER_KEY,
+
+    // ========================================
+    // Patterns
+    // ========================================
+
+    WILDCARD_PAT,
+    LITERAL_PAT,
+    BINDING_PAT,
+    TUPLE_PAT,
+    PATH_PAT,
+    TUPLE_STRUCT_PAT,
+    STRUCT_PAT,
+    /// `[a, ...rest]`
+    LIST_PAT,
+    REST_PAT,
+    /// `{ id, name }`
+    OBJECT_PAT,
+    FIELD_PAT,
+}
+
+use SyntaxKind::*;
+
+impl SyntaxKind {
+    /// Every kind, in discriminant order
+    const ALL: &'static [SyntaxKind] = &[
+        WHITESPACE, COMMENT, DOC_COMMENT, IDENT, KEYWORD, INT_NUMBER, FLOAT_NUMBER, STRING, CHAR,
+        OPERATOR, PUNCT, ERROR_TOKEN,
+        SOURCE_FILE, PACKAGE_DECL, IMPORT, ATTRIBUTE, FN, PARAM, GENERIC_PARAM, TRAIT_BOUND, ASSOC_BINDING, WHERE_CLAUSE,
+        WHERE_PREDICATE, LIFETIME_PARAMS, OUTLIVES, STRUCT, FIELD, PROPERTY, ACCESSOR, ENUM, VARIANT, TRAIT, METHOD_SIGNATURE, ASSOCIATED_TYPE, IMPL, EXTEND, CONST, NAME,
+        ERROR,
+        NAMED_TYPE, FN_TYPE, TUPLE_TYPE, ARRAY_TYPE, SLICE_TYPE, FALLIBLE_TYPE, REF_TYPE,
+        BLOCK, LET_STMT, SHORT_LET_STMT, EXTRACT_STMT, LET_ELSE_STMT, EXPR_STMT, RETURN_STMT, BREAK_STMT, CONTINUE_STMT,
+        FOR_STMT, WHILE_STMT, LOOP_STMT, WITH_STMT, ALLOCATOR, TRY_STMT, CATCH_CLAUSE, UNSAFE_STMT,
+        USING_STMT, RESOURCE, DEFER_STMT,
+        LITERAL, NAME_REF, SELF_EXPR, PREFIX_EXPR, BINARY_EXPR, ASSIGN_EXPR, CALL_EXPR, INDEX_EXPR,
+        FIELD_EXPR, METHOD_CALL_EXPR, CAST_EXPR, RANGE_EXPR, TRY_EXPR, LAMBDA_EXPR, IF_EXPR,
+        IF_BRANCH, MATCH_EXPR, MATCH_ARM, BLOCK_EXPR, ARRAY_EXPR, TUPLE_EXPR, STRUCT_LIT, FIELD_INIT,
+        OBJECT_LIT, DICT_LIT, DICT_ENTRY, QUERY_EXPR, QUERY_CLAUSE, ORDER_KEY,
+        WILDCARD_PAT, LITERAL_PAT, BINDING_PAT, TUPLE_PAT, PATH_PAT, TUPLE_STRUCT_PAT, STRUCT_PAT,
+        LIST_PAT, REST_PAT, OBJECT_PAT, FIELD_PAT,
+    ];
+
+    pub fn is_token(self) -> bool {
+        self <= ERROR_TOKEN
+    }
+
+    /// Whitespace and comments: kept in the tree but never significant
+    pub fn is_trivia(self) -> bool {
+        matches!(self, WHITESPACE | COMMENT | DOC_COMMENT)
+    }
+
+    /// Declarations that own the comments directly above them
+    pub fn is_item(self) -> bool {
+        matches!(
+            self,
+            FN | STRUCT | FIELD | PROPERTY | ENUM | VARIANT | TRAIT | METHOD_SIGNATURE
+                | ASSOCIATED_TYPE | IMPL | EXTEND | CONST
+        )
+    }
+
+    pub fn is_type(self) -> bool {
+        (NAMED_TYPE..=REF_TYPE).contains(&self)
+    }
+
+    pub fn is_stmt(self) -> bool {
+        (LET_STMT..=DEFER_STMT).contains(&self) && !matches!(self, ALLOCATOR | CATCH_CLAUSE | RESOURCE)
+    }
+
+    pub fn is_expr(self) -> bool {
+        (LITERAL..=ORDER_KEY).contains(&self)
+            && !matches!(self, IF_BRANCH | MATCH_ARM | FIELD_INIT | DICT_ENTRY | QUERY_CLAUSE | ORDER_KEY)
+    }
+
+    pub fn is_pattern(self) -> bool {
+        (WILDCARD_PAT..=OBJECT_PAT).contains(&self)
+    }
+
+    /// Token kind for a lexer token
+    pub fn from_token(kind: &TokenType) -> SyntaxKind {
+        match kind {
+            TokenType::Ident(_) | TokenType::Contextual(_) => IDENT,
+            TokenType::IntLit(_) => INT_NUMBER,
+            TokenType::FloatLit(_) | TokenType::DoubleLit(_) => FLOAT_NUMBER,
+            TokenType::StringLit(_) | TokenType::InterpolatedString(_) | TokenType::VerbatimString(_) => STRING,
+            TokenType::CharLit(_) => CHAR,
+            TokenType::Comment(_) => COMMENT,
+            TokenType::DocComment(_) => DOC_COMMENT,
+            TokenType::Newline => WHITESPACE,
+            TokenType::Error(_) | TokenType::Eof => ERROR_TOKEN,
+            other => match operators::class_of(other) {
+                Some(OperatorClass::Delimiter | OperatorClass::Punctuation) => PUNCT,
+                Some(_) => OPERATOR,
+                None if keywords::KEYWORDS.values().any(|k| k == other) => KEYWORD,
+                None => ERROR_TOKEN,
+            },
+        }
+    }
+}
+
+impl From<SyntaxKind> for rowan::SyntaxKind {
+    fn from(kind: SyntaxKind) -> Self {
+        rowan::SyntaxKind(kind as u16)
+    }
+}
+
+/// The rowan language tag for Ubel Stratum trees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UbelLanguage {}
+
+impl rowan::Language for UbelLanguage {
+    type Kind = SyntaxKind;
+
+    fn kind_from_raw(raw: rowan::SyntaxKind) -> SyntaxKind {
+        SyntaxKind::ALL[raw.0 as usize]
+    }
+
+    fn kind_to_raw(kind: SyntaxKind) -> rowan::SyntaxKind {
+        kind.into()
+    }
+}
+
+pub type SyntaxNode = rowan::SyntaxNode<UbelLanguage>;
+pub type SyntaxToken = rowan::SyntaxToken<UbelLanguage>;
+pub type SyntaxElement = rowan::SyntaxElement<UbelLanguage>;
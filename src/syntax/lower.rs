@@ -0,0 +1,444 @@
+//! Build the lossless tree from the token stream and the typed AST
+//!
+//! The AST decides where nodes start and end; tokens fill them, and the
+//! gaps between tokens become `WHITESPACE`, `COMMENT`, or `ERROR_TOKEN` for
+//! text the lexer skipped. Every byte of the source lands in exactly one
+//! token, so the tree's text is always the input.
+//!
+//! Trivia between two nodes belongs to their parent, except that comments on
+//! the lines directly above a declaration belong to the declaration.
+
+use rowan::{GreenNode, GreenNodeBuilder};
+
+use crate::highlight::classify::block_comment_len;
+use crate::lexer::{Span, Token, TokenType};
+use crate::parser::ast::*;
+use crate::parser::visit::*;
+use crate::syntax::kind::SyntaxKind::{self, *};
+
+struct Leaf {
+    kind: SyntaxKind,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    kind: SyntaxKind,
+    start: usize,
+    end: usize,
+}
+
+pub(crate) fn build(source: &str, tokens: &[Token], program: &Program) -> GreenNode {
+    let leaves = leaves(source, tokens);
+
+    let mut nodes = Nodes::default();
+    nodes.visit_program(program);
+    let mut intervals = nodes.0;
+    for interval in intervals.iter_mut().filter(|i| i.kind.is_item()) {
+        interval.start = leading_comments_start(source, &leaves, interval.start);
+    }
+    // Outer nodes first; the walk already put parents before equal-range children
+    intervals.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+    let mut builder = GreenNodeBuilder::new();
+    builder.start_node(SOURCE_FILE.into());
+    let mut open: Vec<usize> = Vec::new();
+    let mut next = 0;
+
+    for leaf in &leaves {
+        while open.last().is_some_and(|&end| end <= leaf.start) {
+            builder.finish_node();
+            open.pop();
+        }
+        while let Some(interval) = intervals.get(next) {
+            // Nodes open at their first token, or at the next significant one
+            // when they start inside a token (`>>` closing two type lists)
+            let reached = interval.start == leaf.start || (interval.start < leaf.start && !leaf.kind.is_trivia());
+            if !reached {
+                break;
+            }
+            next += 1;
+            let fits = open.last().is_none_or(|&end| interval.end <= end);
+            if interval.end > leaf.start && fits {
+                builder.start_node(interval.kind.into());
+                open.push(interval.end);
+            }
+        }
+        builder.token(leaf.kind.into(), &source[leaf.start..leaf.end]);
+    }
+
+    for _ in open {
+        builder.finish_node();
+    }
+    builder.finish_node();
+    builder.finish()
+}
+
+/// Tokens plus the gaps between them, covering `source` exactly
+fn leaves(source: &str, tokens: &[Token]) -> Vec<Leaf> {
+    let mut leaves = Vec::with_capacity(tokens.len() * 2);
+    let mut cursor = 0;
+
+    // The lexer drops `//` and `/* */` comments, so they are found again here
+    let gap = |leaves: &mut Vec<Leaf>, start: usize, end: usize| {
+        let mut pos = start;
+        while pos < end {
+            let rest = &source[pos..end];
+            let (len, kind) = if rest.starts_with("//") {
+                (rest.find('\n').unwrap_or(rest.len()), COMMENT)
+            } else if rest.starts_with("/*") {
+                (block_comment_len(rest), COMMENT)
+            } else if rest.starts_with(char::is_whitespace) {
+                (rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()), WHITESPACE)
+            } else {
+                let len = rest.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(rest.len());
+                (len.max(rest.chars().next().map_or(1, char::len_utf8)), ERROR_TOKEN)
+            };
+            leaves.push(Leaf { kind, start: pos, end: pos + len });
+            pos += len;
+        }
+    };
+
+    for token in tokens {
+        if token.kind == TokenType::Eof || token.span.start < cursor || token.span.is_empty() {
+            continue;
+        }
+        gap(&mut leaves, cursor, token.span.start);
+        leaves.push(Leaf { kind: SyntaxKind::from_token(&token.kind), start: token.span.start, end: token.span.end });
+        cursor = token.span.end;
+    }
+    gap(&mut leaves, cursor, source.len());
+    leaves
+}
+
+/// Start of the comment block directly above the token at `start`: each
+/// comment must begin its own line, with no blank line before the next one
+fn leading_comments_start(source: &str, leaves: &[Leaf], start: usize) -> usize {
+    let Ok(mut first) = leaves.binary_search_by_key(&start, |leaf| leaf.start) else {
+        return start;
+    };
+    let newlines = |leaf: &Leaf| source[leaf.start..leaf.end].matches('\n').count();
+
+    loop {
+        let mut i = first;
+        if i > 0 && leaves[i - 1].kind == WHITESPACE {
+            if newlines(&leaves[i - 1]) > 1 {
+                break;
+            }
+            i -= 1;
+        }
+        if i == 0 || !matches!(leaves[i - 1].kind, COMMENT | DOC_COMMENT) {
+            break;
+        }
+        let comment = i - 1;
+        let own_line = comment == 0 || (leaves[comment - 1].kind == WHITESPACE && newlines(&leaves[comment - 1]) > 0);
+        if !own_line {
+            break;
+        }
+        first = comment;
+    }
+    leaves[first].start
+}
+
+/// Node intervals in AST preorder
+#[derive(Default)]
+struct Nodes(Vec<Interval>);
+
+impl Nodes {
+    fn push(&mut self, kind: SyntaxKind, span: Span) {
+        self.0.push(Interval { kind, start: span.start, end: span.end });
+    }
+}
+
+impl<'ast> Visitor<'ast> for Nodes {
+    fn visit_package_decl(&mut self, package: &'ast PackageDecl) {
+        self.push(PACKAGE_DECL, package.span);
+        walk_package_decl(self, package);
+    }
+
+    fn visit_import(&mut self, import: &'ast Import) {
+        self.push(IMPORT, import.span);
+        walk_import(self, import);
+    }
+
+    /// Dotted paths stay flat: `IDENT` and `.` tokens directly in the parent
+    fn visit_qualified_ident(&mut self, _path: &'ast QualifiedIdent) {}
+
+    fn visit_ident(&mut self, ident: &'ast Ident) {
+        self.push(NAME, ident.span);
+    }
+
+    fn visit_error_node(&mut self, node: &'ast ErrorNode) {
+        self.push(ERROR, node.span);
+    }
+
+    fn visit_attribute(&mut self, attribute: &'ast Attribute) {
+        self.push(ATTRIBUTE, attribute.span);
+        walk_attribute(self, attribute);
+    }
+
+    fn visit_function(&mut self, decl: &'ast FunctionDecl) {
+        self.push(FN, decl.span);
+        walk_function(self, decl);
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.push(PARAM, param.span);
+        walk_param(self, param);
+    }
+
+    fn visit_trait_bound(&mut self, bound: &'ast TraitBound) {
+        self.push(TRAIT_BOUND, bound.span);
+        walk_trait_bound(self, bound);
+    }
+
+    fn visit_assoc_binding(&mut self, binding: &'ast AssocBinding) {
+        self.push(ASSOC_BINDING, binding.span);
+        walk_assoc_binding(self, binding);
+    }
+
+    fn visit_where_clause(&mut self, clause: &'ast WhereClause) {
+        self.push(WHERE_CLAUSE, clause.span);
+        walk_where_clause(self, clause);
+    }
+
+    fn visit_where_predicate(&mut self, predicate: &'ast WherePredicate) {
+        self.push(WHERE_PREDICATE, predicate.span);
+        walk_where_predicate(self, predicate);
+    }
+
+    fn visit_lifetime_params(&mut self, params: &'ast LifetimeParams) {
+        self.push(LIFETIME_PARAMS, params.span);
+        walk_lifetime_params(self, params);
+    }
+
+    fn visit_outlives(&mut self, outlives: &'ast Outlives) {
+        self.push(OUTLIVES, outlives.span);
+        walk_outlives(self, outlives);
+    }
+
+    fn visit_generic_param(&mut self, param: &'ast GenericParam) {
+        self.push(GENERIC_PARAM, param.span);
+        walk_generic_param(self, param);
+    }
+
+    fn visit_struct_decl(&mut self, decl: &'ast StructDecl) {
+        self.push(STRUCT, decl.span);
+        walk_struct_decl(self, decl);
+    }
+
+    fn visit_field(&mut self, field: &'ast FieldDecl) {
+        self.push(FIELD, field.span);
+        walk_field(self, field);
+    }
+
+    fn visit_property(&mut self, property: &'ast PropertyDecl) {
+        self.push(PROPERTY, property.span);
+        walk_property(self, property);
+    }
+
+    fn visit_accessor(&mut self, accessor: &'ast Accessor) {
+        self.push(ACCESSOR, accessor.span);
+        // The implicit `value` spans the `set` keyword and gets no NAME
+        if let Some(body) = &accessor.body {
+            self.visit_block(body);
+        }
+    }
+
+    fn visit_enum_decl(&mut self, decl: &'ast EnumDecl) {
+        self.push(ENUM, decl.span);
+        walk_enum_decl(self, decl);
+    }
+
+    fn visit_variant(&mut self, variant: &'ast EnumVariant) {
+        self.push(VARIANT, variant.span);
+        walk_variant(self, variant);
+    }
+
+    fn visit_trait_decl(&mut self, decl: &'ast TraitDecl) {
+        self.push(TRAIT, decl.span);
+        walk_trait_decl(self, decl);
+    }
+
+    fn visit_method_signature(&mut self, method: &'ast MethodSignature) {
+        self.push(METHOD_SIGNATURE, method.span);
+        walk_method_signature(self, method);
+    }
+
+    fn visit_associated_type(&mut self, assoc: &'ast AssociatedType) {
+        self.push(ASSOCIATED_TYPE, assoc.span);
+        walk_associated_type(self, assoc);
+    }
+
+    fn visit_impl_block(&mut self, block: &'ast ImplBlock) {
+        self.push(IMPL, block.span);
+        walk_impl_block(self, block);
+    }
+
+    fn visit_extend_block(&mut self, block: &'ast ExtendBlock) {
+        self.push(EXTEND, block.span);
+        walk_extend_block(self, block);
+    }
+
+    fn visit_const_decl(&mut self, decl: &'ast ConstDecl) {
+        self.push(CONST, decl.span);
+        walk_const_decl(self, decl);
+    }
+
+    fn visit_type_expr(&mut self, ty: &'ast TypeExpr) {
+        let kind = match &ty.kind {
+            TypeKind::Primitive { .. } | TypeKind::Named { .. } | TypeKind::Task { .. } => NAMED_TYPE,
+            TypeKind::Function { .. } => FN_TYPE,
+            TypeKind::Tuple { .. } => TUPLE_TYPE,
+            TypeKind::Array { .. } => ARRAY_TYPE,
+            TypeKind::Slice { .. } => SLICE_TYPE,
+            TypeKind::Fallible { .. } => FALLIBLE_TYPE,
+            TypeKind::Reference { .. } => REF_TYPE,
+        };
+        self.push(kind, ty.span);
+        walk_type_expr(self, ty);
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.push(BLOCK, block.span);
+        walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        let kind = match &stmt.kind {
+            // The item is its own node
+            StmtKind::Item { .. } => None,
+            StmtKind::Let { .. } => Some(LET_STMT),
+            StmtKind::ShortLet { .. } => Some(SHORT_LET_STMT),
+            StmtKind::Extract { .. } => Some(EXTRACT_STMT),
+            StmtKind::LetElse { .. } => Some(LET_ELSE_STMT),
+            StmtKind::Expr { .. } => Some(EXPR_STMT),
+            StmtKind::Return { .. } => Some(RETURN_STMT),
+            StmtKind::Break => Some(BREAK_STMT),
+            StmtKind::Continue => Some(CONTINUE_STMT),
+            StmtKind::For { .. } => Some(FOR_STMT),
+            StmtKind::While { .. } => Some(WHILE_STMT),
+            StmtKind::Loop { .. } => Some(LOOP_STMT),
+            StmtKind::With { .. } => Some(WITH_STMT),
+            StmtKind::Try { .. } => Some(TRY_STMT),
+            StmtKind::Unsafe { .. } => Some(UNSAFE_STMT),
+            StmtKind::Using { .. } => Some(USING_STMT),
+            StmtKind::Defer { .. } => Some(DEFER_STMT),
+            StmtKind::Error => Some(ERROR),
+        };
+        if let Some(kind) = kind {
+            self.push(kind, stmt.span);
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_allocator(&mut self, allocator: &'ast Allocator) {
+        self.push(ALLOCATOR, allocator.span);
+        walk_allocator(self, allocator);
+    }
+
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        self.push(CATCH_CLAUSE, catch.span);
+        walk_catch_clause(self, catch);
+    }
+
+    fn visit_resource(&mut self, resource: &'ast Resource) {
+        self.push(RESOURCE, resource.span);
+        walk_resource(self, resource);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        let kind = match &expr.kind {
+            ExprKind::Literal { .. } => LITERAL,
+            ExprKind::InterpolatedString { .. } => {
+                // The holes sit inside one string token, which stays whole
+                self.push(LITERAL, expr.span);
+                return;
+            }
+            ExprKind::Ident { .. } => NAME_REF,
+            ExprKind::SelfRef => SELF_EXPR,
+            ExprKind::Unary { .. } => PREFIX_EXPR,
+            ExprKind::Binary { .. } => BINARY_EXPR,
+            ExprKind::Assign { .. } => ASSIGN_EXPR,
+            ExprKind::Call { .. } => CALL_EXPR,
+            ExprKind::Index { .. } => INDEX_EXPR,
+            ExprKind::Field { .. } => FIELD_EXPR,
+            ExprKind::MethodCall { .. } => METHOD_CALL_EXPR,
+            ExprKind::Cast { .. } => CAST_EXPR,
+            ExprKind::Range { .. } => RANGE_EXPR,
+            ExprKind::Try { .. } => TRY_EXPR,
+            ExprKind::Lambda { .. } => LAMBDA_EXPR,
+            ExprKind::If { .. } => IF_EXPR,
+            ExprKind::Match { .. } => MATCH_EXPR,
+            ExprKind::Block { .. } => BLOCK_EXPR,
+            ExprKind::Array { .. } => ARRAY_EXPR,
+            ExprKind::Tuple { .. } => TUPLE_EXPR,
+            ExprKind::StructLit { name, fields } => {
+                // The struct name is a reference, not a declaration
+                self.push(STRUCT_LIT, expr.span);
+                self.push(NAME_REF, name.span);
+                fields.iter().for_each(|f| self.visit_field_init(f));
+                return;
+            }
+            ExprKind::ObjectLit { .. } => OBJECT_LIT,
+            ExprKind::DictLit { .. } => DICT_LIT,
+            ExprKind::Query { .. } => QUERY_EXPR,
+            ExprKind::Error => ERROR,
+        };
+        self.push(kind, expr.span);
+        walk_expr(self, expr);
+    }
+
+    fn visit_if_branch(&mut self, branch: &'ast IfBranch) {
+        self.push(IF_BRANCH, branch.span);
+        walk_if_branch(self, branch);
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.push(MATCH_ARM, arm.span);
+        walk_match_arm(self, arm);
+    }
+
+    fn visit_field_init(&mut self, init: &'ast FieldInit) {
+        self.push(FIELD_INIT, init.span);
+        walk_field_init(self, init);
+    }
+
+    fn visit_dict_entry(&mut self, entry: &'ast DictEntry) {
+        self.push(DICT_ENTRY, entry.span);
+        walk_dict_entry(self, entry);
+    }
+
+    fn visit_query_clause(&mut self, clause: &'ast QueryClause) {
+        self.push(QUERY_CLAUSE, clause.span);
+        walk_query_clause(self, clause);
+    }
+
+    fn visit_order_key(&mut self, key: &'ast OrderKey) {
+        self.push(ORDER_KEY, key.span);
+        walk_order_key(self, key);
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        let kind = match &pattern.kind {
+            PatternKind::Wildcard => WILDCARD_PAT,
+            PatternKind::Literal { .. } => LITERAL_PAT,
+            PatternKind::Binding { .. } => BINDING_PAT,
+            PatternKind::Tuple { .. } => TUPLE_PAT,
+            PatternKind::Path { .. } => PATH_PAT,
+            PatternKind::TupleStruct { .. } => TUPLE_STRUCT_PAT,
+            PatternKind::Struct { .. } => STRUCT_PAT,
+            PatternKind::List { .. } => LIST_PAT,
+            PatternKind::Rest { .. } => REST_PAT,
+            PatternKind::Object { .. } => OBJECT_PAT,
+        };
+        self.push(kind, pattern.span);
+        walk_pattern(self, pattern);
+    }
+
+    fn visit_field_pattern(&mut self, field: &'ast FieldPattern) {
+        self.push(FIELD_PAT, field.span);
+        walk_field_pattern(self, field);
+    }
+}
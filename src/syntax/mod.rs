@@ -0,0 +1,71 @@
+//! Lossless concrete syntax tree for tooling
+//!
+//! A rowan green/red tree built alongside the typed AST: it keeps every
+//! token, whitespace run and comment, so `tree.to_string()` gives back the
+//! exact input. Red nodes (`SyntaxNode`) add parent pointers and absolute
+//! text ranges on top of the shared, immutable green nodes; `ast` layers
+//! typed views over them.
+//!
+//! Edits are persistent: `replace` returns a new root that shares every
+//! untouched subtree with the old one. For in-place editing use rowan's
+//! `clone_for_update` and `splice_children` directly.
+
+pub mod ast;
+pub mod kind;
+mod lower;
+
+pub use kind::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, UbelLanguage};
+
+use rowan::GreenNode;
+
+use crate::error_management::ErrorManager;
+use crate::lexer::LogosLexer;
+use crate::parser;
+
+/// A syntax tree together with the errors found while building it
+pub struct Parse {
+    green: GreenNode,
+    errors: ErrorManager,
+}
+
+impl Parse {
+    pub fn green(&self) -> &GreenNode {
+        &self.green
+    }
+
+    /// The root, as a red node
+    pub fn syntax(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green.clone())
+    }
+
+    pub fn tree(&self) -> ast::SourceFile {
+        use ast::AstNode;
+        ast::SourceFile::cast(self.syntax()).expect("the root is always a SOURCE_FILE")
+    }
+
+    pub fn errors(&self) -> &ErrorManager {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> ErrorManager {
+        self.errors
+    }
+}
+
+/// Build the lossless tree for `source`; never fails, even on invalid input
+pub fn parse(source: &str) -> Parse {
+    let (tokens, diagnostics) = LogosLexer::new(source).tokenize_all();
+    let (program, errors) = parser::parse_tokens(tokens.clone(), diagnostics, source);
+    let green = lower::build(source, &tokens, &program);
+    Parse { green, errors }
+}
+
+/// A new root in which `old` is replaced by `new`
+pub fn replace(old: &SyntaxNode, new: &SyntaxNode) -> SyntaxNode {
+    SyntaxNode::new_root(old.replace_with(new.green().into_owned()))
+}
+
+/// Indented dump of the tree: one line per node or token, with its range
+pub fn debug_tree(node: &SyntaxNode) -> String {
+    format!("{:#?}", node)
+}
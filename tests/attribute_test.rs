@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::{Item, Program, StructMember};
+    use ubel_stratum::semantic::attributes::{self, Attr, Attributed, Target, Tier};
+
+    fn parse(source: &str) -> Program {
+        match parser::parse(source) {
+            Ok(program) => program,
+            Err(manager) => panic!("parse failed: {:?}", manager.diagnostics()),
+        }
+    }
+
+    /// `(code, message)` for every attribute error
+    fn errors(source: &str) -> Vec<(&'static str, String)> {
+        attributes::check(&parse(source)).iter().map(|e| (e.code(), e.message())).collect()
+    }
+
+    #[test]
+    fn test_readme_attributes_check() {
+        let source = "@tier(high)\nasync fn handle_request(req: Request) Task<Response>! {\n    return Response.ok(req)\n}\n\n@tier(mid)\n@inline\nfn parse_json(input: string) JsonView {\n    return JsonView.from(input)\n}\n\n@test\n@allow(unused, shadowing)\nfn parses() {\n}\n\n@deprecated(\"use Point3\")\n@export\nstruct Point {\n    @inline\n    fn norm(self) int { return 0 }\n}\n";
+        assert!(errors(source).is_empty(), "{:?}", errors(source));
+    }
+
+    #[test]
+    fn test_unknown_and_misplaced_attributes() {
+        let program = parse("@teir(high)\nfn a() {\n}\n\n@test\nstruct S {\n    @test\n    fn m(self) {\n        @test\n        fn local() {\n        }\n    }\n}\n\n@export\nimpl Show for S {\n}\n");
+        let errors = attributes::check(&program);
+        let messages: Vec<String> = errors.iter().map(|e| e.message()).collect();
+        // A function declared inside a method body is a function again
+        assert_eq!(
+            messages,
+            [
+                "unknown attribute `@teir`",
+                "`@test` cannot be applied to structs",
+                "`@test` cannot be applied to methods",
+                "`@export` cannot be applied to `impl` blocks",
+            ]
+        );
+        assert_eq!(errors[0].code(), error_codes::E0205);
+        assert_eq!((errors[0].span().line, errors[0].span().column), (1, 2));
+        assert!(errors[0].suggestion().unwrap().starts_with("Known attributes are `@tier`, `@inline`"));
+        assert_eq!(errors[1].code(), error_codes::E0206);
+        assert_eq!(errors[1].suggestion().unwrap(), "`@test` applies to: functions");
+    }
+
+    #[test]
+    fn test_argument_schemas() {
+        let found = errors("@tier(fast)\n@inline(always)\n@deprecated(3)\n@allow\nfn f() {\n}\n\n@tier\n@deprecated\n@allow(a, b)\nfn g() {\n}\n");
+        let codes: Vec<&str> = found.iter().map(|(code, _)| *code).collect();
+        assert_eq!(codes, [error_codes::E0207; 5]);
+        assert_eq!(found[0].1, "invalid arguments to `@tier`");
+        let program = parse("@tier(fast)\nfn f() {\n}\n");
+        assert_eq!(attributes::check(&program)[0].suggestion().unwrap(), "Write it as `@tier(high|mid|low)`");
+    }
+
+    #[test]
+    fn test_duplicates_and_conflicts() {
+        let found = errors("@tier(high)\n@tier(high)\n@tier(low)\n@inline\n@inline\n@test\n@bench\n@allow(unused)\n@allow(dead, unused)\nfn f() {\n}\n");
+        assert_eq!(
+            found,
+            [
+                (error_codes::E0208, "duplicate attribute `@tier`".to_string()),
+                (error_codes::E0209, "`@tier(low)` conflicts with `@tier(high)`".to_string()),
+                (error_codes::E0208, "duplicate attribute `@inline`".to_string()),
+                (error_codes::E0209, "`@bench` conflicts with `@test`".to_string()),
+                (error_codes::E0208, "duplicate attribute `@allow`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_api() {
+        let program = parse("@tier(mid)\n@deprecated(\"use g\")\n@allow(shadowing)\n@teir(low)\nfn f() {\n}\n\nstruct S {\n    @inline\n    fn m(self) {\n    }\n}\n\nconst N: int = 1\n");
+        let item = &program.items[0];
+        assert_eq!(item.tier(), Some(Tier::Mid));
+        assert_eq!(item.deprecation(), Some(Some("use g".to_string())));
+        assert!(item.allows("shadowing"));
+        assert!(!item.allows("unused"));
+        // Unknown attributes are not part of the typed view
+        assert_eq!(item.attrs().len(), 3);
+
+        let Item::Struct(decl) = &program.items[1] else { panic!("expected a struct") };
+        let StructMember::Method(method) = &decl.members[0] else { panic!("expected a method") };
+        assert!(method.has_attr(&Attr::Inline));
+        assert_eq!(decl.tier(), None);
+        assert_eq!(program.items[2].deprecation(), None);
+
+        let tier = attributes::lookup("tier").unwrap();
+        assert_eq!(tier.usage(), "@tier(high|mid|low)");
+        assert!(tier.targets.contains(&Target::Method));
+        assert!(attributes::lookup("teir").is_none());
+    }
+
+    #[test]
+    fn test_error_code_examples() {
+        for code in [error_codes::E0205, error_codes::E0206, error_codes::E0207, error_codes::E0208, error_codes::E0209] {
+            let entry = error_codes::lookup(code).unwrap();
+            let bad: Vec<&str> = errors(entry.bad_example).iter().map(|(code, _)| *code).collect();
+            assert_eq!(bad, [code], "bad example for {}", code);
+            assert!(errors(entry.good_example).is_empty(), "good example for {}", code);
+        }
+    }
+
+    #[test]
+    fn test_semantic_error_is_miette_diagnostic() {
+        use miette::Diagnostic;
+
+        let errors = attributes::check(&parse("@teir(high)\nfn a() {\n}\n"));
+        let error = &errors[0];
+        assert_eq!(Diagnostic::code(error).map(|c| c.to_string()), Some("E0205".to_string()));
+        assert!(error.help().unwrap().to_string().starts_with("Known attributes are `@tier`"));
+        let label = error.labels().unwrap().next().unwrap();
+        assert_eq!((label.offset(), label.len()), (1, 4));
+    }
+}
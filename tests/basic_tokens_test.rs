@@ -26,6 +26,14 @@ mod tests {
         assert_eq!(tokens[16].kind, TokenType::Eof);
     }
 
+    #[test]
+    fn test_extract_using_extend_keywords() {
+        let input = "extract using extend";
+        let kinds: Vec<TokenType> = tokenize(input).unwrap().into_iter().map(|t| t.kind).collect();
+
+        assert_eq!(kinds, [TokenType::Extract, TokenType::Using, TokenType::Extend, TokenType::Eof]);
+    }
+
     #[test]
     fn test_operators() {
         let input = "+ - * / % & | ^ ~ << >> == != < > <= >= ! && || = += -= := => ?.";
@@ -101,6 +109,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_float_literals() {
         let input = "3.14 3.14f 1e10 1e10f 2.5e-3";
         let tokens = tokenize(input).unwrap();
@@ -112,6 +121,32 @@ mod tests {
         assert!(matches!(tokens[4].kind, TokenType::DoubleLit(f) if (f - 2.5e-3).abs() < 0.0001));
     }
 
+    #[test]
+    fn test_float_exponent_after_a_point() {
+        let input = "1.5e3 1.e5 2.5E+2f 6.02_e2_3";
+        let tokens = tokenize(input).unwrap();
+
+        assert_eq!(tokens.len(), 5);
+        assert!(matches!(tokens[0].kind, TokenType::DoubleLit(f) if f == 1500.0));
+        assert!(matches!(tokens[1].kind, TokenType::DoubleLit(f) if f == 1e5));
+        assert!(matches!(tokens[2].kind, TokenType::FloatLit(f) if f == 250.0));
+        assert!(matches!(tokens[3].kind, TokenType::DoubleLit(f) if f == 6.02e23));
+    }
+
+    #[test]
+    fn test_spans_after_hand_lexed_tokens() {
+        // Strings and comments are lexed by hand, then logos restarts after them;
+        // columns count the whitespace logos skips
+        let input = "$\"a\" x @\"b\" /* c */ y";
+        let tokens = tokenize(input).unwrap();
+        let spans: Vec<(&str, usize, usize)> =
+            tokens.iter().map(|t| (t.lexeme.as_str(), t.span.start, t.span.column)).filter(|(l, _, _)| l.len() == 1).collect();
+
+        assert_eq!(spans, [("x", 5, 6), ("y", 20, 21)]);
+        let eof = tokens.last().unwrap();
+        assert_eq!((eof.span.start, eof.span.column), (input.len(), input.len() + 1));
+    }
+
     #[test]
     fn test_simple_strings() {
         let input = r#""hello" "world\n" "with \"quotes\"" "#;
@@ -122,6 +157,23 @@ mod tests {
         assert!(matches!(tokens[2].kind, TokenType::StringLit(ref s) if s == "with \"quotes\""));
     }
 
+    #[test]
+    fn test_unterminated_string_runs_to_end_of_line() {
+        let input = "let y = \"abc + 1\nlet z = #";
+        let Err(mut errors) = tokenize(input) else { panic!("expected lexical errors") };
+        let errors: Vec<(String, usize, usize, usize)> =
+            errors.take_errors().iter().map(|e| (e.message(), e.span().end, e.span().line, e.span().column)).collect();
+
+        // One error for the string, then lexing resumes on the next line
+        assert_eq!(
+            errors,
+            [
+                ("Unterminated string literal".to_string(), input.find('\n').unwrap(), 1, 9),
+                ("Unexpected character '#'".to_string(), input.len(), 2, 9),
+            ]
+        );
+    }
+
     #[test]
     fn test_char_literals() {
         let input = r#"'a' 'Z' '\n' '\t' '\\' '\''"#;
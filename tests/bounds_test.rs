@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::parser;
+    use ubel_stratum::semantic::bounds;
+
+    fn undeclared(source: &str) -> Vec<String> {
+        match parser::parse(source) {
+            Ok(program) => bounds::check(&program).iter().map(|e| e.message()).collect(),
+            Err(manager) => panic!("parse failed: {:?}", manager.diagnostics()),
+        }
+    }
+
+    #[test]
+    fn test_readme_bounds_check() {
+        let source = "fn parse_json_with<F>(input: string, callback: F) Response\n    where F: FnOnce(&JsonView) Response\n{\n    return callback(input)\n}\n";
+        assert!(undeclared(source).is_empty());
+    }
+
+    #[test]
+    fn test_bounds_need_declared_generics() {
+        let program = parser::parse("fn show<T>(value: T) string where U: Display {\n    return value.to_string()\n}\n").unwrap();
+        let errors = bounds::check(&program);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code(), error_codes::E0204);
+        assert_eq!(errors[0].message(), "cannot bound `U`: it is not a generic parameter here");
+        assert_eq!(errors[0].suggestion().unwrap(), "Declare it first: `<U>`");
+        assert_eq!((errors[0].span().line, errors[0].span().column), (1, 35));
+
+        let entry = error_codes::lookup(error_codes::E0204).unwrap();
+        assert_eq!(undeclared(entry.bad_example).len(), 1);
+        assert!(undeclared(entry.good_example).is_empty());
+    }
+
+    #[test]
+    fn test_methods_see_enclosing_generics() {
+        // Generics of the struct, `impl` or `extend` around a method are in
+        // scope; `T` in `extend List<T>` is one because no type is named `T`
+        let source = "struct Box<T> {\n    fn map<U>(self, f: F) U where T: Clone, U: Clone { return f(self) }\n}\n\nimpl<K> Show for Dictionary<K, int> where K: Show {\n    fn show<V>(self) string where K: Display, V: Show { return \"\" }\n}\n\nextend List<T> where T: Display {\n    fn join(self) string where T: Into<string> { return \"\" }\n}\n\ntrait Mapper {\n    fn map<U>(self) U where U: Clone\n}\n";
+        assert!(undeclared(source).is_empty(), "{:?}", undeclared(source));
+
+        assert_eq!(
+            undeclared("struct Item {}\n\nextend List<Item> where Item: Display {}\n\ntrait Mapper {\n    fn map(self) U where U: Clone\n}\n"),
+            ["cannot bound `Item`: it is not a generic parameter here", "cannot bound `U`: it is not a generic parameter here"]
+        );
+    }
+
+    #[test]
+    fn test_nested_items_start_over() {
+        assert_eq!(
+            undeclared("fn outer<T>(x: T) {\n    fn inner(y: int) where T: Clone {}\n    let f = fn(z) z\n}\n"),
+            ["cannot bound `T`: it is not a generic parameter here"]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Output};
+
+    /// Run `stratc <args> <file>` over `source` written to a scratch file
+    fn stratc(name: &str, args: &[&str], source: &str) -> Output {
+        stratc_with_modules(name, args, source, &[])
+    }
+
+    /// As `stratc`, passing each of `modules` with `--module`
+    fn stratc_with_modules(name: &str, args: &[&str], source: &str, modules: &[&str]) -> Output {
+        let dir = std::env::temp_dir().join(format!("ubel_cli_test_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("demo.strat");
+        std::fs::write(&path, source).unwrap();
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_stratc"));
+        command.arg("--quiet").args(args).arg(&path);
+        for (i, module) in modules.iter().enumerate() {
+            let module_path = dir.join(format!("module{}.strat", i));
+            std::fs::write(&module_path, module).unwrap();
+            command.arg("--module").arg(module_path);
+        }
+        let output = command.output().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        output
+    }
+
+    /// stdout as one SARIF log
+    fn sarif_results(output: &Output) -> Vec<serde_json::Value> {
+        let log: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout is a single SARIF document");
+        log["runs"][0]["results"].as_array().unwrap().clone()
+    }
+
+    const WARNS: &str = "fn main() {\n    let b = 1.\n    log(b)\n}\n";
+
+    #[test]
+    fn test_machine_formats_keep_stdout_for_diagnostics() {
+        for command in [&["lex", "--verbose"][..], &["parse"], &["parse", "--cst"], &["check", "--diff"], &["highlight"]] {
+            let args: Vec<&str> = ["--message-format", "sarif"].iter().chain(command).copied().collect();
+            let output = stratc(&command.join("_").replace('-', ""), &args, WARNS);
+            let results = sarif_results(&output);
+            assert_eq!(results.len(), 1, "{:?}", command);
+            assert_eq!(results[0]["ruleId"], "W0003");
+            assert!(!output.stderr.is_empty(), "{:?} dropped its output", command);
+        }
+    }
+
+    #[test]
+    fn test_check_resolves_methods_across_modules() {
+        let ascii = "package text.ascii\n\nextend string {\n    fn shout(self) string { return self }\n}\n";
+        let unicode = "package text.unicode\n\nextend string {\n    fn shout(self) string { return self }\n}\n";
+        let source = "summon text.ascii\nsummon text.unicode\n\nfn main() {\n    \"hi\".shout()\n}\n";
+
+        let output = stratc_with_modules("ambiguous", &["--message-format", "sarif", "check"], source, &[ascii, unicode]);
+        let results = sarif_results(&output);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "E0200");
+        assert_eq!(results[0]["locations"][0]["physicalLocation"]["region"]["startLine"], 5);
+        assert!(!output.status.success());
+
+        let output = stratc_with_modules("summoned", &["check"], "summon text.ascii\n\nfn main() {\n    \"hi\".shout()\n}\n", &[ascii]);
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_text_format_prints_command_output() {
+        let output = stratc("parse_text", &["parse"], WARNS);
+        assert!(String::from_utf8(output.stdout).unwrap().contains("Function"));
+        assert!(output.status.success());
+    }
+}
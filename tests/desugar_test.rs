@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::*;
+    use ubel_stratum::parser::desugar;
+    use ubel_stratum::parser::printer;
+    use ubel_stratum::parser::visit::{self, Visitor};
+
+    /// The method chain `query` stands for, printed
+    fn desugared(query: &str) -> String {
+        let source = format!("fn main() {{\n    q = {}\n}}\n", query);
+        let program = desugar::desugar(parser::parse(&source).expect("query should parse"));
+        let Item::Function(function) = &program.items[0] else { panic!() };
+        let StmtKind::Expr { expr } = &function.body.stmts[0].kind else { panic!() };
+        let ExprKind::Assign { value, .. } = &expr.kind else { panic!() };
+        let printed = printer::print_expr(value);
+
+        // The chain is ordinary source that both parsers read back the same way
+        let reparsed = format!("fn main() {{\n    q = {}\n}}\n", printed);
+        let rd = parser::parse(&reparsed).expect("desugared query should parse");
+        let lalr = parser::lalr::parse(&reparsed).expect("desugared query should parse with LALR");
+        assert_eq!(serde_json::to_value(&rd).unwrap(), serde_json::to_value(&lalr).unwrap());
+        printed
+    }
+
+    #[test]
+    fn test_readme_query() {
+        assert_eq!(
+            desugared("from user in users\n        where user.age >= 18 and user.status == \"active\"\n        orderby user.name\n        select user.name"),
+            "users.where(user => user.age >= 18 and user.status == \"active\").orderby(user => user.name).map(user => user.name)",
+        );
+    }
+
+    #[test]
+    fn test_order_keys() {
+        assert_eq!(
+            desugared("from p in people orderby p.last descending, p.first, p.age descending select p"),
+            "people.orderby_descending(p => p.last).thenby(p => p.first).thenby_descending(p => p.age).map(p => p)",
+        );
+    }
+
+    #[test]
+    fn test_clauses_that_add_range_variables() {
+        assert_eq!(
+            desugared("from c in customers from o in c.orders select o.total"),
+            "customers.flat_map(c => c.orders.map(o => o.total))",
+        );
+        assert_eq!(
+            desugared("from c in customers from o in c.orders where o.total > c.limit select c.name"),
+            "customers.flat_map(c => c.orders.map(o => ({ c = c, o = o }))).where(row => {\n    let c = row.c\n    let o = row.o\n    o.total > c.limit\n}).map(row => {\n    let c = row.c\n    c.name\n})",
+        );
+        assert_eq!(
+            desugared("from x in xs let y = x * 2 select y"),
+            "xs.map(x => ({ x = x, y = x * 2 })).map(row => row.y)",
+        );
+        assert_eq!(
+            desugared("from o in orders join p in products on o.product equals p.id select p.name"),
+            "orders.join(products, o => o.product, p => p.id, (o, p) => p.name)",
+        );
+    }
+
+    #[test]
+    fn test_group_by() {
+        assert_eq!(desugared("from w in words group w by w.len()"), "words.group_by(w => w.len())");
+        assert_eq!(desugared("from w in words group w.upper() by w.len()"), "words.group_by(w => w.len(), w => w.upper())");
+    }
+
+    #[test]
+    fn test_row_name_avoids_query_names() {
+        let chain = desugared("from x in xs let row = x + 1 select row * x");
+        assert!(chain.contains("row1 =>"), "{}", chain);
+    }
+
+    #[test]
+    fn test_nested_queries_desugar_inside_out() {
+        assert_eq!(
+            desugared("from x in xs where (from y in x.ys select y).any() select x"),
+            "xs.where(x => x.ys.map(y => y).any()).map(x => x)",
+        );
+    }
+
+    /// `main`'s body after desugaring, printed and checked to read back the
+    /// same way with both parsers
+    fn desugared_body(statements: &str) -> (Block, String) {
+        let source = format!("fn main() {{\n{}\n}}\n", statements);
+        let program = desugar::desugar(parser::parse(&source).expect("statements should parse"));
+        let printed = printer::print(&program);
+        let rd = parser::parse(&printed).expect("desugared program should parse");
+        let lalr = parser::lalr::parse(&printed).expect("desugared program should parse with LALR");
+        assert_eq!(serde_json::to_value(&rd).unwrap(), serde_json::to_value(&lalr).unwrap());
+        let Some(Item::Function(function)) = program.items.into_iter().next() else { panic!() };
+        (function.body, printed)
+    }
+
+    /// Every way out of a block, with the deferred blocks that run on the way
+    /// out, innermost first
+    #[derive(Default)]
+    struct Exits {
+        deferred: Vec<String>,
+        exits: Vec<(String, Vec<String>)>,
+    }
+
+    impl Exits {
+        fn record(&mut self, exit: String, depth: usize) {
+            let cleanups = self.deferred[depth..].iter().rev().cloned().collect();
+            self.exits.push((exit, cleanups));
+        }
+    }
+
+    impl<'ast> Visitor<'ast> for Exits {
+        fn visit_block(&mut self, block: &'ast Block) {
+            let depth = self.deferred.len();
+            visit::walk_block(self, block);
+            // Falling off the end runs only this block's own deferred blocks
+            if self.deferred.len() > depth {
+                self.record("}".to_string(), depth);
+            }
+            self.deferred.truncate(depth);
+        }
+
+        fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+            match &stmt.kind {
+                StmtKind::Defer { body } => {
+                    let printed = body.stmts.iter().map(|s| match &s.kind {
+                        StmtKind::Expr { expr } => printer::print_expr(expr),
+                        other => panic!("unexpected deferred statement {:?}", other),
+                    });
+                    self.deferred.extend(printed);
+                }
+                StmtKind::Return { .. } => {
+                    visit::walk_stmt(self, stmt);
+                    self.record("return".to_string(), 0);
+                }
+                _ => visit::walk_stmt(self, stmt),
+            }
+        }
+
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            visit::walk_expr(self, expr);
+            if let ExprKind::Try { .. } = &expr.kind {
+                self.record(printer::print_expr(expr), 0);
+            }
+        }
+    }
+
+    fn exits(body: &Block) -> Vec<(String, Vec<String>)> {
+        let mut exits = Exits::default();
+        exits.visit_block(body);
+        exits.exits
+    }
+
+    fn exit(at: &str, cleanups: &[&str]) -> (String, Vec<String>) {
+        (at.to_string(), cleanups.iter().map(|c| c.to_string()).collect())
+    }
+
+    #[test]
+    fn test_using_lowers_to_nested_try_and_defer() {
+        let (body, printed) = desugared_body("    using let file = File.open(\"data.txt\") {\n        read(file)\n    }");
+        assert_eq!(
+            printed,
+            "fn main() {\n    try {\n        let file = File.open(\"data.txt\")\n        defer {\n            file.drop()\n        }\n        read(file)\n    }\n}\n",
+        );
+        assert_eq!(exits(&body), [exit("}", &["file.drop()"])]);
+    }
+
+    #[test]
+    fn test_multiple_resources_drop_in_reverse_order() {
+        let (body, printed) = desugared_body("    using let db = Database.connect(\"localhost\"),\n          let cache = Cache.connect(\"redis\") {\n        sync(db, cache)\n    }\n    log(\"done\")");
+        assert!(printed.contains("let db = Database.connect(\"localhost\")\n        defer {\n            db.drop()\n        }\n        try {\n            let cache"));
+        // The inner `try` ends first, then the outer one; `log` runs after both
+        assert_eq!(exits(&body), [exit("}", &["cache.drop()"]), exit("}", &["db.drop()"])]);
+        assert!(matches!(body.stmts[1].kind, StmtKind::Expr { .. }));
+    }
+
+    #[test]
+    fn test_early_exits_drop_every_bound_resource() {
+        let (body, _) = desugared_body("    using let db = Database.connect(url)?, let cache = Cache.connect(db.config()?), let lock = cache.lock() {\n        if cache.stale() {\n            return\n        }\n        cache.fill(db.load()?)\n    }");
+        assert_eq!(
+            exits(&body),
+            [
+                // Nothing is bound yet, so nothing is dropped
+                exit("Database.connect(url)?", &[]),
+                exit("db.config()?", &["db.drop()"]),
+                exit("return", &["lock.drop()", "cache.drop()", "db.drop()"]),
+                exit("db.load()?", &["lock.drop()", "cache.drop()", "db.drop()"]),
+                exit("}", &["lock.drop()"]),
+                exit("}", &["cache.drop()"]),
+                exit("}", &["db.drop()"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_programs_without_queries_are_unchanged() {
+        let program = parser::parse("fn main() {\n    let s = names.join(\",\")\n    xs.where(x => x > 1)\n}\n").unwrap();
+        assert_eq!(desugar::desugar(program.clone()), program);
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use ubel_stratum::lexer::{tokenize_with_diagnostics, Span};
+    use ubel_stratum::error_management::error_codes::{self, ERROR_CODES};
+    use ubel_stratum::error_management::error_types::{LexicalError, LexicalWarning, StringType};
+
+    fn codes_for(source: &str) -> Vec<&'static str> {
+        let manager = match tokenize_with_diagnostics(source) {
+            Ok((_, manager)) => manager,
+            Err(manager) => manager,
+        };
+        manager.diagnostics().iter().filter_map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_codes_are_unique() {
+        let mut seen = HashSet::new();
+        for entry in ERROR_CODES {
+            assert!(seen.insert(entry.code), "code {} registered twice", entry.code);
+        }
+    }
+
+    #[test]
+    fn test_every_entry_is_explained() {
+        for entry in ERROR_CODES {
+            assert!(!entry.title.is_empty(), "{} has no title", entry.code);
+            assert!(!entry.explanation.is_empty(), "{} has no explanation", entry.code);
+            assert!(!entry.bad_example.is_empty(), "{} has no bad example", entry.code);
+            assert!(!entry.good_example.is_empty(), "{} has no good example", entry.code);
+            assert_ne!(entry.bad_example, entry.good_example, "{} examples are identical", entry.code);
+        }
+    }
+
+    #[test]
+    fn test_every_lexical_code_is_registered() {
+        let span = Span::new(0, 1, 1, 1);
+        let errors = [
+            LexicalError::UnexpectedChar { ch: '`', span, suggestion: None },
+            LexicalError::UnterminatedString { span, string_type: StringType::Normal },
+            LexicalError::UnterminatedBlockComment { span, nesting_level: 1 },
+            LexicalError::InvalidNumber { text: "0x".to_string(), span, reason: String::new() },
+            LexicalError::InvalidEscape { sequence: "\\q".to_string(), span, valid_escapes: vec![] },
+            LexicalError::InvalidInterpolation { message: String::new(), span, suggestion: None },
+            LexicalError::InvalidCharLiteral { content: "ab".to_string(), span, reason: String::new() },
+        ];
+        let warnings = [
+            LexicalWarning::MixedIndentation { span },
+            LexicalWarning::TrailingWhitespaceInVerbatim { span },
+            LexicalWarning::TrailingDotFloat { text: "1.".to_string(), span },
+            LexicalWarning::UnterminatedDocComment { span },
+        ];
+
+        let codes: Vec<&str> = errors.iter().map(|e| e.code())
+            .chain(warnings.iter().map(|w| w.code()))
+            .collect();
+
+        for code in &codes {
+            assert!(error_codes::lookup(code).is_some(), "{} has no explanation", code);
+        }
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len(), "variants share a code");
+    }
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        assert_eq!(error_codes::lookup("e0002").map(|e| e.code), Some("E0002"));
+        assert!(error_codes::lookup("E9999").is_none());
+    }
+
+    #[test]
+    fn test_examples_match_lexer_behaviour() {
+        // Codes the lexer can currently produce from source text
+        let producible = ["E0001", "E0002", "E0003", "E0005", "E0006", "W0001", "W0002", "W0003", "W0004"];
+
+        for code in producible {
+            let entry = error_codes::lookup(code).unwrap();
+            assert!(codes_for(entry.bad_example).contains(&code), "bad example for {} does not trigger it", code);
+            assert!(!codes_for(entry.good_example).contains(&code), "good example for {} still triggers it", code);
+        }
+    }
+
+    #[test]
+    fn test_render_includes_examples() {
+        let text = error_codes::lookup("E0002").unwrap().render();
+        assert!(text.starts_with("E0002: unterminated string literal"));
+        assert!(text.contains("    let name = \"Alice\n"));
+        assert!(text.contains("    let name = \"Alice\"\n"));
+    }
+}
@@ -35,7 +35,7 @@ mod tests {
 
         assert!(result.is_err(), "Should error on unterminated string");
 
-        if let Err(error_manager) = result {
+        if let Err(mut error_manager) = result {
             let errors = error_manager.take_errors();
             // Check that suggestion exists
             assert!(errors.iter().any(|e| e.suggestion().is_some()));
@@ -49,7 +49,7 @@ mod tests {
         Logger::disable();
 
         let input = r#""Hello\xWorld""#; // \x is not valid
-        let result = tokenize(input);
+        let _result = tokenize(input);
 
         // Should either error or treat \x literally
         // (Depends on implementation - simple strings might accept it)
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::lexer::{tokenize, tokenize_with_diagnostics, Span};
+    use ubel_stratum::error_management::{Applicability, ErrorManager, Suggestion};
+    use ubel_stratum::error_management::fixes::{apply_suggestions, diff, write_atomically};
+
+    fn diagnostics(source: &str) -> ErrorManager {
+        match tokenize_with_diagnostics(source) {
+            Ok((_, manager)) => manager,
+            Err(manager) => manager,
+        }
+    }
+
+    fn fix(source: &str) -> String {
+        apply_suggestions(source, &diagnostics(source).suggestions()).output
+    }
+
+    #[test]
+    fn test_unterminated_string_fix() {
+        assert_eq!(fix("let s = \"abc\nlet t = 1"), "let s = \"abc\"\nlet t = 1");
+    }
+
+    #[test]
+    fn test_unterminated_block_comment_fix() {
+        let fixed = fix("let x = 1\n/* outer /* inner */");
+        assert_eq!(fixed, "let x = 1\n/* outer /* inner */ */");
+        assert!(tokenize(&fixed).is_ok());
+    }
+
+    #[test]
+    fn test_smart_quote_fix() {
+        let fixed = fix("let s = \u{201C}hello\u{201D}");
+        assert_eq!(fixed, "let s = \"hello\"");
+        assert!(tokenize(&fixed).is_ok());
+    }
+
+    #[test]
+    fn test_trailing_dot_float_fix() {
+        assert_eq!(fix("let a = 1.\nlet b = 2.f"), "let a = 1.0\nlet b = 2.0f");
+    }
+
+    #[test]
+    fn test_unterminated_doc_comment_fix() {
+        assert_eq!(fix("/** docs"), "/** docs */");
+    }
+
+    #[test]
+    fn test_maybe_incorrect_fixes_are_not_applied() {
+        let source = "let p = @\"a  \nb\"";
+        let manager = diagnostics(source);
+        let suggestions = manager.suggestions();
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].applicability, Applicability::MaybeIncorrect);
+        assert_eq!(apply_suggestions(source, &suggestions).applied, 0);
+    }
+
+    #[test]
+    fn test_overlapping_fixes_conflict() {
+        let span = Span::new(0, 3, 1, 1);
+        let suggestions = vec![
+            Suggestion::replace("first", span, "AAA", Applicability::MachineApplicable),
+            Suggestion::replace("second", Span::new(1, 2, 1, 2), "B", Applicability::MachineApplicable),
+            Suggestion::insert("third", Span::new(3, 3, 1, 4), "!", Applicability::MachineApplicable),
+        ];
+        let result = apply_suggestions("abcdef", &suggestions);
+
+        assert_eq!(result.output, "AAA!def");
+        assert_eq!(result.applied, 2);
+        assert_eq!(result.conflicting, 1);
+    }
+
+    #[test]
+    fn test_diff_preview() {
+        let patch = diff("demo.strat", "let x = 1.\n", "let x = 1.0\n");
+
+        assert!(patch.contains("--- a/demo.strat"));
+        assert!(patch.contains("+++ b/demo.strat"));
+        assert!(patch.contains("-let x = 1."));
+        assert!(patch.contains("+let x = 1.0"));
+    }
+
+    #[test]
+    fn test_write_atomically_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("ubel_fix_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("demo.strat");
+        std::fs::write(&path, "let x = 1.").unwrap();
+
+        write_atomically(&path, "let x = 1.0").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "let x = 1.0");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "temporary file left behind");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::grammar::ebnf::Expr;
+    use ubel_stratum::grammar::fuzz::{self, Config, Generator, Rejection};
+    use ubel_stratum::grammar::Grammar;
+
+    fn documented() -> Grammar {
+        Grammar::parse(include_str!("../docs/ubel.ebnf")).unwrap()
+    }
+
+    /// Rejects any program with a `b` in it
+    fn no_b(source: &str) -> Result<(), Rejection> {
+        match source.find('b') {
+            Some(at) => Err(Rejection { stage: "test", message: "found `b`".to_string(), line: 1, column: at + 1 }),
+            None => Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_documented_grammar_is_complete() {
+        let grammar = documented();
+        assert!(grammar.rule("Program").is_some());
+        assert_eq!(grammar.undefined(), ["StringChar", "InterpolatedChar", "VerbatimChar", "Character"]);
+        assert!(Generator::new(&grammar, Config::default()).is_ok());
+    }
+
+    #[test]
+    fn test_documented_programs_parse() {
+        let grammar = documented();
+        let mut generator = Generator::new(&grammar, Config::default()).unwrap();
+        if let Err(counterexample) = fuzz::fuzz(&mut generator, "Program", 500, fuzz::check) {
+            panic!("{}", counterexample);
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let grammar = documented();
+        let config = Config { seed: 7, ..Config::default() };
+        let mut first = Generator::new(&grammar, config).unwrap();
+        let mut second = Generator::new(&grammar, config).unwrap();
+        let a = first.generate("Program");
+        let b = second.generate("Program");
+        assert_eq!(first.render(&a), second.render(&b));
+    }
+
+    #[test]
+    fn test_parse_notation() {
+        let grammar = Grammar::parse("(* comment *)\nA ::= \"x\" B? | [a-c]+\nB ::= '\"' - C").unwrap();
+        assert_eq!(grammar.rules.len(), 2);
+        assert_eq!(
+            grammar.rule("A").unwrap().body,
+            Expr::Alt(vec![
+                Expr::Seq(vec![Expr::Terminal("x".to_string()), Expr::Optional(Box::new(Expr::Rule("B".to_string())))]),
+                Expr::Repeat { expr: Box::new(Expr::Class(vec![('a', 'c')])), min: 1 },
+            ])
+        );
+        assert_eq!(grammar.undefined(), ["C"]);
+    }
+
+    #[test]
+    fn test_notation_errors() {
+        let error = Grammar::parse("A ::= \"x").unwrap_err();
+        assert_eq!((error.message.as_str(), error.line, error.column), ("unterminated terminal", 1, 7));
+        let error = Grammar::parse("A ::= \"x\"\nA ::= \"y\"").unwrap_err();
+        assert_eq!((error.message.as_str(), error.line, error.column), ("rule `A` is defined twice", 2, 1));
+        let error = Grammar::parse("A ::= (\"x\"").unwrap_err();
+        assert_eq!(error.message, "expected `)`");
+
+        let grammar = Grammar::parse("A ::= B").unwrap();
+        assert_eq!(Generator::new(&grammar, Config::default()).err().unwrap(), "undefined rules: B");
+    }
+
+    #[test]
+    fn test_counterexamples_are_minimised() {
+        let grammar = Grammar::parse("Program ::= Item+\nItem ::= \"a\" | \"b\" | \"(\" Item+ \")\"").unwrap();
+        let config = Config { max_repeat: 4, ..Config::default() };
+        let mut generator = Generator::new(&grammar, config).unwrap();
+        let counterexample = fuzz::fuzz(&mut generator, "Program", 100, no_b).unwrap_err();
+        assert_eq!(counterexample.minimised, "b\n");
+        assert_eq!(counterexample.error.message, "found `b`");
+        assert!(counterexample.original.contains('b'));
+        assert!(counterexample.to_string().starts_with(&format!("seed {}:", counterexample.seed)));
+    }
+
+    #[test]
+    fn test_check_reports_the_first_error() {
+        assert_eq!(fuzz::check("fn main() {}\n"), Ok(()));
+        let rejection = fuzz::check("fn main( {}\n").unwrap_err();
+        assert_eq!(rejection.stage, "recursive descent");
+        assert_eq!(rejection.line, 1);
+        let rejection = fuzz::check("fn main() { \"open }\n").unwrap_err();
+        assert_eq!(rejection.stage, "lexer");
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::fixes;
+    use ubel_stratum::grammar::ebnf::Expr;
+    use ubel_stratum::grammar::reference::{self, ReferenceFormat, DOCUMENT, DOCUMENT_PATH};
+    use ubel_stratum::grammar::Grammar;
+    use ubel_stratum::lexer::keywords::KEYWORDS;
+    use ubel_stratum::parser::precedence::INFIX_OPERATORS;
+
+    fn terminals(expr: &Expr, out: &mut Vec<String>) {
+        match expr {
+            Expr::Terminal(text) => out.push(text.clone()),
+            Expr::Rule(_) | Expr::Class(_) => {}
+            Expr::Seq(items) | Expr::Alt(items) => items.iter().for_each(|e| terminals(e, out)),
+            Expr::Optional(e) | Expr::Repeat { expr: e, .. } => terminals(e, out),
+            Expr::Except(e, except) => {
+                terminals(e, out);
+                terminals(except, out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_committed_grammar_is_up_to_date() {
+        let generated = reference::ebnf().unwrap();
+        if generated != DOCUMENT {
+            panic!(
+                "{} is out of date; regenerate with `stratc grammar --format ebnf > {}`:\n{}",
+                DOCUMENT_PATH,
+                DOCUMENT_PATH,
+                fixes::diff(DOCUMENT_PATH, DOCUMENT, &generated)
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_sections_follow_the_tables() {
+        let grammar = Grammar::parse(&reference::ebnf().unwrap()).unwrap();
+        let mut keywords = Vec::new();
+        terminals(&grammar.rule("Keyword").unwrap().body, &mut keywords);
+        assert_eq!(keywords.len(), KEYWORDS.len());
+        assert!(KEYWORDS.keys().all(|k| keywords.contains(&k.to_string())));
+
+        let mut operators = Vec::new();
+        for rule in &grammar.rules {
+            terminals(&rule.body, &mut operators);
+        }
+        for op in INFIX_OPERATORS {
+            assert!(operators.iter().any(|t| t == op.lexeme), "operator `{}` missing", op.lexeme);
+        }
+        assert_eq!(grammar.rule("CompareExpr").unwrap().to_string(), "CompareExpr ::= BitOrExpr (CompareOp BitOrExpr)?");
+        assert_eq!(grammar.rule("CastExpr").unwrap().to_string(), "CastExpr ::= UnaryExpr (\"as\" CastType)*");
+        assert_eq!(grammar.rule("UnaryOp").unwrap().to_string(), "UnaryOp ::= \"!\" | \"not\" | \"-\" | \"+\" | \"~\" | \"await\"");
+    }
+
+    #[test]
+    fn test_rules_print_as_they_parse() {
+        let grammar = Grammar::parse(DOCUMENT).unwrap();
+        for rule in &grammar.rules {
+            let printed = rule.to_string();
+            let reparsed = Grammar::parse(&printed).unwrap();
+            assert_eq!(reparsed.rules.as_slice(), std::slice::from_ref(rule), "{}", printed);
+        }
+        // A long choice breaks between alternatives, under the `=` of `::=`
+        let printed = grammar.rule("Keyword").unwrap().to_string();
+        assert!(printed.lines().count() > 1);
+        assert!(printed.lines().all(|l| l.len() <= 80));
+        assert!(printed.lines().skip(1).all(|l| l.starts_with("          | ")));
+    }
+
+    #[test]
+    fn test_missing_section_is_reported() {
+        let error = reference::splice("Program ::= Item*\n").unwrap_err();
+        assert_eq!(error, "docs/ubel.ebnf has no section generated from src/lexer/keywords.rs");
+        let error = reference::splice("(* Generated from src/lexer/keywords.rs; do not edit *)\nKeyword ::= \"fn\"\n").unwrap_err();
+        assert_eq!(error, "the section generated from src/lexer/keywords.rs never ends");
+    }
+
+    #[test]
+    fn test_markdown_reference() {
+        let page = reference::render(ReferenceFormat::Markdown).unwrap();
+        assert!(page.contains("| Or | `or` `\\|\\|` | left |"));
+        assert!(page.contains("| Comparison | `==` `!=` `<` `>` `<=` `>=` | none |"));
+        assert!(page.contains("| Prefix | `!` `not` `-` `+` `~` `await` | right |"));
+        assert!(page.contains("Inside a query only: `select`"));
+        assert!(page.contains("## Expressions\n\n```ebnf\n"));
+        assert!(!page.contains("Generated from src/"));
+        assert_eq!("md".parse::<ReferenceFormat>(), Ok(ReferenceFormat::Markdown));
+        assert_eq!("EBNF".parse::<ReferenceFormat>(), Ok(ReferenceFormat::Ebnf));
+        assert!("xml".parse::<ReferenceFormat>().is_err());
+    }
+}
@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::highlight::{highlight, render, textmate, HighlightFormat, HighlightKind};
+    use ubel_stratum::lexer::{keywords::KEYWORDS, operators::{self, OPERATORS}, LogosLexer, TokenType};
+
+    fn kinds_of(source: &str) -> Vec<(String, HighlightKind)> {
+        highlight(source)
+            .into_iter()
+            .filter(|span| span.kind != HighlightKind::Whitespace)
+            .map(|span| (source[span.start..span.end].to_string(), span.kind))
+            .collect()
+    }
+
+    fn kind_of(source: &str, text: &str) -> HighlightKind {
+        kinds_of(source)
+            .into_iter()
+            .find(|(t, _)| t == text)
+            .map(|(_, kind)| kind)
+            .unwrap_or_else(|| panic!("no span for {:?}", text))
+    }
+
+    #[test]
+    fn test_spans_cover_source() {
+        let source = "/*! module */\n@tier(high)\nfn main() {\n    let x = 0x1F + 2.5 // done\n}\n";
+        let spans = highlight(source);
+
+        let mut cursor = 0;
+        for span in &spans {
+            assert_eq!(span.start, cursor, "gap or overlap before {:?}", span);
+            assert!(span.end > span.start);
+            cursor = span.end;
+        }
+        assert_eq!(cursor, source.len());
+    }
+
+    #[test]
+    fn test_basic_kinds() {
+        let source = "/** Docs */\n@tier(mid) @inline\nfn add(a: int) { return a + 1 == true; } /* note */ 'c' \"s\"";
+
+        assert_eq!(kind_of(source, "/** Docs */"), HighlightKind::DocComment);
+        assert_eq!(kind_of(source, "@tier(mid)"), HighlightKind::TierAnnotation);
+        assert_eq!(kind_of(source, "@inline"), HighlightKind::Attribute);
+        assert_eq!(kind_of(source, "fn"), HighlightKind::Keyword);
+        assert_eq!(kind_of(source, "return"), HighlightKind::Keyword);
+        assert_eq!(kind_of(source, "add"), HighlightKind::Identifier);
+        assert_eq!(kind_of(source, "1"), HighlightKind::Number);
+        assert_eq!(kind_of(source, "=="), HighlightKind::Operator);
+        assert_eq!(kind_of(source, "true"), HighlightKind::Constant);
+        assert_eq!(kind_of(source, "/* note */"), HighlightKind::Comment);
+        assert_eq!(kind_of(source, "'c'"), HighlightKind::Char);
+        assert_eq!(kind_of(source, "\"s\""), HighlightKind::String);
+        assert_eq!(kind_of(source, ";"), HighlightKind::Punctuation);
+    }
+
+    #[test]
+    fn test_unknown_tier_level_is_attribute() {
+        assert_eq!(kind_of("@tier(ultra) fn f() {}", "@tier"), HighlightKind::Attribute);
+    }
+
+    #[test]
+    fn test_interpolation_holes_highlighted_separately() {
+        let source = r#"$"Hello {user.name}, you are {age + 1}!""#;
+        let kinds = kinds_of(source);
+
+        let expected = [
+            ("$\"Hello ", HighlightKind::String),
+            ("{", HighlightKind::InterpolationDelimiter),
+            ("user", HighlightKind::Identifier),
+            (".", HighlightKind::Punctuation),
+            ("name", HighlightKind::Identifier),
+            ("}", HighlightKind::InterpolationDelimiter),
+            (", you are ", HighlightKind::String),
+            ("{", HighlightKind::InterpolationDelimiter),
+            ("age", HighlightKind::Identifier),
+            ("+", HighlightKind::Operator),
+            ("1", HighlightKind::Number),
+            ("}", HighlightKind::InterpolationDelimiter),
+            ("!\"", HighlightKind::String),
+        ];
+        let expected: Vec<_> = expected.iter().map(|(t, k)| (t.to_string(), *k)).collect();
+        assert_eq!(kinds, expected);
+    }
+
+    #[test]
+    fn test_verbatim_interpolation_holes() {
+        let source = r#"$@"C:\{dir}\file""#;
+        assert_eq!(kind_of(source, "dir"), HighlightKind::Identifier);
+        assert_eq!(kind_of(source, "{"), HighlightKind::InterpolationDelimiter);
+    }
+
+    #[test]
+    fn test_errors_highlighted() {
+        let source = "let x = `oops\nlet y = \"unterminated\n";
+        assert_eq!(kind_of(source, "`"), HighlightKind::Error);
+        assert_eq!(kind_of(source, "oops"), HighlightKind::Identifier);
+        assert!(kinds_of(source).iter().any(|(t, k)| t.starts_with("\"unterminated") && *k == HighlightKind::Error));
+    }
+
+    #[test]
+    fn test_html_escapes_and_classes() {
+        let html = render("let a = b < c", HighlightFormat::Html);
+        assert!(html.starts_with("<pre class=\"ubel\"><code>"));
+        assert!(html.contains("<span class=\"ub-keyword\">let</span>"));
+        assert!(html.contains("<span class=\"ub-operator\">&lt;</span>"));
+    }
+
+    #[test]
+    fn test_json_output() {
+        let json: serde_json::Value = serde_json::from_str(&render("let x", HighlightFormat::Json)).unwrap();
+        assert_eq!(json[0]["kind"], "keyword");
+        assert_eq!(json[1]["text"], "x");
+        assert_eq!(json[1]["start"], 4);
+    }
+
+    #[test]
+    fn test_ansi_output_round_trips_text() {
+        let source = "fn main() { let s = $\"{x}\" }";
+        let ansi = render(source, HighlightFormat::Ansi);
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for ch in ansi.chars() {
+            match ch {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => {}
+                _ => stripped.push(ch),
+            }
+        }
+        assert_eq!(stripped, source);
+    }
+
+    #[test]
+    fn test_every_keyword_lexes_to_its_token() {
+        for (word, kind) in KEYWORDS.entries() {
+            let tokens = LogosLexer::new(word).tokenize().unwrap();
+            assert_eq!(&tokens[0].kind, kind, "keyword {:?}", word);
+        }
+    }
+
+    #[test]
+    fn test_every_operator_lexes_as_single_token() {
+        for op in OPERATORS {
+            let tokens = LogosLexer::new(op.lexeme).tokenize().unwrap();
+            assert_eq!(tokens.len(), 2, "operator {:?} split into {:?}", op.lexeme, tokens);
+            assert_eq!(tokens[0].kind, op.kind, "operator {:?}", op.lexeme);
+            assert_eq!(operators::lookup(op.lexeme).map(|info| &info.kind), Some(&op.kind));
+        }
+    }
+
+    #[test]
+    fn test_operator_table_is_complete() {
+        // Any punctuation sequence the lexer treats as one operator must be in the table
+        let chars: Vec<char> = "+-*/%&|^~<>=!?.:;,(){}[]@".chars().collect();
+        let mut candidates: Vec<String> = chars.iter().map(|c| c.to_string()).collect();
+        for a in &chars {
+            for b in &chars {
+                candidates.push(format!("{}{}", a, b));
+                for c in ['=', '<', '>', '.'] {
+                    candidates.push(format!("{}{}{}", a, b, c));
+                }
+            }
+        }
+
+        for candidate in candidates {
+            if candidate.contains("//") || candidate.contains("/*") {
+                continue;
+            }
+            let Ok(tokens) = LogosLexer::new(&candidate).tokenize() else { continue };
+            if tokens.len() == 2 && !matches!(tokens[0].kind, TokenType::Error(_)) {
+                assert!(operators::lookup(&candidate).is_some(), "{:?} missing from OPERATORS", candidate);
+            }
+        }
+    }
+
+    #[test]
+    fn test_textmate_grammar_lists_all_keywords() {
+        let grammar = textmate::grammar();
+        let patterns = grammar["repository"]["keywords"]["patterns"].as_array().unwrap();
+        let all: String = patterns.iter().map(|p| p["match"].as_str().unwrap()).collect();
+        for word in KEYWORDS.keys() {
+            assert!(all.contains(&format!("|{}|", word)) || all.contains(&format!("({}|", word))
+                || all.contains(&format!("|{})", word)) || all.contains(&format!("({})", word)),
+                "keyword {:?} missing", word);
+        }
+        assert_eq!(grammar["scopeName"], textmate::SCOPE_NAME);
+    }
+
+    #[test]
+    fn test_committed_textmate_grammar_is_up_to_date() {
+        let committed = include_str!("../editors/textmate/ubel-stratum.tmLanguage.json");
+        assert_eq!(
+            committed,
+            textmate::grammar_json(),
+            "regenerate with `stratc highlight --textmate > editors/textmate/ubel-stratum.tmLanguage.json`"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::*;
+
+    /// Both parsers must accept `source` and build the same tree, spans included
+    fn assert_same(source: &str) -> Program {
+        let rd = match parser::parse(source) {
+            Ok(program) => program,
+            Err(manager) => panic!("recursive descent failed: {:?}", manager.diagnostics()),
+        };
+        let lalr = match parser::parse_lalr(source) {
+            Ok(program) => program,
+            Err(manager) => panic!("LALR failed on {:?}: {:?}", source, manager.diagnostics()),
+        };
+        assert_eq!(rd, lalr, "parsers disagree on {:?}", source);
+        lalr
+    }
+
+    fn lalr_err(source: &str) -> String {
+        match parser::parse_lalr(source) {
+            Ok(program) => panic!("expected an error, got {:?}", program),
+            Err(manager) => manager.diagnostics()[0].message.clone(),
+        }
+    }
+
+    fn collect_examples(dir: &Path, out: &mut Vec<std::path::PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_examples(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "strat") {
+                out.push(path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_examples_match_recursive_descent() {
+        let mut files = Vec::new();
+        collect_examples(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")), &mut files);
+        assert!(!files.is_empty());
+
+        for file in files {
+            let source = fs::read_to_string(&file).unwrap();
+            assert_same(&source);
+        }
+    }
+
+    #[test]
+    fn test_declarations_match_recursive_descent() {
+        assert_same("package app.server;\nsummon std.io as io\nfrom db summon [User, Db]\nfrom util summon a, b\n");
+        assert_same("@tier(high)\npub async fn fetch(id: int, retries = 3) Task<User>! {\n    return load(id)\n}\n");
+        assert_same("pub edge struct Point<T> {\n    pub x: T, y: T\n    z: fn(int) int\n    fn len(self) float { self.x }\n}\n");
+        assert_same("struct Temp {\n    celsius: float { get { self.c } set { self.c = value } }\n}\n");
+        assert_same("struct Temp {\n    c: float { get; set; }\n    f: float {\n        set { self.c = value }\n        get;\n    }\n}\n");
+        assert_same("enum Shape<T> {\n    Circle(float),\n    Rect { w: float, h: float },\n    Empty,\n}\n");
+        assert_same("trait Show {\n    type Output;\n    fn show(self) string\n    async fn later(self) Task<int>;\n}\n");
+        assert_same("impl Show for Point {\n    fn show(self) string { \"p\" }\n}\nconst MAX: int = 10;\nconst MIN = 1\n");
+    }
+
+    #[test]
+    fn test_statements_match_recursive_descent() {
+        assert_same("fn main() {\n    let mut x: int = 1\n    y := x + 2; z := 3\n    x += y * z\n    for i in items { print(i) }\n    while x > 0 { x -= 1 }\n    loop { break }\n    with arena(64) { fill() }\n    try { risky() } catch (e) { log(e) }\n    unsafe { poke() }\n    return\n}\n");
+    }
+
+    #[test]
+    fn test_expressions_match_recursive_descent() {
+        assert_same("fn main() {\n    a = b or c and not d == e\n    f = -g * (h + i) % j\n    k = user?.profile.name\n    l = items[0].len()?\n    m = [1, 2.5, \"s\", 'c', true, null]\n    n = (1, 2)\n    o = ()\n}\n");
+        assert_same("fn main() {\n    p = x => x * 2\n    q = (a, b = 1) => a + b\n    r = fn(x) x\n    s = (x)\n    t = Point { x = 1, y = 2 }\n    u = if a { 1 } elif b { 2 } else { 3 }\n}\n");
+        assert_same("fn main() {\n    match shape {\n        Shape.Circle(r) where r > 0 => area(r),\n        Rect { w, h = 0 } => 0\n        (1, _) => -1\n        _ => { none() }\n    }\n}\n");
+    }
+
+    #[test]
+    fn test_object_literals_match_recursive_descent() {
+        assert_same("fn main() {\n    person = { name = \"Alice\", greet = fn() { println(name) }, }\n    config = {\n        server = { host = \"localhost\", port = 8080 },\n        debug = true\n    }\n    scores = { \"Alice\" = 100, \"Bob\" = 85 }\n}\n");
+        // Bodies after `=>` and `)` are blocks, even where they start like an object
+        assert_same("fn main() {\n    f = x => { y = x }\n    g = fn(x) { y = x }\n    h = x => ({ y = x })\n    n = { (y = 1)\n        y }\n    e = {}\n    match v {\n        A => { y = 1 },\n        _ => ({ \"k\" = 2 }),\n    }\n}\n");
+        assert_same("fn reset() int! {\n    x = 1\n}\nstruct P {\n    v: int { get { v = 1 } set { v = value } }\n}\n");
+    }
+
+    #[test]
+    fn test_queries_match_recursive_descent() {
+        assert_same("fn main() {\n    q = from user in users\n        where user.age >= 18\n          and user.active\n        orderby user.name, user.age descending\n        select user.name\n    n = q\n}\n");
+        assert_same("fn main() {\n    from c in customers\n    from o in c.orders\n    let total = o.total\n    join p in products\n      on o.product\n      equals p.id\n    group p by c.region\n    let by = 1\n}\n");
+        // Nested queries, query words as members and names, and objects after `select`
+        assert_same("fn main() {\n    f(from x in from y in ys select y where from z in x.zs select z.ok select { v = x.select }, 2)\n    s = names.join(\",\"); group = select(on)\n}\n");
+        // After `fn()` a `from` starts the lambda's body, not the next clause
+        assert_same("fn main() {\n    q = from a in fn() from b in c where b select b group a by a.key\n}\n");
+    }
+
+    #[test]
+    fn test_extract_matches_recursive_descent() {
+        assert_same("fn main() {\n    extract (x, y) = point\n    extract [first, second, ...rest] = numbers; extract [a, ...] = xs\n    extract { id, name } = user\n    extract {\n        user = { name, age },\n        status\n    } = data\n    f = (extract (a, b), mut extract [c]: List<int>) => a\n}\n");
+        assert_same("fn process_point(extract (x, y): (int, int), mut extract { id }, n = 1) {\n    println(x)\n}\n");
+        // Object patterns may start an arm on a new line, but after a name
+        // `{ x = 1 }` still continues it as a struct literal
+        assert_same("fn main() {\n    match response {\n        Err(NetworkError(extract { code, message })) => log(code)\n        { kind = k, data = [x, ...] } => k,\n        { kind } => 2\n        [] => 0\n    }\n    v = data.match(p)\n    if ok\n    { go() }\n}\n");
+    }
+
+    #[test]
+    fn test_let_conditionals_match_recursive_descent() {
+        assert_same("fn main() {\n    if let Some(age) = ages.get(\"Alice\") {\n        println(age)\n    } elif let Shape.Circle(r) = s { r } else { 0 }\n    while let [first, ...rest] = xs { xs = rest }\n    let Ok(config) = load(path) else {\n        return\n    }\n    let Shape.Empty = s\n    else { return }\n    let x = if a { 1 } else { 2 }\n}\n");
+    }
+
+    #[test]
+    fn test_interpolated_strings_match_recursive_descent() {
+        assert_same("fn main() {\n    log($\"hi {user?.name.upper()}, {n * (m + 1)} {$\"[{x}]\"}\")\n    let s = $@\"C:\\{dir}\\{files[0]}\"\n    let t = $\"a\n{ {b = 1}.b }\"\n}\n");
+        assert_eq!(lalr_err("fn main() {\n    log($\"{a +}\")\n}\n"), "expected an expression, found `}`");
+    }
+
+    #[test]
+    fn test_extend_matches_recursive_descent() {
+        assert_same("extend int {\n    fn is_even(self) bool {\n        return self % 2 == 0\n    }\n\n    fn times(self, action: fn(int)) {\n        for i in 0..self { action(i) }\n    }\n}\n\n@inline\nextend<K> Dictionary<K, List<int>> { pub fn total(self) int { return 0 } }\nextend List<T> {}\nfn main() {\n    xs.extend(ys)\n}\n");
+    }
+
+    #[test]
+    fn test_lifetimes_match_recursive_descent() {
+        assert_same("fn longest[lifetime L](x: &L str, y: &L str) &L str {\n    return x\n}\n\nfn advanced[lifetime L, lifetime M where M outlives L](x: &L Data, y: &M Config) &L Result {}\n\nfn process_buffer[lifetime L](buf: &mut L [u8]) usize {\n    return 0\n}\n\nfn plain(x: &int, y: &mut List<int>, f: fn(&str) &str) &(int, int) {}\n\ntrait View {\n    fn view[lifetime L](self) &L str\n}\n");
+        assert_eq!(lalr_err("fn f[life L](x: &L str) {}\n"), "expected `lifetime`, found `life`");
+        assert_eq!(lalr_err("fn f[lifetime L where L before M]() {}\n"), "expected `outlives`, found `before`");
+    }
+
+    #[test]
+    fn test_where_clauses_match_recursive_descent() {
+        assert_same("@tier(mid)\nfn parse_json_with<F>(input: string, callback: F) Response\n    where F: FnOnce(&JsonView) Response\n{\n    return callback(input)\n}\n\nfn collect<I: Iterator<Item = T>, T, S: Into<string>>(items: I) List<T>\nwhere\n    F: Fn(T) List<int>,\n    T: Display + Clone,\n{\n    return []\n}\n\nfn nested<K: Into<List<int>>, V: Fn() List<int>>(k: K) where K: Map<string, Item = List<T>> {}\n\ntrait Mapper {\n    fn map<U>(self, f: F) U where F: Fn(int) U\n    fn done(self)\n}\n\nimpl<T> Show for List<T> where T: Show {\n    fn show(self) string { return \"\" }\n}\n\nextend<T> List<T> where T: Display + Into<string> {}\n");
+        assert_eq!(lalr_err("fn f<I: Iterator<Item = T, U>>() {}\n"), "type arguments must come before associated type bindings");
+    }
+
+    #[test]
+    fn test_using_matches_recursive_descent() {
+        assert_same("fn main() {\n    using let file = File.open(\"data.txt\") { read(file) }\n    using let db = Database.connect(\"localhost\"),\n          let cache = Cache.connect(\"redis\")\n    {\n        defer { log(\"synced\") }\n        sync(db, cache)\n    }\n    using let p = (Point { x = 1 }) { }\n    defer {\n        cleanup()\n    }\n    f.using\n}\n");
+    }
+
+    #[test]
+    fn test_struct_literal_vs_block_after_if() {
+        let program = assert_same("fn main() {\n    if ready { go() }\n    while p == Point { x = 1 } { step() }\n}\n");
+        let Item::Function(f) = &program.items[0] else { panic!("expected a function") };
+        match &f.body.stmts[0].kind {
+            StmtKind::Expr { expr } => match &expr.kind {
+                ExprKind::If { branches, .. } => {
+                    assert!(matches!(branches[0].condition.kind, ExprKind::Ident { .. }));
+                }
+                other => panic!("expected if, got {:?}", other),
+            },
+            other => panic!("unexpected statement {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_generics_split_right_shift() {
+        let program = assert_same("fn f(x: Map<string, List<List<int>>>) {\n}\n");
+        let Item::Function(f) = &program.items[0] else { panic!("expected a function") };
+        let TypeKind::Named { args, .. } = &f.params[0].ty.as_ref().unwrap().kind else { panic!("expected a named type") };
+        let TypeKind::Named { args: inner, .. } = &args[1].kind else { panic!("expected a named type") };
+        // The inner list ends at its own `>`, not at the end of `>>`
+        assert_eq!(inner[0].span.end - inner[0].span.start, "List<int>".len());
+    }
+
+    #[test]
+    fn test_lalr_errors() {
+        // `let` takes a name or, for `let ... else`, a pattern
+        let error = lalr_err("fn main() {\n    let = 1\n}\n");
+        assert!(error.starts_with("expected one of `(`, `-`, `[`, `extract`"), "{}", error);
+        assert!(error.ends_with("found `=`"), "{}", error);
+        assert!(lalr_err("fn main() {\n    a < b < c\n}\n").starts_with("expected one of"));
+        assert_eq!(lalr_err("fn main() {\n    x = (a: int)\n}\n"), "expected `=>` after lambda parameters");
+        assert!(lalr_err("fn main() {").ends_with("found end of file"));
+        assert_eq!(lalr_err("struct P {\n    x: int { set; }\n}\n"), "property `x` has no `get` accessor");
+        assert_eq!(lalr_err("fn main() {\n    o = { a = 1, \"b\" = 2 }\n}\n"), "a literal cannot mix field names and string keys");
+        assert_eq!(lalr_err("fn main() {\n    o = { \"a\" = 1, \"a\" = 2 }\n}\n"), "duplicate key \"a\"");
+        assert_eq!(lalr_err("struct P {\n    x: int { get; get; }\n}\n"), "property `x` already has a `get` accessor");
+        assert_eq!(lalr_err("fn main() {\n    q = from x in xs let x = 1 select x\n}\n"), "range variable `x` is already defined in this query");
+        assert!(lalr_err("fn main() {\n    q = from x in xs where x > 1\n}\n").starts_with("expected one of"));
+        assert_eq!(lalr_err("fn main() {\n    extract Ok(x) = r\n}\n"), "refutable pattern in `extract`: `Ok(x)` does not match every value");
+        assert_eq!(lalr_err("fn f(extract [a, ...b, ...c]) {\n}\n"), "a list pattern can only have one `...`");
+        assert_eq!(lalr_err("fn main() {\n    let (a, _) = p else { return }\n}\n"), "irrefutable pattern in `let ... else`: `(a, _)` matches every value");
+    }
+}
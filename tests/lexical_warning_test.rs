@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::lexer::{tokenize, tokenize_with_diagnostics, TokenType};
+    use ubel_stratum::error_management::{Diagnostic, ErrorManager, Severity};
+    use ubel_stratum::lexer::Span;
+
+    fn warnings(input: &str) -> Vec<String> {
+        let (_, diagnostics) = tokenize_with_diagnostics(input).expect("warnings must not fail lexing");
+        diagnostics.diagnostics()
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_input_has_no_warnings() {
+        assert!(warnings("fn main() {\n    let x = 1.0\n}").is_empty());
+    }
+
+    #[test]
+    fn test_mixed_indentation_warning() {
+        let found = warnings("fn main() {\n \tlet x = 1\n}");
+        assert_eq!(found.len(), 1);
+        assert!(found[0].contains("tabs and spaces"));
+    }
+
+    #[test]
+    fn test_trailing_dot_float_warning() {
+        let input = "let x = 1.";
+        let found = warnings(input);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].contains("'1.'"));
+
+        // Still tokenizes to a double
+        let tokens = tokenize(input).unwrap();
+        assert!(matches!(tokens[3].kind, TokenType::DoubleLit(f) if f == 1.0));
+    }
+
+    #[test]
+    fn test_trailing_whitespace_in_verbatim_string() {
+        let found = warnings("let p = @\"first  \nsecond\"");
+        assert_eq!(found.len(), 1);
+        assert!(found[0].contains("verbatim"));
+    }
+
+    #[test]
+    fn test_unterminated_doc_comment_is_warning() {
+        let input = "fn foo() {}\n/** trailing docs";
+        let (tokens, diagnostics) = tokenize_with_diagnostics(input).unwrap();
+
+        assert_eq!(diagnostics.warning_count(), 1);
+        assert!(tokens.iter().any(|t| matches!(t.kind, TokenType::DocComment(ref s) if s == "trailing docs")));
+    }
+
+    #[test]
+    fn test_spans_track_whitespace() {
+        let tokens = tokenize("let  x = \"a\"\n  y").unwrap();
+
+        assert_eq!((tokens[1].span.start, tokens[1].span.column), (5, 6));
+        assert_eq!((tokens[3].span.start, tokens[3].span.column), (9, 10));
+        assert_eq!((tokens[4].span.start, tokens[4].span.line, tokens[4].span.column), (15, 2, 3));
+    }
+
+    #[test]
+    fn test_severity_counts() {
+        let span = Span::new(0, 1, 1, 1);
+        let mut manager = ErrorManager::new("x".to_string());
+        manager.add_diagnostic(Diagnostic::warning("w", span));
+        manager.add_diagnostic(Diagnostic::note("n", span));
+        manager.add_diagnostic(Diagnostic::help("h", span));
+
+        assert!(!manager.has_errors());
+        assert_eq!(manager.warning_count(), 1);
+        assert_eq!(manager.note_count(), 1);
+        assert_eq!(manager.help_count(), 1);
+        assert_eq!(manager.summary(), "1 warning(s), 1 note(s), 1 help(s)");
+    }
+
+    #[test]
+    fn test_deny_warnings_promotes_to_errors() {
+        let (_, mut diagnostics) = tokenize_with_diagnostics("let x = 1.").unwrap();
+        assert!(!diagnostics.has_errors());
+
+        diagnostics.set_deny_warnings(true);
+        assert!(diagnostics.has_errors());
+        assert_eq!(diagnostics.warning_count(), 0);
+        assert_eq!(diagnostics.error_count(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::error_management::error_types::SemanticError;
+    use ubel_stratum::parser;
+    use ubel_stratum::semantic::lifetimes;
+
+    fn check(source: &str) -> Vec<SemanticError> {
+        match parser::parse(source) {
+            Ok(program) => lifetimes::check(&program),
+            Err(manager) => panic!("parse failed: {:?}", manager.diagnostics()),
+        }
+    }
+
+    fn messages(source: &str) -> Vec<String> {
+        check(source).iter().map(|e| e.message()).collect()
+    }
+
+    #[test]
+    fn test_readme_signatures_check() {
+        let source = "fn longest[lifetime L](x: &L str, y: &L str) &L str {\n    let r: &L str = x\n    return r\n}\n\nfn advanced[lifetime L, lifetime M where M outlives L](x: &L Data, y: &M Config) &L Result {}\n\nfn process_buffer[lifetime L](buf: &mut L [u8]) usize {\n    return 0\n}\n\nfn plain(x: &int) &int {\n    return x\n}\n";
+        assert!(check(source).is_empty(), "{:?}", check(source));
+    }
+
+    #[test]
+    fn test_undeclared_lifetimes() {
+        let errors = check("fn first(x: &L str) &L str {\n    return x\n}\n");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code(), error_codes::E0202);
+        assert_eq!(errors[0].message(), "use of undeclared lifetime `L`");
+        assert_eq!(errors[0].suggestion().unwrap(), "Declare it after the function name: `[lifetime L]`");
+        assert_eq!((errors[0].span().line, errors[0].span().column), (1, 14));
+
+        // Constraints, struct fields and bodies need declarations too
+        assert_eq!(
+            messages("fn f[lifetime L where L outlives M](x: &L str) {\n    let y: &N int = 0\n}\n\nstruct View {\n    data: &L str\n}\n"),
+            ["use of undeclared lifetime `M`", "use of undeclared lifetime `N`", "use of undeclared lifetime `L`"]
+        );
+
+        // A nested function does not see the lifetimes around it
+        assert_eq!(
+            messages("fn outer[lifetime L](x: &L str) {\n    fn inner(y: &L str) {}\n    let z: &L str = x\n}\n"),
+            ["use of undeclared lifetime `L`"]
+        );
+    }
+
+    #[test]
+    fn test_outlives_cycles() {
+        let errors = check("fn pick[lifetime L, lifetime M where L outlives M, M outlives L](x: &L str, y: &M str) &L str {\n    return x\n}\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code(), error_codes::E0203);
+        assert_eq!(errors[0].message(), "lifetime constraints form a cycle: `L outlives M outlives L`");
+
+        assert_eq!(
+            messages("fn f[lifetime A, lifetime B, lifetime C where A outlives B, B outlives C, C outlives A]() {}\n"),
+            ["lifetime constraints form a cycle: `A outlives B outlives C outlives A`"]
+        );
+        assert_eq!(
+            messages("fn f[lifetime L where L outlives L]() {}\n"),
+            ["lifetime constraints form a cycle: `L outlives L`"]
+        );
+
+        // Two paths to the same lifetime are not a cycle
+        assert!(check("fn f[lifetime A, lifetime B, lifetime C where A outlives B, A outlives C, B outlives C]() {}\n").is_empty());
+    }
+
+    #[test]
+    fn test_lifetime_codes_are_explained() {
+        for code in [error_codes::E0202, error_codes::E0203] {
+            let entry = error_codes::lookup(code).unwrap();
+            assert!(check(entry.bad_example).iter().any(|e| e.code() == code), "bad example for {} does not trigger it", code);
+            assert!(check(entry.good_example).is_empty(), "good example for {} fails", code);
+        }
+    }
+}
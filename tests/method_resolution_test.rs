@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::error_management::error_types::SemanticError;
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::Program;
+    use ubel_stratum::semantic::methods::{self, MethodTarget, Resolution, Ty};
+
+    fn parse(source: &str) -> Program {
+        match parser::parse(source) {
+            Ok(program) => program,
+            Err(manager) => panic!("parse failed: {:?}", manager.diagnostics()),
+        }
+    }
+
+    fn resolve(source: &str, modules: &[&str]) -> Resolution {
+        let modules: Vec<Program> = modules.iter().map(|m| parse(m)).collect();
+        let modules: Vec<&Program> = modules.iter().collect();
+        methods::resolve(&parse(source), &modules)
+    }
+
+    /// `(method, target)` for every resolved call, in source order
+    fn targets(resolution: &Resolution) -> Vec<(String, MethodTarget)> {
+        resolution.calls.iter().map(|call| (call.method.name.clone(), call.target.clone())).collect()
+    }
+
+    fn extension(module: &str, target: &str) -> MethodTarget {
+        MethodTarget::Extension { module: module.to_string(), target: target.to_string() }
+    }
+
+    const PARITY: &str = "package math.parity\n\nextend int {\n    fn is_even(self) bool {\n        return self % 2 == 0\n    }\n}\n";
+    const LISTS: &str = "package util.lists\n\nextend List<T> {\n    fn second(self) Option<T> {\n        return self.get(1)\n    }\n}\n";
+
+    #[test]
+    fn test_readme_extensions_resolve() {
+        let resolution = resolve(
+            "summon math.parity\nsummon util.lists\n\nfn main() {\n    if 42.is_even() {\n        println(\"Even!\")\n    }\n    let numbers = [1, 2, 3, 4, 5]\n    println(numbers.second())\n}\n",
+            &[PARITY, LISTS],
+        );
+        assert!(resolution.errors.is_empty(), "{:?}", resolution.errors);
+        assert_eq!(
+            targets(&resolution),
+            [
+                ("is_even".to_string(), extension("math.parity", "int")),
+                ("second".to_string(), extension("util.lists", "List<T>")),
+            ]
+        );
+        assert_eq!(resolution.calls[1].receiver.to_string(), "List<int>");
+        assert_eq!(resolution.target_at(resolution.calls[0].method.span), Some(&extension("math.parity", "int")));
+    }
+
+    #[test]
+    fn test_receiver_types_from_the_syntax() {
+        let resolution = resolve(
+            "struct Point { x: int }\n\nextend Point {\n    fn norm(self) int { return self.x.abs() }\n    fn twice(self) int { return self.norm() * 2 }\n}\n\nextend int {\n    fn abs(self) int { return self }\n}\n\nfn origin() Point { return Point { x = 0 } }\n\nfn main(n: int) {\n    p := origin()\n    p.norm().abs()\n    n.abs()\n    (n as int).abs()\n    for n in names { n.abs() }\n    unknown.abs()\n}\n",
+            &[],
+        );
+        let calls: Vec<String> = resolution.calls.iter().map(|call| format!("{}.{}", call.receiver, call.method.name)).collect();
+        // `self.x` has no known type, and the loop's `n` shadows the parameter
+        assert_eq!(calls, ["Point.norm", "Point.norm", "int.abs", "int.abs", "int.abs"]);
+        assert!(resolution.errors.is_empty());
+    }
+
+    #[test]
+    fn test_pattern_bindings_scoped_to_their_branch() {
+        let resolution = resolve(
+            "extend int {\n    fn abs(self) int { return self }\n}\n\nfn main(n: int) {\n    if let Some(n) = find(n.abs()) {\n        n.abs()\n    } elif let [n] = all() {\n        n.abs()\n    } else {\n        n.abs()\n    }\n    while let Some(n) = next() { n.abs() }\n    let Ok(m) = parse(n) else {\n        return n.abs()\n    }\n    n.abs()\n}\n",
+            &[],
+        );
+        // Only the parameter `n` has a known type; the patterns shadow it
+        // inside their own branches and nowhere else
+        let lines: Vec<usize> = resolution.calls.iter().map(|call| call.method.span.line).collect();
+        assert_eq!(lines, [6, 11, 15, 17]);
+    }
+
+    #[test]
+    fn test_inherent_methods_win() {
+        let resolution = resolve(
+            "summon geo.ext\n\nstruct Point { x: int }\n\nimpl Point {\n    fn norm(self) int { return self.x }\n}\n\nfn main() {\n    Point { x = 1 }.norm()\n}\n",
+            &["package geo.ext\n\nextend Point {\n    fn norm(self) int { return 0 }\n}\n"],
+        );
+        assert_eq!(targets(&resolution), [("norm".to_string(), MethodTarget::Inherent { ty: "Point".to_string() })]);
+    }
+
+    #[test]
+    fn test_extensions_need_summon() {
+        let resolution = resolve("fn main() {\n    42.is_even()\n}\n", &[PARITY]);
+        assert!(resolution.calls.is_empty());
+        assert_eq!(
+            resolution.errors,
+            [SemanticError::MethodNotSummoned {
+                method: "is_even".to_string(),
+                ty: "int".to_string(),
+                module: "math.parity".to_string(),
+                span: resolution.errors[0].span(),
+            }]
+        );
+        assert_eq!(resolution.errors[0].code(), error_codes::E0201);
+        assert_eq!(resolution.errors[0].message(), "no method `is_even` on `int` is in scope");
+        assert_eq!(resolution.errors[0].suggestion().unwrap(), "Add `summon math.parity`");
+
+        // Calls nothing answers may be built in, so they are left alone
+        assert!(resolve("fn main() {\n    42.to_string()\n}\n", &[PARITY]).errors.is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_extensions() {
+        let ascii = "package text.ascii\n\nextend string {\n    fn shout(self) string { return self }\n}\n";
+        let unicode = "package text.unicode\n\nextend string {\n    fn shout(self) string { return self }\n}\n";
+        let resolution = resolve("summon text.ascii\nsummon text.unicode\n\nfn main() {\n    \"hi\".shout()\n}\n", &[ascii, unicode]);
+        assert!(resolution.calls.is_empty());
+        assert_eq!(resolution.errors[0].code(), error_codes::E0200);
+        assert_eq!(
+            resolution.errors[0].message(),
+            "`shout` on `string` is ambiguous: it is an extension in `text.ascii` and `text.unicode`"
+        );
+        assert_eq!(resolution.errors[0].suggestion().unwrap(), "Pick one with `from text.ascii summon [shout]`");
+
+        // A method summoned by name beats every module summoned whole
+        let picked = resolve("summon text.ascii\nfrom text.unicode summon [shout]\n\nfn main() {\n    \"hi\".shout()\n}\n", &[ascii, unicode]);
+        assert!(picked.errors.is_empty());
+        assert_eq!(targets(&picked), [("shout".to_string(), extension("text.unicode", "string"))]);
+        let dotted = resolve("summon text.ascii.shout\n\nfn main() {\n    \"hi\".shout()\n}\n", &[ascii, unicode]);
+        assert_eq!(targets(&dotted), [("shout".to_string(), extension("text.ascii", "string"))]);
+
+        // An unsummoned module does not take part
+        let single = resolve("summon text.ascii\n\nfn main() {\n    \"hi\".shout()\n}\n", &[ascii, unicode]);
+        assert_eq!(targets(&single), [("shout".to_string(), extension("text.ascii", "string"))]);
+    }
+
+    #[test]
+    fn test_generic_targets_match_type_arguments() {
+        let resolution = resolve(
+            "struct User { name: string }\n\nextend List<User> {\n    fn names(self) List<string> { return [] }\n}\n\nfn main() {\n    [User { name = \"a\" }].names()\n    [1].names()\n    [].names()\n}\n",
+            &[],
+        );
+        let receivers: Vec<Ty> = resolution.calls.iter().map(|call| call.receiver.clone()).collect();
+        // `User` is a declared type, so `List<User>` only matches lists of users;
+        // an empty list's element type is unknown and may be one
+        assert_eq!(receivers.iter().map(|ty| ty.to_string()).collect::<Vec<_>>(), ["List<User>", "List<_>"]);
+    }
+}
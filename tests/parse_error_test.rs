@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::error_management::error_types::ParseError;
+    use ubel_stratum::lexer::Span;
+    use ubel_stratum::parser::{self, ast::*};
+
+    fn messages(source: &str) -> Vec<String> {
+        let (_, manager) = parser::parse_recovering(source);
+        manager.diagnostics().iter().map(|d| d.message.clone()).collect()
+    }
+
+    fn main_body(program: &Program) -> &[Stmt] {
+        match program.items.iter().find(|item| matches!(item, Item::Function(_))) {
+            Some(Item::Function(f)) => &f.body.stmts,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_expected_one_of_message() {
+        let span = Span::new(0, 1, 1, 1);
+        let error = ParseError::UnexpectedToken {
+            expected: vec!["`,`".to_string(), "`)`".to_string()],
+            found: "`b`".to_string(),
+            span,
+        };
+        assert_eq!(error.message(), "expected one of `,` or `)`, found `b`");
+        assert_eq!(error.code(), error_codes::E0100);
+
+        let error = ParseError::UnexpectedEof { expected: vec!["`}`".to_string()], span };
+        assert_eq!(error.message(), "expected `}`, found end of file");
+        assert_eq!(error.suggestion().as_deref(), Some("Add the missing `}`"));
+    }
+
+    #[test]
+    fn test_one_file_reports_many_errors() {
+        let source = "fn add(a: int b: int) int {\n    return a + b\n}\n\nstruct Point { x: int, y int }\n\nfn main() {\n    let = 5\n    x := 3 3\n    println(\"still parsed\")\n}\n";
+        assert_eq!(messages(source), [
+            "expected one of `,` or `)`, found `b`",
+            "expected `:`, found `int`",
+            "expected a variable name, found `=`",
+            "expected one of `;` or a newline, found `3`",
+        ]);
+    }
+
+    #[test]
+    fn test_items_resynchronise_at_keywords() {
+        let (program, manager) = parser::parse_recovering("fn broken(a b) {}\nstruct Ok { x: int }\nenum E { A }\nconst C = 1\n");
+        assert_eq!(manager.error_count(), 1);
+        assert!(matches!(program.items[0], Item::Error(_)));
+        assert!(matches!(program.items[1], Item::Struct(_)));
+        assert!(matches!(program.items[2], Item::Enum(_)));
+        assert!(matches!(program.items[3], Item::Const(_)));
+        assert_eq!(program.items[0].span().start, 0);
+    }
+
+    #[test]
+    fn test_items_resynchronise_at_extend_and_edge() {
+        let (program, manager) = parser::parse_recovering("fn 3\nextend List<int> {\n    fn sum(self) int { return 0 }\n}\nfn 4\nedge struct Link { to: int }\n");
+        assert_eq!(manager.error_count(), 2);
+        assert!(matches!(program.items[0], Item::Error(_)));
+        assert!(matches!(program.items[1], Item::Extend(_)));
+        assert!(matches!(program.items[2], Item::Error(_)));
+        assert!(matches!(program.items[3], Item::Struct(_)));
+    }
+
+    #[test]
+    fn test_set_only_property_keeps_the_struct() {
+        let (program, manager) = parser::parse_recovering("struct P {\n    x: int { set; }\n    y: int\n    fn norm(self) int { return 0 }\n}\nfn main() {}\n");
+        assert_eq!(messages("struct P {\n    x: int { set; }\n    y: int\n}\n"), ["property `x` has no `get` accessor"]);
+        assert_eq!(manager.error_count(), 1);
+        let Item::Struct(decl) = &program.items[0] else { panic!("expected a struct, got {:?}", program.items[0]) };
+        assert_eq!(decl.members.len(), 3);
+        assert!(matches!(&decl.members[0], StructMember::Property(p) if p.setter.is_some()));
+        assert!(matches!(&decl.members[1], StructMember::Field(f) if f.name.name == "y"));
+        assert!(matches!(&decl.members[2], StructMember::Method(m) if m.name.name == "norm"));
+        assert!(matches!(program.items[1], Item::Function(_)));
+    }
+
+    #[test]
+    fn test_statements_resynchronise_at_boundaries() {
+        let (program, manager) = parser::parse_recovering("fn main() {\n    let = 1; let a = 2\n    let b = )\n    let c = 3\n}\n");
+        assert_eq!(manager.error_count(), 2);
+
+        let kinds: Vec<&StmtKind> = main_body(&program).iter().map(|s| &s.kind).collect();
+        assert!(matches!(kinds[0], StmtKind::Error));
+        assert!(matches!(kinds[1], StmtKind::Let { name, .. } if name.name == "a"));
+        assert!(matches!(kinds[2], StmtKind::Error));
+        assert!(matches!(kinds[3], StmtKind::Let { name, .. } if name.name == "c"));
+    }
+
+    #[test]
+    fn test_missing_comma_in_object_literal_is_one_error() {
+        let source = "fn main() {\n    let p = { a = 1 b = { c = 2 } }\n    let q = { a = 1, b = 2 c = 3 }\n    println(p)\n}\n";
+        let (program, manager) = parser::parse_recovering(source);
+        assert_eq!(messages(source), ["expected one of `,` or `}`, found `b`", "expected one of `,` or `}`, found `c`"]);
+        assert_eq!(manager.diagnostics()[0].code, Some(error_codes::E0102));
+
+        let kinds: Vec<&StmtKind> = main_body(&program).iter().map(|s| &s.kind).collect();
+        assert_eq!(kinds.len(), 3);
+        assert!(matches!(kinds[0], StmtKind::Let { value, .. } if matches!(value.kind, ExprKind::Error) && value.span.len() == "{ a = 1 b = { c = 2 } }".len()));
+        assert!(matches!(kinds[1], StmtKind::Let { name, .. } if name.name == "q"));
+        assert!(matches!(kinds[2], StmtKind::Expr { .. }));
+    }
+
+    #[test]
+    fn test_error_nodes_serialise() {
+        let (program, _) = parser::parse_recovering("fn main() {\n    let = 1\n}\n");
+        let json = serde_json::to_value(&program).unwrap();
+        assert_eq!(json["items"][0]["body"]["stmts"][0]["type"], "Error");
+    }
+
+    #[test]
+    fn test_lexical_and_parse_errors_together() {
+        let (program, manager) = parser::parse_recovering("fn main() {\n    let s = \"open\n    let = 2\n}\n");
+        let codes: Vec<_> = manager.diagnostics().iter().filter_map(|d| d.code).collect();
+        assert_eq!(codes, [error_codes::E0002, error_codes::E0100]);
+        assert_eq!(manager.parse_errors().len(), 1);
+
+        // The bad literal stands in as an `Error` expression instead of cascading
+        match &main_body(&program)[0].kind {
+            StmtKind::Let { value, .. } => assert!(matches!(value.kind, ExprKind::Error)),
+            other => panic!("expected a let, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_brace_fix() {
+        let (_, manager) = parser::parse_recovering("struct P { x: int ");
+        let suggestions = manager.suggestions();
+        assert_eq!(manager.diagnostics()[0].code, Some(error_codes::E0101));
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].replacement, "}");
+    }
+
+    #[test]
+    fn test_parse_codes_are_explained() {
+        // E0102 comes from grammar actions, which only the LALR parser has
+        let cases = [
+            (error_codes::E0100, parser::parse as fn(&str) -> _),
+            (error_codes::E0101, parser::parse),
+            (error_codes::E0102, parser::parse_lalr),
+        ];
+
+        for (code, parse) in cases {
+            let entry = error_codes::lookup(code).unwrap();
+            let manager = parse(entry.bad_example).expect_err("bad example parsed");
+            assert_eq!(manager.diagnostics()[0].code, Some(code), "bad example for {} triggers another code", code);
+            assert!(parse(entry.good_example).is_ok(), "good example for {} fails", code);
+        }
+    }
+}
@@ -0,0 +1,693 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::parser::{self, AstFormat};
+    use ubel_stratum::parser::ast::*;
+
+    fn parse_ok(source: &str) -> Program {
+        match parser::parse(source) {
+            Ok(program) => program,
+            Err(manager) => panic!("parse failed: {:?}", manager.diagnostics()),
+        }
+    }
+
+    fn parse_err(source: &str) -> String {
+        match parser::parse(source) {
+            Ok(program) => panic!("expected an error, got {:?}", program),
+            Err(manager) => manager.diagnostics()[0].message.clone(),
+        }
+    }
+
+    /// Statements of `fn main() { ... }`
+    fn body(statements: &str) -> Vec<Stmt> {
+        let program = parse_ok(&format!("fn main() {{\n{}\n}}", statements));
+        match program.items.into_iter().next() {
+            Some(Item::Function(f)) => f.body.stmts,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    /// The expression statement at `index`
+    fn expr_at(block: &Block, index: usize) -> &Expr {
+        match &block.stmts[index].kind {
+            StmtKind::Expr { expr } => expr,
+            other => panic!("expected an expression statement, got {:?}", other),
+        }
+    }
+
+    fn expr(source: &str) -> Expr {
+        match body(source).remove(0).kind {
+            StmtKind::Expr { expr } => expr,
+            other => panic!("expected an expression statement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_package_and_imports() {
+        let program = parse_ok("package api_server\nsummon std.io as io\nsummon std.json\nfrom database summon [User, Database]\nfrom util summon a, b\n");
+
+        assert_eq!(program.package.unwrap().path.dotted(), "api_server");
+        assert_eq!(program.imports.len(), 4);
+        match &program.imports[0].kind {
+            ImportKind::Summon { path, alias } => {
+                assert_eq!(path.dotted(), "std.io");
+                assert_eq!(alias.as_ref().unwrap().name, "io");
+            }
+            other => panic!("unexpected import {:?}", other),
+        }
+        match &program.imports[2].kind {
+            ImportKind::From { path, items } => {
+                assert_eq!(path.dotted(), "database");
+                assert_eq!(items.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["User", "Database"]);
+            }
+            other => panic!("unexpected import {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_function_with_attributes() {
+        let program = parse_ok("@tier(high)\npub async fn fetch(id: int, retries = 3) Task<User>! {\n}\n");
+        let Item::Function(f) = &program.items[0] else { panic!() };
+
+        assert!(f.is_pub && f.is_async);
+        assert_eq!(f.attributes[0].name.name, "tier");
+        assert_eq!(f.params.len(), 2);
+        assert!(f.params[1].default.is_some());
+        let ret = f.return_type.as_ref().unwrap();
+        assert!(matches!(&ret.kind, TypeKind::Fallible { inner } if matches!(inner.kind, TypeKind::Task { .. })));
+        assert_eq!(f.span.start, 0, "span starts at the attribute");
+    }
+
+    #[test]
+    fn test_struct_members() {
+        let program = parse_ok(
+            "edge struct Node<T: Display + Clone> {\n    value: T,\n    pub next: Node<T>\n    size: int { get { return 1 } set { self.n = 2 } }\n    pub fn new(val: T) Node<T> { return Node { value = val } }\n}\n",
+        );
+        let Item::Struct(s) = &program.items[0] else { panic!() };
+
+        assert!(s.is_edge);
+        assert_eq!(s.generics[0].bounds.len(), 2);
+        assert!(matches!(&s.members[0], StructMember::Field(f) if f.name.name == "value"));
+        assert!(matches!(&s.members[1], StructMember::Field(f) if f.is_pub));
+        assert!(matches!(&s.members[2], StructMember::Property(p) if p.setter.is_some()));
+        assert!(matches!(&s.members[3], StructMember::Method(m) if m.is_pub && m.name.name == "new"));
+    }
+
+    #[test]
+    fn test_properties() {
+        let program = parse_ok(
+            "struct Temp {\n    c: float { get; set; }\n    f: float {\n        set { self.c = (value - 32.0) / 1.8 }\n        get { return self.c * 1.8 + 32.0 }\n    }\n    k: float { get { return self.c + 273.15 } }\n}\n",
+        );
+        let Item::Struct(s) = &program.items[0] else { panic!() };
+        let properties: Vec<&PropertyDecl> = s.members.iter().map(|m| match m {
+            StructMember::Property(p) => p,
+            other => panic!("expected a property, got {:?}", other),
+        }).collect();
+
+        assert!(properties[0].is_auto());
+        assert!(properties[0].getter.body.is_none() && properties[0].setter.as_ref().unwrap().body.is_none());
+
+        // Accessors may come in either order; the setter binds `value` at its keyword
+        let setter = properties[1].setter.as_ref().unwrap();
+        assert!(!properties[1].is_auto());
+        assert_eq!(setter.value.as_ref().map(|v| v.name.as_str()), Some(SETTER_VALUE));
+        assert_eq!(setter.value.as_ref().unwrap().span.line, 4);
+        assert!(properties[1].getter.value.is_none());
+
+        assert!(properties[2].setter.is_none());
+    }
+
+    #[test]
+    fn test_property_errors() {
+        assert_eq!(parse_err("struct P {\n    x: int { set { } }\n}\n"), "property `x` has no `get` accessor");
+        assert_eq!(parse_err("struct P {\n    x: int { set; }\n}\n"), "property `x` has no `get` accessor");
+        assert_eq!(parse_err("struct P {\n    x: int { get; set; get; }\n}\n"), "property `x` already has a `get` accessor");
+        assert_eq!(parse_err("struct P {\n    x: int { get; set { } set { } }\n}\n"), "property `x` already has a `set` accessor");
+        assert_eq!(parse_err("struct P {\n    x: int { get; put; }\n}\n"), "expected one of `get`, `set` or `}`, found `put`");
+    }
+
+    #[test]
+    fn test_enum_trait_impl_const() {
+        let program = parse_ok(
+            "enum Shape { Circle(float), Rect { w: int, h: int }, Empty }\n\
+             trait Show { fn show(self) string; type Item; }\n\
+             impl Show for Shape { fn show(self) string { return \"s\" } }\n\
+             impl Shape { }\n\
+             const MAX: int = 10\n",
+        );
+
+        let Item::Enum(e) = &program.items[0] else { panic!() };
+        assert!(matches!(e.variants[0].payload, Some(EnumPayload::Tuple { .. })));
+        assert!(matches!(e.variants[1].payload, Some(EnumPayload::Struct { .. })));
+        assert!(e.variants[2].payload.is_none());
+
+        let Item::Trait(t) = &program.items[1] else { panic!() };
+        assert!(matches!(t.items[0], TraitItem::Method(_)));
+        assert!(matches!(t.items[1], TraitItem::AssociatedType(_)));
+
+        let Item::Impl(i) = &program.items[2] else { panic!() };
+        assert!(i.trait_name.is_some());
+        assert_eq!(i.methods.len(), 1);
+        let Item::Impl(inherent) = &program.items[3] else { panic!() };
+        assert!(inherent.trait_name.is_none());
+
+        assert!(matches!(&program.items[4], Item::Const(c) if c.name.name == "MAX"));
+    }
+
+    #[test]
+    fn test_extend_blocks() {
+        let program = parse_ok("extend int {\n    fn is_even(self) bool {\n        return self % 2 == 0\n    }\n}\nextend List<T> {\n    fn second(self) Option<T> { return self.get(1) }\n    pub fn last(self) Option<T> { return self.get(self.len() - 1) }\n}\nextend<K> Dictionary<K, int> {}\nfn main() {\n    xs.extend(ys)\n}");
+        let Item::Extend(ints) = &program.items[0] else { panic!("expected an extend block") };
+        assert!(matches!(ints.target.kind, TypeKind::Primitive { name: PrimitiveType::Int }));
+        assert_eq!(ints.methods[0].name.name, "is_even");
+
+        let Item::Extend(lists) = &program.items[1] else { panic!("expected an extend block") };
+        assert!(lists.generics.is_empty());
+        assert!(matches!(&lists.target.kind, TypeKind::Named { name, args } if name.name == "List" && args.len() == 1));
+        assert!(lists.methods[1].is_pub);
+
+        let Item::Extend(dicts) = &program.items[2] else { panic!("expected an extend block") };
+        assert_eq!(dicts.generics[0].name.name, "K");
+        assert!(dicts.methods.is_empty());
+
+        // `extend` is still a method name after `.`
+        let Item::Function(f) = &program.items[3] else { panic!("expected a function") };
+        assert!(matches!(&expr_at(&f.body, 0).kind, ExprKind::MethodCall { method, .. } if method.name == "extend"));
+
+        assert_eq!(parse_err("pub extend int {}"), "expected one of `fn`, `struct`, `enum`, `trait`, `impl`, `extend` or `const`, found `extend`");
+    }
+
+    #[test]
+    fn test_types() {
+        let program = parse_ok("fn f(a: [4]int, b: []string, c: (int, bool), d: fn(int) int, e: List<List<int>>) {}");
+        let Item::Function(f) = &program.items[0] else { panic!() };
+        let kinds: Vec<&TypeKind> = f.params.iter().map(|p| &p.ty.as_ref().unwrap().kind).collect();
+
+        assert!(matches!(kinds[0], TypeKind::Array { size: Some(4), .. }));
+        assert!(matches!(kinds[1], TypeKind::Slice { .. }));
+        assert!(matches!(kinds[2], TypeKind::Tuple { elems } if elems.len() == 2));
+        assert!(matches!(kinds[3], TypeKind::Function { ret: Some(_), .. }));
+        // `>>` closes both generic argument lists
+        assert!(matches!(kinds[4], TypeKind::Named { args, .. } if matches!(&args[0].kind, TypeKind::Named { args, .. } if args.len() == 1)));
+    }
+
+    #[test]
+    fn test_lifetimes_and_references() {
+        let program = parse_ok("fn advanced[lifetime L, lifetime M where M outlives L](x: &L Data, y: &mut M [u8], z: &int) &L Result {}\ntrait View {\n    fn view[lifetime L](self) &L str\n}");
+        let Item::Function(f) = &program.items[0] else { panic!("expected a function") };
+        let lifetimes = f.lifetimes.as_ref().unwrap();
+        let names: Vec<&str> = lifetimes.names.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["L", "M"]);
+        assert_eq!((lifetimes.outlives[0].longer.name.as_str(), lifetimes.outlives[0].shorter.name.as_str()), ("M", "L"));
+
+        let kinds: Vec<&TypeKind> = f.params.iter().map(|p| &p.ty.as_ref().unwrap().kind).collect();
+        assert!(matches!(kinds[0], TypeKind::Reference { mutable: false, lifetime: Some(l), inner } if l.name == "L" && matches!(inner.kind, TypeKind::Named { .. })));
+        assert!(matches!(kinds[1], TypeKind::Reference { mutable: true, lifetime: Some(l), inner } if l.name == "M" && matches!(inner.kind, TypeKind::Array { .. })));
+        // A name is only a lifetime when another type follows it
+        assert!(matches!(kinds[2], TypeKind::Reference { lifetime: None, inner, .. } if matches!(inner.kind, TypeKind::Primitive { .. })));
+        assert!(matches!(&f.return_type.as_ref().unwrap().kind, TypeKind::Reference { lifetime: Some(_), .. }));
+
+        let Item::Trait(t) = &program.items[1] else { panic!("expected a trait") };
+        let TraitItem::Method(view) = &t.items[0] else { panic!("expected a method") };
+        assert_eq!(view.lifetimes.as_ref().unwrap().names[0].name, "L");
+
+        assert_eq!(parse_err("fn f[L](x: &L str) {}"), "expected `lifetime`, found `L`");
+        assert_eq!(parse_err("fn f[lifetime L where L before M]() {}"), "expected `outlives`, found `before`");
+        assert_eq!(parse_err("fn f[lifetime L() {}"), "expected one of `,` or `]`, found `(`");
+    }
+
+    #[test]
+    fn test_where_clauses_and_bounds() {
+        let program = parse_ok("fn parse_json_with<F>(input: string, callback: F) Response\n    where F: FnOnce(&JsonView) Response\n{\n    return callback(input)\n}\nfn collect<I: Iterator<Item = T>, S: Into<List<int>>>(items: I) where T: Display + Map<string, Key = K>, {}\nimpl<T> Show for List<T> where T: Show {}\nextend List<T> where T: Display {}");
+        let Item::Function(f) = &program.items[0] else { panic!("expected a function") };
+        assert!(matches!(&f.return_type.as_ref().unwrap().kind, TypeKind::Named { name, .. } if name.name == "Response"));
+        let predicate = &f.where_clause.as_ref().unwrap().predicates[0];
+        assert_eq!(predicate.name.name, "F");
+        let BoundKind::Function { name, params, ret } = &predicate.bounds[0].kind else { panic!("expected a function bound") };
+        assert_eq!(name.name, "FnOnce");
+        assert!(matches!(params[0].kind, TypeKind::Reference { .. }));
+        assert!(ret.is_some());
+
+        let Item::Function(f) = &program.items[1] else { panic!("expected a function") };
+        let BoundKind::Trait { name, args, bindings } = &f.generics[0].bounds[0].kind else { panic!("expected a trait bound") };
+        assert_eq!((name.name.as_str(), args.len(), bindings[0].name.name.as_str()), ("Iterator", 0, "Item"));
+        // `>>` closes both the bound and the parameter list
+        let BoundKind::Trait { args, .. } = &f.generics[1].bounds[0].kind else { panic!("expected a trait bound") };
+        assert!(matches!(&args[0].kind, TypeKind::Named { name, .. } if name.name == "List"));
+        let predicate = &f.where_clause.as_ref().unwrap().predicates[0];
+        assert_eq!(predicate.bounds.len(), 2);
+        assert!(matches!(&predicate.bounds[1].kind, BoundKind::Trait { args, bindings, .. } if args.len() == 1 && bindings.len() == 1));
+
+        let Item::Impl(block) = &program.items[2] else { panic!("expected an impl block") };
+        assert_eq!(block.where_clause.as_ref().unwrap().predicates[0].name.name, "T");
+        let Item::Extend(block) = &program.items[3] else { panic!("expected an extend block") };
+        assert!(block.where_clause.is_some());
+
+        assert_eq!(parse_err("fn f<I: Iterator<Item = T, U>>() {}"), "type arguments must come before associated type bindings");
+        assert_eq!(parse_err("fn f<T>() where T Display {}"), "expected `:`, found `Display`");
+    }
+
+    #[test]
+    fn test_let_forms() {
+        let stmts = body("let x = 1\nlet mut y: int = 2;\nname := \"Alice\"\nconst PI = 3.14");
+        assert!(matches!(&stmts[0].kind, StmtKind::Let { mutable: false, ty: None, .. }));
+        assert!(matches!(&stmts[1].kind, StmtKind::Let { mutable: true, ty: Some(_), .. }));
+        assert!(matches!(&stmts[2].kind, StmtKind::ShortLet { name, .. } if name.name == "name"));
+        assert!(matches!(&stmts[3].kind, StmtKind::Item { item: Item::Const(_) }));
+    }
+
+    #[test]
+    fn test_extract_forms() {
+        let stmts = body("extract (x, y) = point\nextract [first, second, ...rest] = numbers\nextract { id, name } = user\nextract { user = { name, age }, status } = data\nextract [head, ...] = xs");
+        let patterns: Vec<&Pattern> = stmts
+            .iter()
+            .map(|s| match &s.kind {
+                StmtKind::Extract { pattern, .. } => pattern,
+                other => panic!("expected extract, got {:?}", other),
+            })
+            .collect();
+        assert!(matches!(&patterns[0].kind, PatternKind::Tuple { elems } if elems.len() == 2));
+        let PatternKind::List { elems } = &patterns[1].kind else { panic!("expected a list pattern") };
+        assert!(matches!(&elems[2].kind, PatternKind::Rest { name: Some(name) } if name.name == "rest"));
+        assert!(matches!(&patterns[2].kind, PatternKind::Object { fields } if fields.len() == 2 && fields[0].pattern.is_none()));
+        let PatternKind::Object { fields } = &patterns[3].kind else { panic!("expected an object pattern") };
+        assert!(matches!(&fields[0].pattern, Some(Pattern { kind: PatternKind::Object { fields }, .. }) if fields.len() == 2));
+        assert!(matches!(&patterns[4].kind, PatternKind::List { elems } if matches!(elems[1].kind, PatternKind::Rest { name: None })));
+    }
+
+    #[test]
+    fn test_using_statements() {
+        let stmts = body("using let file = File.open(\"data.txt\") { read(file) }\nusing let db = Database.connect(\"localhost\"),\n      let cache = Cache.connect(\"redis\") {\n    sync(db, cache)\n}\ndefer { log(\"done\") }");
+        let StmtKind::Using { resources, body } = &stmts[0].kind else { panic!("expected using, got {:?}", stmts[0].kind) };
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].name.name, "file");
+        assert!(matches!(resources[0].value.kind, ExprKind::MethodCall { .. }));
+        assert_eq!(body.stmts.len(), 1);
+
+        let StmtKind::Using { resources, .. } = &stmts[1].kind else { panic!("expected using, got {:?}", stmts[1].kind) };
+        let names: Vec<&str> = resources.iter().map(|r| r.name.name.as_str()).collect();
+        assert_eq!(names, ["db", "cache"]);
+        assert!(matches!(&stmts[2].kind, StmtKind::Defer { body } if body.stmts.len() == 1));
+    }
+
+    #[test]
+    fn test_using_errors() {
+        assert_eq!(parse_err("fn main() {\n    using file = open(p) { read(file) }\n}"), "expected `let`, found `file`");
+        assert_eq!(parse_err("fn main() {\n    using let a = open(p), { a }\n}"), "expected `let`, found `{`");
+        assert_eq!(parse_err("fn main() {\n    defer log(x)\n}"), "expected `{`, found `log`");
+    }
+
+    #[test]
+    fn test_extract_params_and_patterns() {
+        let program = parse_ok("fn process_point(extract (x, y): (int, int), scale: int) {\n    f = (extract [a, ...]) => a\n    match response {\n        Err(NetworkError(extract { code, message })) => log(code)\n        { kind = k } => k\n    }\n}\n");
+        let Item::Function(f) = &program.items[0] else { panic!("expected a function") };
+        assert!(matches!(&f.params[0].pattern, Some(Pattern { kind: PatternKind::Tuple { .. }, .. })));
+        assert!(f.params[0].ty.is_some());
+        assert!(f.params[1].pattern.is_none());
+
+        let ExprKind::Assign { value, .. } = &expr_at(&f.body, 0).kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::Lambda { params, .. } if params[0].pattern.is_some()));
+
+        // `extract` inside a pattern only marks it; object patterns also work in `match`
+        let ExprKind::Match { arms, .. } = &expr_at(&f.body, 1).kind else { panic!() };
+        let PatternKind::TupleStruct { elems, .. } = &arms[0].pattern.kind else { panic!() };
+        let PatternKind::TupleStruct { elems, .. } = &elems[0].kind else { panic!() };
+        assert!(matches!(elems[0].kind, PatternKind::Object { .. }));
+        assert!(matches!(arms[1].pattern.kind, PatternKind::Object { .. }));
+    }
+
+    #[test]
+    fn test_extract_errors() {
+        assert_eq!(
+            parse_err("fn main() {\n    extract (x, 0) = point\n}"),
+            "refutable pattern in `extract`: `0` does not match every value",
+        );
+        assert_eq!(
+            parse_err("fn f(extract Some(x): Option<int>) {\n}"),
+            "refutable pattern in `extract`: `Some(x)` does not match every value",
+        );
+        assert_eq!(
+            parse_err("fn main() {\n    extract { shape = Shape.Empty } = s\n}"),
+            "refutable pattern in `extract`: `Shape.Empty` does not match every value",
+        );
+        assert_eq!(parse_err("fn main() {\n    extract [a, ...b, ...c] = xs\n}"), "a list pattern can only have one `...`");
+        assert_eq!(parse_err("fn main() {\n    extract (a, ...b) = xs\n}"), "expected a pattern, found `...`");
+        // Struct patterns name a struct, which always matches
+        body("extract Point { x, y } = p");
+    }
+
+    #[test]
+    fn test_let_conditionals() {
+        let stmts = body("if let Some(age) = ages.get(\"Alice\") {\n    println(age)\n} elif let [first, ...] = names {\n    println(first)\n} elif ready {\n    go()\n}\nwhile let Some(job) = queue.pop() { job.run() }\nlet Ok(config) = load(path) else {\n    return\n}\nlet x = if a { 1 } else { 2 }");
+        let StmtKind::Expr { expr } = &stmts[0].kind else { panic!("expected an if") };
+        let ExprKind::If { branches, .. } = &expr.kind else { panic!("expected an if") };
+        assert!(matches!(&branches[0].pattern, Some(Pattern { kind: PatternKind::TupleStruct { .. }, .. })));
+        assert!(matches!(&branches[0].condition.kind, ExprKind::MethodCall { .. }));
+        assert!(matches!(&branches[1].pattern, Some(Pattern { kind: PatternKind::List { .. }, .. })));
+        assert!(branches[2].pattern.is_none());
+
+        let StmtKind::While { pattern: Some(pattern), condition, .. } = &stmts[1].kind else { panic!("expected while let") };
+        assert!(matches!(&pattern.kind, PatternKind::TupleStruct { .. }));
+        assert!(matches!(&condition.kind, ExprKind::MethodCall { .. }));
+
+        let StmtKind::LetElse { pattern, value, else_block } = &stmts[2].kind else { panic!("expected let-else") };
+        assert!(matches!(&pattern.kind, PatternKind::TupleStruct { path, .. } if path[0].name == "Ok"));
+        assert!(matches!(&value.kind, ExprKind::Call { .. }));
+        assert_eq!(else_block.stmts.len(), 1);
+        assert!(matches!(&stmts[3].kind, StmtKind::Let { .. }));
+    }
+
+    #[test]
+    fn test_let_else_errors() {
+        assert_eq!(
+            parse_err("fn main() {\n    let (a, b) = pair else { return }\n}"),
+            "irrefutable pattern in `let ... else`: `(a, b)` matches every value",
+        );
+        assert_eq!(parse_err("fn main() {\n    let Some(x) = v\n}"), "expected `else`, found `}`");
+        body("let Point { x, y = 0 } = p else { return }");
+        // A list pattern checks the length, unless it is all rest
+        body("let [x] = xs else { return }");
+        body("let [first, ...rest] = xs else { return }");
+        body("let ([a, b], n) = p else { return }");
+        assert_eq!(
+            parse_err("fn main() {\n    let [...rest] = xs else { return }\n}"),
+            "irrefutable pattern in `let ... else`: `[...rest]` matches every value",
+        );
+    }
+
+    #[test]
+    fn test_interpolated_string_holes() {
+        let e = expr("$\"hi {user?.name.upper()}, {n * (m + 1)} {$\"[{x}]\"}\"");
+        let ExprKind::InterpolatedString { parts } = e.kind else { panic!("{:?}", e) };
+        assert_eq!(parts.len(), 6);
+        assert_eq!(parts[0], StringPart::Text("hi ".to_string()));
+        let StringPart::Expr(call) = &parts[1] else { panic!("{:?}", parts[1]) };
+        let ExprKind::MethodCall { receiver, method, .. } = &call.kind else { panic!("{:?}", call) };
+        assert_eq!(method.name, "upper");
+        assert!(matches!(receiver.kind, ExprKind::Field { safe: true, .. }));
+        // Spans are absolute: line 2 of the wrapping `fn main`, inside the literal
+        assert_eq!((call.span.line, call.span.column, call.span.start), (2, 7, 18));
+        let StringPart::Expr(product) = &parts[3] else { panic!("{:?}", parts[3]) };
+        assert!(matches!(product.kind, ExprKind::Binary { op: BinaryOp::Mul, .. }));
+        assert_eq!(product.span.column, 29);
+        let StringPart::Expr(inner) = &parts[5] else { panic!("{:?}", parts[5]) };
+        let ExprKind::InterpolatedString { parts: inner_parts } = &inner.kind else { panic!("{:?}", inner) };
+        let StringPart::Expr(x) = &inner_parts[1] else { panic!("{:?}", inner_parts) };
+        assert_eq!((x.span.line, x.span.column), (2, 47));
+    }
+
+    #[test]
+    fn test_interpolated_string_hole_errors() {
+        let (_, manager) = parser::parse_recovering("fn main() {\n    log($\"{a +} {} {a b}\")\n}");
+        let errors: Vec<(String, usize, usize)> =
+            manager.diagnostics().iter().map(|d| (d.message.clone(), d.span.line, d.span.column)).collect();
+        assert_eq!(
+            errors,
+            [
+                ("expected an expression, found `}`".to_string(), 2, 15),
+                ("empty interpolation hole".to_string(), 2, 18),
+                ("expected `}`, found `b`".to_string(), 2, 23),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolated_string_hole_keeps_lexer_codes() {
+        let (_, manager) = parser::parse_recovering("fn main() {\n    log($\"{`}`}\")\n}");
+        let errors: Vec<(Option<&str>, String, usize)> =
+            manager.diagnostics().iter().map(|d| (d.code, d.message.clone(), d.span.column)).collect();
+        assert_eq!(errors, [(Some("E0001"), "Unexpected character '`'".to_string(), 12)]);
+    }
+
+    #[test]
+    fn test_interpolated_string_hole_warnings() {
+        let source = "fn main() {\n    log($\"x {1.} y\")\n}";
+        let warnings = |manager: &ubel_stratum::error_management::ErrorManager| -> Vec<(Option<&str>, usize, usize)> {
+            manager.diagnostics().iter().map(|d| (d.code, d.span.line, d.span.column)).collect()
+        };
+        let (_, manager) = parser::parse_recovering(source);
+        assert_eq!(warnings(&manager), [(Some("W0003"), 2, 14)]);
+        assert_eq!(manager.suggestions()[0].span.start, source.find("1.").unwrap() + 2);
+
+        let (_, manager) = parser::lalr::parse_with_diagnostics(source).unwrap();
+        assert_eq!(warnings(&manager), [(Some("W0003"), 2, 14)]);
+    }
+
+    #[test]
+    fn test_control_flow_statements() {
+        let stmts = body(
+            "for (k, v) in pairs { continue }\nwhile x < 10 { x += 1 }\nloop { break }\nwith arena(1024) { }\nwith pool<Node>(16) { }\nwith gc { }\ntry { risky() } catch (e) { log(e) }\nunsafe { free(p) }\nreturn",
+        );
+        assert!(matches!(&stmts[0].kind, StmtKind::For { pattern: Pattern { kind: PatternKind::Tuple { .. }, .. }, .. }));
+        assert!(matches!(&stmts[1].kind, StmtKind::While { .. }));
+        assert!(matches!(&stmts[2].kind, StmtKind::Loop { .. }));
+        assert!(matches!(&stmts[3].kind, StmtKind::With { allocator: Allocator { kind: AllocatorKind::Arena { .. }, .. }, .. }));
+        assert!(matches!(&stmts[4].kind, StmtKind::With { allocator: Allocator { kind: AllocatorKind::Pool { .. }, .. }, .. }));
+        assert!(matches!(&stmts[5].kind, StmtKind::With { allocator: Allocator { kind: AllocatorKind::Gc, .. }, .. }));
+        assert!(matches!(&stmts[6].kind, StmtKind::Try { catch: Some(_), .. }));
+        assert!(matches!(&stmts[7].kind, StmtKind::Unsafe { .. }));
+        assert!(matches!(&stmts[8].kind, StmtKind::Return { value: None }));
+    }
+
+    #[test]
+    fn test_precedence() {
+        // a or b and c == d + e * -f
+        let e = expr("a or b and c == d + e * -f");
+        let ExprKind::Binary { op: BinaryOp::Or, rhs, .. } = e.kind else { panic!("{:?}", e) };
+        let ExprKind::Binary { op: BinaryOp::And, rhs, .. } = rhs.kind else { panic!() };
+        let ExprKind::Binary { op: BinaryOp::Eq, rhs, .. } = rhs.kind else { panic!() };
+        let ExprKind::Binary { op: BinaryOp::Add, rhs, .. } = rhs.kind else { panic!() };
+        let ExprKind::Binary { op: BinaryOp::Mul, rhs, .. } = rhs.kind else { panic!() };
+        assert!(matches!(rhs.kind, ExprKind::Unary { op: UnaryOp::Neg, .. }));
+    }
+
+    #[test]
+    fn test_postfix_chain() {
+        let e = expr("await client.fetch(url)?.body[0]");
+        let ExprKind::Unary { op: UnaryOp::Await, operand } = e.kind else { panic!() };
+        let ExprKind::Index { target, .. } = operand.kind else { panic!() };
+        let ExprKind::Field { target, safe: true, .. } = target.kind else { panic!("{:?}", target) };
+        assert!(matches!(target.kind, ExprKind::MethodCall { .. }));
+    }
+
+    #[test]
+    fn test_call_on_next_line_is_new_statement() {
+        let stmts = body("let a = b\n(c)");
+        assert_eq!(stmts.len(), 2);
+        let stmts = body("let xs = list\n    .where(fn(u) u.active)\n    .map(fn(u) u.name)");
+        assert_eq!(stmts.len(), 1, "method chains continue across lines");
+    }
+
+    #[test]
+    fn test_lambdas_and_parens() {
+        assert!(matches!(expr("(a, b) => a + b").kind, ExprKind::Lambda { params, .. } if params.len() == 2));
+        assert!(matches!(expr("x => x * 2").kind, ExprKind::Lambda { .. }));
+        assert!(matches!(expr("fn(u) { u.name }").kind, ExprKind::Lambda { .. }));
+        assert!(matches!(expr("(a + b) * c").kind, ExprKind::Binary { op: BinaryOp::Mul, .. }));
+        assert!(matches!(expr("(a, b)").kind, ExprKind::Tuple { elems } if elems.len() == 2));
+        assert!(matches!(expr("()").kind, ExprKind::Tuple { elems } if elems.is_empty()));
+    }
+
+    #[test]
+    fn test_struct_literal_vs_block_after_if() {
+        let e = expr("if ready { go() }");
+        let ExprKind::If { branches, .. } = e.kind else { panic!() };
+        assert!(matches!(branches[0].condition.kind, ExprKind::Ident { .. }));
+
+        let stmts = body("let p = Point { x = 1, y = 2 }");
+        let StmtKind::Let { value, .. } = &stmts[0].kind else { panic!() };
+        assert!(matches!(&value.kind, ExprKind::StructLit { fields, .. } if fields.len() == 2));
+
+        // Allowed again inside the block
+        let e = expr("if ok { Point { x = 1 } }");
+        assert!(matches!(e.kind, ExprKind::If { .. }));
+    }
+
+    #[test]
+    fn test_object_dict_and_block() {
+        let stmts = body("let person = { name = \"Alice\", address = { city = \"Oslo\" }, }\nlet scores = { \"Alice\" = 100, \"Bob\" = 85 }\nlet total = { sum += a; sum }\nlet empty = {}");
+        let values: Vec<&Expr> = stmts.iter().map(|s| match &s.kind {
+            StmtKind::Let { value, .. } => value,
+            other => panic!("expected let, got {:?}", other),
+        }).collect();
+
+        let ExprKind::ObjectLit { fields } = &values[0].kind else { panic!("expected an object, got {:?}", values[0]) };
+        assert_eq!(fields.iter().map(|f| f.name.name.as_str()).collect::<Vec<_>>(), ["name", "address"]);
+        assert!(matches!(fields[1].value.kind, ExprKind::ObjectLit { .. }));
+
+        let ExprKind::DictLit { entries } = &values[1].kind else { panic!("expected a dictionary, got {:?}", values[1]) };
+        assert_eq!(entries[1].key.kind, ExprKind::Literal { value: Literal::String("Bob".to_string()) });
+
+        // Only `=` after the first name makes an object
+        assert!(matches!(values[2].kind, ExprKind::Block { .. }));
+        assert!(matches!(&values[3].kind, ExprKind::Block { block } if block.stmts.is_empty()));
+    }
+
+    #[test]
+    fn test_brace_after_arrow_opens_a_block() {
+        let e = expr("f = x => { y = x }");
+        let ExprKind::Assign { value, .. } = e.kind else { panic!() };
+        let ExprKind::Lambda { body, .. } = value.kind else { panic!() };
+        assert!(matches!(body.kind, ExprKind::Block { .. }));
+
+        let e = expr("g = fn(x) { y = x }");
+        let ExprKind::Assign { value, .. } = e.kind else { panic!() };
+        let ExprKind::Lambda { body, .. } = value.kind else { panic!() };
+        assert!(matches!(body.kind, ExprKind::Block { .. }));
+
+        let e = expr("match v {\n    A => { y = 1 },\n    B => ({ y = 2 }),\n}");
+        let ExprKind::Match { arms, .. } = e.kind else { panic!() };
+        assert!(matches!(arms[0].body.kind, ExprKind::Block { .. }));
+        assert!(matches!(arms[1].body.kind, ExprKind::ObjectLit { .. }));
+
+        // Parentheses keep an assignment at the start of a block expression
+        let e = expr("x = { (y = 1)\n    y }");
+        let ExprKind::Assign { value, .. } = e.kind else { panic!() };
+        assert!(matches!(value.kind, ExprKind::Block { .. }));
+    }
+
+    #[test]
+    fn test_object_literal_errors() {
+        assert_eq!(parse_err("fn main() {\n    o = { a = 1, \"b\" = 2 }\n}"), "a literal cannot mix field names and string keys");
+        assert_eq!(parse_err("fn main() {\n    o = { \"a\" = 1, b = 2 }\n}"), "a literal cannot mix field names and string keys");
+        assert_eq!(parse_err("fn main() {\n    o = { a = 1, b = 2, a = 3 }\n}"), "duplicate key `a`");
+        assert_eq!(parse_err("fn main() {\n    o = { \"a\" = 1, \"a\" = 3 }\n}"), "duplicate key \"a\"");
+        assert_eq!(parse_err("fn main() {\n    o = { a = 1, 2 }\n}"), "expected one of a field name or `}`, found `2`");
+        assert_eq!(parse_err("fn main() {\n    o = { outer = { a = 1 b = 2 } }\n}"), "expected one of `,` or `}`, found `b`");
+
+        let (_, manager) = parser::parse_recovering("fn main() {\n    x = { y = 1\n        z = 2 }\n}");
+        let diagnostic = &manager.diagnostics()[0];
+        assert!(diagnostic.help.as_deref().is_some_and(|h| h.contains("write `(y = ...)`")), "{:?}", diagnostic);
+    }
+
+    #[test]
+    fn test_query_expressions() {
+        let e = expr("q = from user in users\n    where user.age >= 18\n      and user.active\n    orderby user.name, user.age descending\n    select user.name");
+        let ExprKind::Assign { value, .. } = e.kind else { panic!() };
+        let ExprKind::Query { clauses } = value.kind else { panic!("expected a query, got {:?}", value) };
+        assert!(matches!(&clauses[0].kind, QueryClauseKind::From { name, .. } if name.name == "user"));
+        assert!(matches!(&clauses[1].kind, QueryClauseKind::Where { condition } if matches!(condition.kind, ExprKind::Binary { op: BinaryOp::And, .. })));
+        let QueryClauseKind::OrderBy { keys } = &clauses[2].kind else { panic!("expected orderby, got {:?}", clauses[2]) };
+        assert_eq!(keys.iter().map(|k| k.descending).collect::<Vec<_>>(), [false, true]);
+        assert!(matches!(clauses[3].kind, QueryClauseKind::Select { .. }));
+
+        let e = expr("from c in customers\nfrom o in c.orders\nlet total = o.total\njoin p in products on o.product equals p.id\ngroup p by c.region");
+        let ExprKind::Query { clauses } = e.kind else { panic!() };
+        assert!(matches!(clauses[1].kind, QueryClauseKind::From { .. }));
+        assert!(matches!(clauses[2].kind, QueryClauseKind::Let { .. }));
+        assert!(matches!(&clauses[3].kind, QueryClauseKind::Join { name, .. } if name.name == "p"));
+        assert!(matches!(clauses[4].kind, QueryClauseKind::GroupBy { .. }));
+
+        // `select` ends the query; the object literal after it is its value
+        let e = expr("f(from x in xs select { name = x.name }, 2)");
+        let ExprKind::Call { args, .. } = e.kind else { panic!() };
+        assert_eq!(args.len(), 2);
+        let ExprKind::Query { clauses } = &args[0].kind else { panic!() };
+        assert!(matches!(&clauses[1].kind, QueryClauseKind::Select { value } if matches!(value.kind, ExprKind::ObjectLit { .. })));
+    }
+
+    #[test]
+    fn test_query_words_are_names_outside_queries() {
+        let stmts = body("let group = 1\nlet s = names.join(\",\")\nselect(by)\nq = from x in xs select x.select");
+        assert_eq!(stmts.len(), 4);
+        assert!(matches!(&stmts[0].kind, StmtKind::Let { name, .. } if name.name == "group"));
+        assert!(matches!(&stmts[2].kind, StmtKind::Expr { expr } if matches!(expr.kind, ExprKind::Call { .. })));
+
+        // `from` without `name in` is still an import
+        let program = parse_ok("from util summon a\nfn main() {\n    from x in xs select x\n}\n");
+        assert_eq!(program.imports.len(), 1);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        assert_eq!(
+            parse_err("fn main() {\n    q = from x in xs where x > 1\n}"),
+            "expected one of `where`, `orderby`, `let`, `from`, `join`, `select`, `group`, found `}`",
+        );
+        assert_eq!(parse_err("fn main() {\n    q = from x in xs where select x\n}"), "expected an expression, found `select`");
+        assert_eq!(parse_err("fn main() {\n    q = from x in xs join y in ys select y\n}"), "expected `on`, found `select`");
+        assert_eq!(
+            parse_err("fn main() {\n    q = from x in xs let x = 1 select x\n}"),
+            "range variable `x` is already defined in this query",
+        );
+    }
+
+    #[test]
+    fn test_if_elif_else_and_match() {
+        let e = expr("if a { } elif b { } elif c { } else { }");
+        assert!(matches!(e.kind, ExprKind::If { branches, else_block: Some(_) } if branches.len() == 3));
+
+        let e = expr("match shape {\n    Shape.Circle(r) where r > 0 => r,\n    Rect { w, h = 0 } => { w }\n    (1, _) => -1\n    x => x\n    _ => 0\n}");
+        let ExprKind::Match { arms, .. } = e.kind else { panic!() };
+        assert_eq!(arms.len(), 5);
+        assert!(matches!(&arms[0].pattern.kind, PatternKind::TupleStruct { path, .. } if path.len() == 2));
+        assert!(arms[0].guard.is_some());
+        assert!(matches!(&arms[1].pattern.kind, PatternKind::Struct { fields, .. } if fields[1].pattern.is_some()));
+        assert!(matches!(&arms[2].pattern.kind, PatternKind::Tuple { .. }));
+        assert!(matches!(&arms[3].pattern.kind, PatternKind::Binding { .. }));
+        assert!(matches!(&arms[4].pattern.kind, PatternKind::Wildcard));
+    }
+
+    #[test]
+    fn test_spans() {
+        let source = "fn main() {\n    let total = price * 2\n}";
+        let program = parse_ok(source);
+        let Item::Function(f) = &program.items[0] else { panic!() };
+        let StmtKind::Let { value, name, .. } = &f.body.stmts[0].kind else { panic!() };
+
+        assert_eq!(&source[f.span.start..f.span.end], source);
+        assert_eq!(&source[f.body.stmts[0].span.start..f.body.stmts[0].span.end], "let total = price * 2");
+        assert_eq!(&source[value.span.start..value.span.end], "price * 2");
+        assert_eq!((name.span.line, name.span.column), (2, 9));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_err("fn main() { let x = 1 let y = 2 }"), "expected one of `;` or a newline, found `let`");
+        assert_eq!(parse_err("struct P { x: int "), "expected `}`, found end of file");
+        assert_eq!(parse_err("fn f(a b) {}"), "expected one of `,` or `)`, found `b`");
+        assert!(parse_err("let x = 1").starts_with("expected one of `fn`, `struct`"));
+    }
+
+    #[test]
+    fn test_examples_parse() {
+        for path in ["examples/basic/hello.strat", "examples/basic/variables.strat", "examples/tier_demo/high_tier.strat"] {
+            let source = std::fs::read_to_string(path).unwrap();
+            assert!(parser::parse(&source).is_ok(), "{} failed to parse", path);
+        }
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let program = parse_ok("@tier(high)\nfn main() { let x = a + 1 }");
+
+        let text = parser::tree::render(&program, AstFormat::Text);
+        assert!(text.starts_with("Program @1:1\n"));
+        assert!(text.contains("FunctionDecl @1:1 name=main"));
+        assert!(text.contains("Binary @2:21 op=\"Add\" lhs=a"));
+
+        let json: serde_json::Value = serde_json::from_str(&parser::tree::render(&program, AstFormat::Json)).unwrap();
+        assert_eq!(json["items"][0]["type"], "FunctionDecl");
+        assert_eq!(json["items"][0]["body"]["stmts"][0]["type"], "Let");
+        assert_eq!(json["items"][0]["span"]["line"], 1);
+
+        assert!(parser::tree::render(&program, AstFormat::Debug).contains("FunctionDecl {"));
+        assert_eq!("JSON".parse::<AstFormat>(), Ok(AstFormat::Json));
+        assert!("yaml".parse::<AstFormat>().is_err());
+    }
+
+    #[test]
+    fn test_output_formats_name_string_parts() {
+        let program = parse_ok("fn main() { log($\"Hello {name}!\") }");
+
+        let text = parser::tree::render(&program, AstFormat::Text);
+        assert!(text.contains("Text value=\"Hello \"\n"));
+        assert!(text.contains("Interpolation value=name\n"));
+        assert!(!text.contains("{}"));
+
+        let json: serde_json::Value = serde_json::from_str(&parser::tree::render(&program, AstFormat::Json)).unwrap();
+        let parts = &json["items"][0]["body"]["stmts"][0]["expr"]["args"][0]["parts"];
+        assert_eq!(parts[0]["type"], "Text");
+        assert_eq!(parts[1]["type"], "Interpolation");
+        assert_eq!(parts[1]["value"]["type"], "Ident");
+    }
+}
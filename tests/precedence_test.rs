@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::error_management::error_codes;
+    use ubel_stratum::lexer::{LogosLexer, TokenType};
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::*;
+    use ubel_stratum::parser::precedence::{self, Assoc, Precedence};
+
+    fn parse_expr(source: &str, parse: fn(&str) -> Result<Program, ubel_stratum::error_management::ErrorManager>) -> Expr {
+        let program = match parse(&format!("fn main() {{\n{}\n}}", source)) {
+            Ok(program) => program,
+            Err(manager) => panic!("{:?} failed: {:?}", source, manager.diagnostics()),
+        };
+        let Some(Item::Function(f)) = program.items.into_iter().next() else { panic!() };
+        match f.body.stmts.into_iter().next().map(|s| s.kind) {
+            Some(StmtKind::Expr { expr }) => expr,
+            other => panic!("expected an expression statement, got {:?}", other),
+        }
+    }
+
+    /// Fully parenthesised form of an expression
+    fn shape(e: &Expr) -> String {
+        match &e.kind {
+            ExprKind::Ident { name } => name.clone(),
+            ExprKind::Literal { value: Literal::Int(n) } => n.to_string(),
+            ExprKind::Binary { op, lhs, rhs } => format!("({} {} {})", shape(lhs), op.symbol(), shape(rhs)),
+            ExprKind::Assign { op, target, value } => format!("({} {} {})", shape(target), op.symbol(), shape(value)),
+            ExprKind::Unary { op, operand } => format!("({}{})", op.symbol(), shape(operand)),
+            ExprKind::Range { start, end, inclusive } => {
+                format!("({}{}{})", shape(start), if *inclusive { "..." } else { ".." }, shape(end))
+            }
+            ExprKind::Cast { expr, ty } => format!("({} as {:?})", shape(expr), ty.kind),
+            ExprKind::Field { target, name, .. } => format!("{}.{}", shape(target), name.name),
+            ExprKind::Try { expr } => format!("{}?", shape(expr)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// Both parsers agree, and the tree has the expected shape
+    fn assert_shape(source: &str, expected: &str) {
+        let rd = parse_expr(source, parser::parse);
+        let lalr = parse_expr(source, parser::parse_lalr);
+        assert_eq!(rd, lalr, "parsers disagree on {:?}", source);
+        assert_eq!(shape(&rd), expected, "{:?}", source);
+    }
+
+    fn int() -> String {
+        format!("{:?}", TypeKind::Primitive { name: PrimitiveType::Int })
+    }
+
+    #[test]
+    fn test_adjacent_levels() {
+        // Each pair of neighbouring levels, loosest first, in both orders
+        let cases = [
+            ("a = b |> c", "(a = (b |> c))"),
+            ("a |> b = c", "((a |> b) = c)"),
+            ("a |> b..c", "(a |> (b..c))"),
+            ("a..b |> c", "((a..b) |> c)"),
+            ("a..b or c", "(a..(b or c))"),
+            ("a or b...c", "((a or b)...c)"),
+            ("a or b and c", "(a or (b and c))"),
+            ("a and b or c", "((a and b) or c)"),
+            ("a and b == c", "(a and (b == c))"),
+            ("a == b and c", "((a == b) and c)"),
+            ("a == b | c", "(a == (b | c))"),
+            ("a | b == c", "((a | b) == c)"),
+            ("a | b ^ c", "(a | (b ^ c))"),
+            ("a ^ b | c", "((a ^ b) | c)"),
+            ("a ^ b & c", "(a ^ (b & c))"),
+            ("a & b ^ c", "((a & b) ^ c)"),
+            ("a & b << c", "(a & (b << c))"),
+            ("a >> b & c", "((a >> b) & c)"),
+            ("a << b + c", "(a << (b + c))"),
+            ("a - b >> c", "((a - b) >> c)"),
+            ("a + b * c", "(a + (b * c))"),
+            ("a % b - c", "((a % b) - c)"),
+            ("-a.b", "(-a.b)"),
+            ("~a?", "(~a?)"),
+        ];
+        for (source, expected) in cases {
+            assert_shape(source, expected);
+        }
+
+        // Cast sits between multiplicative and prefix
+        assert_shape("a * b as int", &format!("(a * (b as {}))", int()));
+        assert_shape("a as int / b", &format!("((a as {}) / b)", int()));
+        assert_shape("-a as int", &format!("((-a) as {})", int()));
+    }
+
+    #[test]
+    fn test_associativity() {
+        assert_shape("a - b - c", "((a - b) - c)");
+        assert_shape("a / b * c", "((a / b) * c)");
+        assert_shape("a << b >> c", "((a << b) >> c)");
+        assert_shape("a | b | c", "((a | b) | c)");
+        assert_shape("xs |> f |> g", "((xs |> f) |> g)");
+        assert_shape("- ~a", "(-(~a))");
+        assert_shape("not not a", "(!(!a))");
+        assert_shape("a as int as int", &format!("((a as {}) as {})", int(), int()));
+
+        // Non-associative levels stop after one operator
+        assert!(parser::parse("fn main() {\n    a = b = c\n}").is_err());
+        assert!(parser::parse("fn main() {\n    a..b..c\n}").is_err());
+    }
+
+    #[test]
+    fn test_table_matches_levels() {
+        for window in Precedence::ALL.windows(2) {
+            assert!(window[0] < window[1]);
+            assert_eq!(window[0].tighter(), window[1]);
+        }
+        assert_eq!(Precedence::Comparison.assoc(), Assoc::None);
+        assert_eq!(Precedence::Prefix.rhs(), Precedence::Prefix);
+        assert_eq!(Precedence::Additive.rhs(), Precedence::Multiplicative);
+
+        // Every infix lexeme lexes to the token the table names
+        for op in precedence::INFIX_OPERATORS {
+            let tokens = LogosLexer::new(&format!("a {} b", op.lexeme)).tokenize().unwrap();
+            assert_eq!(tokens[1].kind, op.token, "{}", op.lexeme);
+            assert_eq!(precedence::infix(&op.token).unwrap().lexeme, op.lexeme);
+        }
+    }
+
+    #[test]
+    fn test_cast_type_takes_no_type_arguments() {
+        assert_shape("x as int < y", &format!("((x as {}) < y)", int()));
+
+        let ExprKind::Binary { op: BinaryOp::Eq, lhs, .. } = parse_expr("x as Point == p", parser::parse_lalr).kind else { panic!() };
+        let ExprKind::Cast { ty, .. } = lhs.kind else { panic!() };
+        assert!(matches!(ty.kind, TypeKind::Named { name, args } if name.name == "Point" && args.is_empty()));
+
+        let ExprKind::Cast { ty, .. } = parse_expr("v as [int]!", parser::parse).kind else { panic!() };
+        let TypeKind::Fallible { inner } = ty.kind else { panic!() };
+        assert!(matches!(inner.kind, TypeKind::Array { size: None, .. }));
+    }
+
+    #[test]
+    fn test_chained_comparison_error() {
+        let source = "fn main() {\n    let ok = 0 < x <= 10\n}";
+        let (_, manager) = parser::parse_recovering(source);
+        let diagnostics = manager.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+
+        let d = &diagnostics[0];
+        assert_eq!(d.code, Some(error_codes::E0103));
+        assert_eq!(d.message, "comparison operators cannot be chained");
+        assert_eq!(&source[d.span.start..d.span.end], "0 < x <= 10");
+        assert_eq!(d.help.as_deref(), Some("Split the comparison: `0 < x and x <= 10`"));
+        assert_eq!(d.suggestions[0].replacement, "0 < x and x <= 10");
+        assert!(!d.suggestions[0].is_machine_applicable());
+
+        assert!(error_codes::lookup(error_codes::E0103).is_some());
+    }
+
+    #[test]
+    fn test_range_and_member_lexing() {
+        let kinds = |source: &str| -> Vec<TokenType> {
+            LogosLexer::new(source).tokenize().unwrap().into_iter().map(|t| t.kind).filter(|k| *k != TokenType::Eof).collect()
+        };
+        assert_eq!(kinds("0..10"), [TokenType::IntLit(0), TokenType::DotDot, TokenType::IntLit(10)]);
+        assert_eq!(kinds("1...n"), [TokenType::IntLit(1), TokenType::DotDotDot, TokenType::Ident("n".into())]);
+        assert_eq!(
+            kinds("42.is_even()"),
+            [TokenType::IntLit(42), TokenType::Dot, TokenType::Ident("is_even".into()), TokenType::LeftParen, TokenType::RightParen]
+        );
+        assert_eq!(kinds("xs |> f"), [TokenType::Ident("xs".into()), TokenType::PipeGreater, TokenType::Ident("f".into())]);
+        // Float literals with a suffix or exponent stay whole
+        assert!(matches!(kinds("1.5f")[..], [TokenType::FloatLit(_)]));
+
+        assert_shape("0..10", "(0..10)");
+        assert!(matches!(parse_expr("42.is_even()", parser::parse).kind, ExprKind::MethodCall { .. }));
+    }
+}
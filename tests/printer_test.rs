@@ -0,0 +1,500 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use proptest::prelude::*;
+    use serde_json::Value;
+    use ubel_stratum::lexer::Span;
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::*;
+    use ubel_stratum::parser::printer;
+
+    fn collect_examples(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_examples(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "strat") {
+                out.push(path);
+            }
+        }
+    }
+
+    /// The tree without spans, which the printer cannot keep
+    fn shape(program: &Program) -> Value {
+        fn strip(value: Value) -> Value {
+            match value {
+                Value::Object(map) => Value::Object(map.into_iter().filter(|(k, _)| k != "span").map(|(k, v)| (k, strip(v))).collect()),
+                Value::Array(items) => Value::Array(items.into_iter().map(strip).collect()),
+                other => other,
+            }
+        }
+        strip(serde_json::to_value(program).unwrap())
+    }
+
+    fn assert_round_trips(program: &Program) {
+        let source = printer::print(program);
+        let reparsed = match parser::parse(&source) {
+            Ok(reparsed) => reparsed,
+            Err(manager) => panic!("printed source does not parse:\n{}\n{:?}", source, manager.diagnostics()),
+        };
+        assert_eq!(shape(&reparsed), shape(program), "source:\n{}", source);
+        // Printing is a fixed point
+        assert_eq!(printer::print(&reparsed), source);
+    }
+
+    fn print_expr(source: &str) -> String {
+        let program = parser::parse(&format!("fn main() {{\n{}\n}}", source)).unwrap();
+        let Some(Item::Function(f)) = program.items.first() else { panic!() };
+        let Some(Stmt { kind: StmtKind::Expr { expr }, .. }) = f.body.stmts.first() else { panic!() };
+        printer::print_expr(expr)
+    }
+
+    #[test]
+    fn test_examples_round_trip() {
+        let mut files = Vec::new();
+        collect_examples(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")), &mut files);
+        assert!(!files.is_empty());
+        for file in files {
+            assert_round_trips(&parser::parse(&fs::read_to_string(file).unwrap()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_declarations_round_trip() {
+        let source = r#"package app.core
+
+summon std.io as io
+from std.collections summon List, Map
+
+@tier(high)
+pub async fn run<T: Display + Clone>(mut xs: List<T>, n: int = 3) [4]int! {
+    let mut total: float = 0.0
+    count := n
+    for (i, Point { x, y = _ }) in xs.enumerate() {
+        total += x as float
+    }
+    while let [first, ...rest] = xs {
+        xs = rest
+    }
+    let Shape.Circle(r) = first else {
+        return
+    }
+    with pool<Node>(64) {
+        try {
+            step()?
+        } catch (e) {
+            return
+        }
+    }
+}
+
+edge struct Point<T> {
+    pub x: T
+    y: T
+    pub id: int { get; set; }
+    len: float {
+        get {
+            return 1.0
+        }
+        set {
+            y = value
+        }
+    }
+    fn norm(self) float {
+        return self.len
+    }
+}
+
+enum Shape {
+    Circle(float),
+    Rect { w: int, h: int },
+    Empty
+}
+
+trait Draw {
+    type Output
+    async fn draw(self, scale: double) Task<Output>
+    fn view[lifetime L, lifetime M where M outlives L](self, buf: &mut M [u8]) &L str
+    fn fold<A, F: FnMut(A, Output) A>(self, init: A, f: F) A where A: Into<List<int>>, F: Clone
+}
+
+impl<T: Iterator<Item = List<T>>> Draw for Point<T> where T: Eq<int, Rhs = T> + Fn() {
+    fn draw(self) Task {
+        return match self {
+            Point { x = 0 } => -1,
+            Shape.Circle(r) where r > 1.5f => $"r = {r}\n",
+            _ => @"C:\dir ""q""",
+        }
+    }
+}
+
+extend<K> Dictionary<K, List<int>> {
+    fn second(self) int {
+        return self.len()
+    }
+}
+
+const LIMIT: []u8 = [1, 2, 0x10]
+"#;
+        assert_round_trips(&parser::parse(source).unwrap());
+    }
+
+    #[test]
+    fn test_minimal_parentheses() {
+        let cases = [
+            ("(a + b) * c", "(a + b) * c"),
+            ("a + (b * c)", "a + b * c"),
+            ("(a - b) - c", "a - b - c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a < b) == c", "(a < b) == c"),
+            ("-(a.b)", "-a.b"),
+            ("(-a).b", "(-a).b"),
+            ("(x as int) + 1", "x as int + 1"),
+            ("-(x as int)", "-(x as int)"),
+            ("a = (b = c)", "a = (b = c)"),
+            ("(0..10).len()", "(0..10).len()"),
+            ("f = x => x + 1", "f = x => x + 1"),
+            ("(x => x)(1)", "(x => x)(1)"),
+            ("(a.b)(c)", "(a.b)(c)"),
+            ("(a?).b", "(a?).b"),
+            ("(t.0).1", "(t.0).1"),
+            ("await not x", "await !x"),
+            ("o = ({ a = 1 })", "o = { a = 1 }"),
+            ("f = x => ({ a = 1 })", "f = x => ({ a = 1 })"),
+            ("f = x => ({ \"k\" = 1 }).k", "f = x => ({ \"k\" = 1 }).k"),
+            ("f = x => { a = 1 }", "f = x => {\n    a = 1\n}"),
+            ("b = { (a = 1) }", "b = {\n    (a = 1)\n}"),
+            ("b = { (a += 1) }", "b = {\n    a += 1\n}"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(print_expr(input), expected, "printing {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_restricted_contexts() {
+        let source = "fn main() {\n    if (P { x = 1 }) == p {\n        f()\n    }\n    match v {\n        x where (y => y)(x) => 1,\n        x where x * (a + b) => 2,\n    }\n    a;\n    -b\n}\n";
+        let program = parser::parse(source).unwrap();
+        assert_eq!(printer::print(&program), source);
+        assert_round_trips(&program);
+    }
+
+    // ========================================
+    // Generated trees
+    // ========================================
+
+    fn sp() -> Span {
+        Span::new(0, 0, 1, 1)
+    }
+
+    fn ident(name: &str) -> Ident {
+        Ident { name: name.to_string(), span: sp() }
+    }
+
+    fn expr(kind: ExprKind) -> Expr {
+        Expr::new(kind, sp())
+    }
+
+    fn boxed(e: Expr) -> Box<Expr> {
+        Box::new(e)
+    }
+
+    fn name() -> impl Strategy<Value = String> {
+        prop::sample::select(vec!["a", "b", "xs", "value_1", "Point"]).prop_map(str::to_string)
+    }
+
+    fn literal() -> impl Strategy<Value = Literal> {
+        prop_oneof![
+            (0i64..100_000).prop_map(Literal::Int),
+            prop::sample::select(vec![0.5f32, 2.0, 1e20]).prop_map(Literal::Float),
+            prop::sample::select(vec![0.25f64, 3.0, 1e-7, 6.02e23]).prop_map(Literal::Double),
+            prop::sample::select(vec!["", "plain", "q\"uote\\", "tab\tline\n"]).prop_map(|s| Literal::String(s.to_string())),
+            prop::sample::select(vec!["C:\\dir", "say \"hi\""]).prop_map(|s| Literal::Verbatim(s.to_string())),
+            prop::sample::select(vec!['a', '\'', '\\', '\n', '"']).prop_map(Literal::Char),
+            any::<bool>().prop_map(Literal::Bool),
+            Just(Literal::Null),
+        ]
+    }
+
+    fn cast_type() -> impl Strategy<Value = TypeExpr> {
+        let leaf = prop_oneof![
+            Just(TypeKind::Primitive { name: PrimitiveType::Int }),
+            Just(TypeKind::Primitive { name: PrimitiveType::F64 }),
+            Just(TypeKind::Named { name: ident("Point"), args: Vec::new() }),
+            Just(TypeKind::Task { inner: None }),
+        ]
+        .prop_map(|kind| TypeExpr { kind, span: sp() });
+        leaf.prop_recursive(2, 4, 2, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..3).prop_map(|elems| TypeKind::Tuple { elems }),
+                inner.clone().prop_map(|elem| TypeKind::Array { elem: Box::new(elem), size: None }),
+                inner.prop_map(|inner| TypeKind::Fallible { inner: Box::new(inner) }),
+            ]
+            .prop_map(|kind| TypeExpr { kind, span: sp() })
+        })
+    }
+
+    fn pattern() -> impl Strategy<Value = Pattern> {
+        let leaf = prop_oneof![
+            Just(PatternKind::Wildcard),
+            name().prop_map(|n| PatternKind::Binding { name: ident(&n) }),
+            literal().prop_map(|value| PatternKind::Literal { value }),
+            Just(PatternKind::Literal { value: Literal::Int(-3) }),
+            Just(PatternKind::Path { path: vec![ident("Shape"), ident("Empty")] }),
+        ]
+        .prop_map(|kind| Pattern { kind, span: sp() });
+        leaf.prop_recursive(3, 12, 3, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..3).prop_map(|elems| PatternKind::Tuple { elems }),
+                prop::collection::vec(inner.clone(), 0..3)
+                    .prop_map(|elems| PatternKind::TupleStruct { path: vec![ident("Shape"), ident("Circle")], elems }),
+                field_patterns(inner.clone()).prop_map(|fields| PatternKind::Struct { path: vec![ident("Point")], fields }),
+                field_patterns(inner.clone()).prop_map(|fields| PatternKind::Object { fields }),
+                (prop::collection::vec(inner, 0..3), prop::option::of((0..3usize, prop::option::of(name())))).prop_map(
+                    |(mut elems, rest)| {
+                        if let Some((at, name)) = rest {
+                            let rest = Pattern { kind: PatternKind::Rest { name: name.map(|n| ident(&n)) }, span: sp() };
+                            elems.insert(at.min(elems.len()), rest);
+                        }
+                        PatternKind::List { elems }
+                    }
+                ),
+            ]
+            .prop_map(|kind| Pattern { kind, span: sp() })
+        })
+    }
+
+    fn field_patterns(inner: impl Strategy<Value = Pattern>) -> impl Strategy<Value = Vec<FieldPattern>> {
+        prop::collection::vec((name(), prop::option::of(inner)), 0..3).prop_map(|fields| {
+            fields.into_iter().map(|(n, pattern)| FieldPattern { name: ident(&n), pattern, span: sp() }).collect()
+        })
+    }
+
+    /// Patterns `extract` accepts
+    fn irrefutable_pattern() -> impl Strategy<Value = Pattern> {
+        pattern().prop_filter("refutable", |p| p.refutable_part().is_none())
+    }
+
+    /// Patterns `let ... else` accepts
+    fn refutable_pattern() -> impl Strategy<Value = Pattern> {
+        pattern().prop_filter("irrefutable", |p| p.refutable_part().is_some())
+    }
+
+    fn block(stmts: impl Strategy<Value = Vec<Stmt>>) -> impl Strategy<Value = Block> {
+        stmts.prop_map(|stmts| Block { stmts, span: sp() })
+    }
+
+    /// A block of expression statements
+    fn expr_block(exprs: Vec<Expr>) -> Block {
+        let stmts = exprs.into_iter().map(|e| Stmt { kind: StmtKind::Expr { expr: e }, span: sp() }).collect();
+        Block { stmts, span: sp() }
+    }
+
+    fn expression() -> impl Strategy<Value = Expr> {
+        let leaf = prop_oneof![
+            4 => literal().prop_map(|value| expr(ExprKind::Literal { value })),
+            4 => name().prop_map(|name| expr(ExprKind::Ident { name })),
+            1 => Just(expr(ExprKind::SelfRef)),
+        ];
+
+        leaf.prop_recursive(4, 48, 4, |inner| {
+            let stmt = prop_oneof![
+                inner.clone().prop_map(|e| StmtKind::Expr { expr: e }),
+                (name(), inner.clone()).prop_map(|(n, value)| StmtKind::Let { mutable: false, name: ident(&n), ty: None, value }),
+                prop::option::of(inner.clone()).prop_map(|value| StmtKind::Return { value }),
+                (irrefutable_pattern(), inner.clone()).prop_map(|(pattern, value)| StmtKind::Extract { pattern, value }),
+                (refutable_pattern(), inner.clone(), prop::collection::vec(inner.clone(), 0..2)).prop_map(
+                    |(pattern, value, otherwise)| StmtKind::LetElse { pattern, value, else_block: expr_block(otherwise) }
+                ),
+                (prop::collection::vec((name(), inner.clone()), 1..3), prop::collection::vec(inner.clone(), 0..2)).prop_map(
+                    |(resources, body)| StmtKind::Using {
+                        resources: resources
+                            .into_iter()
+                            .map(|(n, value)| Resource { name: ident(&n), value, span: sp() })
+                            .collect(),
+                        body: expr_block(body),
+                    }
+                ),
+                prop::collection::vec(inner.clone(), 0..2).prop_map(|body| StmtKind::Defer { body: expr_block(body) }),
+            ]
+            .prop_map(|kind| Stmt { kind, span: sp() });
+            let stmts = prop::collection::vec(stmt, 0..3);
+            let args = prop::collection::vec(inner.clone(), 0..3);
+
+            prop_oneof![
+                (prop::sample::select(vec![UnaryOp::Not, UnaryOp::Neg, UnaryOp::Plus, UnaryOp::BitNot, UnaryOp::Await]), inner.clone())
+                    .prop_map(|(op, e)| expr(ExprKind::Unary { op, operand: boxed(e) })),
+                (
+                    prop::sample::select(vec![
+                        BinaryOp::Or, BinaryOp::And, BinaryOp::Eq, BinaryOp::Lt, BinaryOp::Ge, BinaryOp::BitOr,
+                        BinaryOp::BitXor, BinaryOp::BitAnd, BinaryOp::Shl, BinaryOp::Shr, BinaryOp::Add,
+                        BinaryOp::Sub, BinaryOp::Mul, BinaryOp::Rem, BinaryOp::Pipe,
+                    ]),
+                    inner.clone(),
+                    inner.clone(),
+                )
+                    .prop_map(|(op, l, r)| expr(ExprKind::Binary { op, lhs: boxed(l), rhs: boxed(r) })),
+                (prop::sample::select(vec![AssignOp::Assign, AssignOp::Add, AssignOp::Shr]), inner.clone(), inner.clone())
+                    .prop_map(|(op, t, v)| expr(ExprKind::Assign { op, target: boxed(t), value: boxed(v) })),
+                (inner.clone(), inner.clone(), any::<bool>())
+                    .prop_map(|(s, e, inclusive)| expr(ExprKind::Range { start: boxed(s), end: boxed(e), inclusive })),
+                (inner.clone(), cast_type()).prop_map(|(e, ty)| expr(ExprKind::Cast { expr: boxed(e), ty })),
+                (inner.clone(), args.clone()).prop_map(|(c, args)| expr(ExprKind::Call { callee: boxed(c), args })),
+                (inner.clone(), inner.clone()).prop_map(|(t, i)| expr(ExprKind::Index { target: boxed(t), index: boxed(i) })),
+                (inner.clone(), prop_oneof![name(), Just("0".to_string()), Just("get".to_string())], any::<bool>())
+                    .prop_map(|(t, n, safe)| expr(ExprKind::Field { target: boxed(t), name: ident(&n), safe })),
+                (inner.clone(), name(), args.clone(), any::<bool>()).prop_map(|(r, m, args, safe)| {
+                    expr(ExprKind::MethodCall { receiver: boxed(r), method: ident(&m), args, safe })
+                }),
+                inner.clone().prop_map(|e| expr(ExprKind::Try { expr: boxed(e) })),
+                inner.clone().prop_map(|e| expr(ExprKind::InterpolatedString {
+                    parts: vec![StringPart::Text("n = {".to_string()), StringPart::Expr(e)],
+                })),
+                (
+                    prop::collection::vec((name(), prop::option::of(irrefutable_pattern()), prop::option::of(cast_type())), 0..3),
+                    inner.clone(),
+                )
+                    .prop_map(|(params, body)| {
+                        let params = params
+                            .into_iter()
+                            .map(|(n, pattern, ty)| match pattern {
+                                Some(pattern) => Param::destructured(false, pattern, ty, None, sp()),
+                                None => Param { is_mut: false, name: ident(&n), pattern: None, ty, default: None, span: sp() },
+                            })
+                            .collect();
+                        expr(ExprKind::Lambda { params, body: boxed(body) })
+                    }),
+                (
+                    prop::collection::vec((prop::option::of(pattern()), inner.clone(), block(stmts.clone())), 1..3),
+                    prop::option::of(block(stmts.clone())),
+                )
+                    .prop_map(|(branches, else_block)| {
+                        let branches = branches
+                            .into_iter()
+                            .map(|(pattern, condition, body)| IfBranch { pattern, condition, body, span: sp() })
+                            .collect();
+                        expr(ExprKind::If { branches, else_block })
+                    }),
+                (
+                    inner.clone(),
+                    prop::collection::vec(
+                        (
+                            pattern(),
+                            prop::option::of(inner.clone()).prop_filter("a tuple guard reads as lambda parameters", |g| {
+                                !matches!(g, Some(Expr { kind: ExprKind::Tuple { .. }, .. }))
+                            }),
+                            inner.clone(),
+                        ),
+                        0..3,
+                    ),
+                )
+                    .prop_map(|(scrutinee, arms)| {
+                        let arms = arms
+                            .into_iter()
+                            .map(|(pattern, guard, body)| MatchArm { pattern, guard, body, span: sp() })
+                            .collect();
+                        expr(ExprKind::Match { scrutinee: boxed(scrutinee), arms })
+                    }),
+                block(stmts).prop_map(|block| expr(ExprKind::Block { block })),
+                args.clone().prop_map(|elems| expr(ExprKind::Array { elems })),
+                args.prop_map(|elems| expr(ExprKind::Tuple { elems })),
+                prop::collection::vec((name(), inner.clone()), 1..4).prop_map(|fields| {
+                    let mut seen = Vec::new();
+                    let fields = fields
+                        .into_iter()
+                        .filter(|(f, _)| !seen.contains(f) && { seen.push(f.clone()); true })
+                        .map(|(f, value)| FieldInit { name: ident(&f), value, span: sp() })
+                        .collect();
+                    expr(ExprKind::ObjectLit { fields })
+                }),
+                prop::collection::vec((prop::sample::select(vec!["", "a", "two words", "q\"uote"]), inner.clone()), 1..4).prop_map(|entries| {
+                    let mut seen = Vec::new();
+                    let entries = entries
+                        .into_iter()
+                        .filter(|(k, _)| !seen.contains(k) && { seen.push(*k); true })
+                        .map(|(k, value)| {
+                            let key = expr(ExprKind::Literal { value: Literal::String(k.to_string()) });
+                            DictEntry { key, value, span: sp() }
+                        })
+                        .collect();
+                    expr(ExprKind::DictLit { entries })
+                }),
+                (
+                    (name(), inner.clone()),
+                    prop::collection::vec(query_clause(inner.clone()), 0..3),
+                    prop_oneof![
+                        inner.clone().prop_map(|value| QueryClauseKind::Select { value }),
+                        (inner.clone(), inner.clone()).prop_map(|(value, key)| QueryClauseKind::GroupBy { value, key }),
+                    ],
+                )
+                    .prop_filter("range variables are unique", |((first, _), middle, _)| {
+                        let mut seen = vec![first.clone()];
+                        middle.iter().all(|clause| match clause {
+                            QueryClauseKind::From { name, .. } | QueryClauseKind::Let { name, .. } | QueryClauseKind::Join { name, .. } => {
+                                !seen.contains(&name.name) && { seen.push(name.name.clone()); true }
+                            }
+                            _ => true,
+                        })
+                    })
+                    .prop_map(|((n, source), middle, last)| {
+                        let clauses = std::iter::once(QueryClauseKind::From { name: ident(&n), source })
+                            .chain(middle)
+                            .chain(std::iter::once(last))
+                            .map(|kind| QueryClause { kind, span: sp() })
+                            .collect();
+                        expr(ExprKind::Query { clauses })
+                    }),
+                (name(), prop::collection::vec((name(), inner), 0..3)).prop_map(|(n, fields)| {
+                    let fields = fields
+                        .into_iter()
+                        .map(|(f, value)| FieldInit { name: ident(&f), value, span: sp() })
+                        .collect();
+                    expr(ExprKind::StructLit { name: ident(&n), fields })
+                }),
+            ]
+        })
+    }
+
+    fn query_clause(inner: impl Strategy<Value = Expr> + Clone) -> impl Strategy<Value = QueryClauseKind> {
+        prop_oneof![
+            (name(), inner.clone()).prop_map(|(n, source)| QueryClauseKind::From { name: ident(&n), source }),
+            (name(), inner.clone()).prop_map(|(n, value)| QueryClauseKind::Let { name: ident(&n), value }),
+            inner.clone().prop_map(|condition| QueryClauseKind::Where { condition }),
+            prop::collection::vec((inner.clone(), any::<bool>()), 1..3).prop_map(|keys| QueryClauseKind::OrderBy {
+                keys: keys.into_iter().map(|(key, descending)| OrderKey { key, descending, span: sp() }).collect(),
+            }),
+            (name(), inner.clone(), inner.clone(), inner).prop_map(|(n, source, outer_key, inner_key)| {
+                QueryClauseKind::Join { name: ident(&n), source, outer_key: Box::new(outer_key), inner_key: Box::new(inner_key) }
+            }),
+        ]
+    }
+
+    /// `fn main() { ... }` around generated statements
+    fn program(exprs: Vec<Expr>) -> Program {
+        let main = FunctionDecl {
+            attributes: Vec::new(),
+            is_pub: false,
+            is_async: false,
+            name: ident("main"),
+            lifetimes: None,
+            generics: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            where_clause: None,
+            body: expr_block(exprs),
+            span: sp(),
+        };
+        Program { package: None, imports: Vec::new(), items: vec![Item::Function(main)], span: sp() }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(512))]
+
+        #[test]
+        fn test_generated_trees_round_trip(exprs in prop::collection::vec(expression(), 1..4)) {
+            assert_round_trips(&program(exprs));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use ubel_stratum::lexer::tokenize_with_diagnostics;
+    use ubel_stratum::error_management::{ErrorManager, MessageFormat};
+    use ubel_stratum::error_management::renderer::{renderer_for, FancyRenderer, PlainRenderer};
+
+    const SOURCE: &str = "let x = 1.\nlet y = \"abc\n";
+
+    fn diagnostics() -> ErrorManager {
+        match tokenize_with_diagnostics(SOURCE) {
+            Ok((_, manager)) => manager,
+            Err(manager) => manager,
+        }
+    }
+
+    fn render(format: MessageFormat) -> String {
+        diagnostics().render(renderer_for(format, false).as_ref(), "demo.strat")
+    }
+
+    #[test]
+    fn test_message_format_from_str() {
+        assert_eq!("json".parse::<MessageFormat>(), Ok(MessageFormat::Json));
+        assert_eq!("SARIF".parse::<MessageFormat>(), Ok(MessageFormat::Sarif));
+        assert_eq!("plain".parse::<MessageFormat>(), Ok(MessageFormat::Plain));
+        assert_eq!("fancy".parse::<MessageFormat>(), Ok(MessageFormat::Fancy));
+        assert!("xml".parse::<MessageFormat>().is_err());
+    }
+
+    #[test]
+    fn test_plain_renderer_has_no_ansi() {
+        let output = render(MessageFormat::Plain);
+
+        assert!(!output.contains('\x1b'));
+        assert!(output.contains("warning[W0003]: Float literal '1.'"));
+        assert!(output.contains("error[E0002]: Unterminated string literal"));
+        assert!(output.contains("--> demo.strat:2:9"));
+    }
+
+    #[test]
+    fn test_fancy_renderer_respects_no_color() {
+        let manager = diagnostics();
+        let plain = manager.render(&FancyRenderer { colored: false }, "demo.strat");
+        let colored = manager.render(&FancyRenderer { colored: true }, "demo.strat");
+
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("E0002"));
+        assert!(plain.contains("Unterminated string literal"));
+        assert!(colored.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_json_lines_output() {
+        let output = render(MessageFormat::Json);
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("each line is a JSON object"))
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["severity"], "warning");
+        assert_eq!(lines[0]["code"], "W0003");
+        assert_eq!(lines[1]["severity"], "error");
+        assert_eq!(lines[1]["file"], "demo.strat");
+        assert_eq!(lines[1]["span"]["line"], 2);
+        assert_eq!(lines[1]["span"]["column"], 9);
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        let output = render(MessageFormat::Sarif);
+        let log: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "stratc");
+
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert!(rules.iter().any(|r| r["id"] == "E0002"));
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["ruleId"], "E0002");
+        assert_eq!(results[1]["level"], "error");
+        let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 2);
+        assert_eq!(region["startColumn"], 9);
+    }
+
+    #[test]
+    fn test_sarif_offsets_count_code_points() {
+        let source = "let s = \"héllo\"; let t = 1.\n";
+        let manager = match tokenize_with_diagnostics(source) {
+            Ok((_, manager)) => manager,
+            Err(manager) => manager,
+        };
+        let output = manager.render(renderer_for(MessageFormat::Sarif, false).as_ref(), "demo.strat");
+        let log: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(log["runs"][0]["columnKind"], "unicodeCodePoints");
+
+        let offset = source.find("1.").unwrap() - 1;
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["charOffset"], offset);
+        assert_eq!(region["charLength"], 2);
+        assert_eq!(region["startColumn"], offset + 1);
+
+        let deleted = &result["fixes"][0]["artifactChanges"][0]["replacements"][0]["deletedRegion"];
+        assert_eq!(deleted["charOffset"], offset + 2);
+        assert_eq!(deleted["charLength"], 0);
+    }
+
+    #[test]
+    fn test_sarif_without_diagnostics_is_still_a_log() {
+        let manager = ErrorManager::new(String::new());
+        let output = manager.render(renderer_for(MessageFormat::Sarif, false).as_ref(), "empty.strat");
+        let log: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert!(log["runs"][0]["results"].as_array().unwrap().is_empty());
+        assert!(manager.render(&PlainRenderer, "empty.strat").is_empty());
+    }
+
+    #[test]
+    fn test_lexical_error_is_miette_diagnostic() {
+        use miette::Diagnostic;
+
+        let mut manager = diagnostics();
+        let errors = manager.take_errors();
+        let error = &errors[0];
+
+        assert_eq!(Diagnostic::code(error).map(|c| c.to_string()), Some("E0002".to_string()));
+        assert!(error.help().is_some());
+        assert_eq!(error.labels().unwrap().count(), 1);
+    }
+}
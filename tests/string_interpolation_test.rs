@@ -131,6 +131,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_literals_inside_holes() {
+        // Quotes and braces inside a hole's own literals don't end the hole
+        let input = r#"$"a {f("}", '"')} b {$"\{{x}"} c""#;
+        let tokens = tokenize(input).unwrap();
+
+        match &tokens[0].kind {
+            TokenType::InterpolatedString(parts) => {
+                assert_eq!(parts.len(), 5);
+                assert!(matches!(parts[1], InterpolationPart::Expr(ref s) if s == r#"f("}", '"')"#));
+                assert!(matches!(parts[3], InterpolationPart::Expr(ref s) if s == r#"$"\{{x}""#));
+                assert!(matches!(parts[4], InterpolationPart::Text(ref s) if s == " c"));
+            }
+            _ => panic!("Expected interpolated string"),
+        }
+    }
+
     #[test]
     fn test_unterminated_interpolated_string() {
         let input = r#"$"Hello, {name}"#; // Missing closing "
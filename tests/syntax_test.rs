@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use rowan::Language;
+    use ubel_stratum::parser::ast::BinaryOp;
+    use ubel_stratum::syntax::{self, SyntaxKind, SyntaxNode, UbelLanguage};
+    use ubel_stratum::syntax::ast::{self as cst, AstNode, HasDocComments, HasName};
+
+    fn collect_examples(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_examples(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "strat") {
+                out.push(path);
+            }
+        }
+    }
+
+    fn first(node: &SyntaxNode, kind: SyntaxKind) -> SyntaxNode {
+        node.descendants().find(|n| n.kind() == kind).unwrap_or_else(|| panic!("no {:?}", kind))
+    }
+
+    const SOURCE: &str = "package demo\n\n// Adds two numbers\n/** Documented */\npub fn add(a: int, b: int) int {\n    let total = a + b // trailing\n    return total\n}\n";
+
+    #[test]
+    fn test_round_trips_exactly() {
+        let mut files = Vec::new();
+        collect_examples(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")), &mut files);
+        assert!(!files.is_empty());
+
+        let mut sources: Vec<String> = files.iter().map(|f| fs::read_to_string(f).unwrap()).collect();
+        sources.push(SOURCE.to_string());
+        sources.push("fn main() {\n    let x = 1 let y = `\n    /* unterminated".to_string());
+        sources.push("struct P { ys: List<List<int>> }\r\n\t/* a /* nested */ comment */".to_string());
+
+        for source in sources {
+            let parse = syntax::parse(&source);
+            assert_eq!(parse.syntax().to_string(), source);
+            assert_eq!(usize::from(parse.syntax().text_range().end()), source.len());
+        }
+    }
+
+    #[test]
+    fn test_kinds_round_trip_through_rowan() {
+        for raw in 0..=SyntaxKind::FIELD_PAT as u16 {
+            let kind = UbelLanguage::kind_from_raw(rowan::SyntaxKind(raw));
+            assert_eq!(kind as u16, raw);
+        }
+        assert!(SyntaxKind::COMMENT.is_trivia() && SyntaxKind::COMMENT.is_token());
+        assert!(SyntaxKind::BINARY_EXPR.is_expr() && !SyntaxKind::MATCH_ARM.is_expr());
+        assert!(SyntaxKind::FALLIBLE_TYPE.is_type() && SyntaxKind::LET_STMT.is_stmt());
+    }
+
+    #[test]
+    fn test_parent_pointers_and_ranges() {
+        let root = syntax::parse(SOURCE).syntax();
+        let binary = first(&root, SyntaxKind::BINARY_EXPR);
+        assert_eq!(binary.text().to_string(), "a + b");
+
+        let ancestors: Vec<SyntaxKind> = binary.ancestors().skip(1).map(|n| n.kind()).collect();
+        assert_eq!(ancestors, [SyntaxKind::LET_STMT, SyntaxKind::BLOCK, SyntaxKind::FN, SyntaxKind::SOURCE_FILE]);
+
+        let range = binary.text_range();
+        assert_eq!(&SOURCE[usize::from(range.start())..usize::from(range.end())], "a + b");
+    }
+
+    #[test]
+    fn test_comment_placement() {
+        let parse = syntax::parse(SOURCE);
+        let cst::Item::Fn(f) = parse.tree().items().next().unwrap() else { panic!() };
+
+        // Comments above a declaration belong to it
+        let docs: Vec<String> = f.doc_comments().iter().map(|t| t.text().to_string()).collect();
+        assert_eq!(docs, ["// Adds two numbers", "/** Documented */"]);
+        assert!(f.syntax().text().to_string().starts_with("// Adds"));
+
+        // A trailing comment stays in the enclosing block
+        let comment = f.syntax().descendants_with_tokens().find(|e| e.as_token().is_some_and(|t| t.text() == "// trailing")).unwrap();
+        assert_eq!(comment.parent().unwrap().kind(), SyntaxKind::BLOCK);
+
+        // A blank line detaches a comment
+        let parse = syntax::parse("// file header\n\nfn main() {}\n");
+        let cst::Item::Fn(f) = parse.tree().items().next().unwrap() else { panic!() };
+        assert!(f.doc_comments().is_empty());
+    }
+
+    #[test]
+    fn test_typed_views() {
+        let parse = syntax::parse(SOURCE);
+        let cst::Item::Fn(f) = parse.tree().items().next().unwrap() else { panic!() };
+
+        assert_eq!(f.name().unwrap().text(), "add");
+        assert!(f.is_pub() && !f.is_async());
+        let params: Vec<String> = f.params().map(|p| p.name().unwrap().text()).collect();
+        assert_eq!(params, ["a", "b"]);
+        assert_eq!(f.return_type().unwrap().syntax().text().to_string(), "int");
+
+        let body = f.body().unwrap();
+        let stmts: Vec<SyntaxKind> = body.stmts().map(|s| s.kind()).collect();
+        assert_eq!(stmts, [SyntaxKind::LET_STMT, SyntaxKind::RETURN_STMT]);
+
+        let let_stmt = cst::LetStmt::cast(body.stmts().next().unwrap().syntax().clone()).unwrap();
+        assert_eq!(let_stmt.name().unwrap().text(), "total");
+        let binary = cst::BinaryExpr::cast(let_stmt.value().unwrap().syntax().clone()).unwrap();
+        assert_eq!(binary.op(), Some(BinaryOp::Add));
+        assert_eq!(binary.lhs().unwrap().syntax().text().to_string(), "a");
+        assert_eq!(binary.rhs().unwrap().syntax().text().to_string(), "b");
+
+        let parse = syntax::parse("fn main() {\n    if a { f(1, x and y) } else { }\n}\n");
+        let call = cst::CallExpr::cast(first(&parse.syntax(), SyntaxKind::CALL_EXPR)).unwrap();
+        assert_eq!(call.callee().unwrap().syntax().text().to_string(), "f");
+        assert_eq!(call.args().count(), 2);
+        let and = cst::BinaryExpr::cast(first(&parse.syntax(), SyntaxKind::BINARY_EXPR)).unwrap();
+        assert_eq!(and.op(), Some(BinaryOp::And));
+        let if_expr = cst::IfExpr::cast(first(&parse.syntax(), SyntaxKind::IF_EXPR)).unwrap();
+        assert_eq!(if_expr.conditions().count(), 1);
+        assert!(if_expr.else_block().is_some());
+    }
+
+    #[test]
+    fn test_edit_subtree_keeps_comments() {
+        let root = syntax::parse(SOURCE).syntax();
+        let old = first(&root, SyntaxKind::BINARY_EXPR);
+        let new = first(&syntax::parse("const X = a * (b - 1)").syntax(), SyntaxKind::BINARY_EXPR);
+
+        // Persistent: the old tree is unchanged
+        let edited = syntax::replace(&old, &new);
+        assert_eq!(edited.to_string(), SOURCE.replace("a + b", "a * (b - 1)"));
+        assert_eq!(root.to_string(), SOURCE);
+
+        // In place, through rowan's mutable trees
+        let root = root.clone_for_update();
+        let name = first(&root, SyntaxKind::NAME);
+        let replacement = first(&syntax::parse("fn sum() {}").syntax(), SyntaxKind::NAME).clone_for_update();
+        let index = name.index();
+        name.parent().unwrap().splice_children(index..index + 1, vec![replacement.into()]);
+        assert_eq!(root.to_string(), SOURCE.replace("fn add", "fn sum"));
+    }
+
+    #[test]
+    fn test_errors_keep_a_lossless_tree() {
+        let source = "fn main() {\n    let = 1\n    ok()\n}\nfn 3\n";
+        let parse = syntax::parse(source);
+        assert!(parse.errors().has_errors());
+        assert_eq!(parse.syntax().to_string(), source);
+        assert!(parse.syntax().descendants().any(|n| n.kind() == SyntaxKind::ERROR));
+        // Recovery still produces the statement after the error
+        assert!(parse.syntax().descendants().any(|n| n.kind() == SyntaxKind::CALL_EXPR));
+        assert!(syntax::debug_tree(&parse.syntax()).starts_with("SOURCE_FILE@0.."));
+    }
+}
@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use ubel_stratum::parser;
+    use ubel_stratum::parser::ast::*;
+    use ubel_stratum::parser::fold::{self, Fold};
+    use ubel_stratum::parser::visit::{self, Visitor};
+    use ubel_stratum::parser::visit_mut::{self, MutVisitor};
+
+    fn collect_examples(dir: &Path, out: &mut Vec<PathBuf>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_examples(&path, out);
+            } else if path.extension().is_some_and(|ext| ext == "strat") {
+                out.push(path);
+            }
+        }
+    }
+
+    fn example_programs() -> Vec<Program> {
+        let mut files = Vec::new();
+        collect_examples(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/examples")), &mut files);
+        let programs: Vec<Program> = files
+            .iter()
+            .filter_map(|f| parser::parse(&fs::read_to_string(f).unwrap()).ok())
+            .collect();
+        assert!(!programs.is_empty());
+        programs
+    }
+
+    /// Counts every node that carries a span
+    #[derive(Default)]
+    struct Counter(usize);
+
+    macro_rules! count {
+        ($($method:ident($ty:ty) => $walk:ident),* $(,)?) => {
+            $(fn $method(&mut self, node: &'ast $ty) {
+                self.0 += 1;
+                visit::$walk(self, node);
+            })*
+        };
+    }
+
+    impl<'ast> Visitor<'ast> for Counter {
+        count! {
+            visit_program(Program) => walk_program,
+            visit_package_decl(PackageDecl) => walk_package_decl,
+            visit_import(Import) => walk_import,
+            visit_qualified_ident(QualifiedIdent) => walk_qualified_ident,
+            visit_attribute(Attribute) => walk_attribute,
+            visit_function(FunctionDecl) => walk_function,
+            visit_param(Param) => walk_param,
+            visit_generic_param(GenericParam) => walk_generic_param,
+            visit_struct_decl(StructDecl) => walk_struct_decl,
+            visit_field(FieldDecl) => walk_field,
+            visit_property(PropertyDecl) => walk_property,
+            visit_accessor(Accessor) => walk_accessor,
+            visit_enum_decl(EnumDecl) => walk_enum_decl,
+            visit_variant(EnumVariant) => walk_variant,
+            visit_trait_decl(TraitDecl) => walk_trait_decl,
+            visit_method_signature(MethodSignature) => walk_method_signature,
+            visit_associated_type(AssociatedType) => walk_associated_type,
+            visit_impl_block(ImplBlock) => walk_impl_block,
+            visit_const_decl(ConstDecl) => walk_const_decl,
+            visit_type_expr(TypeExpr) => walk_type_expr,
+            visit_block(Block) => walk_block,
+            visit_stmt(Stmt) => walk_stmt,
+            visit_allocator(Allocator) => walk_allocator,
+            visit_catch_clause(CatchClause) => walk_catch_clause,
+            visit_expr(Expr) => walk_expr,
+            visit_if_branch(IfBranch) => walk_if_branch,
+            visit_match_arm(MatchArm) => walk_match_arm,
+            visit_field_init(FieldInit) => walk_field_init,
+            visit_pattern(Pattern) => walk_pattern,
+            visit_field_pattern(FieldPattern) => walk_field_pattern,
+        }
+
+        fn visit_ident(&mut self, _ident: &'ast Ident) {
+            self.0 += 1;
+        }
+
+        fn visit_error_node(&mut self, _node: &'ast ErrorNode) {
+            self.0 += 1;
+        }
+    }
+
+    fn spanned_objects(value: &serde_json::Value) -> usize {
+        match value {
+            serde_json::Value::Object(map) => {
+                usize::from(map.contains_key("span")) + map.values().map(spanned_objects).sum::<usize>()
+            }
+            serde_json::Value::Array(items) => items.iter().map(spanned_objects).sum(),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn test_visitor_reaches_every_node() {
+        for program in example_programs() {
+            let mut counter = Counter::default();
+            counter.visit_program(&program);
+            assert_eq!(counter.0, spanned_objects(&serde_json::to_value(&program).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_visitor_can_borrow_from_the_tree() {
+        struct Calls<'ast>(Vec<&'ast str>);
+        impl<'ast> Visitor<'ast> for Calls<'ast> {
+            fn visit_expr(&mut self, expr: &'ast Expr) {
+                if let ExprKind::Call { callee, .. } = &expr.kind {
+                    if let ExprKind::Ident { name } = &callee.kind {
+                        self.0.push(name);
+                    }
+                }
+                visit::walk_expr(self, expr);
+            }
+        }
+
+        let program = parser::parse("fn main() {\n    f(g(1), h())\n}\n").unwrap();
+        let mut calls = Calls(Vec::new());
+        calls.visit_program(&program);
+        assert_eq!(calls.0, ["f", "g", "h"]);
+    }
+
+    #[test]
+    fn test_mut_visitor_renames_in_place() {
+        struct Rename;
+        impl MutVisitor for Rename {
+            fn visit_ident(&mut self, ident: &mut Ident) {
+                if ident.name == "old" {
+                    ident.name = "new".to_string();
+                }
+            }
+
+            fn visit_expr(&mut self, expr: &mut Expr) {
+                if let ExprKind::Ident { name } = &mut expr.kind {
+                    if name == "old" {
+                        *name = "new".to_string();
+                    }
+                }
+                visit_mut::walk_expr(self, expr);
+            }
+        }
+
+        let mut program = parser::parse("fn old(old: int) int {\n    return old + 1\n}\n").unwrap();
+        Rename.visit_program(&mut program);
+        let renamed = parser::parse("fn new(new: int) int {\n    return new + 1\n}\n").unwrap();
+        // Same shape; only the names changed
+        assert_eq!(serde_json::to_string(&program).unwrap(), serde_json::to_string(&renamed).unwrap());
+    }
+
+    #[test]
+    fn test_identity_fold_preserves_examples() {
+        struct Identity;
+        impl Fold for Identity {}
+
+        for program in example_programs() {
+            assert_eq!(Identity.fold_program(program.clone()), program);
+        }
+    }
+
+    #[test]
+    fn test_fold_constant_arithmetic() {
+        struct ConstFold;
+        impl Fold for ConstFold {
+            fn fold_expr(&mut self, expr: Expr) -> Expr {
+                // Children first, so nested sums collapse bottom-up
+                let expr = fold::walk_expr(self, expr);
+                if let ExprKind::Binary { op: BinaryOp::Add, lhs, rhs } = &expr.kind {
+                    if let (ExprKind::Literal { value: Literal::Int(a) }, ExprKind::Literal { value: Literal::Int(b) }) = (&lhs.kind, &rhs.kind) {
+                        return Expr::new(ExprKind::Literal { value: Literal::Int(a + b) }, expr.span);
+                    }
+                }
+                expr
+            }
+        }
+
+        let program = parser::parse("const X = 1 + 2 + 3\n").unwrap();
+        let program = ConstFold.fold_program(program);
+        let Some(Item::Const(decl)) = program.items.first() else { panic!() };
+        assert_eq!(decl.value.kind, ExprKind::Literal { value: Literal::Int(6) });
+        assert_eq!(decl.value.span.start, 10);
+    }
+}